@@ -1,33 +1,46 @@
-use criterion::{black_box, criterion_group, criterion_main, Bencher, Criterion};
+use std::collections::VecDeque;
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Bencher, Criterion};
 use post_tag::{
-    system::{BitString, VecDequeBools},
+    batch, census,
+    cycle::{self, Algorithm},
+    seeds,
+    system::{
+        BitString, BitString128, HybridBitString, RopeBitString, SharedBitString, SmallBitString,
+        VecDequeBools,
+    },
+    tag::de_mol,
     PostSystem,
 };
 
 fn bench_evolve_5854<S: PostSystem>() -> impl Fn(&mut Bencher) {
-    let compressed = black_box([
-        true, false, true, true, false, true, true, false, true, true, true, true, false,
-    ]);
+    let seed = black_box(seeds::SEED_5854);
     move |b| {
         b.iter(|| {
-            let mut system = S::new_decompressed(&compressed);
-            system.evolve_multi(341_992);
+            let mut system: S = seed.system();
+            let _ = system.evolve_multi(341_992);
         });
     }
 }
 
+fn bench_evolve_blocks_5854(b: &mut Bencher) {
+    let seed = black_box(seeds::SEED_5854);
+    b.iter(|| {
+        let mut system: BitString = seed.system();
+        let _ = system.evolve_blocks(341_992);
+    });
+}
+
 fn bench_floyd_5854<S: PostSystem>() -> impl Fn(&mut Bencher) {
-    let compressed = black_box([
-        true, false, true, true, false, true, true, false, true, true, true, true, false,
-    ]);
+    let seed = black_box(seeds::SEED_5854);
     move |b| {
         b.iter(|| {
-            let mut tortoise = S::new_decompressed(&compressed);
+            let mut tortoise: S = seed.system();
             let mut hare = tortoise.clone();
 
             loop {
-                tortoise.evolve_multi(S::PREFERRED_TIMESTEP as _);
-                hare.evolve_multi(S::PREFERRED_TIMESTEP as usize + 1);
+                let _ = tortoise.evolve_multi(tortoise.preferred_timestep() as _);
+                let _ = hare.evolve_multi(hare.preferred_timestep() as usize + 1);
 
                 if tortoise == hare {
                     break;
@@ -37,6 +50,106 @@ fn bench_floyd_5854<S: PostSystem>() -> impl Fn(&mut Bencher) {
     }
 }
 
+fn bench_detect_5854(parallel: bool) -> impl Fn(&mut Bencher) {
+    let seed = black_box(seeds::SEED_5854);
+    move |b| {
+        b.iter(|| {
+            let system: BitString = seed.system();
+            match parallel {
+                false => cycle::detect(system, Algorithm::Floyd, 10_000_000),
+                true => cycle::detect_parallel_floyd(system, 10_000_000),
+            }
+        });
+    }
+}
+
+fn bench_halting_times_serial<S: PostSystem>() -> impl Fn(&mut Bencher) {
+    let seeds: Vec<_> = census::seeds(black_box(10)).collect();
+    move |b| {
+        b.iter(|| {
+            for seed in &seeds {
+                let mut system = S::new_decompressed(seed);
+                let _ = system.evolve_multi(1000);
+            }
+        })
+    }
+}
+
+fn bench_halting_times_bit_sliced(b: &mut Bencher) {
+    let seeds: Vec<_> = census::seeds(black_box(10)).collect();
+    b.iter(|| batch::halting_times(&seeds, 1000));
+}
+
+#[cfg(feature = "rayon")]
+fn bench_par_census(b: &mut Bencher) {
+    use post_tag::{cycle::Algorithm, runner::Runner};
+
+    // Many short runs, so per-thread setup such as building lookup tables isn't amortized.
+    let census = census::Census::new(black_box(12), Runner::new(10_000, Some(Algorithm::Brent)));
+    b.iter(|| census.par_run::<BitString>());
+}
+
+fn bench_fingerprint_short(b: &mut Bencher) {
+    // Short states, as in a census, where the cost of hashing is dominated by per-call overhead.
+    let systems: Vec<BitString> = census::seeds(black_box(10))
+        .map(|seed| BitString::new_decompressed(&seed))
+        .collect();
+    b.iter(|| {
+        systems
+            .iter()
+            .map(|system| system.fingerprint())
+            .fold(0, u64::wrapping_add)
+    });
+}
+
+fn bench_hash_set_census(b: &mut Bencher) {
+    use post_tag::{cycle::Algorithm, runner::Runner};
+
+    let census = census::Census::new(black_box(9), Runner::new(10_000, Some(Algorithm::HashSet)));
+    b.iter(|| census.run::<BitString>());
+}
+
+fn bench_eq_long(b: &mut Bencher) {
+    let bits: VecDeque<bool> = (0..1_000_000u32).map(|i| i.count_ones() % 3 == 0).collect();
+
+    // Equal states with different bit offsets, as in cycle detection.
+    let mut shifted = BitString::from_list([false; 3].into_iter().chain(bits.clone()).collect());
+    let _ = shifted.evolve();
+    let mut unshifted = bits;
+    unshifted.extend([false, false]);
+    let unshifted = BitString::from_list(unshifted);
+
+    b.iter(|| assert!(black_box(&shifted) == black_box(&unshifted)));
+}
+
+fn bench_evolve_long<S: PostSystem>() -> impl Fn(&mut Bencher) {
+    // A state of ten million bits, so that moving or reallocating the whole buffer is expensive.
+    let state = S::from_list(
+        (0..10_000_000u32)
+            .map(|i| i.count_ones() % 3 == 0)
+            .collect(),
+    );
+
+    move |b| {
+        b.iter_batched(
+            || state.clone(),
+            |mut system| system.evolve_multi(black_box(1_000_000)),
+            BatchSize::LargeInput,
+        )
+    }
+}
+
+fn bench_de_mol<S: post_tag::TagSystem>(encode: fn(usize) -> S) -> impl Fn(&mut Bencher) {
+    // 27 takes 70 steps of the Collatz map to reach one, passing through numbers in the thousands.
+    move |b| {
+        b.iter_batched(
+            || encode(black_box(27)),
+            |mut system| system.evolve_multi(usize::MAX),
+            BatchSize::SmallInput,
+        )
+    }
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function(
         "VecDequeBools evolve 5854",
@@ -45,12 +158,72 @@ fn criterion_benchmark(c: &mut Criterion) {
 
     c.bench_function("BitString evolve 5854", bench_evolve_5854::<BitString>());
 
+    c.bench_function("BitString block evolve 5854", bench_evolve_blocks_5854);
+
+    c.bench_function(
+        "BitString128 evolve 5854",
+        bench_evolve_5854::<BitString128>(),
+    );
+
+    c.bench_function(
+        "HybridBitString evolve 5854",
+        bench_evolve_5854::<HybridBitString>(),
+    );
+
+    c.bench_function("BitString evolve long", bench_evolve_long::<BitString>());
+
+    c.bench_function(
+        "RopeBitString evolve long",
+        bench_evolve_long::<RopeBitString>(),
+    );
+
     c.bench_function(
         "VecDequeBools floyd 5854",
         bench_floyd_5854::<VecDequeBools>(),
     );
 
     c.bench_function("BitString floyd 5854", bench_floyd_5854::<BitString>());
+
+    c.bench_function(
+        "SharedBitString floyd 5854",
+        bench_floyd_5854::<SharedBitString>(),
+    );
+
+    c.bench_function("BitString detect floyd 5854", bench_detect_5854(false));
+
+    c.bench_function(
+        "BitString detect parallel floyd 5854",
+        bench_detect_5854(true),
+    );
+
+    c.bench_function("BitString eq long", bench_eq_long);
+
+    // Build with and without the `ahash` feature to compare hashers.
+    c.bench_function("BitString fingerprint short", bench_fingerprint_short);
+
+    c.bench_function("BitString hash set census", bench_hash_set_census);
+
+    c.bench_function(
+        "BitString halting times",
+        bench_halting_times_serial::<BitString>(),
+    );
+
+    c.bench_function(
+        "SmallBitString halting times",
+        bench_halting_times_serial::<SmallBitString>(),
+    );
+
+    c.bench_function("BitSliced halting times", bench_halting_times_bit_sliced);
+
+    #[cfg(feature = "rayon")]
+    c.bench_function("BitString parallel census", bench_par_census);
+
+    c.bench_function("SymbolString De Mol 27", bench_de_mol(de_mol::encode));
+
+    c.bench_function(
+        "PackedString De Mol 27",
+        bench_de_mol(de_mol::encode_packed),
+    );
 }
 
 criterion_group!(evolution, criterion_benchmark);
@@ -0,0 +1,26 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // Generate the C header whenever the C API is built, into the build directory rather than the source tree.
+    // The copy in `include/` is checked against it by the tests.
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+
+        let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let config = cbindgen::Config {
+            language: cbindgen::Language::C,
+            include_guard: Some("POST_TAG_H".to_string()),
+            header: Some("/* Generated by cbindgen from src/ffi.rs; do not edit. */".to_string()),
+            usize_is_size_t: true,
+            ..Default::default()
+        };
+
+        cbindgen::Builder::new()
+            .with_src(format!("{dir}/src/ffi.rs"))
+            .with_config(config)
+            .generate()
+            .expect("failed to generate the C header")
+            .write_to_file(format!("{}/post_tag.h", std::env::var("OUT_DIR").unwrap()));
+    }
+}
@@ -0,0 +1,102 @@
+/* Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#ifndef POST_TAG_H
+#define POST_TAG_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * A 3-tag system.
+ */
+typedef struct PostTagSystem PostTagSystem;
+
+/**
+ * Create a system from its state, a NUL-terminated string of `0`s and `1`s.
+ *
+ * Returns null if `state` is null or not a valid state.
+ *
+ * # Safety
+ *
+ * `state` must be null or point to a NUL-terminated string.
+ */
+struct PostTagSystem *post_tag_new(const char *state);
+
+/**
+ * Create a system from a compressed seed of `len` symbols, each a byte which is zero for `0` and nonzero for `1`.
+ *
+ * As with [`PostSystem::new_decompressed`], each symbol `x` becomes `x00`.
+ *
+ * # Safety
+ *
+ * `seed` must point to `len` readable bytes, or be null if `len` is zero.
+ */
+struct PostTagSystem *post_tag_new_decompressed(const uint8_t *seed,
+                                                size_t len);
+
+/**
+ * Create a copy of `system`, which must later be freed separately.
+ *
+ * Returns null if `system` is null.
+ *
+ * # Safety
+ *
+ * `system` must be null or a live system.
+ */
+struct PostTagSystem *post_tag_clone(const struct PostTagSystem *system);
+
+/**
+ * Destroy `system`. Does nothing if it is null.
+ *
+ * # Safety
+ *
+ * `system` must be null or a live system, which must not be used afterwards.
+ */
+void post_tag_free(struct PostTagSystem *system);
+
+/**
+ * Evolve `system` by up to `n` steps, returning the number taken, which is fewer than `n` if it halts.
+ *
+ * # Safety
+ *
+ * `system` must be null or a live system.
+ */
+uint64_t post_tag_evolve(struct PostTagSystem *system,
+                         uint64_t n);
+
+/**
+ * Get the length of the state of `system`.
+ *
+ * # Safety
+ *
+ * `system` must be null or a live system.
+ */
+size_t post_tag_length(const struct PostTagSystem *system);
+
+/**
+ * Get the fingerprint of the state of `system`, a hash which is equal for equal states.
+ *
+ * # Safety
+ *
+ * `system` must be null or a live system.
+ */
+uint64_t post_tag_fingerprint(const struct PostTagSystem *system);
+
+/**
+ * Write the state of `system` to `out`, one byte per symbol, each `0` or `1`.
+ *
+ * At most `capacity` bytes are written. Returns the length of the state,
+ * so if it is greater than `capacity`, the state was truncated and the call can be repeated with a larger buffer.
+ *
+ * # Safety
+ *
+ * `system` must be null or a live system, and `out` must point to `capacity` writable bytes, or be null if `capacity` is zero.
+ */
+size_t post_tag_serialize(const struct PostTagSystem *system,
+                          uint8_t *out,
+                          size_t capacity);
+
+#endif  /* POST_TAG_H */
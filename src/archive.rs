@@ -0,0 +1,108 @@
+//! Zero-copy archives of very large states, using [`rkyv`].
+//!
+//! An archived state can be accessed directly from a byte buffer (for example, a memory-mapped checkpoint)
+//! without deserializing or copying its bits.
+
+use rkyv::{rancor::Error, util::AlignedVec, Archive, Deserialize, Serialize};
+
+use crate::PostSystem;
+
+/// A state packed into little-endian 64-bit words, starting at bit zero of the first word.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+pub struct PackedState {
+    /// The length of the state in bits.
+    pub len: u64,
+    /// The bits of the state. Bits beyond `len` are zero.
+    pub words: Vec<u64>,
+}
+
+impl PackedState {
+    /// Pack the state of `system`.
+    pub fn from_system<S: PostSystem>(system: &S) -> Self {
+        let len = system.length();
+
+        let mut words = vec![0; len.div_ceil(64)];
+        for (i, bit) in system.bits().enumerate() {
+            words[i / 64] |= (bit as u64) << (i % 64);
+        }
+
+        Self {
+            len: len as u64,
+            words,
+        }
+    }
+
+    /// Unpack the state into a system.
+    pub fn to_system<S: PostSystem>(&self) -> S {
+        S::from_list(
+            (0..self.len as usize)
+                .map(|i| (self.words[i / 64] >> (i % 64)) & 1 == 1)
+                .collect(),
+        )
+    }
+}
+
+impl ArchivedPackedState {
+    /// Get the length of the archived state in bits.
+    pub fn len(&self) -> u64 {
+        self.len.to_native()
+    }
+
+    /// Check whether the archived state is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the bit at `index`, or `None` if it is out of bounds.
+    pub fn get(&self, index: u64) -> Option<bool> {
+        (index < self.len()).then(|| {
+            let word = self.words[(index / 64) as usize].to_native();
+            (word >> (index % 64)) & 1 == 1
+        })
+    }
+
+    /// Unpack the archived state into a system.
+    pub fn to_system<S: PostSystem>(&self) -> S {
+        S::from_list((0..self.len()).map(|i| self.get(i).unwrap()).collect())
+    }
+}
+
+/// Archive the state of `system` into an aligned byte buffer.
+pub fn to_bytes<S: PostSystem>(system: &S) -> AlignedVec {
+    rkyv::to_bytes::<Error>(&PackedState::from_system(system))
+        .expect("archiving a packed state cannot fail")
+}
+
+/// Validate and access an archived state in `bytes` without copying it.
+pub fn access(bytes: &[u8]) -> Result<&ArchivedPackedState, Error> {
+    rkyv::access::<ArchivedPackedState, Error>(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::system::BitString;
+
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        let mut system = BitString::new_decompressed(&[true, false, true, true, false]);
+        let _ = system.evolve_multi(1000);
+
+        let bytes = to_bytes(&system);
+        let archived = access(&bytes).unwrap();
+
+        assert_eq!(archived.len(), system.length() as u64);
+        for (i, bit) in system.bits().enumerate() {
+            assert_eq!(archived.get(i as u64), Some(bit));
+        }
+        assert_eq!(archived.get(archived.len()), None);
+
+        assert_eq!(archived.to_system::<BitString>(), system);
+    }
+
+    #[test]
+    fn rejects_invalid() {
+        assert!(access(&[0xFF; 3]).is_err());
+    }
+}
@@ -0,0 +1,252 @@
+//! Choosing a system representation by name at runtime.
+//!
+//! Most of the library is generic over [`PostSystem`], so the representation is fixed at compile time.
+//! A [`Backend`] hides the representation behind a trait object instead,
+//! so a [`Registry`] of them can be searched by name, for example from a command-line flag.
+//! Other crates can provide backends for their own systems by registering a [`Native`] or their own implementation.
+
+use std::{fmt, io, marker::PhantomData};
+
+use crate::{
+    cycle::CycleInfo,
+    progress::Progress,
+    runner::{Outcome, Report, Runner},
+    system::{
+        dynamic::DynPostSystem, BitString, BitString128, BitString32, BitString64, HybridBitString,
+        RopeBitString, SharedBitString, SmallBitString, VecDequeBools,
+    },
+    PostSystem,
+};
+
+/// A system representation which can be run without knowing its type.
+pub trait Backend: Send + Sync {
+    /// The name the backend is selected by, which should be short, lowercase, and unique within a [`Registry`].
+    fn name(&self) -> &str;
+
+    /// A one-line description of the backend.
+    fn description(&self) -> &str;
+
+    /// Create the system decompressed from `seed`.
+    fn system(&self, seed: &[bool]) -> Box<dyn DynPostSystem>;
+
+    /// Run the system decompressed from `seed` with `runner`, reporting to `progress`,
+    /// and return the report with any states converted to [`BitString`]s.
+    fn run(
+        &self,
+        runner: &Runner,
+        seed: &[bool],
+        progress: &mut dyn Progress,
+    ) -> io::Result<Report<BitString>>;
+}
+
+/// A [`Backend`] for any [`PostSystem`] type `S`.
+pub struct Native<S> {
+    name: String,
+    description: String,
+    system: PhantomData<fn() -> S>,
+}
+
+impl<S> Native<S> {
+    /// Create a backend for `S` with the given name and description.
+    pub fn new(name: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            system: PhantomData,
+        }
+    }
+}
+
+impl<S> fmt::Debug for Native<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Native")
+            .field("name", &self.name)
+            .field("system", &std::any::type_name::<S>())
+            .finish()
+    }
+}
+
+impl<S: PostSystem + 'static> Backend for Native<S> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn system(&self, seed: &[bool]) -> Box<dyn DynPostSystem> {
+        Box::new(S::new_decompressed(seed))
+    }
+
+    fn run(
+        &self,
+        runner: &Runner,
+        seed: &[bool],
+        progress: &mut dyn Progress,
+    ) -> io::Result<Report<BitString>> {
+        let report = runner.try_run_with_progress(S::new_decompressed(seed), progress)?;
+
+        let outcome = match report.outcome {
+            Outcome::Halted(steps) => Outcome::Halted(steps),
+            Outcome::Cycled(info) => Outcome::Cycled(CycleInfo {
+                detected_at: info.detected_at,
+                period: info.period,
+                preperiod: info.preperiod,
+                entry: BitString::from_list(info.entry.as_list()),
+            }),
+            Outcome::BudgetExhausted => Outcome::BudgetExhausted,
+            Outcome::MemoryLimitExceeded(step) => Outcome::MemoryLimitExceeded(step),
+            Outcome::Cancelled(step) => Outcome::Cancelled(step),
+            Outcome::TimedOut(step) => Outcome::TimedOut(step),
+            Outcome::LengthLimitExceeded(step) => Outcome::LengthLimitExceeded(step),
+        };
+
+        Ok(Report {
+            outcome,
+            peak_length: report.peak_length,
+            work: report.work,
+        })
+    }
+}
+
+/// A set of [`Backend`]s, looked up by name.
+#[derive(Default)]
+pub struct Registry {
+    backends: Vec<Box<dyn Backend>>,
+}
+
+impl Registry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry of the backends provided by this crate, with the default [`BitString`] first, named `bitstring`.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(Native::<BitString>::new(
+            "bitstring",
+            "bit string of native words, with lookup table jumps",
+        ));
+        registry.register(Native::<BitString32>::new(
+            "bitstring32",
+            "bit string of 32-bit words",
+        ));
+        registry.register(Native::<BitString64>::new(
+            "bitstring64",
+            "bit string of 64-bit words",
+        ));
+        registry.register(Native::<BitString128>::new(
+            "bitstring128",
+            "bit string of 128-bit words",
+        ));
+        registry.register(Native::<SmallBitString>::new(
+            "small",
+            "bit string stored inline while it is short",
+        ));
+        registry.register(Native::<HybridBitString>::new(
+            "hybrid",
+            "long runs stored by length, everything else packed into words",
+        ));
+        registry.register(Native::<RopeBitString>::new(
+            "rope",
+            "balanced tree of shared chunks",
+        ));
+        registry.register(Native::<SharedBitString>::new(
+            "shared",
+            "chunks shared between clones, for constant-time cloning",
+        ));
+        #[cfg(feature = "compress")]
+        registry.register(Native::<crate::system::CompressedBitString>::new(
+            "compressed",
+            "mostly compressed chunks, for very long states",
+        ));
+        registry.register(Native::<VecDequeBools>::new(
+            "vec-deque",
+            "one byte per symbol, for reference",
+        ));
+        registry
+    }
+
+    /// Add `backend`, replacing and returning any backend with the same name.
+    pub fn register(&mut self, backend: impl Backend + 'static) -> Option<Box<dyn Backend>> {
+        let backend = Box::new(backend);
+        match self
+            .backends
+            .iter_mut()
+            .find(|b| b.name() == backend.name())
+        {
+            Some(existing) => Some(std::mem::replace(existing, backend)),
+            None => {
+                self.backends.push(backend);
+                None
+            }
+        }
+    }
+
+    /// Get the backend named `name`, if there is one.
+    pub fn get(&self, name: &str) -> Option<&dyn Backend> {
+        self.backends
+            .iter()
+            .find(|backend| backend.name() == name)
+            .map(|backend| &**backend)
+    }
+
+    /// Iterate over the backends in the order they were first registered.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Backend> + '_ {
+        self.backends.iter().map(|backend| &**backend)
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.iter().map(|backend| backend.name()))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{cycle::Algorithm, seeds::SEED_5854};
+
+    use super::*;
+
+    #[test]
+    fn runs_every_builtin() {
+        let seed = SEED_5854.system::<BitString>().as_compressed().unwrap();
+        let runner = Runner::new(1_000_000, Some(Algorithm::Brent));
+        let expected = runner.run_with_report(BitString::new_decompressed(&seed));
+
+        let registry = Registry::builtin();
+        assert_eq!(registry.iter().next().unwrap().name(), "bitstring");
+        for backend in registry.iter() {
+            let mut reports = 0;
+            let report = backend
+                .run(&runner, &seed, &mut |_: u64, _: usize, _: u64| reports += 1)
+                .unwrap();
+            assert_eq!(report, expected, "{}", backend.name());
+            assert!(reports > 0);
+
+            let system: Box<dyn DynPostSystem> = Box::new(BitString::new_decompressed(&seed));
+            assert!(backend.system(&seed) == system);
+        }
+    }
+
+    #[test]
+    fn registers_by_name() {
+        let mut registry = Registry::new();
+        assert!(registry.get("bitstring").is_none());
+
+        assert!(registry
+            .register(Native::<BitString>::new("bits", "first"))
+            .is_none());
+        registry.register(Native::<VecDequeBools>::new("list", "second"));
+        let replaced = registry.register(Native::<BitString64>::new("bits", "third"));
+        assert_eq!(replaced.unwrap().description(), "first");
+
+        assert_eq!(registry.get("bits").unwrap().description(), "third");
+        assert_eq!(format!("{registry:?}"), r#"["bits", "list"]"#);
+    }
+}
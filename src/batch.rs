@@ -0,0 +1,303 @@
+//! Evolving many independent systems at once.
+
+use std::collections::VecDeque;
+#[cfg(feature = "rayon")]
+use std::ops::ControlFlow;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::PostSystem;
+
+/// Evolve each of `systems` by `n` steps in parallel on the global rayon thread pool.
+///
+/// Returns the result of [`PostSystem::evolve_multi`] for each system, in the same order.
+/// Systems are distributed by work stealing, so a few slow systems don't hold up the rest.
+#[cfg(feature = "rayon")]
+pub fn evolve_batch<S: PostSystem + Send>(systems: &mut [S], n: usize) -> Vec<ControlFlow<usize>> {
+    systems
+        .par_iter_mut()
+        .with_max_len(1)
+        .map(|system| system.evolve_multi(n))
+        .collect()
+}
+
+/// The number of systems evolved together by a [`BitSliced`] engine.
+pub const LANES: usize = u64::BITS as usize;
+
+/// Up to [`LANES`] independent systems, evolved in lockstep.
+///
+/// The systems are bit-sliced: the `i`th symbol of every system is packed into the `i`th word of a shared tape,
+/// one bit per system, or "lane".
+/// Every running system deletes three symbols each step, so the heads of all the systems stay aligned,
+/// and the first symbols of every system are read and deleted with a few word operations.
+/// Only the appended symbols, whose positions depend on each system's length, are written lane by lane.
+#[derive(Debug, Clone)]
+pub struct BitSliced {
+    /// The symbols of each system, starting at the shared head.
+    ///
+    /// Bits at or beyond the length of their lane are always zero.
+    tape: VecDeque<u64>,
+    /// The length of each running lane, offset by the number of steps taken.
+    ///
+    /// Every step deletes three symbols and appends at least two, so the length of a lane is `ends[lane] - step`,
+    /// and only lanes which append the two extra symbols of `1101` need to be updated.
+    ends: [u64; LANES],
+    /// A lower bound on the step at which the next lane may halt.
+    next_halt: u64,
+    /// The number of lanes in use.
+    lanes: usize,
+    /// A mask of the lanes which have not yet halted.
+    running: u64,
+    /// The number of steps taken by the running lanes.
+    step: u64,
+    /// The halting step and final state of each halted lane.
+    halted: Vec<Option<(u64, VecDeque<bool>)>>,
+}
+
+impl BitSliced {
+    /// Create an engine running one system for each of the compressed `seeds`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than [`LANES`] seeds.
+    pub fn new<T: AsRef<[bool]>>(seeds: impl IntoIterator<Item = T>) -> Self {
+        let mut this = Self {
+            tape: VecDeque::new(),
+            ends: [0; LANES],
+            next_halt: 0,
+            lanes: 0,
+            running: 0,
+            step: 0,
+            halted: Vec::new(),
+        };
+
+        for (lane, seed) in seeds.into_iter().enumerate() {
+            assert!(lane < LANES, "at most {LANES} systems can be bit-sliced");
+
+            for (i, &symbol) in seed.as_ref().iter().enumerate() {
+                if symbol {
+                    this.set(lane, 3 * i);
+                }
+            }
+
+            this.ends[lane] = 3 * seed.as_ref().len() as u64;
+            this.lanes += 1;
+            this.running |= 1 << lane;
+            this.halted.push(None);
+        }
+
+        this
+    }
+
+    /// Get the number of lanes in use.
+    pub fn lanes(&self) -> usize {
+        self.lanes
+    }
+
+    /// Get a mask of the lanes which have not yet halted.
+    pub fn running(&self) -> u64 {
+        self.running
+    }
+
+    /// Get the number of steps the running lanes have taken.
+    pub fn step(&self) -> u64 {
+        self.step
+    }
+
+    /// Get the number of steps `lane` took before halting, or `None` if it is still running.
+    pub fn halted_at(&self, lane: usize) -> Option<u64> {
+        self.halted[lane].as_ref().map(|(step, _)| *step)
+    }
+
+    /// Get the current length of `lane`.
+    pub fn length(&self, lane: usize) -> usize {
+        match &self.halted[lane] {
+            Some((_, state)) => state.len(),
+            None => (self.ends[lane] - self.step) as usize,
+        }
+    }
+
+    /// Get the current state of `lane`.
+    pub fn state<S: PostSystem>(&self, lane: usize) -> S {
+        match &self.halted[lane] {
+            Some((_, state)) => S::from_list(state.clone()),
+            None => S::from_list(self.list(lane)),
+        }
+    }
+
+    /// Evolve every running lane by one step, returning the mask of lanes which are still running.
+    pub fn evolve(&mut self) -> u64 {
+        if self.step >= self.next_halt {
+            self.halt_short_lanes();
+
+            if self.running == 0 {
+                return 0;
+            }
+        }
+
+        let first = self.tape.front().copied().unwrap_or(0) & self.running;
+        for _ in 0..3 {
+            self.tape.pop_front();
+        }
+
+        // Lanes which read a `0` append `00`, which is already there.
+        let mut ones = first;
+        while ones != 0 {
+            let lane = ones.trailing_zeros() as usize;
+            ones &= ones - 1;
+
+            // Three symbols were deleted, so the old end of the lane is now at `len - 3`.
+            let end = (self.ends[lane] - self.step - 3) as usize;
+            self.set(lane, end);
+            self.set(lane, end + 1);
+            self.set(lane, end + 3);
+            self.ends[lane] += 2;
+        }
+
+        self.step += 1;
+        self.running
+    }
+
+    /// Evolve every running lane by up to `n` steps, stopping early if every lane halts.
+    ///
+    /// Returns the mask of lanes which are still running.
+    pub fn evolve_multi(&mut self, n: u64) -> u64 {
+        for _ in 0..n {
+            if self.evolve() == 0 {
+                break;
+            }
+        }
+        self.running
+    }
+
+    /// Halt the running lanes which are shorter than three symbols, and find when the next one may halt.
+    fn halt_short_lanes(&mut self) {
+        let mut next_halt = u64::MAX;
+
+        let mut running = self.running;
+        while running != 0 {
+            let lane = running.trailing_zeros() as usize;
+            running &= running - 1;
+
+            // Lanes shrink by at most one symbol per step, so this lane can't halt before its length drops below three.
+            let halt = self.ends[lane].saturating_sub(2);
+            if self.step >= halt {
+                self.halted[lane] = Some((self.step, self.list(lane)));
+                self.running &= !(1 << lane);
+            } else {
+                next_halt = next_halt.min(halt);
+            }
+        }
+
+        self.next_halt = next_halt;
+    }
+
+    /// Set the symbol at `position` of `lane`.
+    fn set(&mut self, lane: usize, position: usize) {
+        if self.tape.len() <= position {
+            self.tape.resize(position + 1, 0);
+        }
+        self.tape[position] |= 1 << lane;
+    }
+
+    /// Read the symbols of the running `lane`.
+    fn list(&self, lane: usize) -> VecDeque<bool> {
+        (0..(self.ends[lane] - self.step) as usize)
+            .map(|i| self.tape.get(i).is_some_and(|word| word & (1 << lane) != 0))
+            .collect()
+    }
+}
+
+/// Find the halting time of each of the compressed `seeds`, simulating at most `budget` steps of each.
+///
+/// Seeds are run [`LANES`] at a time with a [`BitSliced`] engine.
+/// Returns `None` for seeds which do not halt within the budget.
+pub fn halting_times<T: AsRef<[bool]>>(seeds: &[T], budget: u64) -> Vec<Option<u64>> {
+    seeds
+        .chunks(LANES)
+        .flat_map(|chunk| {
+            let mut engine = BitSliced::new(chunk);
+            engine.evolve_multi(budget);
+
+            (0..chunk.len())
+                .map(|lane| engine.halted_at(lane))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{census::seeds, system::BitString};
+
+    use super::*;
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn evolves_in_parallel() {
+        let mut systems: Vec<_> = seeds(6)
+            .map(|seed| BitString::new_decompressed(&seed))
+            .collect();
+        let mut expected = systems.clone();
+
+        let outcomes = evolve_batch(&mut systems, 500);
+        let expected_outcomes: Vec<_> = expected
+            .iter_mut()
+            .map(|system| system.evolve_multi(500))
+            .collect();
+
+        assert_eq!(outcomes, expected_outcomes);
+        assert_eq!(systems, expected);
+        assert!(outcomes.contains(&ControlFlow::Break(4)));
+    }
+
+    #[test]
+    fn evolves_bit_sliced() {
+        let seeds: Vec<_> = seeds(6).collect();
+
+        for chunk in seeds.chunks(LANES) {
+            let mut engine = BitSliced::new(chunk);
+            assert_eq!(engine.lanes(), chunk.len());
+
+            let mut systems: Vec<_> = chunk
+                .iter()
+                .map(|seed| BitString::new_decompressed(seed))
+                .collect();
+            let halted: Vec<_> = systems
+                .iter_mut()
+                .map(|system| system.evolve_multi(500).break_value())
+                .collect();
+
+            engine.evolve_multi(500);
+            for (lane, system) in systems.iter().enumerate() {
+                assert_eq!(engine.halted_at(lane), halted[lane].map(|n| n as u64));
+                assert_eq!(engine.length(lane), system.length());
+                assert_eq!(&engine.state::<BitString>(lane), system);
+            }
+        }
+    }
+
+    #[test]
+    fn halts_empty_seeds() {
+        let mut engine = BitSliced::new([&[][..], &[true]]);
+        assert_eq!(engine.evolve(), 0b10);
+        assert_eq!(engine.halted_at(0), Some(0));
+        assert_eq!(
+            engine.state::<BitString>(0),
+            BitString::new_decompressed(&[])
+        );
+    }
+
+    #[test]
+    fn finds_halting_times() {
+        let seeds: Vec<_> = seeds(7).collect();
+        let times = halting_times(&seeds, 1000);
+
+        for (seed, time) in seeds.iter().zip(times) {
+            let mut system = BitString::new_decompressed(seed);
+            let expected = system.evolve_multi(1000).break_value().map(|n| n as u64);
+            assert_eq!(time, expected, "{seed:?}");
+        }
+    }
+}
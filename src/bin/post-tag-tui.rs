@@ -0,0 +1,207 @@
+//! Interactive terminal viewer for evolving Post tag systems.
+//!
+//! Usage: `post-tag-tui <compressed seed>`, e.g. `post-tag-tui 1011011011110`.
+
+use std::{env, io, ops::ControlFlow, process, time::Duration};
+
+use post_tag::{delta::DeltaLog, system::BitString, PostSystem};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, Paragraph, Sparkline, Wrap},
+    DefaultTerminal, Frame,
+};
+
+/// The maximum number of lengths kept for the length graph.
+const HISTORY: usize = 1024;
+
+/// The number of most recent steps whose changes are logged, for stepping backward quickly.
+const LOGGED_STEPS: usize = 1 << 24;
+
+/// The number of steps between the snapshots kept for stepping backward past the logged changes.
+const SNAPSHOT_EVERY: u64 = 1 << 16;
+
+struct App {
+    /// The current state of the system.
+    system: BitString,
+    /// The changes made by recent steps, and snapshots of earlier ones, for stepping backward and forward again.
+    log: DeltaLog,
+    /// Whether the system has halted.
+    halted: bool,
+    /// Recent lengths of the system, for the length graph.
+    lengths: Vec<u64>,
+    /// Whether evolution is paused.
+    paused: bool,
+    /// The number of steps taken per tick, and per seek.
+    stride: usize,
+}
+
+impl App {
+    fn new(seed: &[bool]) -> Self {
+        let system = BitString::new_decompressed(seed);
+        Self {
+            lengths: vec![system.length() as u64],
+            system,
+            log: DeltaLog::new(0)
+                .with_max_len(LOGGED_STEPS)
+                .with_snapshots(SNAPSHOT_EVERY),
+            halted: false,
+            paused: true,
+            stride: 1,
+        }
+    }
+
+    /// Evolve the system by `n` steps, recording its length.
+    fn advance(&mut self, n: usize) {
+        if self.halted {
+            return;
+        }
+
+        if self.log.evolve_multi(&mut self.system, n).is_break() {
+            self.halted = true;
+            self.paused = true;
+        }
+
+        self.lengths.push(self.system.length() as u64);
+        if self.lengths.len() > HISTORY {
+            self.lengths.remove(0);
+        }
+    }
+
+    /// Move back by `n` steps using the log.
+    fn rewind(&mut self, n: usize) {
+        self.log.rewind(&mut self.system, n as u64);
+        self.halted = false;
+
+        self.lengths.clear();
+        self.lengths.push(self.system.length() as u64);
+    }
+
+    /// Move forward to the latest step taken, after moving back.
+    fn seek_end(&mut self) {
+        self.log.seek(&mut self.system, self.log.end());
+
+        self.lengths.clear();
+        self.lengths.push(self.system.length() as u64);
+    }
+
+    /// Handle a key press, returning `Break` if the viewer should quit.
+    fn handle_key(&mut self, key: KeyCode) -> ControlFlow<()> {
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return ControlFlow::Break(()),
+            KeyCode::Char(' ') => self.paused = !self.paused,
+            KeyCode::Char('n') | KeyCode::Right => self.advance(self.stride),
+            KeyCode::Left => self.rewind(self.stride),
+            KeyCode::Char('+') | KeyCode::Char('=') => {
+                self.stride = self.stride.saturating_mul(2);
+            }
+            KeyCode::Char('-') => self.stride = (self.stride / 2).max(1),
+            KeyCode::Char('r') => self.rewind(usize::MAX),
+            KeyCode::End => self.seek_end(),
+            _ => {}
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [status, state, graph, help] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(3),
+            Constraint::Length(8),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let status_text = format!(
+            "step {}  length {}  stride {}  {}",
+            self.log.step(),
+            self.system.length(),
+            self.stride,
+            match (self.halted, self.paused) {
+                (true, _) => "halted",
+                (false, true) => "paused",
+                (false, false) => "running",
+            }
+        );
+        frame.render_widget(
+            Paragraph::new(status_text).block(Block::bordered().title("post-tag")),
+            status,
+        );
+
+        // Only render as many symbols as can fit in the state pane.
+        let capacity =
+            (state.width.saturating_sub(2) as usize) * (state.height.saturating_sub(2) as usize);
+        let symbols: String = self
+            .system
+            .bits()
+            .take(capacity)
+            .map(|b| if b { '1' } else { '0' })
+            .collect();
+        frame.render_widget(
+            Paragraph::new(symbols)
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title("state")),
+            state,
+        );
+
+        let visible = graph.width.saturating_sub(2) as usize;
+        let lengths = &self.lengths[self.lengths.len().saturating_sub(visible)..];
+        frame.render_widget(
+            Sparkline::default()
+                .data(lengths)
+                .style(Style::default().fg(Color::Cyan))
+                .block(Block::bordered().title("length")),
+            graph,
+        );
+
+        frame.render_widget(
+            Line::from(
+                "space: pause  n/→: step  ←: back  end: latest  +/-: stride  r: restart  q: quit",
+            ),
+            help,
+        );
+    }
+
+    fn run(mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if event::poll(Duration::from_millis(30))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press && self.handle_key(key.code).is_break() {
+                        return Ok(());
+                    }
+                }
+            } else if !self.paused {
+                self.advance(self.stride);
+            }
+        }
+    }
+}
+
+/// Parse a compressed seed written as a string of `0`s and `1`s.
+fn parse_seed(s: &str) -> Option<Vec<bool>> {
+    s.chars()
+        .map(|c| match c {
+            '0' => Some(false),
+            '1' => Some(true),
+            _ => None,
+        })
+        .collect()
+}
+
+fn main() -> io::Result<()> {
+    let Some(seed) = env::args().nth(1).as_deref().and_then(parse_seed) else {
+        eprintln!("usage: post-tag-tui <compressed seed, e.g. 1011011011110>");
+        process::exit(2);
+    };
+
+    let mut terminal = ratatui::init();
+    let result = App::new(&seed).run(&mut terminal);
+    ratatui::restore();
+
+    result
+}
@@ -0,0 +1,87 @@
+use std::{
+    io::{self, BufWriter, Write},
+    process::ExitCode,
+};
+
+use post_tag::{
+    census::{self, Census, Champion},
+    runner::Runner,
+    system::BitString,
+};
+
+use crate::Algorithm;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The maximum compressed seed length to search.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..=census::MAX_LEN as i64))]
+    max_len: u32,
+
+    /// The maximum number of steps to simulate per seed.
+    #[arg(short, long, value_parser = crate::parse_steps, default_value = "10_000_000")]
+    budget: u64,
+
+    /// The cycle detection algorithm to use.
+    #[arg(short, long, value_enum, default_value_t = Algorithm::Brent)]
+    algorithm: Algorithm,
+
+    /// Print each new record to stderr as it is found.
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+pub fn run(args: Args) -> ExitCode {
+    match busy_beaver(&args, &mut BufWriter::new(io::stdout().lock())) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Search every seed up to the maximum length, writing one tab-separated row of records per length.
+fn busy_beaver(args: &Args, out: &mut impl Write) -> io::Result<()> {
+    let census = Census::new(
+        args.max_len,
+        Runner::new(args.budget, Some(args.algorithm.into())),
+    );
+
+    let table = census.busy_beaver::<BitString>(|record| {
+        if args.verbose {
+            eprintln!(
+                "new record: {} {:?}",
+                format_seed(&record.seed),
+                record.class
+            );
+        }
+    });
+
+    writeln!(
+        out,
+        "len\thalt_seed\thalt_steps\tcycle_seed\tcycle_steps\tunknown"
+    )?;
+    for entry in table.lengths() {
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}",
+            entry.len,
+            format_champion(&entry.longest_halt),
+            format_champion(&entry.longest_preperiod),
+            entry.unknown,
+        )?;
+    }
+
+    out.flush()
+}
+
+fn format_seed(seed: &[bool]) -> String {
+    seed.iter().map(|&b| if b { '1' } else { '0' }).collect()
+}
+
+fn format_champion(champion: &Option<Champion>) -> String {
+    match champion {
+        Some(champion) => format!("{}\t{}", format_seed(&champion.seed), champion.steps),
+        None => "-\t-".to_owned(),
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    io::{self, BufWriter, Write},
+    ops::ControlFlow,
+    process::ExitCode,
+    time::Duration,
+};
+
+use post_tag::{
+    census::{self, Census, Class},
+    export::histogram::{Binning, Histogram},
+    runner::Runner,
+    system::BitString,
+};
+
+use crate::Algorithm;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The maximum compressed seed length to simulate.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..=census::MAX_LEN as i64))]
+    max_len: u32,
+
+    /// The maximum number of steps to simulate per seed.
+    #[arg(short, long, value_parser = crate::parse_steps, default_value = "10_000_000")]
+    budget: u64,
+
+    /// The cycle detection algorithm to use.
+    #[arg(short, long, value_enum, default_value_t = Algorithm::Brent)]
+    algorithm: Algorithm,
+
+    /// The maximum number of bytes of states to keep per seed; seeds exceeding it are reported as unknown.
+    #[arg(long, value_parser = crate::parse_bytes)]
+    memory_limit: Option<u64>,
+
+    /// The maximum time to spend on each seed, e.g. `500ms` or `2m`; seeds exceeding it are reported as unknown.
+    #[arg(long, value_parser = crate::parse_duration)]
+    time_limit: Option<Duration>,
+
+    /// The maximum number of symbols each seed's state may grow to; seeds exceeding it are reported as unknown.
+    #[arg(long, value_parser = crate::parse_steps)]
+    length_limit: Option<u64>,
+
+    /// Write a histogram of halting times in the given format instead of a row per seed.
+    #[arg(long, value_enum)]
+    histogram: Option<HistogramFormat>,
+
+    /// The width of each histogram bin, in steps.
+    /// Without this, the bins' bounds are successive powers of two.
+    #[arg(long, value_parser = crate::parse_steps)]
+    bin_width: Option<u64>,
+
+    /// Include the empty bins below the last nonempty one in the histogram.
+    #[arg(long)]
+    empty_bins: bool,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum HistogramFormat {
+    Csv,
+    Json,
+}
+
+pub fn run(args: Args) -> ExitCode {
+    match census(&args, &mut BufWriter::new(io::stdout().lock())) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Simulate every seed up to the maximum length, writing one tab-separated row per seed.
+fn census(args: &Args, out: &mut impl Write) -> io::Result<()> {
+    let mut runner = Runner::new(args.budget, Some(args.algorithm.into()));
+    runner.memory_limit = args.memory_limit;
+    runner.time_limit = args.time_limit;
+    runner.length_limit = args.length_limit;
+    let census = Census::new(args.max_len, runner);
+
+    if let Some(format) = args.histogram {
+        return histogram(args, &census, format, out);
+    }
+
+    writeln!(out, "seed\toutcome\tsteps\tperiod\tpreperiod")?;
+
+    let result = census.run_each::<BitString, _>(|record| {
+        let seed: String = record
+            .seed
+            .iter()
+            .map(|&b| if b { '1' } else { '0' })
+            .collect();
+
+        let written = match record.class {
+            Class::Halted(steps) => writeln!(out, "{seed}\thalt\t{steps}\t-\t-"),
+            Class::Cycled {
+                detected_at,
+                period,
+                preperiod,
+            } => {
+                let preperiod =
+                    preperiod.map_or_else(|| "-".to_owned(), |preperiod| preperiod.to_string());
+                writeln!(out, "{seed}\tcycle\t{detected_at}\t{period}\t{preperiod}")
+            }
+            Class::Unknown => writeln!(out, "{seed}\tunknown\t-\t-\t-"),
+        };
+
+        match written {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(e) => ControlFlow::Break(e),
+        }
+    });
+
+    if let ControlFlow::Break(e) = result {
+        return Err(e);
+    }
+
+    out.flush()
+}
+
+/// Simulate every seed up to the maximum length, writing a histogram of the halting times.
+fn histogram(
+    args: &Args,
+    census: &Census,
+    format: HistogramFormat,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let binning = match args.bin_width {
+        Some(0) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "bin width must be nonzero",
+            ))
+        }
+        Some(width) => Binning::Linear(width),
+        None => Binning::Log2,
+    };
+
+    let histogram =
+        Histogram::of_census(binning, &census.run::<BitString>()).with_empty_bins(args.empty_bins);
+    match format {
+        HistogramFormat::Csv => histogram.write_csv(&mut *out)?,
+        HistogramFormat::Json => histogram.write_json(&mut *out)?,
+    }
+
+    out.flush()
+}
@@ -0,0 +1,126 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use indicatif::{ProgressBar, ProgressStyle};
+use post_tag::{
+    backend::Registry,
+    runner::{Outcome, Runner},
+    system::{BitString, BitString128},
+    PostSystem,
+};
+
+use crate::{Algorithm, Seed};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The compressed seed, e.g. `1011011011110`.
+    seed: Seed,
+
+    /// The cycle detection algorithm to use.
+    #[arg(short, long, value_enum, default_value_t = Algorithm::Brent)]
+    algorithm: Algorithm,
+
+    /// The maximum number of steps to simulate.
+    #[arg(short, long, value_parser = crate::parse_steps, default_value = "10_000_000")]
+    budget: u64,
+
+    /// The system representation to simulate with; run with an unknown name to list them.
+    #[arg(long, default_value = "bitstring", conflicts_with = "lut")]
+    backend: String,
+
+    /// A lookup table saved by the `lut` subcommand, to jump further per step.
+    #[arg(long)]
+    lut: Option<PathBuf>,
+
+    /// Show a progress bar on standard error.
+    #[arg(long)]
+    progress: bool,
+}
+
+pub fn run(args: Args) -> ExitCode {
+    let mut bar = match args.progress {
+        true => ProgressBar::new(args.budget).with_style(
+            ProgressStyle::with_template("{bar:40} {human_pos}/{human_len} steps, {msg}").unwrap(),
+        ),
+        false => ProgressBar::hidden(),
+    };
+    let runner = Runner::new(args.budget, Some(args.algorithm.into()));
+
+    let exit = match &args.lut {
+        Some(path) => match crate::lut::load(path) {
+            Ok(lut) => {
+                let system = BitString128::new_decompressed(&args.seed.0).with_lut(lut);
+                report(runner.run_with_progress(system, &mut bar), &args)
+            }
+            Err(e) => {
+                eprintln!("error: failed to load lookup table: {e}");
+                ExitCode::FAILURE
+            }
+        },
+        None => {
+            let registry = Registry::builtin();
+            match registry.get(&args.backend) {
+                Some(backend) => {
+                    let result = backend.run(&runner, &args.seed.0, &mut bar);
+                    report(result.expect("the runner has no checkpoint").outcome, &args)
+                }
+                None => {
+                    eprintln!(
+                        "error: unknown backend {:?}, expected one of:",
+                        args.backend
+                    );
+                    for backend in registry.iter() {
+                        eprintln!("  {:<14}{}", backend.name(), backend.description());
+                    }
+                    ExitCode::FAILURE
+                }
+            }
+        }
+    };
+    bar.finish_and_clear();
+
+    exit
+}
+
+fn report<S: PostSystem>(outcome: Outcome<S>, args: &Args) -> ExitCode {
+    match outcome {
+        Outcome::Cycled(info) => {
+            // Hashes are of bit strings, so that they don't depend on the backend.
+            let hash = |state: &S| BitString::from_list(state.as_list()).fingerprint();
+
+            match info.preperiod {
+                Some(preperiod) => {
+                    let mut entry = S::new_decompressed(&args.seed.0);
+                    let _ = entry.evolve_multi(preperiod as usize);
+
+                    println!("preperiod  {preperiod}");
+                    println!("period     {}", info.period);
+                    println!("entry hash {:016x}", hash(&entry));
+                }
+                // Without the exact entry, the canonical state of the cycle is hashed instead.
+                None => {
+                    println!("preperiod <= {}", info.detected_at);
+                    println!("period     {}", info.period);
+                    println!("cycle hash {:016x}", hash(&info.representative()));
+                }
+            }
+
+            ExitCode::SUCCESS
+        }
+        Outcome::Halted(steps) => {
+            println!("halted after {steps} steps");
+
+            ExitCode::SUCCESS
+        }
+        Outcome::BudgetExhausted => {
+            eprintln!("no cycle found within {} steps", args.budget);
+
+            ExitCode::FAILURE
+        }
+        Outcome::MemoryLimitExceeded(_)
+        | Outcome::Cancelled(_)
+        | Outcome::TimedOut(_)
+        | Outcome::LengthLimitExceeded(_) => {
+            unreachable!("the runner has no memory, time, or length limit, or cancellation")
+        }
+    }
+}
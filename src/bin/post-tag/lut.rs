@@ -0,0 +1,62 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use post_tag::system::{BitString128, Lut};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The number of steps per table access. The table has `2^timestep` entries.
+    #[arg(short, long, default_value_t = 20)]
+    timestep: u8,
+
+    /// The number of table accesses per jump.
+    #[arg(short, long, default_value_t = 1)]
+    lookups: u8,
+
+    /// Where to save the table.
+    out: PathBuf,
+}
+
+pub fn run(args: Args) -> ExitCode {
+    let lut = match BitString128::builder()
+        .timestep(args.timestep)
+        .lookups(args.lookups)
+        .build()
+    {
+        Ok(lut) => lut,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let saved = File::create(&args.out).and_then(|file| {
+        let mut out = BufWriter::new(file);
+        lut.write_to(&mut out)?;
+        out.flush()
+    });
+
+    match saved {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Load a table saved by the `lut` subcommand, mapping it into memory if possible.
+pub fn load(path: &Path) -> io::Result<Lut<u128>> {
+    let file = File::open(path)?;
+
+    #[cfg(feature = "mmap")]
+    // SAFETY: the table file is only ever written by the `lut` subcommand, and not while it is in use.
+    return unsafe { Lut::map(&file) };
+
+    #[cfg(not(feature = "mmap"))]
+    Lut::read_from(io::BufReader::new(file))
+}
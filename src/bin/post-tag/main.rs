@@ -0,0 +1,161 @@
+//! Command-line interface for simulating and analyzing Post tag systems.
+
+mod busy_beaver;
+mod census;
+mod detect_cycle;
+mod lut;
+mod rules;
+mod trace;
+
+use std::{process::ExitCode, time::Duration};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use post_tag::{cycle, system::BitString, PostSystem};
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Run cycle detection on a seed.
+    DetectCycle(detect_cycle::Args),
+    /// Simulate every seed up to a length and classify each one.
+    Census(census::Args),
+    /// Find the longest-lived seeds of each length.
+    BusyBeaver(busy_beaver::Args),
+    /// Print the states of a system as lines of symbols.
+    Trace(trace::Args),
+    /// Generate a lookup table and save it, so that later runs can load it with `--lut`.
+    Lut(lut::Args),
+    /// Work with tag systems following rules other than Post's.
+    Rules(rules::Args),
+}
+
+/// A cycle detection algorithm.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Algorithm {
+    Floyd,
+    Brent,
+    Hashset,
+}
+
+impl From<Algorithm> for cycle::Algorithm {
+    fn from(algorithm: Algorithm) -> Self {
+        match algorithm {
+            Algorithm::Floyd => Self::Floyd,
+            Algorithm::Brent => Self::Brent,
+            Algorithm::Hashset => Self::HashSet,
+        }
+    }
+}
+
+/// A compressed seed, written as a string of `0`s and `1`s, or as a hexadecimal number prefixed with `0x`.
+#[derive(Debug, Clone)]
+struct Seed(Vec<bool>);
+
+impl std::str::FromStr for Seed {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("0x") {
+            let system = BitString::from_seed_hex(s).map_err(|e| e.to_string())?;
+            return Ok(Self(system.bits().step_by(3).collect()));
+        }
+
+        s.chars()
+            .map(|c| match c {
+                '0' => Ok(false),
+                '1' => Ok(true),
+                _ => Err(format!("invalid symbol {c:?}, expected 0 or 1")),
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// Parse a step count, allowing `_` separators.
+fn parse_steps(s: &str) -> Result<u64, String> {
+    s.replace('_', "").parse().map_err(|e| format!("{e}"))
+}
+
+/// Parse a number of bytes, allowing `_` separators and a binary `K`, `M`, or `G` suffix.
+fn parse_bytes(s: &str) -> Result<u64, String> {
+    let s = s.replace('_', "");
+    let (digits, shift) = match s.char_indices().last() {
+        Some((i, 'K' | 'k')) => (&s[..i], 10),
+        Some((i, 'M' | 'm')) => (&s[..i], 20),
+        Some((i, 'G' | 'g')) => (&s[..i], 30),
+        _ => (&s[..], 0),
+    };
+
+    let n: u64 = digits.parse().map_err(|e| format!("{e}"))?;
+    n.checked_mul(1 << shift)
+        .ok_or_else(|| "number of bytes is too large".to_string())
+}
+
+/// Parse a duration given as a number of seconds, or with an `ms`, `s`, `m`, or `h` suffix.
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let (digits, unit) = match s.find(|c: char| !c.is_ascii_digit() && c != '.') {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let seconds = match unit {
+        "ms" => 1e-3,
+        "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        _ => return Err(format!("invalid unit {unit:?}, expected ms, s, m, or h")),
+    };
+
+    let n: f64 = digits.parse().map_err(|e| format!("{e}"))?;
+    Duration::try_from_secs_f64(n * seconds).map_err(|e| format!("{e}"))
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::DetectCycle(args) => detect_cycle::run(args),
+        Command::Census(args) => census::run(args),
+        Command::BusyBeaver(args) => busy_beaver::run(args),
+        Command::Trace(args) => trace::run(args),
+        Command::Lut(args) => lut::run(args),
+        Command::Rules(args) => rules::run(args),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use std::time::Duration;
+
+    use super::{parse_bytes, parse_duration, Cli};
+
+    #[test]
+    fn verifies() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn parses_bytes() {
+        assert_eq!(parse_bytes("1_000"), Ok(1000));
+        assert_eq!(parse_bytes("4K"), Ok(4096));
+        assert_eq!(parse_bytes("2G"), Ok(2 << 30));
+        assert!(parse_bytes("M").is_err());
+        assert!(parse_bytes("100000000000G").is_err());
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("3"), Ok(Duration::from_secs(3)));
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration("1.5m"), Ok(Duration::from_secs(90)));
+        assert!(parse_duration("2d").is_err());
+        assert!(parse_duration("h").is_err());
+    }
+}
@@ -0,0 +1,192 @@
+use std::{
+    fs,
+    io::{self, BufWriter, Write},
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::Arc,
+};
+
+use clap::Subcommand;
+use post_tag::{
+    tag::{presets, SymbolString, TagRules},
+    TagSystem,
+};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List the preset rules from the literature, which can be given by name in place of rules.
+    List,
+    /// Check a rules file, reporting errors and rules which behave trivially.
+    ///
+    /// Files ending in `.toml` or `.json` are read as such, and any other file as the textual form, like `v=3; 0 -> 00; 1 -> 1101`.
+    Validate(ValidateArgs),
+    /// Print the states of a system following the rules as lines of symbols.
+    Trace(TraceArgs),
+}
+
+#[derive(Debug, clap::Args)]
+struct ValidateArgs {
+    /// The rules file.
+    file: PathBuf,
+
+    /// Fail if the rules behave trivially, as well as if they are invalid.
+    #[arg(long)]
+    strict: bool,
+}
+
+#[derive(Debug, clap::Args)]
+struct TraceArgs {
+    /// The rules, e.g. `v=2; 0 -> 12; 1 -> 0; 2 -> 000`, or the name of a preset.
+    #[arg(value_parser = parse_rules)]
+    rules: TagRules,
+
+    /// The initial state, written with the same symbols as the rules, e.g. `000`.
+    state: String,
+
+    /// The maximum number of steps to simulate.
+    #[arg(short, long, value_parser = crate::parse_steps, default_value = "100")]
+    steps: u64,
+
+    /// Print only every `every`-th state.
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    every: u64,
+
+    /// Prefix each line with its step number.
+    #[arg(short, long)]
+    numbered: bool,
+}
+
+pub fn run(args: Args) -> ExitCode {
+    let result = match args.command {
+        Command::List => list(),
+        Command::Validate(args) => return validate(&args),
+        Command::Trace(args) => trace(&args),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        // Stop quietly when piped into e.g. `head`.
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Parse rules given inline, or by the name of a preset.
+fn parse_rules(s: &str) -> Result<TagRules, String> {
+    match presets::get(s) {
+        Some(preset) => Ok(preset.rules()),
+        None => s.parse().map_err(|e| format!("{e}")),
+    }
+}
+
+fn list() -> io::Result<()> {
+    let out = &mut io::stdout().lock();
+    for preset in presets::ALL {
+        writeln!(out, "{}\t{}", preset.name, preset.source)?;
+        writeln!(out, "\t{}", preset.description)?;
+        writeln!(out, "\t{}", preset.citation)?;
+    }
+
+    Ok(())
+}
+
+fn validate(args: &ValidateArgs) -> ExitCode {
+    let rules = match load(&args.file) {
+        Ok(rules) => rules,
+        Err(e) => {
+            // TOML errors end with a new line of their own.
+            let message = e.to_string();
+            eprintln!("error: {}: {}", args.file.display(), message.trim_end());
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let lints = rules.lints();
+    for lint in &lints {
+        println!("warning: {lint}");
+    }
+    println!(
+        "{}: {} symbols, deleting {} per step",
+        args.file.display(),
+        rules.alphabet(),
+        rules.deletion()
+    );
+
+    if args.strict && !lints.is_empty() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Load rules from a file, choosing the format by its extension.
+fn load(path: &Path) -> Result<TagRules, Box<dyn std::error::Error>> {
+    let contents = fs::read_to_string(path)?;
+
+    Ok(match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => TagRules::from_toml(&contents)?,
+        Some("json") => TagRules::from_json(&contents)?,
+        _ => contents.parse()?,
+    })
+}
+
+fn trace(args: &TraceArgs) -> io::Result<()> {
+    let symbols = args
+        .state
+        .chars()
+        .map(|c| {
+            c.to_digit(36).map(|digit| digit as u8).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid symbol {c:?} in the state"),
+                )
+            })
+        })
+        .collect::<io::Result<Vec<u8>>>()?;
+    let mut system = SymbolString::new(Arc::new(args.rules.clone()), symbols)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    let out = &mut BufWriter::new(io::stdout().lock());
+    let mut step = 0;
+    loop {
+        print_state(args, step, &system, out)?;
+
+        let chunk = args.every.min(args.steps - step);
+        if chunk == 0 {
+            break;
+        }
+
+        if let ControlFlow::Break(n) = system.evolve_multi(chunk as usize) {
+            if n > 0 {
+                print_state(args, step + n as u64, &system, out)?;
+            }
+            break;
+        }
+        step += chunk;
+    }
+
+    out.flush()
+}
+
+fn print_state(
+    args: &TraceArgs,
+    step: u64,
+    system: &SymbolString,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    if args.numbered {
+        write!(out, "{step}\t")?;
+    }
+
+    writeln!(out, "{system}")
+}
@@ -0,0 +1,108 @@
+use std::{
+    io::{self, BufWriter, Write},
+    ops::ControlFlow,
+    process::ExitCode,
+};
+
+use post_tag::{system::BitString, PostSystem};
+
+use crate::Seed;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The compressed seed, e.g. `1011011011110`.
+    seed: Seed,
+
+    /// The maximum number of steps to simulate.
+    #[arg(short, long, value_parser = crate::parse_steps, default_value = "100")]
+    steps: u64,
+
+    /// Print only every `every`-th state.
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    every: u64,
+
+    /// Truncate each line to at most this many symbols.
+    #[arg(short, long)]
+    width: Option<usize>,
+
+    /// The glyph printed for a `0` symbol.
+    #[arg(long, default_value_t = '0')]
+    zero: char,
+
+    /// The glyph printed for a `1` symbol.
+    #[arg(long, default_value_t = '1')]
+    one: char,
+
+    /// Prefix each line with its step number.
+    #[arg(short, long)]
+    numbered: bool,
+
+    /// Stop once a printed state is longer than this.
+    #[arg(long)]
+    max_length: Option<usize>,
+}
+
+pub fn run(args: Args) -> ExitCode {
+    match trace(&args, &mut BufWriter::new(io::stdout().lock())) {
+        Ok(()) => ExitCode::SUCCESS,
+        // Stop quietly when piped into e.g. `head`.
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Print every `every`-th state of the system until it halts or a stopping condition is reached.
+fn trace(args: &Args, out: &mut impl Write) -> io::Result<()> {
+    let mut system = BitString::new_decompressed(&args.seed.0);
+    let mut step = 0;
+
+    loop {
+        print_state(args, step, &system, out)?;
+
+        if args
+            .max_length
+            .is_some_and(|max_length| system.length() > max_length)
+        {
+            break;
+        }
+
+        let chunk = args.every.min(args.steps - step);
+        if chunk == 0 {
+            break;
+        }
+
+        if let ControlFlow::Break(n) = system.evolve_multi(chunk as usize) {
+            // Always show the final state, even if it falls between samples.
+            if n > 0 {
+                print_state(args, step + n as u64, &system, out)?;
+            }
+            break;
+        }
+        step += chunk;
+    }
+
+    out.flush()
+}
+
+fn print_state(args: &Args, step: u64, system: &BitString, out: &mut impl Write) -> io::Result<()> {
+    if args.numbered {
+        write!(out, "{step}\t")?;
+    }
+
+    let width = args.width.unwrap_or(usize::MAX);
+    let line: String = system
+        .bits()
+        .take(width)
+        .map(|b| if b { args.one } else { args.zero })
+        .collect();
+    write!(out, "{line}")?;
+
+    if system.length() > width {
+        write!(out, "…")?;
+    }
+
+    writeln!(out)
+}
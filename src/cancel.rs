@@ -0,0 +1,90 @@
+//! Cooperative cancellation of long runs.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// A flag which can be set from any thread to ask long-running work to stop.
+///
+/// Clones share the same flag, so a clone can be handed to the work while the original is kept to cancel it.
+/// Work only checks the flag every so often, typically every [`CHUNK`] steps, so it stops soon after cancellation but not instantly.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+/// The number of steps between checks of a [`CancellationToken`].
+pub const CHUNK: usize = 1 << 16;
+
+impl CancellationToken {
+    /// Create a token which has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the work holding this token, or any of its clones, to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Check whether the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl PartialEq for CancellationToken {
+    /// Tokens are equal if they share the same flag.
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CancellationToken {}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+
+    use crate::{seeds, system::BitString, PostSystem};
+
+    use super::*;
+
+    #[test]
+    fn shares_flag_between_clones() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert_eq!(token, clone);
+        assert_ne!(token, CancellationToken::new());
+
+        assert!(!clone.is_cancelled());
+        token.cancel();
+        assert!(clone.is_cancelled());
+    }
+
+    #[test]
+    fn stops_evolution() {
+        let token = CancellationToken::new();
+        let mut system: BitString = seeds::SEED_5854.system();
+        let mut expected = system.clone();
+
+        assert_eq!(
+            system.evolve_multi_cancellable(3 * CHUNK + 5, &token),
+            ControlFlow::Continue(3 * CHUNK + 5)
+        );
+        let _ = expected.evolve_multi(3 * CHUNK + 5);
+        assert_eq!(system, expected);
+
+        token.cancel();
+        assert_eq!(
+            system.evolve_multi_cancellable(CHUNK, &token),
+            ControlFlow::Continue(0)
+        );
+        assert_eq!(system, expected);
+
+        let mut system: BitString = seeds::ONES_5.system();
+        assert_eq!(
+            system.evolve_multi_cancellable(1000, &CancellationToken::new()),
+            ControlFlow::Break(409)
+        );
+    }
+}
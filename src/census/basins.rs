@@ -0,0 +1,128 @@
+//! Basins of attraction: which attractor each seed ends in.
+
+use std::collections::BTreeMap;
+
+use crate::{
+    cycle::{CycleDatabase, CycleId},
+    PostSystem,
+};
+
+use super::{seed_count, Census, Class};
+
+/// Where a seed's trajectory ends up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Attractor {
+    /// The system halted.
+    Halt,
+    /// The system entered the given cycle.
+    Cycle(CycleId),
+    /// The step budget ran out before the system halted or a cycle was found.
+    Unknown,
+}
+
+/// The attractor of every seed in a census.
+#[derive(Debug, Clone)]
+pub struct Basins<S> {
+    /// The cycles which the seeds ended in.
+    pub cycles: CycleDatabase<S>,
+    /// The attractor of each seed, in the order of [`super::seeds`].
+    pub attractors: Vec<Attractor>,
+}
+
+impl<S> Basins<S> {
+    /// Get the number of seeds which end in `attractor`.
+    pub fn size(&self, attractor: Attractor) -> u64 {
+        self.attractors.iter().filter(|&&a| a == attractor).count() as u64
+    }
+
+    /// Get the number of seeds which end in each attractor.
+    ///
+    /// Attractors which no seed ends in are omitted.
+    pub fn sizes(&self) -> BTreeMap<Attractor, u64> {
+        let mut sizes = BTreeMap::new();
+        for &attractor in &self.attractors {
+            *sizes.entry(attractor).or_insert(0) += 1;
+        }
+        sizes
+    }
+}
+
+impl Census {
+    /// Find the attractor of every seed.
+    ///
+    /// Runs stop as soon as they join a cycle that an earlier seed already ended in,
+    /// so a trajectory which merges into a known attractor is not simulated until its own cycle is detected.
+    /// If the runner's [`cancellation`](crate::runner::Runner::cancellation) token is cancelled,
+    /// only the seeds classified before then are included.
+    pub fn basins<S: PostSystem>(&self) -> Basins<S> {
+        let mut cycles = CycleDatabase::new();
+
+        let attractors = (0..seed_count(self.max_len))
+            .map_while(|index| {
+                Some(match self.classify_with_cycles(index, &mut cycles)? {
+                    (_, Some(id)) => Attractor::Cycle(id),
+                    (record, None) => match record.class {
+                        Class::Halted(_) => Attractor::Halt,
+                        Class::Cycled { .. } | Class::Unknown => Attractor::Unknown,
+                    },
+                })
+            })
+            .collect();
+
+        Basins { cycles, attractors }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cycle::Algorithm,
+        runner::{Outcome, Runner},
+        system::BitString,
+    };
+
+    use super::{super::seeds, *};
+
+    #[test]
+    fn finds_basins() {
+        let runner = Runner::new(100_000, Some(Algorithm::Brent));
+        let census = Census::new(7, runner.clone());
+        let basins = census.basins::<BitString>();
+
+        assert_eq!(basins.attractors.len() as u64, seed_count(7));
+        assert_eq!(basins.sizes().values().sum::<u64>(), seed_count(7));
+        assert_eq!(basins.size(Attractor::Unknown), 0);
+
+        // Each seed's cycle matches the one found by running it alone.
+        for (seed, &attractor) in seeds(7).zip(&basins.attractors) {
+            match runner.run(BitString::new_decompressed(&seed)) {
+                Outcome::Halted(_) => assert_eq!(attractor, Attractor::Halt),
+                Outcome::Cycled(info) => {
+                    let Attractor::Cycle(id) = attractor else {
+                        panic!("{seed:?} should cycle, but ended in {attractor:?}");
+                    };
+
+                    let mut alone = CycleDatabase::new();
+                    let alone_id = alone.insert(&info.entry, info.period);
+                    assert_eq!(basins.cycles.get(id), alone.get(alone_id));
+                }
+                Outcome::BudgetExhausted
+                | Outcome::MemoryLimitExceeded(_)
+                | Outcome::Cancelled(_)
+                | Outcome::TimedOut(_)
+                | Outcome::LengthLimitExceeded(_) => unreachable!(),
+            }
+        }
+
+        // Distinct identifiers are distinct cycles.
+        let representatives: Vec<_> = basins
+            .cycles
+            .iter()
+            .map(|(_, cycle)| &cycle.representative)
+            .collect();
+        for (i, a) in representatives.iter().enumerate() {
+            assert!(!representatives[i + 1..].contains(a));
+        }
+    }
+}
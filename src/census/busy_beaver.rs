@@ -0,0 +1,189 @@
+//! Busy-beaver searches for the longest-lived seeds of each length.
+
+use std::ops::ControlFlow;
+
+use crate::PostSystem;
+
+use super::{Census, Class, Record};
+
+/// A seed which holds a record, and the number of steps that earned it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Champion {
+    /// The compressed seed.
+    pub seed: Vec<bool>,
+    /// The number of steps before halting, or before entering the cycle.
+    pub steps: u64,
+}
+
+/// The records held among the seeds of a single length.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LengthRecords {
+    /// The length of the compressed seeds.
+    pub len: u32,
+    /// The seed which runs longest before halting.
+    pub longest_halt: Option<Champion>,
+    /// The seed which runs longest before entering its cycle.
+    ///
+    /// Seeds whose exact preperiod was not found are ranked by the step at which their cycle was detected instead.
+    pub longest_preperiod: Option<Champion>,
+    /// The number of seeds which were neither halted nor cycled within the step budget.
+    ///
+    /// If this is nonzero, one of these seeds may hold a record which was not found.
+    pub unknown: u64,
+}
+
+/// A table of the busy-beaver records for every seed length up to a maximum.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordTable {
+    lengths: Vec<LengthRecords>,
+}
+
+impl RecordTable {
+    /// Create an empty table for seeds of length at most `max_len`.
+    pub fn new(max_len: u32) -> Self {
+        Self {
+            lengths: (1..=max_len)
+                .map(|len| LengthRecords {
+                    len,
+                    longest_halt: None,
+                    longest_preperiod: None,
+                    unknown: 0,
+                })
+                .collect(),
+        }
+    }
+
+    /// Get the records for each seed length, starting at length one.
+    pub fn lengths(&self) -> &[LengthRecords] {
+        &self.lengths
+    }
+
+    /// Get the records for seeds of length `len`, if the table covers it.
+    pub fn get(&self, len: u32) -> Option<&LengthRecords> {
+        self.lengths.get(len.checked_sub(1)? as usize)
+    }
+
+    /// Account for `record`, returning whether it set a new record.
+    ///
+    /// Ties are broken in favour of the seed seen first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the seed is empty or longer than the table covers.
+    pub fn update(&mut self, record: &Record) -> bool {
+        let entry = &mut self.lengths[record.seed.len() - 1];
+
+        let (champion, steps) = match record.class {
+            Class::Halted(steps) => (&mut entry.longest_halt, steps),
+            Class::Cycled {
+                detected_at,
+                preperiod,
+                ..
+            } => (
+                &mut entry.longest_preperiod,
+                preperiod.unwrap_or(detected_at),
+            ),
+            Class::Unknown => {
+                entry.unknown += 1;
+                return false;
+            }
+        };
+
+        if champion
+            .as_ref()
+            .is_some_and(|champion| champion.steps >= steps)
+        {
+            return false;
+        }
+
+        *champion = Some(Champion {
+            seed: record.seed.clone(),
+            steps,
+        });
+        true
+    }
+}
+
+impl Census {
+    /// Search for the longest-lived seeds of each length, calling `on_record` whenever a seed sets a new record.
+    pub fn busy_beaver<S: PostSystem>(&self, mut on_record: impl FnMut(&Record)) -> RecordTable {
+        let mut table = RecordTable::new(self.max_len);
+
+        let _ = self.run_each::<S, ()>(|record| {
+            if table.update(&record) {
+                on_record(&record);
+            }
+            ControlFlow::Continue(())
+        });
+
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{cycle::Algorithm, runner::Runner, system::BitString};
+
+    use super::*;
+
+    #[test]
+    fn finds_records() {
+        let census = Census::new(7, Runner::new(100_000, Some(Algorithm::Brent)));
+
+        let mut announced = Vec::new();
+        let table = census.busy_beaver::<BitString>(|record| announced.push(record.clone()));
+        let records = census.run::<BitString>();
+
+        for entry in table.lengths() {
+            let of_len = || {
+                records
+                    .iter()
+                    .filter(|r| r.seed.len() == entry.len as usize)
+            };
+
+            let longest_halt = of_len()
+                .filter_map(|r| match r.class {
+                    Class::Halted(steps) => Some(steps),
+                    _ => None,
+                })
+                .max();
+            assert_eq!(entry.longest_halt.as_ref().map(|c| c.steps), longest_halt);
+
+            let longest_preperiod = of_len()
+                .filter_map(|r| match r.class {
+                    Class::Cycled { preperiod, .. } => preperiod,
+                    _ => None,
+                })
+                .max();
+            assert_eq!(
+                entry.longest_preperiod.as_ref().map(|c| c.steps),
+                longest_preperiod
+            );
+
+            assert_eq!(entry.unknown, 0);
+        }
+
+        // Every champion was announced when it took the record.
+        for champion in table
+            .lengths()
+            .iter()
+            .flat_map(|entry| [&entry.longest_halt, &entry.longest_preperiod])
+            .flatten()
+        {
+            assert!(announced.iter().any(|r| r.seed == champion.seed));
+        }
+
+        assert_eq!(
+            table.get(1).unwrap().longest_halt,
+            Some(Champion {
+                seed: vec![false],
+                steps: 1
+            })
+        );
+        assert_eq!(table.get(0), None);
+        assert_eq!(table.get(8), None);
+    }
+}
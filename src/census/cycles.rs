@@ -0,0 +1,193 @@
+//! Statistics of the cycles which seeds end in: how many seeds reach each, and how long they take.
+
+use crate::{
+    cycle::{CycleDatabase, CycleId},
+    PostSystem,
+};
+
+use super::{seed_count, Census, Class};
+
+/// The count, minimum, maximum, and median of a nonempty set of values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Summary {
+    /// The number of values.
+    pub count: u64,
+    /// The smallest value.
+    pub min: u64,
+    /// The largest value.
+    pub max: u64,
+    /// The median value, taking the lower of the two middle values if there are an even number,
+    /// so that it is always one of the values.
+    pub median: u64,
+}
+
+impl Summary {
+    /// Summarize `values`, which must be sorted in ascending order, or return `None` if there are none.
+    pub fn of_sorted(values: &[u64]) -> Option<Self> {
+        Some(Self {
+            count: values.len() as u64,
+            min: *values.first()?,
+            max: *values.last()?,
+            median: values[(values.len() - 1) / 2],
+        })
+    }
+
+    /// Summarize `values`, or return `None` if there are none.
+    pub fn of(values: impl IntoIterator<Item = u64>) -> Option<Self> {
+        let mut values: Vec<u64> = values.into_iter().collect();
+        values.sort_unstable();
+        Self::of_sorted(&values)
+    }
+}
+
+/// The cycles which the seeds in a census end in, with the preperiod of each seed that reaches them.
+#[derive(Debug, Clone)]
+pub struct CycleStatistics<S> {
+    /// The cycles which the seeds ended in.
+    pub cycles: CycleDatabase<S>,
+    /// The preperiods of the seeds ending in each cycle, indexed by [`CycleId::index`], in ascending order.
+    preperiods: Vec<Vec<u64>>,
+}
+
+impl<S> CycleStatistics<S> {
+    /// Get the number of seeds which end in the cycle `id`.
+    pub fn seeds(&self, id: CycleId) -> u64 {
+        self.preperiods[id.index()].len() as u64
+    }
+
+    /// Get the preperiods of the seeds which end in the cycle `id`, in ascending order.
+    pub fn preperiods(&self, id: CycleId) -> &[u64] {
+        &self.preperiods[id.index()]
+    }
+
+    /// Summarize the preperiods of the seeds which end in the cycle `id`.
+    pub fn preperiod_summary(&self, id: CycleId) -> Summary {
+        Summary::of_sorted(self.preperiods(id)).expect("every cycle is reached by some seed")
+    }
+
+    /// Summarize the preperiods of every seed which ends in a cycle, or return `None` if none do.
+    pub fn all_preperiods_summary(&self) -> Option<Summary> {
+        Summary::of(self.preperiods.iter().flatten().copied())
+    }
+
+    /// Summarize the periods of the distinct cycles, or return `None` if no seed ends in a cycle.
+    pub fn period_summary(&self) -> Option<Summary> {
+        Summary::of(self.cycles.iter().map(|(_, cycle)| cycle.period))
+    }
+}
+
+impl Census {
+    /// Find the cycle which each seed ends in, and its exact preperiod.
+    ///
+    /// Runs stop as soon as they join a known cycle, as in [`Self::basins`],
+    /// and each seed ending in a cycle is then run again to the step at which it joined,
+    /// so this takes up to about twice as long as [`Self::basins`] for seeds which cycle.
+    /// If the runner's [`cancellation`](crate::runner::Runner::cancellation) token is cancelled,
+    /// only the seeds classified before then are included.
+    pub fn cycle_statistics<S: PostSystem>(&self) -> CycleStatistics<S> {
+        let mut cycles = CycleDatabase::new();
+        let mut preperiods: Vec<Vec<u64>> = Vec::new();
+
+        for index in 0..seed_count(self.max_len) {
+            let Some((record, id)) = self.classify_with_cycles::<S>(index, &mut cycles) else {
+                break;
+            };
+            let (Some(id), Class::Cycled { detected_at, .. }) = (id, record.class) else {
+                continue;
+            };
+
+            let (preperiod, joined) = cycles
+                .join(&mut S::new_decompressed(&record.seed), detected_at)
+                .expect("the seed was on the cycle when it was detected");
+            debug_assert_eq!(joined, id);
+
+            if id.index() >= preperiods.len() {
+                preperiods.resize_with(id.index() + 1, Vec::new);
+            }
+            preperiods[id.index()].push(preperiod);
+        }
+
+        for preperiods in &mut preperiods {
+            preperiods.sort_unstable();
+        }
+
+        CycleStatistics { cycles, preperiods }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cycle::Algorithm,
+        runner::{Outcome, Runner},
+        system::BitString,
+    };
+
+    use super::{
+        super::{seeds, Attractor},
+        *,
+    };
+
+    #[test]
+    fn summarizes() {
+        assert_eq!(Summary::of([]), None);
+        assert_eq!(
+            Summary::of([5, 1, 3, 2]),
+            Some(Summary {
+                count: 4,
+                min: 1,
+                max: 5,
+                median: 2
+            })
+        );
+        assert_eq!(Summary::of([7]).map(|summary| summary.median), Some(7));
+    }
+
+    #[test]
+    fn finds_exact_preperiods() {
+        let runner = Runner::new(100_000, Some(Algorithm::Brent));
+        let census = Census::new(7, runner);
+        let statistics = census.cycle_statistics::<BitString>();
+        let basins = census.basins::<BitString>();
+
+        assert_eq!(statistics.cycles.len(), basins.cycles.len());
+        for (id, _) in statistics.cycles.iter() {
+            assert_eq!(statistics.seeds(id), basins.size(Attractor::Cycle(id)));
+        }
+
+        // Each seed's preperiod is the first step at which it is on its cycle.
+        for seed in seeds(7) {
+            let system = BitString::new_decompressed(&seed);
+            let Outcome::Cycled(info) =
+                Runner::new(100_000, Some(Algorithm::Brent)).run(system.clone())
+            else {
+                continue;
+            };
+
+            let id = statistics.cycles.find(&info.entry).unwrap();
+            let (preperiod, _) = statistics
+                .cycles
+                .join(&mut system.clone(), info.detected_at)
+                .unwrap();
+            assert!(statistics.preperiods(id).contains(&preperiod));
+
+            let mut before = system;
+            if preperiod > 0 {
+                let _ = before.evolve_multi(preperiod as usize - 1);
+                assert_eq!(statistics.cycles.find(&before), None);
+            }
+        }
+
+        let periods = statistics.period_summary().unwrap();
+        assert_eq!(periods.count, statistics.cycles.len() as u64);
+        assert!(periods.min <= periods.median && periods.median <= periods.max);
+
+        let total: u64 = statistics
+            .cycles
+            .iter()
+            .map(|(id, _)| statistics.seeds(id))
+            .sum();
+        assert_eq!(statistics.all_preperiods_summary().unwrap().count, total);
+    }
+}
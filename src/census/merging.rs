@@ -0,0 +1,177 @@
+//! Classes of seeds whose trajectories merge.
+
+use std::collections::BTreeMap;
+
+use crate::{hash::StateMap, PostSystem};
+
+use super::{seed_at, seed_count, Census};
+
+/// The seeds of a census grouped into classes, such that two seeds are in the same class
+/// if their trajectories pass through a common state, or are linked by a chain of seeds whose trajectories do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MergeClasses {
+    /// The class of each seed, identified by the index of its first seed in the order of [`super::seeds`].
+    classes: Vec<u64>,
+}
+
+impl MergeClasses {
+    /// Get the number of seeds.
+    pub fn len(&self) -> usize {
+        self.classes.len()
+    }
+
+    /// Check whether there are no seeds.
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+
+    /// Get the class of the seed at `index` in the order of [`super::seeds`],
+    /// identified by the index of the first seed in the class.
+    pub fn class(&self, index: u64) -> u64 {
+        self.classes[index as usize]
+    }
+
+    /// Get the number of seeds in each class, identified by the index of its first seed.
+    pub fn sizes(&self) -> BTreeMap<u64, u64> {
+        let mut sizes = BTreeMap::new();
+        for &class in &self.classes {
+            *sizes.entry(class).or_insert(0) += 1;
+        }
+        sizes
+    }
+
+    /// Get the number of classes.
+    pub fn class_count(&self) -> usize {
+        self.classes
+            .iter()
+            .enumerate()
+            .filter(|&(index, &class)| index as u64 == class)
+            .count()
+    }
+}
+
+impl Census {
+    /// Group the seeds by whether their trajectories merge within the runner's [step budget](crate::runner::Runner::step_budget).
+    ///
+    /// Each seed's trajectory is simulated for up to the step budget,
+    /// and the [fingerprint](PostSystem::fingerprint) of every state is kept in an index shared by all the runs,
+    /// so a run can stop as soon as it reaches a state whose future an earlier run has already covered.
+    /// That index takes a few dozen bytes per distinct state visited, which bounds the budgets and seed lengths this is practical for.
+    /// States are compared by fingerprint alone, so two trajectories could be merged falsely by a collision,
+    /// but with 64-bit fingerprints that is vanishingly unlikely.
+    ///
+    /// Only the runner's step budget and [`cancellation`](crate::runner::Runner::cancellation) token are used.
+    /// If the token is cancelled, only the seeds run before then are included.
+    pub fn merge_classes<S: PostSystem>(&self) -> MergeClasses {
+        let budget = self.runner.step_budget;
+
+        // The first seed to visit each state, and the step at which it did.
+        let mut visited: StateMap<u64, (u64, u64)> = StateMap::default();
+        let mut parents: Vec<u64> = Vec::new();
+
+        for index in 0..seed_count(self.max_len) {
+            if let Some(token) = &self.runner.cancellation {
+                if token.is_cancelled() {
+                    break;
+                }
+            }
+
+            parents.push(index);
+            let mut state = S::new_decompressed(&seed_at(index));
+            for step in 0..=budget {
+                match visited.get(&state.fingerprint()) {
+                    Some(&(first, first_step)) => {
+                        union(&mut parents, index, first);
+
+                        // The rest of this trajectory is that of the first visitor, which has been covered
+                        // as far as the budget allows, unless this trajectory reached the state sooner.
+                        if first_step <= step {
+                            break;
+                        }
+                    }
+                    None => {
+                        visited.insert(state.fingerprint(), (index, step));
+                    }
+                }
+
+                if step == budget || state.evolve().is_break() {
+                    break;
+                }
+            }
+        }
+
+        // Each seed's parent precedes it, so resolving them in order leaves every seed pointing at its root.
+        for index in 0..parents.len() {
+            parents[index] = parents[parents[index] as usize];
+        }
+
+        MergeClasses { classes: parents }
+    }
+}
+
+/// Find the root of the class containing `index`, halving the path to it as we go.
+fn find(parents: &mut [u64], mut index: u64) -> u64 {
+    while parents[index as usize] != index {
+        let grandparent = parents[parents[index as usize] as usize];
+        parents[index as usize] = grandparent;
+        index = grandparent;
+    }
+    index
+}
+
+/// Merge the classes containing `a` and `b`, keeping the smaller root so that each class is rooted at its first seed.
+fn union(parents: &mut [u64], a: u64, b: u64) {
+    let (a, b) = (find(parents, a), find(parents, b));
+    parents[a.max(b) as usize] = a.min(b);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{runner::Runner, system::BitString};
+
+    use super::{super::seeds, *};
+
+    #[test]
+    fn groups_merging_trajectories() {
+        const MAX_LEN: u32 = 6;
+        const BUDGET: u64 = 300;
+
+        let classes = Census::new(MAX_LEN, Runner::new(BUDGET, None)).merge_classes::<BitString>();
+        assert_eq!(classes.len() as u64, seed_count(MAX_LEN));
+
+        // Compare with the classes found by intersecting every pair of complete trajectories.
+        let trajectories: Vec<HashSet<BitString>> = seeds(MAX_LEN)
+            .map(|seed| {
+                let mut state = BitString::new_decompressed(&seed);
+                let mut visited = HashSet::from([state.clone()]);
+                for _ in 0..BUDGET {
+                    if state.evolve().is_break() {
+                        break;
+                    }
+                    visited.insert(state.clone());
+                }
+                visited
+            })
+            .collect();
+
+        let mut parents: Vec<u64> = (0..trajectories.len() as u64).collect();
+        for (i, a) in trajectories.iter().enumerate() {
+            for (j, b) in trajectories.iter().enumerate().skip(i + 1) {
+                if !a.is_disjoint(b) {
+                    union(&mut parents, i as u64, j as u64);
+                }
+            }
+        }
+        for index in 0..trajectories.len() as u64 {
+            assert_eq!(classes.class(index), find(&mut parents, index));
+        }
+
+        let sizes = classes.sizes();
+        assert_eq!(sizes.len(), classes.class_count());
+        assert_eq!(sizes.values().sum::<u64>(), seed_count(MAX_LEN));
+        assert!(classes.class_count() < classes.len());
+    }
+}
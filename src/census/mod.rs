@@ -0,0 +1,428 @@
+//! Exhaustive censuses of every seed up to a given length.
+
+mod basins;
+mod busy_beaver;
+mod cycles;
+mod merging;
+mod progress;
+pub mod sequence;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+use std::{io, ops::ControlFlow, path::Path};
+
+use crate::{
+    cycle::{Certificate, CycleDatabase, CycleId},
+    runner::{Outcome, Runner},
+    telemetry, PostSystem,
+};
+
+pub use basins::{Attractor, Basins};
+pub use busy_beaver::{Champion, LengthRecords, RecordTable};
+pub use cycles::{CycleStatistics, Summary};
+pub use merging::MergeClasses;
+
+/// The longest compressed seeds a census can cover, since the number of seeds must fit in a `u64`.
+pub const MAX_LEN: u32 = 62;
+
+/// The classification of a single seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Class {
+    /// The system halted after the given number of steps.
+    Halted(u64),
+    /// The system entered a cycle of length `period`, which was detected at step `detected_at`.
+    ///
+    /// `preperiod` is the exact step at which the system entered the cycle, if the run found it;
+    /// otherwise it is only known to be at most `detected_at`.
+    Cycled {
+        detected_at: u64,
+        period: u64,
+        #[cfg_attr(feature = "serde", serde(default))]
+        preperiod: Option<u64>,
+    },
+    /// The step budget or a memory, time, or length limit ran out before the system halted or a cycle was found.
+    Unknown,
+}
+
+impl<S> From<&Outcome<S>> for Class {
+    fn from(outcome: &Outcome<S>) -> Self {
+        match outcome {
+            Outcome::Halted(steps) => Self::Halted(*steps),
+            Outcome::Cycled(info) => Self::Cycled {
+                detected_at: info.detected_at,
+                period: info.period,
+                preperiod: info.preperiod,
+            },
+            Outcome::BudgetExhausted
+            | Outcome::MemoryLimitExceeded(_)
+            | Outcome::Cancelled(_)
+            | Outcome::TimedOut(_)
+            | Outcome::LengthLimitExceeded(_) => Self::Unknown,
+        }
+    }
+}
+
+/// A row of a census: a seed and its classification.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Record {
+    /// The compressed seed.
+    pub seed: Vec<bool>,
+    /// What happened to the seed.
+    pub class: Class,
+}
+
+impl Record {
+    /// Certify the cycle this seed entered, if it entered one, so the classification can be checked independently.
+    ///
+    /// See [`Certificate::new`] for the cost, and [`crate::cycle::verify_certificate`] to check the result.
+    pub fn certificate<S: PostSystem>(&self) -> Option<Certificate<S>> {
+        match self.class {
+            Class::Cycled {
+                detected_at,
+                period,
+                ..
+            } => Certificate::new(&self.seed, detected_at, period),
+            Class::Halted(_) | Class::Unknown => None,
+        }
+    }
+}
+
+/// A census of every compressed seed up to a given length.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Census {
+    /// The maximum length of the compressed seeds.
+    pub max_len: u32,
+    /// The runner used to classify each seed.
+    pub runner: Runner,
+}
+
+impl Census {
+    /// Create a new census.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_len` is greater than [`MAX_LEN`].
+    pub fn new(max_len: u32, runner: Runner) -> Self {
+        assert!(
+            max_len <= MAX_LEN,
+            "seeds can be at most {MAX_LEN} symbols long, not {max_len}"
+        );
+        Self { max_len, runner }
+    }
+
+    /// Run the census, calling `f` with the record for each seed in the order of [`seeds`].
+    ///
+    /// If `f` returns `Break`, the census stops early and the value is returned.
+    /// If the runner's [`cancellation`](Runner::cancellation) token is cancelled, the census stops before the seed being run.
+    pub fn run_each<S: PostSystem, B>(
+        &self,
+        mut f: impl FnMut(Record) -> ControlFlow<B>,
+    ) -> ControlFlow<B> {
+        for index in 0..seed_count(self.max_len) {
+            let Some(record) = self.classify::<S>(index) else {
+                break;
+            };
+            f(record)?;
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Run the census, returning the record for each seed in the order of [`seeds`].
+    ///
+    /// If the runner's [`cancellation`](Runner::cancellation) token is cancelled, only the seeds classified before then are returned.
+    pub fn run<S: PostSystem>(&self) -> Vec<Record> {
+        let mut records = Vec::new();
+        let _ = self.run_each::<S, ()>(|record| {
+            records.push(record);
+            ControlFlow::Continue(())
+        });
+        records
+    }
+
+    /// Run the census in parallel on the global rayon thread pool.
+    ///
+    /// The records are returned in the order of [`seeds`], exactly as [`Self::run`] would return them.
+    /// If the runner's [`cancellation`](Runner::cancellation) token is cancelled, only the seeds classified before then are returned,
+    /// which need not be a prefix of the seeds.
+    #[cfg(feature = "rayon")]
+    pub fn par_run<S: PostSystem + Send>(&self) -> Vec<Record> {
+        use rayon::prelude::*;
+
+        (0..seed_count(self.max_len))
+            .into_par_iter()
+            .filter_map(|index| self.classify::<S>(index))
+            .collect()
+    }
+
+    /// Run the census, recording progress in the file at `path` so that an interrupted census can be resumed.
+    ///
+    /// If `path` already holds progress from a census with the same parameters,
+    /// only the seeds which were not yet classified are run.
+    /// Seeds which ran out of memory, time, or length are not saved, so they are run again on resume.
+    /// The records are returned in the order of [`seeds`], exactly as [`Self::run`] would return them.
+    /// If the runner's [`cancellation`](Runner::cancellation) token is cancelled, the census stops after saving the seeds classified so far,
+    /// which are returned, so it can be resumed later.
+    pub fn run_resumable<S: PostSystem>(&self, path: impl AsRef<Path>) -> io::Result<Vec<Record>> {
+        progress::run(self, path.as_ref(), |indices| {
+            indices
+                .iter()
+                .map(|&index| self.classify_lasting::<S>(index))
+                .collect()
+        })
+    }
+
+    /// Like [`Self::run_resumable`], but runs each batch of seeds in parallel on the global rayon thread pool.
+    #[cfg(feature = "rayon")]
+    pub fn par_run_resumable<S: PostSystem + Send>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> io::Result<Vec<Record>> {
+        use rayon::prelude::*;
+
+        progress::run(self, path.as_ref(), |indices| {
+            indices
+                .par_iter()
+                .map(|&index| self.classify_lasting::<S>(index))
+                .collect()
+        })
+    }
+
+    /// Run the census, stopping each run early once it joins a cycle in `cycles`.
+    ///
+    /// Each newly discovered cycle is added to `cycles`, so later seeds which fall into it are classified sooner.
+    /// Cycles found this way are reported as detected when the run joined them,
+    /// which is typically earlier than [`Self::run`] would report.
+    /// If the runner's [`cancellation`](Runner::cancellation) token is cancelled, only the seeds classified before then are returned.
+    pub fn run_with_known_cycles<S: PostSystem>(
+        &self,
+        cycles: &mut CycleDatabase<S>,
+    ) -> Vec<Record> {
+        (0..seed_count(self.max_len))
+            .map_while(|index| Some(self.classify_with_cycles(index, cycles)?.0))
+            .collect()
+    }
+
+    /// Classify the seed at `index`, or return `None` if the run was cancelled.
+    fn classify<S: PostSystem>(&self, index: u64) -> Option<Record> {
+        self.classify_lasting::<S>(index).map(|(record, _)| record)
+    }
+
+    /// Like [`Self::classify`], but also return whether the classification is lasting:
+    /// that is, not because a memory, time, or length limit ran out, which a later run might not hit.
+    fn classify_lasting<S: PostSystem>(&self, index: u64) -> Option<(Record, bool)> {
+        let seed = seed_at(index);
+        let outcome = self.runner.run(S::new_decompressed(&seed));
+        let lasting = match outcome {
+            Outcome::Cancelled(_) => return None,
+            Outcome::Halted(_) | Outcome::Cycled(_) | Outcome::BudgetExhausted => true,
+            Outcome::MemoryLimitExceeded(_)
+            | Outcome::TimedOut(_)
+            | Outcome::LengthLimitExceeded(_) => false,
+        };
+
+        let class = (&outcome).into();
+        telemetry::seed_classified(&class);
+        Some((Record { seed, class }, lasting))
+    }
+
+    /// Classify the seed at `index`, stopping once it joins a cycle in `cycles` and adding any new cycle to it.
+    ///
+    /// Returns the record along with the cycle the seed ended in, if any, or `None` if the run was cancelled.
+    fn classify_with_cycles<S: PostSystem>(
+        &self,
+        index: u64,
+        cycles: &mut CycleDatabase<S>,
+    ) -> Option<(Record, Option<CycleId>)> {
+        let seed = seed_at(index);
+        let outcome = self
+            .runner
+            .run_with_known_cycles(S::new_decompressed(&seed), cycles);
+
+        let id = match &outcome {
+            Outcome::Cycled(info) => Some(cycles.insert(&info.entry, info.period)),
+            Outcome::Cancelled(_) => return None,
+            Outcome::Halted(_)
+            | Outcome::BudgetExhausted
+            | Outcome::MemoryLimitExceeded(_)
+            | Outcome::TimedOut(_)
+            | Outcome::LengthLimitExceeded(_) => None,
+        };
+
+        let class = (&outcome).into();
+        telemetry::seed_classified(&class);
+        Some((Record { seed, class }, id))
+    }
+}
+
+/// Enumerate every nonempty compressed seed of length at most `max_len`.
+///
+/// Seeds are ordered by length, and then lexicographically.
+///
+/// # Panics
+///
+/// Panics if `max_len` is greater than [`MAX_LEN`].
+pub fn seeds(max_len: u32) -> impl Iterator<Item = Vec<bool>> {
+    (0..seed_count(max_len)).map(seed_at)
+}
+
+/// Get the number of nonempty compressed seeds of length at most `max_len`.
+///
+/// # Panics
+///
+/// Panics if `max_len` is greater than [`MAX_LEN`].
+pub fn seed_count(max_len: u32) -> u64 {
+    assert!(
+        max_len <= MAX_LEN,
+        "seeds can be at most {MAX_LEN} symbols long, not {max_len}"
+    );
+    (1 << (max_len + 1)) - 2
+}
+
+/// Get the seed at `index` in the order of [`seeds`].
+pub fn seed_at(index: u64) -> Vec<bool> {
+    // Seeds of length `len` occupy the indices `2^len - 2 .. 2^(len + 1) - 2`.
+    let len = (index + 2).ilog2();
+    let bits = index + 2 - (1 << len);
+
+    (0..len).rev().map(|i| (bits >> i) & 1 == 1).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cancel::CancellationToken,
+        cycle::{verify_certificate, Algorithm},
+        system::BitString,
+    };
+
+    use super::*;
+
+    #[test]
+    fn enumerates_seeds() {
+        let seeds: Vec<_> = seeds(2).collect();
+        assert_eq!(
+            seeds,
+            [
+                vec![false],
+                vec![true],
+                vec![false, false],
+                vec![false, true],
+                vec![true, false],
+                vec![true, true],
+            ]
+        );
+
+        assert_eq!(super::seeds(10).count(), (1 << 11) - 2);
+        assert_eq!(seed_at(seed_count(10) - 1), vec![true; 10]);
+        assert_eq!(
+            seed_at(seed_count(MAX_LEN) - 1),
+            vec![true; MAX_LEN as usize]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn refuses_seeds_too_long_to_count() {
+        Census::new(MAX_LEN + 1, Runner::new(10, None));
+    }
+
+    #[test]
+    fn classifies_seeds() {
+        let census = Census::new(3, Runner::new(10_000, Some(Algorithm::Brent)));
+        let records = census.run::<BitString>();
+
+        assert_eq!(records.len(), 14);
+        assert_eq!(records[0].class, Class::Halted(1));
+        assert!(matches!(records[1].class, Class::Cycled { period: 2, .. }));
+        assert_eq!(records[2].class, Class::Halted(4));
+        assert!(records.iter().all(|record| record.class != Class::Unknown));
+    }
+
+    #[test]
+    fn stops_when_cancelled() {
+        let token = CancellationToken::new();
+        let mut runner = Runner::new(10_000, Some(Algorithm::Brent));
+        runner.cancellation = Some(token.clone());
+        let census = Census::new(8, runner);
+
+        let mut records = Vec::new();
+        let _ = census.run_each::<BitString, ()>(|record| {
+            records.push(record);
+            if records.len() == 5 {
+                token.cancel();
+            }
+            ControlFlow::Continue(())
+        });
+        assert_eq!(records.len(), 5);
+
+        assert!(census.run::<BitString>().is_empty());
+        assert!(census.basins::<BitString>().attractors.is_empty());
+    }
+
+    #[test]
+    fn learns_cycles() {
+        let census = Census::new(8, Runner::new(100_000, Some(Algorithm::Brent)));
+        let mut cycles = CycleDatabase::new();
+
+        let learned = census.run_with_known_cycles::<BitString>(&mut cycles);
+        let records = census.run::<BitString>();
+        assert_eq!(learned.len(), records.len());
+
+        for (learned, record) in learned.iter().zip(&records) {
+            match (learned.class, record.class) {
+                (
+                    Class::Cycled {
+                        detected_at,
+                        period,
+                        preperiod,
+                    },
+                    Class::Cycled {
+                        detected_at: expected_detected_at,
+                        period: expected_period,
+                        preperiod: expected_preperiod,
+                    },
+                ) => {
+                    assert_eq!(period, expected_period);
+                    if let Some(preperiod) = preperiod {
+                        assert_eq!(Some(preperiod), expected_preperiod);
+                    }
+                    assert!(detected_at <= expected_detected_at.max(1 << 10));
+                }
+                (class, expected) => assert_eq!(class, expected),
+            }
+        }
+
+        assert!(!cycles.is_empty());
+    }
+
+    #[test]
+    fn certifies_records() {
+        let census = Census::new(8, Runner::new(100_000, Some(Algorithm::Brent)));
+        let records = census.run::<BitString>();
+        let learned = census.run_with_known_cycles::<BitString>(&mut CycleDatabase::new());
+
+        for record in records.iter().chain(&learned) {
+            let certificate = record.certificate::<BitString>();
+            match record.class {
+                Class::Cycled { period, .. } => {
+                    let certificate = certificate.expect("cycled records should be certified");
+                    assert_eq!(certificate.period, period);
+                    assert_eq!(verify_certificate(&certificate), Ok(()));
+                }
+                _ => assert_eq!(certificate, None),
+            }
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn runs_in_parallel() {
+        let census = Census::new(8, Runner::new(10_000, Some(Algorithm::Brent)));
+
+        assert_eq!(census.par_run::<BitString>(), census.run::<BitString>());
+    }
+}
@@ -0,0 +1,297 @@
+//! Persisted census progress.
+//!
+//! The progress file is a text log: a header line identifying the format, a line holding the census parameters,
+//! and then one line per classified seed, giving its index followed by its classification.
+//! Seeds which ran out of memory, time, or length are not saved, since a later run might classify them.
+//! Lines are only ever appended, so a crash can at worst leave a truncated final line, which is discarded on resume.
+
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use super::{seed_at, seed_count, Census, Class, Record};
+
+/// The first line of every progress file.
+const HEADER: &str = "post-tag census progress v2";
+
+/// The number of seeds classified between writes to the progress file.
+const BATCH: usize = 1024;
+
+/// Run `census`, persisting progress to `path` and classifying batches of seed indices with `classify`.
+///
+/// `classify` returns `None` for seeds whose runs were cancelled, which are not saved, and after which no more batches are run.
+/// Otherwise it returns the record along with whether it is lasting; records which are not are returned but not saved.
+pub(super) fn run(
+    census: &Census,
+    path: &Path,
+    classify: impl Fn(&[u64]) -> Vec<Option<(Record, bool)>>,
+) -> io::Result<Vec<Record>> {
+    let runner = &census.runner;
+    let parameters = format!(
+        "max_len {} budget {} detection {:?} memory_limit {:?} time_limit {:?} length_limit {:?}",
+        census.max_len,
+        runner.step_budget,
+        runner.cycle_detection,
+        runner.memory_limit,
+        runner.time_limit,
+        runner.length_limit
+    );
+
+    let mut done = load(path, &parameters)?;
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if file.metadata()?.len() == 0 {
+        writeln!(file, "{HEADER}\n{parameters}")?;
+    }
+    let mut out = BufWriter::new(file);
+
+    let pending: Vec<u64> = (0..seed_count(census.max_len))
+        .filter(|index| !done.contains_key(index))
+        .collect();
+
+    for batch in pending.chunks(BATCH) {
+        let records = classify(batch);
+
+        let mut cancelled = false;
+        for (&index, record) in batch.iter().zip(&records) {
+            let Some((record, lasting)) = record else {
+                cancelled = true;
+                continue;
+            };
+            if *lasting {
+                writeln!(out, "{index} {}", format_class(&record.class))?;
+            }
+            done.insert(index, record.class);
+        }
+
+        out.flush()?;
+        out.get_ref().sync_data()?;
+        if cancelled {
+            break;
+        }
+    }
+
+    Ok(done
+        .into_iter()
+        .map(|(index, class)| Record {
+            seed: seed_at(index),
+            class,
+        })
+        .collect())
+}
+
+/// Load the classified seeds from the progress file at `path`, if it exists.
+///
+/// A truncated final line is removed from the file.
+fn load(path: &Path, parameters: &str) -> io::Result<BTreeMap<u64, Class>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut reader = BufReader::new(file);
+    let mut done = BTreeMap::new();
+    let mut valid_len = 0;
+    let mut line = String::new();
+
+    for line_number in 0.. {
+        line.clear();
+        let read = reader.read_line(&mut line)?;
+        if read == 0 || !line.ends_with('\n') {
+            break;
+        }
+
+        let content = line.trim_end();
+        match line_number {
+            0 if content != HEADER => return Err(invalid_data("not a census progress file")),
+            1 if content != parameters => {
+                return Err(invalid_data(format!(
+                    "progress file is for a different census ({content}), expected {parameters}"
+                )))
+            }
+            0 | 1 => {}
+            _ => {
+                let (index, class) = content
+                    .split_once(' ')
+                    .and_then(|(index, class)| Some((index.parse().ok()?, parse_class(class)?)))
+                    .ok_or_else(|| invalid_data(format!("invalid progress line {content:?}")))?;
+                done.insert(index, class);
+            }
+        }
+
+        valid_len += read as u64;
+    }
+
+    OpenOptions::new()
+        .write(true)
+        .open(path)?
+        .set_len(valid_len)?;
+
+    Ok(done)
+}
+
+fn format_class(class: &Class) -> String {
+    match class {
+        Class::Halted(steps) => format!("halt {steps}"),
+        Class::Cycled {
+            detected_at,
+            period,
+            preperiod,
+        } => {
+            let preperiod =
+                preperiod.map_or_else(|| "-".to_owned(), |preperiod| preperiod.to_string());
+            format!("cycle {detected_at} {period} {preperiod}")
+        }
+        Class::Unknown => "unknown".to_owned(),
+    }
+}
+
+fn parse_class(s: &str) -> Option<Class> {
+    let mut parts = s.split(' ');
+
+    let class = match parts.next()? {
+        "halt" => Class::Halted(parts.next()?.parse().ok()?),
+        "cycle" => Class::Cycled {
+            detected_at: parts.next()?.parse().ok()?,
+            period: parts.next()?.parse().ok()?,
+            preperiod: match parts.next()? {
+                "-" => None,
+                preperiod => Some(preperiod.parse().ok()?),
+            },
+        },
+        "unknown" => Class::Unknown,
+        _ => return None,
+    };
+
+    parts.next().is_none().then_some(class)
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{cancel::CancellationToken, cycle::Algorithm, runner::Runner, system::BitString};
+
+    use super::*;
+
+    #[test]
+    fn resumes() {
+        let path = std::env::temp_dir().join(format!(
+            "post-tag-census-progress-{}.txt",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        let census = Census::new(11, Runner::new(10_000, Some(Algorithm::Brent)));
+        let expected = census.run::<BitString>();
+
+        let records = census.run_resumable::<BitString>(&path).unwrap();
+        assert_eq!(records, expected);
+
+        // Simulate an interrupted census, with a truncated final line.
+        let contents = fs::read_to_string(&path).unwrap();
+        let kept: Vec<_> = contents.lines().take(1500).collect();
+        fs::write(&path, kept.join("\n") + "\n17 hal").unwrap();
+
+        let records = census.run_resumable::<BitString>(&path).unwrap();
+        assert_eq!(records, expected);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap().lines().count(),
+            contents.lines().count()
+        );
+
+        // Progress from a different census is rejected.
+        let other = Census::new(11, Runner::new(20_000, Some(Algorithm::Brent)));
+        assert!(other.run_resumable::<BitString>(&path).is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn resumes_after_cancellation() {
+        let path = std::env::temp_dir().join(format!(
+            "post-tag-census-cancelled-{}.txt",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        let mut census = Census::new(8, Runner::new(10_000, Some(Algorithm::Brent)));
+        let expected = census.run::<BitString>();
+
+        let token = CancellationToken::new();
+        token.cancel();
+        census.runner.cancellation = Some(token);
+        assert_eq!(census.run_resumable::<BitString>(&path).unwrap(), []);
+
+        census.runner.cancellation = Some(CancellationToken::new());
+        assert_eq!(census.run_resumable::<BitString>(&path).unwrap(), expected);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reruns_seeds_which_hit_limits() {
+        let path = std::env::temp_dir().join(format!(
+            "post-tag-census-limited-{}.txt",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        let mut runner = Runner::new(10_000, Some(Algorithm::Brent));
+        runner.length_limit = Some(20);
+        let census = Census::new(8, runner);
+        let expected = census.run::<BitString>();
+        assert!(expected.iter().any(|record| record.class == Class::Unknown));
+
+        assert_eq!(census.run_resumable::<BitString>(&path).unwrap(), expected);
+        let saved = fs::read_to_string(&path).unwrap();
+        assert!(!saved.contains("unknown"));
+        assert_eq!(
+            saved.lines().count() - 2,
+            expected
+                .iter()
+                .filter(|record| record.class != Class::Unknown)
+                .count()
+        );
+        assert_eq!(census.run_resumable::<BitString>(&path).unwrap(), expected);
+
+        // Progress from a census with different limits is rejected.
+        let mut other = census.clone();
+        other.runner.length_limit = None;
+        assert!(other.run_resumable::<BitString>(&path).is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn formats_classes() {
+        for class in [
+            Class::Halted(12),
+            Class::Cycled {
+                detected_at: 34,
+                period: 6,
+                preperiod: Some(21),
+            },
+            Class::Cycled {
+                detected_at: 34,
+                period: 6,
+                preperiod: None,
+            },
+            Class::Unknown,
+        ] {
+            assert_eq!(parse_class(&format_class(&class)), Some(class));
+        }
+
+        assert_eq!(parse_class("halt"), None);
+        assert_eq!(parse_class("cycle 1 2"), None);
+        assert_eq!(parse_class("cycle 1 2 3 4"), None);
+    }
+}
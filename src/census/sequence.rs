@@ -0,0 +1,127 @@
+//! Integer sequences indexed by numeric seeds, for OEIS-style analysis.
+
+use std::fmt::Write;
+
+use crate::{
+    cycle::CycleDatabase,
+    runner::{Outcome, Runner},
+    PostSystem,
+};
+
+/// The sentinel term written for seeds which never halt, or whose fate is unknown.
+pub const NEVER_HALTS: i64 = -1;
+
+/// The sequence whose `k`th term is the halting time of the seed [`PostSystem::from_seed_u128`]`(k)`, for `k >= 1`.
+///
+/// Terms are computed on demand and cached, so extending the sequence only runs the new seeds.
+/// Cycles found along the way are remembered, so seeds which fall into them are classified early.
+#[derive(Debug, Clone)]
+pub struct HaltingTimes<S> {
+    runner: Runner,
+    cycles: CycleDatabase<S>,
+    /// The halting time of seed `k` is at index `k - 1`.
+    terms: Vec<Option<u64>>,
+}
+
+impl<S: PostSystem> HaltingTimes<S> {
+    /// Create an empty sequence whose terms are computed by `runner`.
+    pub fn new(runner: Runner) -> Self {
+        Self {
+            runner,
+            cycles: CycleDatabase::new(),
+            terms: Vec::new(),
+        }
+    }
+
+    /// Get the halting time of seed `k`, or `Some(None)` if it does not halt within the runner's budget.
+    ///
+    /// Returns `None` if the runner's [`cancellation`](Runner::cancellation) token was cancelled before the term was computed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero.
+    pub fn get(&mut self, k: usize) -> Option<Option<u64>> {
+        assert!(k > 0, "the sequence starts at seed 1");
+        self.first(k).get(k - 1).copied()
+    }
+
+    /// Get the halting times of seeds `1..=n`.
+    ///
+    /// If the runner's [`cancellation`](Runner::cancellation) token is cancelled, only the terms computed before then are returned.
+    pub fn first(&mut self, n: usize) -> &[Option<u64>] {
+        for k in self.terms.len() + 1..=n {
+            let system = S::from_seed_u128(k as u128);
+            let term = match self.runner.run_with_known_cycles(system, &self.cycles) {
+                Outcome::Halted(steps) => Some(steps),
+                Outcome::Cycled(info) => {
+                    self.cycles.insert(&info.entry, info.period);
+                    None
+                }
+                Outcome::BudgetExhausted
+                | Outcome::MemoryLimitExceeded(_)
+                | Outcome::TimedOut(_)
+                | Outcome::LengthLimitExceeded(_) => None,
+                Outcome::Cancelled(_) => break,
+            };
+            self.terms.push(term);
+        }
+
+        &self.terms[..n.min(self.terms.len())]
+    }
+}
+
+/// Format `terms` as a comma-separated list, writing [`NEVER_HALTS`] for seeds which never halt.
+pub fn format_terms(terms: &[Option<u64>]) -> String {
+    let mut out = String::new();
+    for (i, term) in terms.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        match term {
+            Some(steps) => write!(out, "{steps}"),
+            None => write!(out, "{NEVER_HALTS}"),
+        }
+        .expect("writing to a string cannot fail");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{cancel::CancellationToken, cycle::Algorithm, seeds, system::BitString};
+
+    use super::*;
+
+    #[test]
+    fn generates_terms() {
+        let mut times =
+            HaltingTimes::<BitString>::new(Runner::new(1_000_000, Some(Algorithm::Brent)));
+
+        // Seed 14 is `1110`, the longest-halting seed of length four.
+        assert_eq!(
+            format_terms(times.first(20)),
+            "-1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 418, -1, -1, -1, 419, -1, -1"
+        );
+
+        assert_eq!(times.get(0b11111), Some(Some(409)));
+        assert_eq!(times.first(3), [None, None, None]);
+        assert_eq!(
+            times.get(seeds::SEED_6008.seed as usize),
+            Some(Some(261741))
+        );
+    }
+
+    #[test]
+    fn stops_when_cancelled() {
+        let mut runner = Runner::new(1_000_000, Some(Algorithm::Brent));
+        let token = CancellationToken::new();
+        runner.cancellation = Some(token.clone());
+        let mut times = HaltingTimes::<BitString>::new(runner);
+
+        assert_eq!(times.get(14), Some(Some(418)));
+        token.cancel();
+        assert_eq!(times.get(20), None);
+        assert_eq!(times.get(14), Some(Some(418)));
+        assert_eq!(times.first(20).len(), 14);
+    }
+}
@@ -0,0 +1,274 @@
+//! An SQLite store of census results, which can be queried while a census is still running.
+
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::{runner::Outcome, PostSystem};
+
+use super::{seed_at, seed_count, Census, Class, Record};
+
+/// The number of seeds inserted per transaction.
+const BATCH: u64 = 1024;
+
+/// A table of per-seed census outcomes in an SQLite database.
+///
+/// Results are stored in the `outcomes` table, with the columns:
+///
+/// - `seed`: the compressed seed as a string of `0`s and `1`s, which is the primary key
+/// - `len`: the length of the compressed seed
+/// - `outcome`: one of `halt`, `cycle`, or `unknown`
+/// - `steps`: the halting step, or the step at which the cycle was detected, or `NULL` if unknown
+/// - `period`: the period of the cycle, or `NULL` if the seed did not cycle
+/// - `max_length`: the length of the longest state the seed passed through
+/// - `preperiod`: the exact step at which the seed entered its cycle, or `NULL` if it did not cycle or the step was not found
+#[derive(Debug)]
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    /// Open the store in the database at `path`, creating the database and table if necessary.
+    pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Open a store in a new in-memory database.
+    pub fn open_in_memory() -> rusqlite::Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> rusqlite::Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS outcomes (
+                seed TEXT PRIMARY KEY NOT NULL,
+                len INTEGER NOT NULL,
+                outcome TEXT NOT NULL CHECK (outcome IN ('halt', 'cycle', 'unknown')),
+                steps INTEGER,
+                period INTEGER,
+                max_length INTEGER NOT NULL,
+                preperiod INTEGER
+            );
+            CREATE INDEX IF NOT EXISTS outcomes_by_len ON outcomes (len, outcome);",
+        )?;
+
+        // Stores created before the preperiod was recorded lack its column.
+        if conn.prepare("SELECT preperiod FROM outcomes").is_err() {
+            conn.execute_batch("ALTER TABLE outcomes ADD COLUMN preperiod INTEGER")?;
+        }
+
+        Ok(Self { conn })
+    }
+
+    /// Get the underlying connection, for running arbitrary queries.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Store the outcome of a seed, replacing any previous outcome for it.
+    pub fn insert(&mut self, record: &Record, max_length: u64) -> rusqlite::Result<()> {
+        insert(&self.conn, record, max_length)
+    }
+
+    /// Get the stored classification and maximum length of `seed`, if it has been stored.
+    pub fn get(&self, seed: &[bool]) -> rusqlite::Result<Option<(Class, u64)>> {
+        self.conn
+            .query_row(
+                "SELECT outcome, steps, period, max_length, preperiod FROM outcomes WHERE seed = ?1",
+                [format_seed(seed)],
+                |row| {
+                    let class = match row.get_ref(0)?.as_str()? {
+                        "halt" => Class::Halted(row.get(1)?),
+                        "cycle" => Class::Cycled {
+                            detected_at: row.get(1)?,
+                            period: row.get(2)?,
+                            preperiod: row.get(4)?,
+                        },
+                        _ => Class::Unknown,
+                    };
+                    Ok((class, row.get(3)?))
+                },
+            )
+            .optional()
+    }
+
+    /// Get the number of stored seeds.
+    pub fn len(&self) -> rusqlite::Result<u64> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM outcomes", [], |row| row.get(0))
+    }
+
+    /// Check whether no seeds are stored.
+    pub fn is_empty(&self) -> rusqlite::Result<bool> {
+        self.len().map(|len| len == 0)
+    }
+}
+
+impl Census {
+    /// Run the census, storing each outcome in `store` as it is found.
+    ///
+    /// Seeds which are already in the store are skipped, so an interrupted census can be resumed with the same store.
+    /// Outcomes are committed in batches, so they can be queried from another connection while the census runs.
+    /// If the runner's [`cancellation`](crate::runner::Runner::cancellation) token is cancelled,
+    /// the census stops after committing the outcomes found so far, so it can be resumed later.
+    pub fn run_into_sqlite<S: PostSystem>(&self, store: &mut SqliteStore) -> rusqlite::Result<()> {
+        let count = seed_count(self.max_len);
+
+        for batch in (0..count).step_by(BATCH as usize) {
+            let tx = store.conn.transaction()?;
+
+            for index in batch..(batch + BATCH).min(count) {
+                let seed = seed_at(index);
+                let stored: bool = tx.query_row(
+                    "SELECT EXISTS (SELECT 1 FROM outcomes WHERE seed = ?1)",
+                    [format_seed(&seed)],
+                    |row| row.get(0),
+                )?;
+                if stored {
+                    continue;
+                }
+
+                let (outcome, max_length) = self.runner.run_with_peak(S::new_decompressed(&seed));
+                if let Outcome::Cancelled(_) = outcome {
+                    return tx.commit();
+                }
+
+                let record = Record {
+                    seed,
+                    class: (&outcome).into(),
+                };
+                insert(&tx, &record, max_length)?;
+            }
+
+            tx.commit()?;
+        }
+
+        Ok(())
+    }
+}
+
+fn insert(conn: &Connection, record: &Record, max_length: u64) -> rusqlite::Result<()> {
+    let (outcome, steps, period, preperiod) = match record.class {
+        Class::Halted(steps) => ("halt", Some(steps), None, None),
+        Class::Cycled {
+            detected_at,
+            period,
+            preperiod,
+        } => ("cycle", Some(detected_at), Some(period), preperiod),
+        Class::Unknown => ("unknown", None, None, None),
+    };
+
+    conn.prepare_cached(
+        "INSERT OR REPLACE INTO outcomes (seed, len, outcome, steps, period, max_length, preperiod)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    )?
+    .execute(params![
+        format_seed(&record.seed),
+        record.seed.len(),
+        outcome,
+        steps,
+        period,
+        max_length,
+        preperiod
+    ])?;
+
+    Ok(())
+}
+
+fn format_seed(seed: &[bool]) -> String {
+    seed.iter().map(|&b| if b { '1' } else { '0' }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{cancel::CancellationToken, cycle::Algorithm, runner::Runner, system::BitString};
+
+    use super::*;
+
+    #[test]
+    fn stores_outcomes() {
+        let census = Census::new(6, Runner::new(10_000, Some(Algorithm::Brent)));
+        let mut store = SqliteStore::open_in_memory().unwrap();
+
+        // Store a stale outcome, which the census must not overwrite.
+        store
+            .insert(
+                &Record {
+                    seed: vec![true],
+                    class: Class::Unknown,
+                },
+                0,
+            )
+            .unwrap();
+
+        census.run_into_sqlite::<BitString>(&mut store).unwrap();
+        assert_eq!(store.len().unwrap(), seed_count(6));
+        assert_eq!(store.get(&[true]).unwrap(), Some((Class::Unknown, 0)));
+
+        for record in census.run::<BitString>().into_iter().skip(2) {
+            let (class, max_length) = store.get(&record.seed).unwrap().unwrap();
+            assert_eq!(class, record.class);
+            assert!(max_length >= 3 * record.seed.len() as u64);
+        }
+
+        assert_eq!(store.get(&[false; 7]).unwrap(), None);
+
+        let halting: u64 = store
+            .connection()
+            .query_row(
+                "SELECT COUNT(*) FROM outcomes WHERE len = 2 AND outcome = 'halt'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(halting, 1);
+    }
+
+    #[test]
+    fn stops_when_cancelled() {
+        let mut census = Census::new(6, Runner::new(10_000, Some(Algorithm::Brent)));
+        let mut store = SqliteStore::open_in_memory().unwrap();
+
+        let token = CancellationToken::new();
+        token.cancel();
+        census.runner.cancellation = Some(token);
+        census.run_into_sqlite::<BitString>(&mut store).unwrap();
+        assert!(store.is_empty().unwrap());
+
+        census.runner.cancellation = Some(CancellationToken::new());
+        census.run_into_sqlite::<BitString>(&mut store).unwrap();
+        for record in census.run::<BitString>() {
+            assert_eq!(store.get(&record.seed).unwrap().unwrap().0, record.class);
+        }
+    }
+
+    #[test]
+    fn adds_preperiod_column() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE outcomes (
+                seed TEXT PRIMARY KEY NOT NULL,
+                len INTEGER NOT NULL,
+                outcome TEXT NOT NULL,
+                steps INTEGER,
+                period INTEGER,
+                max_length INTEGER NOT NULL
+            );
+            INSERT INTO outcomes VALUES ('11', 2, 'cycle', 6, 2, 9);",
+        )
+        .unwrap();
+
+        let store = SqliteStore::with_connection(conn).unwrap();
+        assert_eq!(
+            store.get(&[true, true]).unwrap(),
+            Some((
+                Class::Cycled {
+                    detected_at: 6,
+                    period: 2,
+                    preperiod: None
+                },
+                9
+            ))
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! Certificates of cycling, which can be checked independently of the detection that produced them.
+
+use std::{error::Error, fmt, ops::ControlFlow};
+
+use crate::PostSystem;
+
+/// A compact, checkable claim that a seed enters a cycle.
+///
+/// The claim is that the seed reaches `entry` after exactly `preperiod` steps and not before,
+/// and that `entry` recurs every `period` steps and no more often.
+/// Use [`verify_certificate`] to check it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Certificate<S> {
+    /// The compressed seed.
+    pub seed: Vec<bool>,
+    /// The number of steps taken before the system first enters the cycle, often written μ.
+    pub preperiod: u64,
+    /// The length of the cycle, often written λ.
+    pub period: u64,
+    /// The first state on the cycle, which the system reaches at step `preperiod`.
+    pub entry: S,
+}
+
+impl<S: PostSystem> Certificate<S> {
+    /// Certify that `seed` enters a cycle of length `period` by step `detected_at`, as reported by cycle detection.
+    ///
+    /// The exact preperiod and entry state are found by simulating at most `2 * detected_at + period` steps.
+    /// Returns `None` if the seed does not enter a cycle of that length by then.
+    pub fn new(seed: &[bool], detected_at: u64, period: u64) -> Option<Self> {
+        if period == 0 {
+            return None;
+        }
+
+        // Keep `ahead` one period in front of `behind`, so they first meet where the cycle begins.
+        let behind = S::new_decompressed(seed);
+        let mut ahead = behind.clone();
+        evolve_by(&mut ahead, period).continue_value()?;
+        let (preperiod, entry) = super::find_entry(behind, ahead, detected_at)?;
+
+        Some(Self {
+            seed: seed.to_vec(),
+            preperiod,
+            period,
+            entry,
+        })
+    }
+}
+
+/// A reason a [`Certificate`] was rejected by [`verify_certificate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CertificateError {
+    /// The certificate claims a cycle of length zero.
+    ZeroPeriod,
+    /// The system halted at the given step, so it never cycles.
+    Halted(u64),
+    /// The state at step `preperiod` is not the claimed entry state.
+    EntryMismatch,
+    /// The system was already on the cycle one step before the claimed preperiod.
+    PreperiodNotMinimal,
+    /// The entry state recurred after the given number of steps, before the claimed period was up.
+    PeriodNotMinimal(u64),
+    /// The entry state did not recur after the claimed period.
+    NotPeriodic,
+}
+
+impl fmt::Display for CertificateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroPeriod => write!(f, "the claimed period is zero"),
+            Self::Halted(step) => write!(f, "the system halted at step {step}"),
+            Self::EntryMismatch => write!(f, "the state at the claimed preperiod is not the entry"),
+            Self::PreperiodNotMinimal => write!(
+                f,
+                "the system entered the cycle before the claimed preperiod"
+            ),
+            Self::PeriodNotMinimal(period) => {
+                write!(
+                    f,
+                    "the entry recurred after {period} steps, before the claimed period"
+                )
+            }
+            Self::NotPeriodic => write!(f, "the entry did not recur after the claimed period"),
+        }
+    }
+}
+
+impl Error for CertificateError {}
+
+/// Check a [`Certificate`] by simulating its seed for `preperiod + period` steps.
+///
+/// This relies only on [`PostSystem::evolve`] and state comparison, not on any cycle detection algorithm,
+/// so a census result backed by a certificate can be trusted without trusting how it was found.
+pub fn verify_certificate<S: PostSystem>(
+    certificate: &Certificate<S>,
+) -> Result<(), CertificateError> {
+    let Certificate {
+        seed,
+        preperiod,
+        period,
+        entry,
+    } = certificate;
+
+    if *period == 0 {
+        return Err(CertificateError::ZeroPeriod);
+    }
+
+    // The state just before the entry, which must not lie on the cycle if the preperiod is minimal.
+    let mut state = S::new_decompressed(seed);
+    let mut before = None;
+    if *preperiod > 0 {
+        if let ControlFlow::Break(n) = evolve_by(&mut state, preperiod - 1) {
+            return Err(CertificateError::Halted(n));
+        }
+        before = Some(state.clone());
+        if state.evolve().is_break() {
+            return Err(CertificateError::Halted(preperiod - 1));
+        }
+    }
+
+    if state != *entry {
+        return Err(CertificateError::EntryMismatch);
+    }
+
+    for i in 0..*period {
+        // The state `period - 1` steps into the cycle is the entry's predecessor on it.
+        if i == period - 1 && before.as_ref() == Some(&state) {
+            return Err(CertificateError::PreperiodNotMinimal);
+        }
+
+        if state.evolve().is_break() {
+            return Err(CertificateError::Halted(preperiod + i));
+        }
+
+        if i + 1 < *period && state == *entry {
+            return Err(CertificateError::PeriodNotMinimal(i + 1));
+        }
+    }
+
+    if state != *entry {
+        return Err(CertificateError::NotPeriodic);
+    }
+
+    Ok(())
+}
+
+/// Evolve `system` by `steps` steps, returning `Break(n)` if it halts after `n` steps.
+fn evolve_by<S: PostSystem>(system: &mut S, steps: u64) -> ControlFlow<u64> {
+    let mut taken = 0;
+    while taken < steps {
+        let chunk = (steps - taken).min(usize::MAX as u64);
+        if let ControlFlow::Break(n) = system.evolve_multi(chunk as usize) {
+            return ControlFlow::Break(taken + n as u64);
+        }
+        taken += chunk;
+    }
+
+    ControlFlow::Continue(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cycle::{detect, Algorithm, Detection},
+        seeds::{self, Fate},
+        system::{BitString, VecDequeBools},
+    };
+
+    use super::*;
+
+    #[test]
+    fn certifies_detected_cycles() {
+        // `100` enters the cycle `10100 -> 001101 -> 10100` after four steps.
+        let certificate =
+            Certificate::<BitString>::new(&[true], 100, 2).expect("`100` should cycle");
+        assert_eq!(certificate.preperiod, 4);
+        assert_eq!(certificate.entry.to_string(), "10100");
+        assert_eq!(verify_certificate(&certificate), Ok(()));
+
+        let system: BitString = seeds::SEED_5854.system();
+        let Detection::Cycle(info) = detect(system.clone(), Algorithm::Brent, 1_000_000) else {
+            panic!("seed 5854 should cycle");
+        };
+        let certificate = Certificate::<VecDequeBools>::new(
+            &system.as_compressed().unwrap(),
+            info.detected_at,
+            info.period,
+        )
+        .unwrap();
+        assert_eq!(
+            seeds::SEED_5854.fate,
+            Fate::Cycles {
+                preperiod: certificate.preperiod,
+                period: certificate.period
+            }
+        );
+        assert_eq!(verify_certificate(&certificate), Ok(()));
+    }
+
+    #[test]
+    fn refuses_to_certify_wrong_claims() {
+        assert_eq!(Certificate::<BitString>::new(&[true], 3, 2), None);
+        assert_eq!(Certificate::<BitString>::new(&[true], 100, 0), None);
+        assert_eq!(Certificate::<BitString>::new(&[false, false], 100, 2), None);
+    }
+
+    #[test]
+    fn rejects_invalid_certificates() {
+        let valid = Certificate::<BitString>::new(&[true], 100, 2).unwrap();
+
+        let check = |f: fn(&mut Certificate<BitString>)| {
+            let mut certificate = valid.clone();
+            f(&mut certificate);
+            verify_certificate(&certificate)
+        };
+
+        assert_eq!(check(|c| c.period = 0), Err(CertificateError::ZeroPeriod));
+        assert_eq!(
+            check(|c| {
+                c.seed = vec![false, false];
+                c.preperiod = 5;
+            }),
+            Err(CertificateError::Halted(4))
+        );
+        assert_eq!(
+            check(|c| c.preperiod = 3),
+            Err(CertificateError::EntryMismatch)
+        );
+        assert_eq!(
+            check(|c| c.preperiod = 6),
+            Err(CertificateError::PreperiodNotMinimal)
+        );
+        assert_eq!(
+            check(|c| c.period = 4),
+            Err(CertificateError::PeriodNotMinimal(2))
+        );
+        assert_eq!(check(|c| c.period = 1), Err(CertificateError::NotPeriodic));
+    }
+}
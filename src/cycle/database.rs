@@ -0,0 +1,274 @@
+//! A database of known cycles, for recognizing when a trajectory joins one.
+
+use std::hash::Hash;
+
+use crate::{hash::StateMap, PostSystem};
+
+/// Identifies a cycle in a [`CycleDatabase`].
+///
+/// Identifiers are assigned in order of insertion, starting from zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CycleId(usize);
+
+impl CycleId {
+    /// Get the index of the cycle in insertion order.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// A cycle stored in a [`CycleDatabase`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KnownCycle<S> {
+    /// The canonical state of the cycle: its shortest state, ties broken lexicographically.
+    ///
+    /// This does not depend on where the cycle was entered, so the same cycle always has the same representative.
+    pub representative: S,
+    /// The length of the cycle.
+    pub period: u64,
+}
+
+/// A set of known cycles, indexed by every state on them.
+///
+/// Every state of every cycle is stored, so this is only suitable for cycles with reasonably short periods.
+#[derive(Debug, Clone)]
+pub struct CycleDatabase<S> {
+    cycles: Vec<KnownCycle<S>>,
+    members: StateMap<S, CycleId>,
+}
+
+impl<S> Default for CycleDatabase<S> {
+    fn default() -> Self {
+        Self {
+            cycles: Vec::new(),
+            members: StateMap::default(),
+        }
+    }
+}
+
+impl<S: PostSystem> CycleDatabase<S> {
+    /// Create an empty database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the cycle of length `period` through `entry`, returning its identifier.
+    ///
+    /// If the cycle is already known, nothing is added and the existing identifier is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `entry` does not lie on a cycle of length `period`.
+    pub fn insert(&mut self, entry: &S, period: u64) -> CycleId {
+        if let Some(id) = self.find(entry) {
+            return id;
+        }
+
+        let id = CycleId(self.cycles.len());
+
+        let members = members(entry, period);
+        let representative = canonical(&members).clone();
+
+        self.members
+            .extend(members.into_iter().map(|member| (member, id)));
+        self.cycles.push(KnownCycle {
+            representative,
+            period,
+        });
+
+        id
+    }
+
+    /// Add every cycle in `other` which is not already known, returning the identifier each of its cycles has in `self`.
+    ///
+    /// Cycles are matched by their [representatives](KnownCycle::representative),
+    /// so a cycle found from different seeds by different databases is only stored once.
+    pub fn merge(&mut self, other: &CycleDatabase<S>) -> Vec<CycleId> {
+        other
+            .iter()
+            .map(|(_, cycle)| self.insert(&cycle.representative, cycle.period))
+            .collect()
+    }
+
+    /// Evolve `state` until it lies on a known cycle, returning the number of steps taken and the cycle.
+    ///
+    /// Unlike [`Runner::run_with_known_cycles`](crate::runner::Runner::run_with_known_cycles),
+    /// membership is checked at every step, so the number of steps is exactly the preperiod
+    /// of a trajectory which ends in a known cycle.
+    /// Returns `None`, leaving `state` wherever it stopped, if it halts or is not on a known cycle after `limit` steps.
+    pub fn join(&self, state: &mut S, limit: u64) -> Option<(u64, CycleId)> {
+        for step in 0..=limit {
+            if let Some(id) = self.find(state) {
+                return Some((step, id));
+            }
+            if step == limit || state.evolve().is_break() {
+                break;
+            }
+        }
+
+        None
+    }
+}
+
+/// Compute the canonical representative of the cycle of length `period` through `entry`,
+/// as stored in [`KnownCycle::representative`].
+///
+/// Every state on a cycle has the same representative, so it identifies the cycle regardless of where it was entered.
+///
+/// # Panics
+///
+/// Panics if `entry` does not lie on a cycle of length `period`.
+pub fn representative<S: PostSystem>(entry: &S, period: u64) -> S {
+    canonical(&members(entry, period)).clone()
+}
+
+/// Collect the states of the cycle of length `period` through `entry`, starting from `entry`.
+fn members<S: PostSystem>(entry: &S, period: u64) -> Vec<S> {
+    let mut members = Vec::with_capacity(period as usize);
+    let mut state = entry.clone();
+    for _ in 0..period {
+        members.push(state.clone());
+        assert!(state.evolve().is_continue(), "entry is not on a cycle");
+    }
+    assert!(
+        state == *entry,
+        "entry is not on a cycle of the given period"
+    );
+
+    members
+}
+
+/// Choose the canonical state among the `members` of a cycle: the shortest, ties broken lexicographically.
+fn canonical<S: PostSystem>(members: &[S]) -> &S {
+    members
+        .iter()
+        .min_by_key(|member| (member.length(), member.as_list()))
+        .expect("cycles have at least one state")
+}
+
+impl<S: Eq + Hash> CycleDatabase<S> {
+    /// Find the known cycle which `state` lies on, if any.
+    pub fn find(&self, state: &S) -> Option<CycleId> {
+        self.members.get(state).copied()
+    }
+}
+
+impl<S> CycleDatabase<S> {
+    /// Get the cycle with identifier `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is from a different database.
+    pub fn get(&self, id: CycleId) -> &KnownCycle<S> {
+        &self.cycles[id.0]
+    }
+
+    /// Get the number of known cycles.
+    pub fn len(&self) -> usize {
+        self.cycles.len()
+    }
+
+    /// Check whether no cycles are known.
+    pub fn is_empty(&self) -> bool {
+        self.cycles.is_empty()
+    }
+
+    /// Iterate over the known cycles in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (CycleId, &KnownCycle<S>)> {
+        self.cycles
+            .iter()
+            .enumerate()
+            .map(|(i, cycle)| (CycleId(i), cycle))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        census::seeds,
+        cycle::{detect, Algorithm, Detection},
+        system::BitString,
+    };
+
+    use super::*;
+
+    #[test]
+    fn stores_cycles() {
+        // `100` enters the cycle `10100 -> 001101 -> 10100` after four steps.
+        let mut entry = BitString::new_decompressed(&[true]);
+        let _ = entry.evolve_multi(4);
+
+        let mut database = CycleDatabase::new();
+        let id = database.insert(&entry, 2);
+
+        let mut other = entry.clone();
+        let _ = other.evolve();
+        assert_eq!(database.find(&other), Some(id));
+        assert_eq!(database.insert(&other, 2), id);
+        assert_eq!(database.len(), 1);
+
+        assert_eq!(database.get(id).period, 2);
+        assert_eq!(
+            database.get(id).representative,
+            "10100".parse::<BitString>().unwrap()
+        );
+
+        assert_eq!(database.find(&BitString::new_decompressed(&[true])), None);
+
+        let mut seed = BitString::new_decompressed(&[true]);
+        assert_eq!(database.join(&mut seed.clone(), 3), None);
+        assert_eq!(database.join(&mut seed, 10), Some((4, id)));
+        assert_eq!(seed, entry);
+    }
+
+    #[test]
+    fn merges_databases() {
+        // Seeds ending in the same cycle, entered at different states.
+        let entries: Vec<(BitString, u64)> = seeds(7)
+            .filter_map(|seed| {
+                match detect(
+                    BitString::new_decompressed(&seed),
+                    Algorithm::Brent,
+                    100_000,
+                ) {
+                    Detection::Cycle(info) => Some((info.entry, info.period)),
+                    Detection::Halted(_) | Detection::Exhausted => None,
+                }
+            })
+            .collect();
+
+        let mut all = CycleDatabase::new();
+        for (entry, period) in &entries {
+            all.insert(entry, *period);
+        }
+
+        let (front, back) = entries.split_at(entries.len() / 2);
+        let mut merged = CycleDatabase::new();
+        let mut other = CycleDatabase::new();
+        for (entry, period) in front {
+            merged.insert(entry, *period);
+        }
+        for (entry, period) in back {
+            other.insert(entry, *period);
+        }
+
+        let ids = merged.merge(&other);
+        assert_eq!(merged.len(), all.len());
+        for ((_, cycle), id) in other.iter().zip(ids) {
+            assert_eq!(merged.get(id), cycle);
+        }
+
+        for (entry, period) in &entries {
+            let id = all.find(entry).unwrap();
+            assert_eq!(representative(entry, *period), all.get(id).representative);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_non_cycles() {
+        CycleDatabase::new().insert(&BitString::new_decompressed(&[true]), 2);
+    }
+}
@@ -0,0 +1,440 @@
+//! Cycle detection.
+
+mod certificate;
+mod database;
+mod parallel;
+
+use std::ops::ControlFlow;
+
+use crate::{hash::StateMap, PostSystem};
+
+pub use certificate::{verify_certificate, Certificate, CertificateError};
+pub use database::{representative, CycleDatabase, CycleId, KnownCycle};
+pub use parallel::{detect_distinguished, detect_parallel_floyd, SYNC_INTERVAL};
+
+/// A cycle detection algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Algorithm {
+    /// Floyd's tortoise and hare, which keeps the tortoise, the hare, and the initial state, to find the preperiod.
+    Floyd,
+    /// Brent's power-of-two algorithm, which keeps the same three states as Floyd's but compares less often.
+    Brent,
+    /// Remember every visited state in a hash map, which finds cycles as soon as they close but uses a lot of memory.
+    HashSet,
+}
+
+/// Information about a detected cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CycleInfo<S> {
+    /// The step at which the cycle was detected.
+    ///
+    /// The system has entered the cycle by this step, so this is an upper bound on the preperiod.
+    pub detected_at: u64,
+    /// The length of the cycle.
+    pub period: u64,
+    /// The exact number of steps before the system entered the cycle, or `None` if it is only bounded by [`Self::detected_at`].
+    ///
+    /// A [`Detector`] finds this after detecting the cycle, by stepping through the trajectory again from the start,
+    /// which takes about as many steps again as the preperiod.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub preperiod: Option<u64>,
+    /// The state of the system at step `detected_at`, which lies on the cycle.
+    pub entry: S,
+}
+
+impl<S: PostSystem> CycleInfo<S> {
+    /// Compute the canonical [representative] of the cycle, which is the same wherever it was entered.
+    pub fn representative(&self) -> S {
+        representative(&self.entry, self.period)
+    }
+}
+
+/// Find where a trajectory enters its cycle, given its state at step 0 and its state at step `d`, where `d` is a multiple of the period:
+/// that is the first step `i` at which its states at steps `i` and `i + d` are equal.
+///
+/// Returns that step and the state there, or `None` if the states do not meet within `limit` steps or either halts first.
+fn find_entry<S: crate::TagSystem>(mut behind: S, mut ahead: S, limit: u64) -> Option<(u64, S)> {
+    let mut preperiod = 0;
+    while behind != ahead {
+        if preperiod == limit {
+            return None;
+        }
+
+        behind.evolve().continue_value()?;
+        ahead.evolve().continue_value()?;
+        preperiod += 1;
+    }
+
+    Some((preperiod, behind))
+}
+
+/// The result of running cycle detection on a system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Detection<S> {
+    /// The system entered a cycle.
+    Cycle(CycleInfo<S>),
+    /// The system halted after the given number of steps.
+    Halted(u64),
+    /// No cycle was found without simulating more than the step budget.
+    Exhausted,
+}
+
+/// Run cycle detection on `system`, simulating no step beyond `budget`.
+pub fn detect<S: crate::TagSystem>(system: S, algorithm: Algorithm, budget: u64) -> Detection<S> {
+    let mut detector = Detector::new(system, algorithm, budget);
+
+    loop {
+        if let ControlFlow::Break(detection) = detector.advance() {
+            return detection;
+        }
+    }
+}
+
+/// Incremental cycle detection, which can be advanced a little at a time.
+///
+/// This allows callers to interleave detection with other work, like checkpointing.
+#[derive(Debug, Clone)]
+pub struct Detector<S> {
+    inner: Inner<S>,
+    budget: u64,
+    peak_length: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Inner<S> {
+    /// Floyd's algorithm: advance the hare two steps for every step of the tortoise until they meet.
+    Floyd {
+        start: S,
+        tortoise: S,
+        hare: S,
+        step: u64,
+    },
+    /// Brent's algorithm: teleport the tortoise to the hare at every power of two until the hare catches up to it.
+    Brent {
+        start: S,
+        tortoise: S,
+        hare: S,
+        step: u64,
+        power: u64,
+        period: u64,
+    },
+    /// Remember every visited state until one repeats.
+    HashSet {
+        seen: StateMap<S, u64>,
+        system: S,
+        step: u64,
+        /// The total memory usage of the states in `seen`.
+        seen_memory: usize,
+    },
+}
+
+impl<S: crate::TagSystem> Detector<S> {
+    /// Start detecting cycles in `system`, simulating no step beyond `budget`.
+    pub fn new(system: S, algorithm: Algorithm, budget: u64) -> Self {
+        let peak_length = system.length();
+        let inner = match algorithm {
+            Algorithm::Floyd => Inner::Floyd {
+                start: system.clone(),
+                tortoise: system.clone(),
+                hare: system,
+                step: 0,
+            },
+            Algorithm::Brent => Inner::Brent {
+                start: system.clone(),
+                tortoise: system.clone(),
+                hare: system,
+                step: 0,
+                power: 1,
+                period: 0,
+            },
+            Algorithm::HashSet => Inner::HashSet {
+                seen: [(system.clone(), 0)].into_iter().collect(),
+                seen_memory: system.memory_usage(),
+                system,
+                step: 0,
+            },
+        };
+
+        Self {
+            inner,
+            budget,
+            peak_length,
+        }
+    }
+
+    /// Advance detection by one iteration, returning `Break` once detection has finished.
+    pub fn advance(&mut self) -> ControlFlow<Detection<S>> {
+        match &mut self.inner {
+            Inner::Floyd {
+                start,
+                tortoise,
+                hare,
+                step,
+            } => {
+                if 2 * (*step + 1) > self.budget {
+                    return ControlFlow::Break(Detection::Exhausted);
+                }
+
+                for i in 0..2 {
+                    if hare.evolve().is_break() {
+                        return ControlFlow::Break(Detection::Halted(2 * *step + i));
+                    }
+                    self.peak_length = self.peak_length.max(hare.length());
+                }
+                let _ = tortoise.evolve();
+                *step += 1;
+
+                if tortoise != hare {
+                    return ControlFlow::Continue(());
+                }
+
+                // The tortoise is now on the cycle, so walk the hare around it once to find the period.
+                let mut period = 0;
+                loop {
+                    let _ = hare.evolve();
+                    period += 1;
+
+                    if tortoise == hare {
+                        break;
+                    }
+                }
+
+                // The hare is `step` steps ahead of the tortoise, a multiple of the period, so restarting the tortoise finds the entry.
+                ControlFlow::Break(Detection::Cycle(CycleInfo {
+                    detected_at: *step,
+                    period,
+                    preperiod: find_entry(start.clone(), tortoise.clone(), *step)
+                        .map(|(preperiod, _)| preperiod),
+                    entry: tortoise.clone(),
+                }))
+            }
+            Inner::Brent {
+                start,
+                tortoise,
+                hare,
+                step,
+                power,
+                period,
+            } => {
+                if *step == self.budget {
+                    return ControlFlow::Break(Detection::Exhausted);
+                }
+
+                if hare.evolve().is_break() {
+                    return ControlFlow::Break(Detection::Halted(*step));
+                }
+                self.peak_length = self.peak_length.max(hare.length());
+                *step += 1;
+                *period += 1;
+
+                if tortoise == hare {
+                    let mut ahead = start.clone();
+                    let _ = ahead.evolve_multi(*period as usize);
+                    return ControlFlow::Break(Detection::Cycle(CycleInfo {
+                        detected_at: *step,
+                        period: *period,
+                        preperiod: find_entry(start.clone(), ahead, *step)
+                            .map(|(preperiod, _)| preperiod),
+                        entry: hare.clone(),
+                    }));
+                }
+
+                if period == power {
+                    tortoise.clone_from(hare);
+                    *power *= 2;
+                    *period = 0;
+                }
+
+                ControlFlow::Continue(())
+            }
+            Inner::HashSet {
+                seen,
+                system,
+                step,
+                seen_memory,
+            } => {
+                if *step == self.budget {
+                    return ControlFlow::Break(Detection::Exhausted);
+                }
+
+                if system.evolve().is_break() {
+                    return ControlFlow::Break(Detection::Halted(*step));
+                }
+                self.peak_length = self.peak_length.max(system.length());
+                *step += 1;
+
+                match seen.insert(system.clone(), *step) {
+                    Some(first) => ControlFlow::Break(Detection::Cycle(CycleInfo {
+                        detected_at: *step,
+                        period: *step - first,
+                        preperiod: Some(first),
+                        entry: system.clone(),
+                    })),
+                    None => {
+                        *seen_memory += system.memory_usage();
+                        ControlFlow::Continue(())
+                    }
+                }
+            }
+        }
+    }
+
+    /// Get the furthest step simulated so far.
+    pub fn step(&self) -> u64 {
+        match &self.inner {
+            Inner::Floyd { step, .. } => 2 * step,
+            Inner::Brent { step, .. } | Inner::HashSet { step, .. } => *step,
+        }
+    }
+
+    /// Get the length of the longest state simulated so far.
+    pub fn peak_length(&self) -> usize {
+        self.peak_length
+    }
+
+    /// Estimate the number of bytes of memory occupied by the states kept by the detector.
+    pub fn memory_usage(&self) -> usize {
+        match &self.inner {
+            Inner::Floyd {
+                start,
+                tortoise,
+                hare,
+                ..
+            }
+            | Inner::Brent {
+                start,
+                tortoise,
+                hare,
+                ..
+            } => start.memory_usage() + tortoise.memory_usage() + hare.memory_usage(),
+            Inner::HashSet {
+                system,
+                seen_memory,
+                ..
+            } => system.memory_usage() + seen_memory,
+        }
+    }
+
+    /// Get the furthest state simulated so far, which is the state at [`Self::step`].
+    pub fn state(&self) -> &S {
+        match &self.inner {
+            Inner::Floyd { hare, .. } | Inner::Brent { hare, .. } => hare,
+            Inner::HashSet { system, .. } => system,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        seeds::{self, Fate},
+        system::BitString,
+        PostSystem,
+    };
+
+    use super::*;
+
+    const ALGORITHMS: [Algorithm; 3] = [Algorithm::Floyd, Algorithm::Brent, Algorithm::HashSet];
+
+    #[test]
+    fn detects_cycles() {
+        // `100` enters the cycle `10100 -> 001101 -> 10100` after four steps.
+        for algorithm in ALGORITHMS {
+            let Detection::Cycle(info) =
+                detect(BitString::new_decompressed(&[true]), algorithm, 100)
+            else {
+                panic!("{algorithm:?} found no cycle");
+            };
+
+            assert_eq!(info.period, 2, "{algorithm:?}");
+            assert_eq!(info.preperiod, Some(4), "{algorithm:?}");
+            assert!(info.detected_at >= 4, "{algorithm:?}");
+
+            let mut entry = BitString::new_decompressed(&[true]);
+            let _ = entry.evolve_multi(info.detected_at as usize);
+            assert_eq!(info.entry, entry, "{algorithm:?}");
+            assert_eq!(info.representative(), "10100".parse().unwrap());
+        }
+    }
+
+    #[test]
+    fn finds_exact_preperiods() {
+        for seed in crate::census::seeds(7) {
+            let system = BitString::new_decompressed(&seed);
+            let detections = ALGORITHMS.map(|algorithm| detect(system.clone(), algorithm, 100_000));
+            let Detection::Cycle(exact) = &detections[2] else {
+                continue;
+            };
+
+            let preperiod = exact.preperiod.unwrap();
+            let mut entry = system.clone();
+            let _ = entry.evolve_multi(preperiod as usize);
+            let mut after = entry.clone();
+            let _ = after.evolve_multi(exact.period as usize);
+            assert_eq!(entry, after, "{system}");
+
+            if preperiod > 0 {
+                let mut before = system.clone();
+                let _ = before.evolve_multi(preperiod as usize - 1);
+                let mut after = before.clone();
+                let _ = after.evolve_multi(exact.period as usize);
+                assert_ne!(before, after, "{system}");
+            }
+
+            for (algorithm, detection) in ALGORITHMS.iter().zip(&detections) {
+                let Detection::Cycle(info) = detection else {
+                    panic!("{algorithm:?} found no cycle for {system}");
+                };
+                assert_eq!(info.preperiod, Some(preperiod), "{algorithm:?}, {system}");
+            }
+        }
+
+        let Fate::Cycles { preperiod, .. } = seeds::SEED_5854.fate else {
+            unreachable!();
+        };
+        let Detection::Cycle(info) = detect(
+            seeds::SEED_5854.system::<BitString>(),
+            Algorithm::Brent,
+            10_000_000,
+        ) else {
+            panic!("seed 5854 should cycle");
+        };
+        assert_eq!(info.preperiod, Some(preperiod));
+    }
+
+    #[test]
+    fn detects_halting() {
+        for algorithm in ALGORITHMS {
+            assert_eq!(
+                detect(BitString::new_decompressed(&[false, false]), algorithm, 100),
+                Detection::Halted(4),
+                "{algorithm:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn tracks_peak_length() {
+        // `100` passes through `1101`, `11101`, `011101`, `10100`, and then alternates with `001101`.
+        for algorithm in ALGORITHMS {
+            let mut detector = Detector::new(BitString::new_decompressed(&[true]), algorithm, 100);
+            while detector.advance().is_continue() {}
+
+            assert_eq!(detector.peak_length(), 6, "{algorithm:?}");
+        }
+    }
+
+    #[test]
+    fn respects_budget() {
+        for algorithm in ALGORITHMS {
+            assert_eq!(
+                detect(BitString::new_decompressed(&[true]), algorithm, 3),
+                Detection::Exhausted,
+                "{algorithm:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,395 @@
+//! Cycle detection on more than one thread.
+
+use std::{
+    ops::ControlFlow,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Barrier, Mutex,
+    },
+    thread,
+};
+
+use crate::{hash::StateMap, PostSystem};
+
+use super::{Algorithm, CycleInfo, Detection, Detector};
+
+/// The number of tortoise steps between comparisons in [`detect_parallel_floyd`].
+pub const SYNC_INTERVAL: u64 = 1 << 14;
+
+/// Run Floyd's algorithm with the tortoise and hare evolving concurrently on two threads,
+/// simulating no step beyond `budget`.
+///
+/// The threads only meet every [`SYNC_INTERVAL`] tortoise steps to compare the tortoise and hare,
+/// so in between each evolves in bulk with [`PostSystem::evolve_multi`] and the hare's two steps per tortoise step set the pace,
+/// rather than the three steps per iteration of [`Algorithm::Floyd`](super::Algorithm::Floyd).
+/// Because the states are compared less often, the cycle is found at the first comparison at which the tortoise is on it
+/// and the hare is a whole number of periods ahead, which can be up to [`SYNC_INTERVAL`] periods later than Floyd's algorithm would find it.
+/// The period and entry state are exact, as ever.
+pub fn detect_parallel_floyd<S: PostSystem + Send>(system: S, budget: u64) -> Detection<S> {
+    let initial = system.clone();
+    let tortoise = Mutex::new(system.clone());
+    let mut hare = system;
+
+    // The tortoise's thread evolves it by `round` steps between the two barriers, until told to stop.
+    let round = Mutex::new(0);
+    let stop = AtomicBool::new(false);
+    let start = Barrier::new(2);
+    let end = Barrier::new(2);
+
+    thread::scope(|scope| {
+        scope.spawn(|| loop {
+            start.wait();
+            if stop.load(Ordering::Relaxed) {
+                return;
+            }
+
+            let n = *round.lock().unwrap();
+            let _ = tortoise.lock().unwrap().evolve_multi(n as usize);
+            end.wait();
+        });
+
+        let mut step = 0;
+        let detection = loop {
+            let n = SYNC_INTERVAL.min(budget / 2 - step);
+            if n == 0 {
+                break Detection::Exhausted;
+            }
+
+            *round.lock().unwrap() = n;
+            start.wait();
+            let evolved = hare.evolve_multi(2 * n as usize);
+            end.wait();
+
+            if let ControlFlow::Break(taken) = evolved {
+                break Detection::Halted(2 * step + taken as u64);
+            }
+            step += n;
+
+            let tortoise = tortoise.lock().unwrap();
+            if *tortoise != hare {
+                continue;
+            }
+
+            // The tortoise is now on the cycle, so walk the hare around it once to find the period.
+            let mut period = 0;
+            loop {
+                let _ = hare.evolve();
+                period += 1;
+
+                if *tortoise == hare {
+                    break;
+                }
+            }
+
+            break Detection::Cycle(CycleInfo {
+                detected_at: step,
+                period,
+                preperiod: super::find_entry(initial.clone(), tortoise.clone(), step)
+                    .map(|(preperiod, _)| preperiod),
+                entry: tortoise.clone(),
+            });
+        };
+
+        stop.store(true, Ordering::Relaxed);
+        start.wait();
+        detection
+    })
+}
+
+/// Detect cycles in each of `systems` on `threads` threads, sharing distinguished points between them,
+/// and simulating no step of any system beyond `budget`.
+///
+/// A state is distinguished if its fingerprint has at least `trailing_zeros` trailing zeros,
+/// so about one in `2^trailing_zeros` states is, and every distinguished state reached is remembered along with who reached it and when.
+/// A system which reaches a distinguished state it reached before has found its cycle,
+/// and one which reaches a state another system reached has joined that system's trajectory, so it stops and takes its fate from the other's.
+/// Each system also runs [`Algorithm::Brent`], which finds cycles with no distinguished states.
+///
+/// This pays off when many trajectories merge into a few long ones, as in a census,
+/// since each shared stretch is only simulated once, using memory proportional to the steps simulated divided by `2^trailing_zeros`.
+/// A system which joins another that exhausts its budget is reported as [`Detection::Exhausted`], even if it had budget to spare.
+/// Steps and cycle entries are exact, but a system's fate is taken from the system it joined, so it can lie beyond `budget`.
+pub fn detect_distinguished<S: PostSystem + Send>(
+    systems: Vec<S>,
+    budget: u64,
+    trailing_zeros: u32,
+    threads: usize,
+) -> Vec<Detection<S>> {
+    let count = systems.len();
+    let queue = Mutex::new(systems.into_iter().enumerate());
+    let shared = Mutex::new(Shared {
+        points: StateMap::default(),
+        joins: vec![None; count],
+    });
+
+    let mut runs: Vec<Option<Run<S>>> = (0..count).map(|_| None).collect();
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.max(1))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut finished = Vec::new();
+                    loop {
+                        let Some((index, system)) = queue.lock().unwrap().next() else {
+                            return finished;
+                        };
+                        let run = run_distinguished(index, system, budget, trailing_zeros, &shared);
+                        finished.push((index, run));
+                    }
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            for (index, run) in worker.join().unwrap() {
+                runs[index] = Some(run);
+            }
+        }
+    });
+
+    let joins = shared.into_inner().unwrap().joins;
+    let mut detections: Vec<Option<Detection<S>>> = (0..count).map(|_| None).collect();
+    for index in 0..count {
+        // Find the chain of unresolved joins from this system, then resolve it from the far end.
+        let mut chain = vec![index];
+        while let Some(join) = joins[*chain.last().unwrap()] {
+            if detections[join.into].is_some() {
+                break;
+            }
+            chain.push(join.into);
+        }
+
+        for &index in chain.iter().rev() {
+            if detections[index].is_some() {
+                continue;
+            }
+
+            let detection = match runs[index].take().unwrap() {
+                Run::Finished(detection) => detection,
+                Run::Joined { state, offset } => {
+                    let join = joins[index].unwrap();
+                    match detections[join.into].as_ref().unwrap() {
+                        Detection::Halted(steps) => {
+                            Detection::Halted(join.step + steps - join.their)
+                        }
+                        Detection::Exhausted => Detection::Exhausted,
+                        Detection::Cycle(info) if info.detected_at >= join.their => {
+                            Detection::Cycle(CycleInfo {
+                                detected_at: join.step + info.detected_at - join.their,
+                                period: info.period,
+                                // If the other system entered the cycle after the join, so did this one, as many steps later.
+                                preperiod: info
+                                    .preperiod
+                                    .filter(|&preperiod| preperiod > join.their)
+                                    .map(|preperiod| join.step + preperiod - join.their),
+                                entry: info.entry.clone(),
+                            })
+                        }
+                        Detection::Cycle(info) => {
+                            // The other system was already on the cycle, so this one was too when they met.
+                            let mut entry = state;
+                            let _ = entry.evolve_multi(offset as usize);
+                            Detection::Cycle(CycleInfo {
+                                detected_at: join.step,
+                                period: info.period,
+                                preperiod: None,
+                                entry,
+                            })
+                        }
+                    }
+                }
+            };
+            detections[index] = Some(detection);
+        }
+    }
+
+    detections.into_iter().map(Option::unwrap).collect()
+}
+
+/// The state shared between the threads of [`detect_distinguished`].
+struct Shared<S> {
+    /// The distinguished states reached so far, with the first system to reach each and the step at which it did.
+    points: StateMap<S, (usize, u64)>,
+    /// How each system which joined another did so.
+    joins: Vec<Option<Join>>,
+}
+
+/// A system joining the trajectory of another in [`detect_distinguished`].
+#[derive(Debug, Clone, Copy)]
+struct Join {
+    /// The index of the other system, which had not joined any other when this one joined it.
+    into: usize,
+    /// The step of this system at which its state equals the other's at `their`.
+    step: u64,
+    /// The step of the other system.
+    their: u64,
+}
+
+/// How a single system's run in [`detect_distinguished`] ended.
+enum Run<S> {
+    /// Detection finished on its own.
+    Finished(Detection<S>),
+    /// The system joined another, as recorded in [`Shared::joins`], when it was `offset` steps short of the join's step.
+    Joined { state: S, offset: u64 },
+}
+
+/// Run detection on the system at `index` for [`detect_distinguished`].
+fn run_distinguished<S: PostSystem>(
+    index: usize,
+    system: S,
+    budget: u64,
+    trailing_zeros: u32,
+    shared: &Mutex<Shared<S>>,
+) -> Run<S> {
+    let mut detector = Detector::new(system, Algorithm::Brent, budget);
+
+    loop {
+        if let ControlFlow::Break(detection) = detector.advance() {
+            return Run::Finished(detection);
+        }
+
+        let state = detector.state();
+        if state.fingerprint().trailing_zeros() < trailing_zeros {
+            continue;
+        }
+        let step = detector.step();
+
+        let mut shared = shared.lock().unwrap();
+        let Some(&(mut owner, mut at)) = shared.points.get(state) else {
+            shared.points.insert(state.clone(), (index, step));
+            continue;
+        };
+
+        // If the point's system has since joined another, follow it there, counting the steps this system would take to catch up.
+        let mut offset = 0;
+        while let Some(join) = shared.joins[owner] {
+            offset += join.step - at;
+            (owner, at) = (join.into, join.their);
+        }
+
+        if owner != index {
+            shared.joins[index] = Some(Join {
+                into: owner,
+                step: step + offset,
+                their: at,
+            });
+            return Run::Joined {
+                state: state.clone(),
+                offset,
+            };
+        }
+        drop(shared);
+
+        // This system will return to the state at `at`, so that state and every later one is on the cycle.
+        let entry = state.clone();
+        let mut hare = entry.clone();
+        let mut period = 0;
+        loop {
+            let _ = hare.evolve();
+            period += 1;
+
+            if hare == entry {
+                break;
+            }
+        }
+
+        return Run::Finished(Detection::Cycle(CycleInfo {
+            detected_at: step,
+            period,
+            preperiod: None,
+            entry,
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        census,
+        cycle::detect,
+        seeds::{self, Fate},
+        system::BitString,
+    };
+
+    use super::*;
+
+    #[test]
+    fn detects_cycles() {
+        let Detection::Cycle(info) =
+            detect_parallel_floyd(BitString::new_decompressed(&[true]), 100)
+        else {
+            panic!("`100` should cycle");
+        };
+        assert_eq!(info.period, 2);
+
+        let mut entry = BitString::new_decompressed(&[true]);
+        let _ = entry.evolve_multi(info.detected_at as usize);
+        assert_eq!(info.entry, entry);
+
+        let Fate::Cycles { preperiod, period } = seeds::SEED_5854.fate else {
+            unreachable!();
+        };
+        let Detection::Cycle(info) =
+            detect_parallel_floyd(seeds::SEED_5854.system::<BitString>(), 10_000_000)
+        else {
+            panic!("seed 5854 should cycle");
+        };
+        assert_eq!(info.period, period);
+        assert_eq!(info.preperiod, Some(preperiod));
+        assert!(info.detected_at >= preperiod);
+    }
+
+    #[test]
+    fn detects_halting() {
+        assert_eq!(
+            detect_parallel_floyd(seeds::SEED_6008.system::<BitString>(), 10_000_000),
+            Detection::Halted(261_741)
+        );
+        assert_eq!(
+            detect_parallel_floyd(BitString::new_decompressed(&[false, false]), 100),
+            detect(
+                BitString::new_decompressed(&[false, false]),
+                Algorithm::Floyd,
+                100
+            )
+        );
+    }
+
+    #[test]
+    fn respects_budget() {
+        assert_eq!(
+            detect_parallel_floyd(BitString::new_decompressed(&[true]), 3),
+            Detection::Exhausted
+        );
+        assert_eq!(
+            detect_parallel_floyd(seeds::SEED_5854.system::<BitString>(), 100_000),
+            Detection::Exhausted
+        );
+    }
+
+    #[test]
+    fn detects_distinguished() {
+        let systems: Vec<BitString> = census::seeds(6)
+            .map(|seed| BitString::new_decompressed(&seed))
+            .collect();
+
+        for trailing_zeros in [0, 3, 64] {
+            let detections = detect_distinguished(systems.clone(), 100_000, trailing_zeros, 4);
+
+            for (system, detection) in systems.iter().zip(detections) {
+                match (detect(system.clone(), Algorithm::Brent, 100_000), detection) {
+                    (Detection::Cycle(expected), Detection::Cycle(info)) => {
+                        assert_eq!(info.period, expected.period, "{system}");
+                        if let Some(preperiod) = info.preperiod {
+                            assert_eq!(Some(preperiod), expected.preperiod, "{system}");
+                        }
+
+                        let mut entry = system.clone();
+                        let _ = entry.evolve_multi(info.detected_at as usize);
+                        assert_eq!(info.entry, entry, "{system}");
+                    }
+                    (expected, detection) => assert_eq!(detection, expected, "{system}"),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,385 @@
+//! Logs of per-step changes, for stepping backward through a trajectory without re-running it from the seed.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    ops::ControlFlow,
+};
+
+use crate::{snapshot, PostSystem};
+
+/// The most snapshots a [`DeltaLog`] keeps before thinning them out.
+const MAX_SNAPSHOTS: usize = 256;
+
+/// The change made to a system by a single step: three symbols deleted from the front and a production appended.
+///
+/// The production is determined by the first deleted symbol, so only the deleted symbols are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Delta(u8);
+
+impl Delta {
+    /// Create the delta of a step which deleted the symbols `deleted`.
+    pub fn new(deleted: [bool; 3]) -> Self {
+        Self(deleted[0] as u8 | (deleted[1] as u8) << 1 | (deleted[2] as u8) << 2)
+    }
+
+    /// Get the symbols deleted from the front of the system.
+    pub fn deleted(self) -> [bool; 3] {
+        [self.0 & 1 != 0, self.0 & 2 != 0, self.0 & 4 != 0]
+    }
+
+    /// Get the production appended to the end of the system.
+    pub fn appended(self) -> &'static [bool] {
+        match self.0 & 1 != 0 {
+            false => &[false, false],
+            true => &[true, true, false, true],
+        }
+    }
+}
+
+/// A log of the [`Delta`]s of consecutive steps of a single trajectory.
+///
+/// Evolving a system through the log records each step, after which the system can be moved to any earlier step
+/// in time proportional to its length plus the number of steps undone, and then forward again.
+/// The log takes one byte per step, and can be limited to the most recent steps with [`Self::with_max_len`].
+/// Earlier steps can still be reached by keeping periodic snapshots with [`Self::with_snapshots`],
+/// from which the trajectory is simulated again.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeltaLog {
+    /// The step before the oldest logged delta.
+    start: u64,
+    /// The logged deltas, oldest first.
+    deltas: VecDeque<Delta>,
+    /// The step of the system the log is following, which is before the last logged step after seeking backward.
+    step: u64,
+    /// The most deltas to keep, dropping the oldest ones beyond it.
+    max_len: Option<usize>,
+    /// The number of steps between snapshots, if any are kept.
+    snapshot_every: Option<u64>,
+    /// Snapshots of the system at multiples of [`Self::snapshot_every`], in the [`snapshot`] format, by step.
+    snapshots: BTreeMap<u64, Vec<u8>>,
+}
+
+impl DeltaLog {
+    /// Create an empty log of a system which is `start` steps from its seed.
+    pub fn new(start: u64) -> Self {
+        Self {
+            start,
+            step: start,
+            ..Self::default()
+        }
+    }
+
+    /// Keep only the most recent `max_len` deltas, dropping older ones as new steps are logged.
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    /// Keep a snapshot of the system every `every` steps, so that steps whose deltas were dropped can still be sought.
+    ///
+    /// Once more than a few hundred snapshots are kept, every other one is dropped and the interval doubled,
+    /// so the snapshots take memory proportional to the length of the system rather than the number of steps.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `every` is zero.
+    pub fn with_snapshots(mut self, every: u64) -> Self {
+        assert_ne!(every, 0, "snapshots must be at least one step apart");
+        self.snapshot_every = Some(every);
+        self
+    }
+
+    /// Get the earliest step which can be sought.
+    pub fn start(&self) -> u64 {
+        match self.snapshots.first_key_value() {
+            Some((&step, _)) => step.min(self.start),
+            None => self.start,
+        }
+    }
+
+    /// Get the step of the system the log is following.
+    pub fn step(&self) -> u64 {
+        self.step
+    }
+
+    /// Get the latest logged step, which can be sought back to after seeking backward.
+    pub fn end(&self) -> u64 {
+        self.start + self.deltas.len() as u64
+    }
+
+    /// Get the number of logged steps.
+    pub fn len(&self) -> usize {
+        self.deltas.len()
+    }
+
+    /// Check whether no steps have been logged.
+    pub fn is_empty(&self) -> bool {
+        self.deltas.is_empty()
+    }
+
+    /// Get the logged deltas, oldest first.
+    pub fn deltas(&self) -> &VecDeque<Delta> {
+        &self.deltas
+    }
+
+    /// Estimate the number of bytes of memory occupied by the log.
+    pub fn memory_usage(&self) -> usize {
+        self.deltas.capacity() * size_of::<Delta>()
+            + self
+                .snapshots
+                .values()
+                .map(|snapshot| snapshot.capacity())
+                .sum::<usize>()
+    }
+
+    /// Evolve `system` by one step, logging the change.
+    ///
+    /// `system` must be the state at [`Self::step`].
+    /// Steps before [`Self::end`] were logged already, so they are only replayed.
+    pub fn evolve<S: PostSystem>(&mut self, system: &mut S) -> ControlFlow<()> {
+        if self.step < self.end() {
+            system.evolve()?;
+            self.step += 1;
+            return ControlFlow::Continue(());
+        }
+
+        let (Some(a), Some(b), Some(c)) = (system.get(0), system.get(1), system.get(2)) else {
+            return ControlFlow::Break(());
+        };
+
+        self.snapshot(system);
+        system.evolve()?;
+        self.deltas.push_back(Delta::new([a, b, c]));
+        self.step += 1;
+
+        if self
+            .max_len
+            .is_some_and(|max_len| self.deltas.len() > max_len)
+        {
+            self.deltas.pop_front();
+            self.start += 1;
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Evolve `system` by `n` steps, logging each change.
+    ///
+    /// If the system halts, returns `Break(n)`, where `n` is the number of steps taken before halting.
+    pub fn evolve_multi<S: PostSystem>(&mut self, system: &mut S, n: usize) -> ControlFlow<usize> {
+        let room = self.max_len.map_or(usize::MAX, |max_len| {
+            max_len - self.deltas.len().min(max_len)
+        });
+        self.deltas.reserve(n.min(1 << 20).min(room));
+        for i in 0..n {
+            if self.evolve(system).is_break() {
+                return ControlFlow::Break(i);
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Keep a snapshot of `system`, which is the state at [`Self::step`], if one is due.
+    fn snapshot<S: PostSystem>(&mut self, system: &S) {
+        let Some(every) = self.snapshot_every else {
+            return;
+        };
+        if !self.step.is_multiple_of(every) || self.snapshots.contains_key(&self.step) {
+            return;
+        }
+
+        let mut bytes = Vec::new();
+        snapshot::save(system, self.step, &mut bytes).expect("writing to a Vec cannot fail");
+        self.snapshots.insert(self.step, bytes);
+
+        if self.snapshots.len() > MAX_SNAPSHOTS {
+            let every = every.saturating_mul(2);
+            self.snapshots.retain(|step, _| step.is_multiple_of(every));
+            self.snapshot_every = Some(every);
+        }
+    }
+
+    /// Move `system`, which must be the state at [`Self::step`], back by up to `n` steps.
+    ///
+    /// Returns the number of steps moved, which is less than `n` if [`Self::start`] is reached.
+    /// The steps moved over stay logged, so they can be sought again.
+    pub fn rewind<S: PostSystem>(&mut self, system: &mut S, n: u64) -> u64 {
+        let n = n.min(self.step - self.start());
+        self.seek(system, self.step - n);
+        n
+    }
+
+    /// Move `system`, which must be the state at [`Self::step`], to the step `step`, which may be before or after it.
+    ///
+    /// Steps whose deltas are kept are undone from the log, and others are simulated again,
+    /// from the latest snapshot before `step` if that is closer than the current step.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` is before [`Self::start`] or after [`Self::end`].
+    pub fn seek<S: PostSystem>(&mut self, system: &mut S, step: u64) {
+        assert!(
+            (self.start()..=self.end()).contains(&step),
+            "step {step} is outside the logged steps {}..={}",
+            self.start(),
+            self.end()
+        );
+
+        if (self.start..self.step).contains(&step) {
+            self.undo(system, self.step - step);
+            return;
+        }
+
+        // Before the kept deltas, there is always a snapshot to start from.
+        let from = if step < self.step { 0 } else { self.step };
+        if let Some((&snapshot_step, bytes)) = self.snapshots.range(from..=step).next_back() {
+            *system = snapshot::load(&mut bytes.as_slice())
+                .expect("logged snapshots are valid")
+                .state;
+            self.step = snapshot_step;
+        }
+
+        let n = usize::try_from(step - self.step).expect("too many steps to replay");
+        let _ = system.evolve_multi(n);
+        self.step = step;
+    }
+
+    /// Undo the `n` logged steps before [`Self::step`].
+    fn undo<S: PostSystem>(&mut self, system: &mut S, n: u64) {
+        let end = (self.step - self.start) as usize;
+
+        let mut list = system.as_list();
+        for delta in self.deltas.range(end - n as usize..end).rev() {
+            let kept = list.len() - delta.appended().len();
+            debug_assert!(list.range(kept..).eq(delta.appended()));
+            list.truncate(kept);
+
+            for &symbol in delta.deleted().iter().rev() {
+                list.push_front(symbol);
+            }
+        }
+        *system = S::from_list(list);
+
+        self.step -= n;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        seeds,
+        system::{BitString, VecDequeBools},
+    };
+
+    use super::*;
+
+    #[test]
+    fn records_deltas() {
+        // `100` evolves to `1101`, which evolves to `11101`.
+        let mut system = BitString::new_decompressed(&[true]);
+        let mut log = DeltaLog::new(0);
+
+        assert_eq!(log.evolve_multi(&mut system, 2), ControlFlow::Continue(()));
+        assert_eq!(log.step(), 2);
+        assert_eq!(
+            log.deltas()
+                .iter()
+                .map(|delta| delta.deleted())
+                .collect::<Vec<_>>(),
+            [[true, false, false], [true, true, false]]
+        );
+        assert_eq!(log.deltas()[0].appended(), [true, true, false, true]);
+    }
+
+    #[test]
+    fn rewinds_and_seeks() {
+        let mut system: VecDequeBools = seeds::SEED_5854.system();
+        let mut log = DeltaLog::new(0);
+        let mut states = vec![system.clone()];
+        for _ in 0..100 {
+            let _ = log.evolve_multi(&mut system, 100);
+            states.push(system.clone());
+        }
+
+        assert_eq!(log.rewind(&mut system, 100), 100);
+        assert_eq!(system, states[99]);
+        assert_eq!(log.step(), 9_900);
+        assert_eq!(log.end(), 10_000);
+
+        log.seek(&mut system, 4_200);
+        assert_eq!(system, states[42]);
+
+        // Seeking backward keeps the later steps, so they can be sought again.
+        log.seek(&mut system, 7_700);
+        assert_eq!(system, states[77]);
+        assert_eq!(log.len(), 10_000);
+
+        // Stepping forward again replays the same trajectory.
+        let _ = log.evolve_multi(&mut system, 100);
+        assert_eq!(system, states[78]);
+        assert_eq!(log.len(), 10_000);
+
+        assert_eq!(log.rewind(&mut system, u64::MAX), 7_800);
+        assert_eq!(system, states[0]);
+
+        log.seek(&mut system, 10_000);
+        assert_eq!(system, states[100]);
+    }
+
+    #[test]
+    fn keeps_recent_steps_and_snapshots() {
+        let seed: VecDequeBools = seeds::SEED_5854.system();
+        let mut system = seed.clone();
+        let mut log = DeltaLog::new(0).with_max_len(1_000).with_snapshots(10);
+        let mut states = vec![system.clone()];
+        for _ in 0..100 {
+            let _ = log.evolve_multi(&mut system, 100);
+            states.push(system.clone());
+        }
+
+        assert_eq!(log.len(), 1_000);
+        assert!(log.snapshots.len() <= MAX_SNAPSHOTS);
+        assert_eq!(log.start(), 0);
+
+        log.seek(&mut system, 9_500);
+        assert_eq!(system, states[95]);
+        log.seek(&mut system, 4_200);
+        assert_eq!(system, states[42]);
+        log.seek(&mut system, 10_000);
+        assert_eq!(system, states[100]);
+        assert_eq!(log.rewind(&mut system, u64::MAX), 10_000);
+        assert_eq!(system, states[0]);
+
+        // Without snapshots, only the kept deltas can be sought.
+        let mut system = seed;
+        let mut log = DeltaLog::new(0).with_max_len(1_000);
+        let _ = log.evolve_multi(&mut system, 10_000);
+        assert_eq!(log.start(), 9_000);
+        assert_eq!(log.rewind(&mut system, u64::MAX), 1_000);
+        assert_eq!(system, states[90]);
+    }
+
+    #[test]
+    fn stops_at_halting() {
+        let mut system: BitString = seeds::ONES_5.system();
+        let seed = system.clone();
+        let mut log = DeltaLog::new(0);
+
+        assert_eq!(log.evolve_multi(&mut system, 1000), ControlFlow::Break(409));
+        assert_eq!(log.step(), 409);
+
+        log.seek(&mut system, 0);
+        assert_eq!(system, seed);
+    }
+
+    #[test]
+    #[should_panic]
+    fn refuses_to_seek_outside_log() {
+        let mut system = BitString::new_decompressed(&[true]);
+        let mut log = DeltaLog::new(10);
+        let _ = log.evolve_multi(&mut system, 5);
+
+        log.seek(&mut system, 9);
+    }
+}
@@ -0,0 +1,291 @@
+//! Aggregate statistics over runs of many seeds.
+//!
+//! A [`Census`](crate::census::Census) keeps a record per seed, which is what's wanted for finding particular seeds,
+//! but sweeps over many seeds are usually summarized by a handful of observables.
+//! An [`Ensemble`] runs each seed and folds its [`Report`] into [`Statistics`] as it goes, keeping nothing per seed.
+
+use std::collections::BTreeMap;
+
+use crate::{
+    export::histogram::{Binning, Histogram},
+    runner::{Outcome, Report, Runner},
+};
+
+/// Runs batches of seeds, aggregating their outcomes into [`Statistics`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ensemble {
+    /// The runner used to run each seed.
+    pub runner: Runner,
+}
+
+impl Ensemble {
+    /// Create a new ensemble.
+    pub fn new(runner: Runner) -> Self {
+        Self { runner }
+    }
+
+    /// Run each of `systems` in turn.
+    ///
+    /// If the runner's [`cancellation`](Runner::cancellation) token is cancelled,
+    /// the statistics cover only the systems run to completion before then.
+    pub fn run<S: crate::TagSystem>(&self, systems: impl IntoIterator<Item = S>) -> Statistics {
+        let mut statistics = Statistics::default();
+        for system in systems {
+            let report = self.runner.run_with_report(system);
+            if let Outcome::Cancelled(_) = report.outcome {
+                break;
+            }
+            statistics.record(&report);
+        }
+
+        statistics
+    }
+
+    /// Run `systems` in parallel on the global rayon thread pool.
+    ///
+    /// The statistics are exactly those [`Self::run`] would return,
+    /// except that if the runner's [`cancellation`](Runner::cancellation) token is cancelled,
+    /// the systems run to completion before then need not be a prefix of `systems`.
+    #[cfg(feature = "rayon")]
+    pub fn par_run<S: crate::TagSystem + Send>(
+        &self,
+        systems: impl rayon::iter::IntoParallelIterator<Item = S>,
+    ) -> Statistics {
+        use rayon::prelude::*;
+
+        systems
+            .into_par_iter()
+            .fold(Statistics::default, |mut statistics, system| {
+                let report = self.runner.run_with_report(system);
+                if !matches!(report.outcome, Outcome::Cancelled(_)) {
+                    statistics.record(&report);
+                }
+                statistics
+            })
+            .reduce(Statistics::default, Statistics::merge)
+    }
+}
+
+/// Observables aggregated over the runs of an [`Ensemble`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Statistics {
+    runs: u64,
+    halted: u64,
+    /// The number of halting runs which halted at each step.
+    halting_times: BTreeMap<u64, u64>,
+    cycled: u64,
+    /// The number of cycling runs with each period.
+    periods: BTreeMap<u64, u64>,
+    peak_length_sum: u128,
+    max_peak_length: u64,
+}
+
+impl Statistics {
+    /// Fold the report of a run into the statistics.
+    pub fn record<S>(&mut self, report: &Report<S>) {
+        self.runs += 1;
+        self.peak_length_sum += report.peak_length as u128;
+        self.max_peak_length = self.max_peak_length.max(report.peak_length);
+
+        match &report.outcome {
+            Outcome::Halted(steps) => {
+                self.halted += 1;
+                *self.halting_times.entry(*steps).or_default() += 1;
+            }
+            Outcome::Cycled(info) => {
+                self.cycled += 1;
+                *self.periods.entry(info.period).or_default() += 1;
+            }
+            Outcome::BudgetExhausted
+            | Outcome::MemoryLimitExceeded(_)
+            | Outcome::Cancelled(_)
+            | Outcome::TimedOut(_)
+            | Outcome::LengthLimitExceeded(_) => {}
+        }
+    }
+
+    /// Combine the statistics of two sets of runs.
+    pub fn merge(mut self, other: Self) -> Self {
+        self.runs += other.runs;
+        self.halted += other.halted;
+        for (steps, count) in other.halting_times {
+            *self.halting_times.entry(steps).or_default() += count;
+        }
+        self.cycled += other.cycled;
+        for (period, count) in other.periods {
+            *self.periods.entry(period).or_default() += count;
+        }
+        self.peak_length_sum += other.peak_length_sum;
+        self.max_peak_length = self.max_peak_length.max(other.max_peak_length);
+
+        self
+    }
+
+    /// Get the number of runs.
+    pub fn runs(&self) -> u64 {
+        self.runs
+    }
+
+    /// Get the number of runs which halted.
+    pub fn halted(&self) -> u64 {
+        self.halted
+    }
+
+    /// Get the number of runs which entered a cycle.
+    pub fn cycled(&self) -> u64 {
+        self.cycled
+    }
+
+    /// Get the number of runs which neither halted nor were found to cycle before a limit ran out.
+    pub fn unknown(&self) -> u64 {
+        self.runs - self.halted() - self.cycled
+    }
+
+    /// Get the number of halting runs which halted at each step, in ascending order of step.
+    pub fn halting_times(&self) -> &BTreeMap<u64, u64> {
+        &self.halting_times
+    }
+
+    /// Get the fraction of runs which halted.
+    pub fn halting_fraction(&self) -> f64 {
+        self.fraction(self.halted())
+    }
+
+    /// Get the fraction of runs which halted within `budget` steps,
+    /// which is the halting fraction an ensemble with that step budget would have found.
+    pub fn halted_within(&self, budget: u64) -> f64 {
+        self.fraction(
+            self.halting_times
+                .range(..=budget)
+                .map(|(_, count)| count)
+                .sum(),
+        )
+    }
+
+    /// Bin the halting times of the runs which halted.
+    pub fn halting_histogram(&self, binning: Binning) -> Histogram {
+        Histogram::from_counts(
+            binning,
+            self.halting_times
+                .iter()
+                .map(|(&steps, &count)| (steps, count)),
+        )
+    }
+
+    /// Get the number of cycling runs with each period, in ascending order of period.
+    pub fn periods(&self) -> &BTreeMap<u64, u64> {
+        &self.periods
+    }
+
+    /// Get the mean of the runs' peak lengths, as measured by [`Report::peak_length`].
+    pub fn mean_peak_length(&self) -> f64 {
+        if self.runs == 0 {
+            return 0.0;
+        }
+
+        self.peak_length_sum as f64 / self.runs as f64
+    }
+
+    /// Get the largest of the runs' peak lengths.
+    pub fn max_peak_length(&self) -> u64 {
+        self.max_peak_length
+    }
+
+    fn fraction(&self, count: u64) -> f64 {
+        if self.runs == 0 {
+            return 0.0;
+        }
+
+        count as f64 / self.runs as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        census::{self, Census, Class},
+        cycle::Algorithm,
+        system::BitString,
+        PostSystem,
+    };
+
+    use super::*;
+
+    fn seeds(max_len: u32) -> impl Iterator<Item = BitString> {
+        census::seeds(max_len).map(|seed| BitString::new_decompressed(&seed))
+    }
+
+    #[test]
+    fn agrees_with_census() {
+        let runner = Runner::new(10_000, Some(Algorithm::Brent));
+        let statistics = Ensemble::new(runner.clone()).run(seeds(8));
+        let records = Census::new(8, runner).run::<BitString>();
+
+        assert_eq!(statistics.runs(), records.len() as u64);
+        let mut halting_times: Vec<u64> = records
+            .iter()
+            .filter_map(|record| match record.class {
+                Class::Halted(steps) => Some(steps),
+                Class::Cycled { .. } | Class::Unknown => None,
+            })
+            .collect();
+        halting_times.sort();
+        let recorded: Vec<u64> = statistics
+            .halting_times()
+            .iter()
+            .flat_map(|(&steps, &count)| std::iter::repeat_n(steps, count as usize))
+            .collect();
+        assert_eq!(recorded, halting_times);
+        assert_eq!(statistics.halted(), halting_times.len() as u64);
+
+        let cycled = records
+            .iter()
+            .filter(|record| matches!(record.class, Class::Cycled { .. }))
+            .count() as u64;
+        assert_eq!(statistics.cycled(), cycled);
+        assert_eq!(statistics.periods().values().sum::<u64>(), cycled);
+        assert_eq!(
+            statistics.unknown(),
+            statistics.runs() - statistics.halted() - cycled
+        );
+
+        assert!(statistics.mean_peak_length() <= statistics.max_peak_length() as f64);
+        assert!(statistics.max_peak_length() > 0);
+    }
+
+    #[test]
+    fn tracks_halting_fraction_by_budget() {
+        let statistics = Ensemble::new(Runner::new(10_000, None)).run(seeds(6));
+        let steps: Vec<u64> = statistics.halting_times().keys().copied().collect();
+
+        assert_eq!(statistics.halted_within(0), 0.0);
+        assert_eq!(
+            statistics.halted_within(*steps.last().unwrap()),
+            statistics.halting_fraction()
+        );
+        assert!(statistics.halted_within(steps[steps.len() / 2]) < statistics.halting_fraction());
+
+        // Reports folded in by hand, in any order, give the same statistics.
+        let runner = Runner::new(10_000, None);
+        let mut by_hand = Statistics::default();
+        for system in seeds(6).collect::<Vec<_>>().into_iter().rev() {
+            by_hand.record(&runner.run_with_report(system));
+        }
+        assert_eq!(by_hand, statistics);
+
+        let empty = Ensemble::new(Runner::new(10, None)).run(std::iter::empty::<BitString>());
+        assert_eq!(empty.runs(), 0);
+        assert_eq!(empty.halting_fraction(), 0.0);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn runs_in_parallel() {
+        let ensemble = Ensemble::new(Runner::new(10_000, Some(Algorithm::Brent)));
+        let seeds: Vec<BitString> = seeds(8).collect();
+
+        assert_eq!(ensemble.par_run(seeds.clone()), ensemble.run(seeds));
+    }
+}
@@ -0,0 +1,72 @@
+//! CSV output of per-step metrics.
+
+use std::io::{self, Write};
+
+use crate::{record::Sink, PostSystem};
+
+/// Writes one CSV row of metrics per sampled step to a writer.
+///
+/// The columns are `step,length,ones,fingerprint`, and a header row is written before the first sample.
+#[derive(Debug)]
+pub struct Csv<W> {
+    out: W,
+    header_written: bool,
+}
+
+impl<W: Write> Csv<W> {
+    /// Create a new exporter writing to `out`.
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            header_written: false,
+        }
+    }
+
+    /// Consume the exporter, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl<S: PostSystem, W: Write> Sink<S> for Csv<W> {
+    type Error = io::Error;
+
+    fn record(&mut self, step: u64, state: &S) -> io::Result<()> {
+        if !self.header_written {
+            writeln!(self.out, "step,length,ones,fingerprint")?;
+            self.header_written = true;
+        }
+
+        let ones = state.count_ones();
+
+        writeln!(
+            self.out,
+            "{step},{},{ones},{:016x}",
+            state.length(),
+            state.fingerprint()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{record::Recorder, system::BitString, PostSystem};
+
+    use super::*;
+
+    #[test]
+    fn writes_rows() {
+        let mut system = BitString::new_decompressed(&[true]);
+        let mut recorder = Recorder::with_sink(1, Csv::new(Vec::new()));
+        let _ = recorder.run(&mut system, 2).unwrap();
+
+        let out = String::from_utf8(recorder.into_sink().into_inner()).unwrap();
+        let rows: Vec<_> = out.lines().collect();
+
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0], "step,length,ones,fingerprint");
+        assert!(rows[1].starts_with("0,3,1,"));
+        assert!(rows[2].starts_with("1,4,3,"));
+        assert!(rows[3].starts_with("2,5,4,"));
+    }
+}
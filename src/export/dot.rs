@@ -0,0 +1,156 @@
+//! Graphviz DOT output of state-transition graphs.
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use crate::PostSystem;
+
+/// The directed graph of states reachable from a set of seeds, with an edge from each state to its successor.
+#[derive(Debug, Clone)]
+pub struct StateGraph<S> {
+    /// The states, in order of discovery.
+    states: Vec<S>,
+    /// The index of each state in `states`.
+    indices: HashMap<S, usize>,
+    /// The index of each seed in `states`.
+    seeds: Vec<usize>,
+    /// The index of the successor of each state, if it was explored and did not halt.
+    successors: Vec<Option<usize>>,
+}
+
+impl<S: PostSystem> StateGraph<S> {
+    /// Explore the states reachable from `seeds`, stopping once the graph holds `max_states` states.
+    ///
+    /// Each trajectory is followed until it halts or reaches a state already in the graph,
+    /// so trajectories which merge share their common suffix and cycles close on themselves.
+    pub fn explore(seeds: impl IntoIterator<Item = S>, max_states: usize) -> Self {
+        let mut graph = Self {
+            states: Vec::new(),
+            indices: HashMap::new(),
+            seeds: Vec::new(),
+            successors: Vec::new(),
+        };
+
+        for seed in seeds {
+            if graph.states.len() >= max_states && !graph.indices.contains_key(&seed) {
+                break;
+            }
+
+            let (mut index, new) = graph.intern(seed);
+            graph.seeds.push(index);
+            if !new {
+                continue;
+            }
+
+            while graph.states.len() < max_states {
+                let mut next = graph.states[index].clone();
+                if next.evolve().is_break() {
+                    break;
+                }
+
+                let (next, new) = graph.intern(next);
+                graph.successors[index] = Some(next);
+                if !new {
+                    break;
+                }
+                index = next;
+            }
+        }
+
+        graph
+    }
+
+    /// Get the number of states in the graph.
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Check whether the graph has no states.
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// Write the graph in Graphviz DOT format.
+    ///
+    /// States no longer than `max_label_len` are labelled with their symbols, and longer ones with their fingerprint.
+    /// Seeds are drawn as boxes, and halted states with a double outline.
+    pub fn write_dot(&self, max_label_len: usize, mut out: impl Write) -> io::Result<()> {
+        writeln!(out, "digraph {{")?;
+        writeln!(out, "    node [fontname=monospace];")?;
+
+        for (i, state) in self.states.iter().enumerate() {
+            let label: String = if state.length() <= max_label_len {
+                state.bits().map(|b| if b { '1' } else { '0' }).collect()
+            } else {
+                format!("#{:016x}", state.fingerprint())
+            };
+
+            let mut attributes = format!("label=\"{label}\"");
+            if self.seeds.contains(&i) {
+                attributes.push_str(", shape=box");
+            }
+            if state.length() < 3 {
+                attributes.push_str(", peripheries=2");
+            }
+
+            writeln!(out, "    s{i} [{attributes}];")?;
+        }
+
+        for (i, successor) in self.successors.iter().enumerate() {
+            if let Some(j) = successor {
+                writeln!(out, "    s{i} -> s{j};")?;
+            }
+        }
+
+        writeln!(out, "}}")
+    }
+
+    /// Add `state` to the graph if it is not already present, returning its index and whether it was added.
+    fn intern(&mut self, state: S) -> (usize, bool) {
+        if let Some(&index) = self.indices.get(&state) {
+            return (index, false);
+        }
+
+        let index = self.states.len();
+        self.indices.insert(state.clone(), index);
+        self.states.push(state);
+        self.successors.push(None);
+        (index, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::system::BitString;
+
+    use super::*;
+
+    #[test]
+    fn writes_graph() {
+        // `100` enters the cycle `10100 -> 001101 -> 10100` after four steps, and `000` halts.
+        let seeds = [
+            BitString::new_decompressed(&[true]),
+            BitString::new_decompressed(&[false]),
+        ];
+        let graph = StateGraph::explore(seeds, 100);
+        assert_eq!(graph.len(), 8);
+
+        let mut out = Vec::new();
+        graph.write_dot(8, &mut out).unwrap();
+        let dot = String::from_utf8(out).unwrap();
+
+        assert!(dot.starts_with("digraph {\n"));
+        assert!(dot.contains("s0 [label=\"100\", shape=box];"));
+        assert!(dot.contains("[label=\"10100\"];"));
+        assert_eq!(dot.matches(" -> ").count(), 7);
+        assert!(dot.contains("[label=\"00\", peripheries=2];"));
+    }
+
+    #[test]
+    fn respects_limit() {
+        let graph = StateGraph::explore([BitString::new_decompressed(&[true; 7])], 10);
+        assert_eq!(graph.len(), 10);
+    }
+}
@@ -0,0 +1,270 @@
+//! Histograms of halting times, as CSV or JSON.
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use crate::census::{Class, Record};
+
+/// How halting times are grouped into bins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Binning {
+    /// Bins of the given width, starting from zero.
+    Linear(u64),
+    /// Bins `[0, 0]`, `[1, 1]`, `[2, 3]`, `[4, 7]`, and so on up to `[2^63, 2^64 - 1]`,
+    /// which suit halting times spread over many orders of magnitude.
+    Log2,
+}
+
+impl Binning {
+    /// Get the index of the bin containing `steps`.
+    fn index(self, steps: u64) -> u64 {
+        match self {
+            Self::Linear(width) => steps / width,
+            Self::Log2 => (u64::BITS - steps.leading_zeros()) as u64,
+        }
+    }
+
+    /// Get the first and last step counts in the bin at `index`.
+    fn bounds(self, index: u64) -> (u64, u64) {
+        match self {
+            Self::Linear(width) => {
+                let start = index * width;
+                (start, start.saturating_add(width - 1))
+            }
+            Self::Log2 => match index {
+                0 => (0, 0),
+                _ => (1 << (index - 1), u64::MAX >> (u64::BITS as u64 - index)),
+            },
+        }
+    }
+}
+
+/// A bin of a [`Histogram`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bin {
+    /// The first step count in the bin.
+    pub start: u64,
+    /// The last step count in the bin.
+    pub last: u64,
+    /// The number of halting times in the bin.
+    pub count: u64,
+}
+
+/// A histogram of halting times.
+///
+/// Only the bins holding some halting time are stored, so fine bins over a large budget cost no more than coarse ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Histogram {
+    /// How the halting times were binned.
+    pub binning: Binning,
+    /// Whether to write the empty bins between zero and the last nonempty bin, as well as the nonempty ones.
+    pub empty_bins: bool,
+    /// The number of halting times in each nonempty bin, by index.
+    counts: BTreeMap<u64, u64>,
+}
+
+impl Histogram {
+    /// Bin `halting_times`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `binning` is [`Binning::Linear`] with a width of zero.
+    pub fn new(binning: Binning, halting_times: impl IntoIterator<Item = u64>) -> Self {
+        Self::from_counts(binning, halting_times.into_iter().map(|steps| (steps, 1)))
+    }
+
+    /// Bin halting times given as pairs of a step and the number of runs which halted at it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `binning` is [`Binning::Linear`] with a width of zero.
+    pub fn from_counts(binning: Binning, counts: impl IntoIterator<Item = (u64, u64)>) -> Self {
+        if let Binning::Linear(width) = binning {
+            assert_ne!(width, 0, "bins must have a nonzero width");
+        }
+
+        let mut histogram = Self {
+            binning,
+            empty_bins: false,
+            counts: BTreeMap::new(),
+        };
+        for (steps, count) in counts {
+            *histogram.counts.entry(binning.index(steps)).or_default() += count;
+        }
+
+        histogram
+    }
+
+    /// Bin the halting times of the seeds in a census which halted.
+    pub fn of_census(binning: Binning, records: &[Record]) -> Self {
+        Self::new(
+            binning,
+            records.iter().filter_map(|record| match record.class {
+                Class::Halted(steps) => Some(steps),
+                Class::Cycled { .. } | Class::Unknown => None,
+            }),
+        )
+    }
+
+    /// Set [`Self::empty_bins`].
+    pub fn with_empty_bins(mut self, empty_bins: bool) -> Self {
+        self.empty_bins = empty_bins;
+        self
+    }
+
+    /// Iterate over the nonempty bins, in ascending order.
+    pub fn bins(&self) -> impl Iterator<Item = Bin> + '_ {
+        self.counts
+            .iter()
+            .map(|(&index, &count)| self.bin(index, count))
+    }
+
+    /// Iterate over every bin from zero up to the last nonempty bin, including empty ones, in ascending order.
+    pub fn all_bins(&self) -> impl Iterator<Item = Bin> + '_ {
+        let end = self
+            .counts
+            .last_key_value()
+            .map_or(0, |(&index, _)| index + 1);
+        (0..end).map(|index| self.bin(index, self.counts.get(&index).copied().unwrap_or(0)))
+    }
+
+    fn bin(&self, index: u64, count: u64) -> Bin {
+        let (start, last) = self.binning.bounds(index);
+        Bin { start, last, count }
+    }
+
+    /// Iterate over the bins to write, according to [`Self::empty_bins`].
+    fn written_bins(&self) -> Box<dyn Iterator<Item = Bin> + '_> {
+        match self.empty_bins {
+            true => Box::new(self.all_bins()),
+            false => Box::new(self.bins()),
+        }
+    }
+
+    /// Get the total number of halting times in the histogram.
+    pub fn total(&self) -> u64 {
+        self.counts.values().sum()
+    }
+
+    /// Write the histogram as CSV, with the columns `start,last,count` and a header row.
+    pub fn write_csv(&self, mut out: impl Write) -> io::Result<()> {
+        writeln!(out, "start,last,count")?;
+        for Bin { start, last, count } in self.written_bins() {
+            writeln!(out, "{start},{last},{count}")?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the histogram as a JSON array of bins, each like `{"start":4,"last":7,"count":2}`.
+    pub fn write_json(&self, mut out: impl Write) -> io::Result<()> {
+        write!(out, "[")?;
+        for (index, Bin { start, last, count }) in self.written_bins().enumerate() {
+            if index > 0 {
+                write!(out, ",")?;
+            }
+            write!(out, r#"{{"start":{start},"last":{last},"count":{count}}}"#)?;
+        }
+
+        writeln!(out, "]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        census::Census, ensemble::Ensemble, runner::Runner, system::BitString, PostSystem,
+    };
+
+    use super::*;
+
+    fn bin(start: u64, last: u64, count: u64) -> Bin {
+        Bin { start, last, count }
+    }
+
+    #[test]
+    fn bins_linearly() {
+        let histogram = Histogram::new(Binning::Linear(10), [0, 3, 9, 10, 35]);
+
+        assert_eq!(
+            histogram.bins().collect::<Vec<_>>(),
+            [bin(0, 9, 3), bin(10, 19, 1), bin(30, 39, 1)]
+        );
+        assert_eq!(
+            histogram.all_bins().collect::<Vec<_>>(),
+            [bin(0, 9, 3), bin(10, 19, 1), bin(20, 29, 0), bin(30, 39, 1)]
+        );
+        assert_eq!(histogram.total(), 5);
+
+        // Fine bins over huge halting times cost nothing for the bins in between.
+        let histogram = Histogram::new(Binning::Linear(1), [u64::MAX]);
+        assert_eq!(
+            histogram.bins().collect::<Vec<_>>(),
+            [bin(u64::MAX, u64::MAX, 1)]
+        );
+    }
+
+    #[test]
+    fn bins_logarithmically() {
+        let histogram = Histogram::new(Binning::Log2, [0, 1, 2, 3, 4, 7, 8, u64::MAX]);
+
+        let bins: Vec<_> = histogram.all_bins().collect();
+        assert_eq!(bins.len(), 65);
+        assert_eq!(
+            bins[..5],
+            [
+                bin(0, 0, 1),
+                bin(1, 1, 1),
+                bin(2, 3, 2),
+                bin(4, 7, 2),
+                bin(8, 15, 1)
+            ]
+        );
+        assert_eq!(bins[64], bin(1 << 63, u64::MAX, 1));
+        assert_eq!(histogram.total(), 8);
+
+        assert_eq!(Histogram::new(Binning::Log2, []).all_bins().count(), 0);
+    }
+
+    #[test]
+    fn writes_csv_and_json() {
+        let histogram = Histogram::new(Binning::Log2, [1, 2, 3]);
+
+        let mut csv = Vec::new();
+        histogram.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "start,last,count\n1,1,1\n2,3,2\n"
+        );
+
+        let mut json = Vec::new();
+        histogram
+            .with_empty_bins(true)
+            .write_json(&mut json)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            concat!(
+                r#"[{"start":0,"last":0,"count":0},{"start":1,"last":1,"count":1},{"start":2,"last":3,"count":2}]"#,
+                "\n"
+            )
+        );
+    }
+
+    #[test]
+    fn census_and_ensemble_agree() {
+        let runner = Runner::new(10_000, None);
+        let records = Census::new(8, runner.clone()).run::<BitString>();
+        let statistics = Ensemble::new(runner)
+            .run(crate::census::seeds(8).map(|seed| BitString::new_decompressed(&seed)));
+
+        let histogram = Histogram::of_census(Binning::Log2, &records);
+        assert_eq!(histogram, statistics.halting_histogram(Binning::Log2));
+        assert_eq!(histogram.total(), statistics.halted());
+    }
+}
@@ -0,0 +1,107 @@
+//! JSON Lines output, with one object per sampled step.
+
+use std::io::{self, Write};
+
+use crate::{record::Sink, PostSystem};
+
+/// How each state is represented in the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum StateContent {
+    /// The full state, as a string of `0`s and `1`s.
+    #[default]
+    Full,
+    /// The state's [fingerprint](PostSystem::fingerprint), as a hex string.
+    Fingerprint,
+    /// Only the step and length.
+    None,
+}
+
+/// Writes one JSON object per sampled step to a writer.
+///
+/// Each line looks like `{"step":3,"length":6,"state":"011101"}`.
+#[derive(Debug)]
+pub struct JsonLines<W> {
+    out: W,
+    content: StateContent,
+}
+
+impl<W: Write> JsonLines<W> {
+    /// Create a new emitter writing to `out`.
+    pub fn new(out: W, content: StateContent) -> Self {
+        Self { out, content }
+    }
+
+    /// Consume the emitter, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl<S: PostSystem, W: Write> Sink<S> for JsonLines<W> {
+    type Error = io::Error;
+
+    fn record(&mut self, step: u64, state: &S) -> io::Result<()> {
+        write!(self.out, r#"{{"step":{step},"length":{}"#, state.length())?;
+
+        match self.content {
+            StateContent::Full => {
+                let bits: String = state.bits().map(|b| if b { '1' } else { '0' }).collect();
+                write!(self.out, r#","state":"{bits}""#)?;
+            }
+            StateContent::Fingerprint => {
+                write!(self.out, r#","fingerprint":"{:016x}""#, state.fingerprint())?;
+            }
+            StateContent::None => {}
+        }
+
+        writeln!(self.out, "}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+
+    use crate::{record::Recorder, system::BitString, PostSystem};
+
+    use super::*;
+
+    #[test]
+    fn emits_lines() {
+        let mut system = BitString::new_decompressed(&[true]);
+        let mut recorder = Recorder::with_sink(2, JsonLines::new(Vec::new(), StateContent::Full));
+
+        assert_eq!(
+            recorder.run(&mut system, 3).unwrap(),
+            ControlFlow::Continue(())
+        );
+
+        let out = String::from_utf8(recorder.into_sink().into_inner()).unwrap();
+        assert_eq!(
+            out,
+            concat!(
+                r#"{"step":0,"length":3,"state":"100"}"#,
+                "\n",
+                r#"{"step":2,"length":5,"state":"11101"}"#,
+                "\n",
+            )
+        );
+    }
+
+    #[test]
+    fn emits_fingerprints() {
+        let system = BitString::new_decompressed(&[true, false]);
+
+        let mut sink = JsonLines::new(Vec::new(), StateContent::Fingerprint);
+        sink.record(7, &system).unwrap();
+
+        let out = String::from_utf8(sink.into_inner()).unwrap();
+        assert_eq!(
+            out,
+            format!(
+                "{{\"step\":7,\"length\":6,\"fingerprint\":\"{:016x}\"}}\n",
+                system.fingerprint()
+            )
+        );
+    }
+}
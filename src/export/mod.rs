@@ -0,0 +1,10 @@
+//! Export of trajectories, state graphs, and halting-time histograms to data formats.
+//!
+//! The trajectory exporters are [`Sink`](crate::record::Sink)s, so they can be plugged into a [`Recorder`](crate::record::Recorder).
+
+pub mod csv;
+pub mod dot;
+pub mod histogram;
+pub mod jsonl;
+#[cfg(feature = "parquet")]
+pub mod parquet;
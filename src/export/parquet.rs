@@ -0,0 +1,231 @@
+//! Arrow tables and Parquet files of census results and per-step metrics.
+
+use std::{io::Write, sync::Arc};
+
+use ::parquet::{arrow::ArrowWriter, errors::ParquetError};
+use arrow_array::{
+    builder::{ArrayBuilder, StringBuilder, UInt32Builder, UInt64Builder},
+    ArrayRef, RecordBatch,
+};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef};
+
+use crate::{
+    census::{Class, Record},
+    record::Sink,
+    PostSystem,
+};
+
+/// The number of sampled steps buffered before they are written as a row group.
+const METRICS_BATCH: usize = 1 << 16;
+
+/// Get the schema of [`census_batch`] tables.
+///
+/// The columns are `seed` (the compressed seed as a string of `0`s and `1`s), `len`, `outcome`
+/// (one of `halt`, `cycle`, or `unknown`), `steps` (the halting or cycle detection step), `period`,
+/// and `preperiod` (the exact step at which the cycle was entered, if found).
+/// `steps`, `period`, and `preperiod` are null where they do not apply.
+pub fn census_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("seed", DataType::Utf8, false),
+        Field::new("len", DataType::UInt32, false),
+        Field::new("outcome", DataType::Utf8, false),
+        Field::new("steps", DataType::UInt64, true),
+        Field::new("period", DataType::UInt64, true),
+        Field::new("preperiod", DataType::UInt64, true),
+    ]))
+}
+
+/// Build an Arrow table of census records, with one row per record.
+pub fn census_batch(records: &[Record]) -> Result<RecordBatch, ArrowError> {
+    let mut seeds = StringBuilder::new();
+    let mut lens = UInt32Builder::with_capacity(records.len());
+    let mut outcomes = StringBuilder::new();
+    let mut steps = UInt64Builder::with_capacity(records.len());
+    let mut periods = UInt64Builder::with_capacity(records.len());
+    let mut preperiods = UInt64Builder::with_capacity(records.len());
+
+    for record in records {
+        let seed: String = record
+            .seed
+            .iter()
+            .map(|&b| if b { '1' } else { '0' })
+            .collect();
+        seeds.append_value(seed);
+        lens.append_value(record.seed.len() as u32);
+
+        let (outcome, step, period, preperiod) = match record.class {
+            Class::Halted(step) => ("halt", Some(step), None, None),
+            Class::Cycled {
+                detected_at,
+                period,
+                preperiod,
+            } => ("cycle", Some(detected_at), Some(period), preperiod),
+            Class::Unknown => ("unknown", None, None, None),
+        };
+        outcomes.append_value(outcome);
+        steps.append_option(step);
+        periods.append_option(period);
+        preperiods.append_option(preperiod);
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(seeds.finish()),
+        Arc::new(lens.finish()),
+        Arc::new(outcomes.finish()),
+        Arc::new(steps.finish()),
+        Arc::new(periods.finish()),
+        Arc::new(preperiods.finish()),
+    ];
+    RecordBatch::try_new(census_schema(), columns)
+}
+
+/// Write census records to `out` as a Parquet file.
+pub fn write_census<W: Write + Send>(records: &[Record], out: W) -> Result<W, ParquetError> {
+    let mut writer = ArrowWriter::try_new(out, census_schema(), None)?;
+    writer.write(&census_batch(records)?)?;
+    writer.into_inner()
+}
+
+/// Get the schema of the tables written by [`ParquetMetrics`].
+///
+/// The columns are `step`, `length`, `ones`, and `fingerprint`, as in the CSV exporter.
+pub fn metrics_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("step", DataType::UInt64, false),
+        Field::new("length", DataType::UInt64, false),
+        Field::new("ones", DataType::UInt64, false),
+        Field::new("fingerprint", DataType::UInt64, false),
+    ]))
+}
+
+/// Writes per-step metrics of a trajectory to a Parquet file.
+///
+/// Samples are buffered and written in row groups, and the file is only complete once [`Self::finish`] is called.
+pub struct ParquetMetrics<W: Write + Send> {
+    writer: ArrowWriter<W>,
+    steps: UInt64Builder,
+    lengths: UInt64Builder,
+    ones: UInt64Builder,
+    fingerprints: UInt64Builder,
+}
+
+impl<W: Write + Send> ParquetMetrics<W> {
+    /// Create a new exporter writing to `out`.
+    pub fn new(out: W) -> Result<Self, ParquetError> {
+        Ok(Self {
+            writer: ArrowWriter::try_new(out, metrics_schema(), None)?,
+            steps: UInt64Builder::new(),
+            lengths: UInt64Builder::new(),
+            ones: UInt64Builder::new(),
+            fingerprints: UInt64Builder::new(),
+        })
+    }
+
+    /// Write any buffered samples and the file footer, returning the underlying writer.
+    pub fn finish(mut self) -> Result<W, ParquetError> {
+        self.flush()?;
+        self.writer.into_inner()
+    }
+
+    /// Write the buffered samples as a row group.
+    fn flush(&mut self) -> Result<(), ParquetError> {
+        if self.steps.is_empty() {
+            return Ok(());
+        }
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.steps.finish()),
+            Arc::new(self.lengths.finish()),
+            Arc::new(self.ones.finish()),
+            Arc::new(self.fingerprints.finish()),
+        ];
+        self.writer
+            .write(&RecordBatch::try_new(metrics_schema(), columns)?)?;
+        self.writer.flush()
+    }
+}
+
+impl<S: PostSystem, W: Write + Send> Sink<S> for ParquetMetrics<W> {
+    type Error = ParquetError;
+
+    fn record(&mut self, step: u64, state: &S) -> Result<(), ParquetError> {
+        let ones = state.count_ones();
+
+        self.steps.append_value(step);
+        self.lengths.append_value(state.length() as u64);
+        self.ones.append_value(ones as u64);
+        self.fingerprints.append_value(state.fingerprint());
+
+        if self.steps.len() >= METRICS_BATCH {
+            self.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File};
+
+    use ::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use arrow_array::{cast::AsArray, types::UInt64Type};
+
+    use crate::{
+        census::Census, cycle::Algorithm, record::Recorder, runner::Runner, system::BitString,
+    };
+
+    use super::*;
+
+    fn read_back(bytes: Vec<u8>, name: &str) -> Vec<RecordBatch> {
+        let path =
+            std::env::temp_dir().join(format!("post-tag-{name}-{}.parquet", std::process::id()));
+        fs::write(&path, bytes).unwrap();
+
+        let batches = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        fs::remove_file(&path).unwrap();
+        batches
+    }
+
+    #[test]
+    fn writes_census() {
+        let records =
+            Census::new(3, Runner::new(10_000, Some(Algorithm::Brent))).run::<BitString>();
+        let batches = read_back(write_census(&records, Vec::new()).unwrap(), "census");
+
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0], census_batch(&records).unwrap());
+
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 14);
+        assert_eq!(batch.column(0).as_string::<i32>().value(2), "00");
+        assert_eq!(batch.column(2).as_string::<i32>().value(2), "halt");
+        assert_eq!(batch.column(3).as_primitive::<UInt64Type>().value(2), 4);
+        assert!(batch.column(4).is_null(2));
+    }
+
+    #[test]
+    fn writes_metrics() {
+        let mut system = BitString::new_decompressed(&[true]);
+        let mut recorder = Recorder::with_sink(1, ParquetMetrics::new(Vec::new()).unwrap());
+        let _ = recorder.run(&mut system, 2).unwrap();
+
+        let batches = read_back(recorder.into_sink().finish().unwrap(), "metrics");
+        let batch = &batches[0];
+
+        assert_eq!(batch.num_rows(), 3);
+        assert_eq!(
+            batch.column(1).as_primitive::<UInt64Type>().values(),
+            &[3, 4, 5]
+        );
+        assert_eq!(
+            batch.column(2).as_primitive::<UInt64Type>().values(),
+            &[1, 3, 4]
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! A C API, for driving the simulator from other languages.
+//!
+//! Build a shared or static library with the `ffi` feature, e.g. with
+//! `cargo rustc --release --features ffi --crate-type cdylib`, and include `include/post_tag.h`.
+//! cbindgen generates the header from this module into the build directory whenever the crate is built with the feature,
+//! and the tests check that the copy in `include/` matches it.
+//!
+//! Systems are opaque [`PostTagSystem`] pointers created by [`post_tag_new`] or [`post_tag_new_decompressed`]
+//! and destroyed by [`post_tag_free`].
+//! Every function accepts a null system pointer and treats it as an empty system, so errors in the caller do not crash the process.
+
+use std::{
+    ffi::{c_char, CStr},
+    ops::ControlFlow,
+    ptr, slice,
+    str::FromStr,
+};
+
+use crate::{system::BitString, PostSystem};
+
+/// A 3-tag system.
+#[derive(Debug, Clone)]
+pub struct PostTagSystem(BitString);
+
+/// Create a system from its state, a NUL-terminated string of `0`s and `1`s.
+///
+/// Returns null if `state` is null or not a valid state.
+///
+/// # Safety
+///
+/// `state` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn post_tag_new(state: *const c_char) -> *mut PostTagSystem {
+    if state.is_null() {
+        return ptr::null_mut();
+    }
+
+    // SAFETY: the caller guarantees `state` is NUL-terminated.
+    let state = unsafe { CStr::from_ptr(state) };
+    match state.to_str().map(BitString::from_str) {
+        Ok(Ok(system)) => Box::into_raw(Box::new(PostTagSystem(system))),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Create a system from a compressed seed of `len` symbols, each a byte which is zero for `0` and nonzero for `1`.
+///
+/// As with [`PostSystem::new_decompressed`], each symbol `x` becomes `x00`.
+///
+/// # Safety
+///
+/// `seed` must point to `len` readable bytes, or be null if `len` is zero.
+#[no_mangle]
+pub unsafe extern "C" fn post_tag_new_decompressed(
+    seed: *const u8,
+    len: usize,
+) -> *mut PostTagSystem {
+    let seed: Vec<bool> = match len {
+        0 => Vec::new(),
+        // SAFETY: the caller guarantees `seed` points to `len` bytes.
+        _ => unsafe { slice::from_raw_parts(seed, len) }
+            .iter()
+            .map(|&symbol| symbol != 0)
+            .collect(),
+    };
+
+    Box::into_raw(Box::new(PostTagSystem(BitString::new_decompressed(&seed))))
+}
+
+/// Create a copy of `system`, which must later be freed separately.
+///
+/// Returns null if `system` is null.
+///
+/// # Safety
+///
+/// `system` must be null or a live system.
+#[no_mangle]
+pub unsafe extern "C" fn post_tag_clone(system: *const PostTagSystem) -> *mut PostTagSystem {
+    // SAFETY: the caller guarantees `system` is null or live.
+    match unsafe { system.as_ref() } {
+        Some(system) => Box::into_raw(Box::new(system.clone())),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Destroy `system`. Does nothing if it is null.
+///
+/// # Safety
+///
+/// `system` must be null or a live system, which must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn post_tag_free(system: *mut PostTagSystem) {
+    if !system.is_null() {
+        // SAFETY: the caller guarantees `system` was created by this library and is not used again.
+        drop(unsafe { Box::from_raw(system) });
+    }
+}
+
+/// Evolve `system` by up to `n` steps, returning the number taken, which is fewer than `n` if it halts.
+///
+/// # Safety
+///
+/// `system` must be null or a live system.
+#[no_mangle]
+pub unsafe extern "C" fn post_tag_evolve(system: *mut PostTagSystem, n: u64) -> u64 {
+    // SAFETY: the caller guarantees `system` is null or live.
+    let Some(PostTagSystem(system)) = (unsafe { system.as_mut() }) else {
+        return 0;
+    };
+
+    match system.evolve_multi(n as usize) {
+        ControlFlow::Break(taken) => taken as u64,
+        ControlFlow::Continue(()) => n,
+    }
+}
+
+/// Get the length of the state of `system`.
+///
+/// # Safety
+///
+/// `system` must be null or a live system.
+#[no_mangle]
+pub unsafe extern "C" fn post_tag_length(system: *const PostTagSystem) -> usize {
+    // SAFETY: the caller guarantees `system` is null or live.
+    unsafe { system.as_ref() }.map_or(0, |PostTagSystem(system)| system.length())
+}
+
+/// Get the fingerprint of the state of `system`, a hash which is equal for equal states.
+///
+/// # Safety
+///
+/// `system` must be null or a live system.
+#[no_mangle]
+pub unsafe extern "C" fn post_tag_fingerprint(system: *const PostTagSystem) -> u64 {
+    // SAFETY: the caller guarantees `system` is null or live.
+    unsafe { system.as_ref() }.map_or(0, |PostTagSystem(system)| system.fingerprint())
+}
+
+/// Write the state of `system` to `out`, one byte per symbol, each `0` or `1`.
+///
+/// At most `capacity` bytes are written. Returns the length of the state,
+/// so if it is greater than `capacity`, the state was truncated and the call can be repeated with a larger buffer.
+///
+/// # Safety
+///
+/// `system` must be null or a live system, and `out` must point to `capacity` writable bytes, or be null if `capacity` is zero.
+#[no_mangle]
+pub unsafe extern "C" fn post_tag_serialize(
+    system: *const PostTagSystem,
+    out: *mut u8,
+    capacity: usize,
+) -> usize {
+    // SAFETY: the caller guarantees `system` is null or live.
+    let Some(PostTagSystem(system)) = (unsafe { system.as_ref() }) else {
+        return 0;
+    };
+
+    if capacity > 0 {
+        // SAFETY: the caller guarantees `out` points to `capacity` bytes.
+        let out = unsafe { slice::from_raw_parts_mut(out, capacity) };
+        for (byte, bit) in out.iter_mut().zip(system.bits()) {
+            *byte = bit as u8;
+        }
+    }
+
+    system.length()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_is_up_to_date() {
+        assert_eq!(
+            include_str!("../include/post_tag.h"),
+            include_str!(concat!(env!("OUT_DIR"), "/post_tag.h")),
+            "include/post_tag.h is stale; copy the generated header from the build directory"
+        );
+    }
+
+    #[test]
+    fn drives_systems() {
+        unsafe {
+            let system = post_tag_new_decompressed([1u8].as_ptr(), 1);
+            assert_eq!(post_tag_length(system), 3);
+
+            assert_eq!(post_tag_evolve(system, 2), 2);
+            let mut out = [9; 8];
+            assert_eq!(post_tag_serialize(system, out.as_mut_ptr(), out.len()), 5);
+            assert_eq!(out, [1, 1, 1, 0, 1, 9, 9, 9]);
+
+            let copy = post_tag_clone(system);
+            assert_eq!(post_tag_fingerprint(copy), post_tag_fingerprint(system));
+            post_tag_free(system);
+
+            let mut out = [9; 2];
+            assert_eq!(post_tag_serialize(copy, out.as_mut_ptr(), out.len()), 5);
+            assert_eq!(out, [1, 1]);
+            post_tag_free(copy);
+        }
+    }
+
+    #[test]
+    fn halts_and_rejects_invalid_input() {
+        unsafe {
+            let system = post_tag_new(c"00000".as_ptr());
+            assert_eq!(post_tag_evolve(system, 10), 3);
+            assert_eq!(post_tag_length(system), 2);
+            post_tag_free(system);
+
+            assert!(post_tag_new(c"0120".as_ptr()).is_null());
+            assert!(post_tag_new(ptr::null()).is_null());
+            assert_eq!(post_tag_evolve(ptr::null_mut(), 10), 0);
+            assert_eq!(post_tag_length(ptr::null()), 0);
+            post_tag_free(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,321 @@
+//! A GPU compute backend for sweeping many seeds at once.
+//!
+//! Each seed runs in its own invocation of a compute shader, with a fixed-size ring buffer for its state.
+//! Seeds which outgrow their buffer are finished on the CPU instead.
+
+use std::{error::Error, fmt, sync::mpsc};
+
+use wgpu::util::DeviceExt;
+
+use crate::{system::BitString, PostSystem};
+
+/// The number of invocations per workgroup, which must match the shader.
+const WORKGROUP_SIZE: u32 = 64;
+
+/// The maximum number of steps each seed takes per dispatch, which keeps dispatches short enough not to time out.
+const STEPS_PER_DISPATCH: u32 = 1 << 16;
+
+/// The number of dispatches submitted between checks for whether every seed has finished.
+const DISPATCHES_PER_SUBMIT: u32 = 16;
+
+/// The status of a seed on the GPU, which must match the shader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+enum Status {
+    Running = 0,
+    Halted = 1,
+    Exhausted = 2,
+    Overflowed = 3,
+}
+
+impl Status {
+    fn from_u32(status: u32) -> Self {
+        match status {
+            0 => Self::Running,
+            1 => Self::Halted,
+            2 => Self::Exhausted,
+            3 => Self::Overflowed,
+            _ => unreachable!("invalid status {status} from shader"),
+        }
+    }
+}
+
+/// An error returned when the GPU backend cannot be initialized.
+#[derive(Debug)]
+pub enum GpuError {
+    /// No suitable adapter was found.
+    NoAdapter,
+    /// The adapter could not provide a device.
+    RequestDevice(wgpu::RequestDeviceError),
+}
+
+impl fmt::Display for GpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoAdapter => write!(f, "no suitable GPU adapter found"),
+            Self::RequestDevice(e) => write!(f, "failed to request GPU device: {e}"),
+        }
+    }
+}
+
+impl Error for GpuError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::NoAdapter => None,
+            Self::RequestDevice(e) => Some(e),
+        }
+    }
+}
+
+/// Runs many seeds in parallel on a GPU, each with a fixed amount of memory.
+#[derive(Debug)]
+pub struct GpuSweeper {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+    words_per_seed: u32,
+    /// The maximum number of seeds per batch, limited by buffer sizes and dispatch dimensions.
+    batch_size: usize,
+}
+
+impl GpuSweeper {
+    /// Initialize the GPU, giving each seed a ring buffer of `words_per_seed` 32-bit words.
+    ///
+    /// Seeds whose state ever exceeds `32 * words_per_seed` symbols are finished on the CPU.
+    pub fn new(words_per_seed: u32) -> Result<Self, GpuError> {
+        assert!(words_per_seed > 0, "seeds need at least one word of memory");
+
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .ok_or(GpuError::NoAdapter)?;
+
+        let limits = adapter.limits();
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("post-tag sweep"),
+                required_features: wgpu::Features::empty(),
+                required_limits: limits.clone(),
+                memory_hints: wgpu::MemoryHints::Performance,
+            },
+            None,
+        ))
+        .map_err(GpuError::RequestDevice)?;
+
+        let module = device.create_shader_module(wgpu::include_wgsl!("sweep.wgsl"));
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("post-tag sweep"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        let tape_bytes = words_per_seed as u64 * 4;
+        let max_binding =
+            (limits.max_storage_buffer_binding_size as u64).min(limits.max_buffer_size);
+        let batch_size = (max_binding / tape_bytes)
+            .min(limits.max_compute_workgroups_per_dimension as u64 * WORKGROUP_SIZE as u64)
+            .min(max_binding / 16) as usize;
+
+        Ok(Self {
+            device,
+            queue,
+            pipeline,
+            words_per_seed,
+            batch_size,
+        })
+    }
+
+    /// Find the halting time of each of the compressed `seeds`, simulating at most `budget` steps of each.
+    ///
+    /// Returns `None` for seeds which do not halt within the budget, exactly as [`crate::batch::halting_times`] does.
+    pub fn halting_times<T: AsRef<[bool]>>(&self, seeds: &[T], budget: u32) -> Vec<Option<u64>> {
+        seeds
+            .chunks(self.batch_size.max(1))
+            .flat_map(|batch| self.run_batch(batch, budget))
+            .collect()
+    }
+
+    fn run_batch<T: AsRef<[bool]>>(&self, seeds: &[T], budget: u32) -> Vec<Option<u64>> {
+        let capacity = self.words_per_seed as usize * 32;
+
+        // Pack each seed into its ring buffer, leaving seeds which are already too long to the CPU.
+        let mut tapes = vec![0u32; seeds.len() * self.words_per_seed as usize];
+        let mut states = vec![[0u32; 4]; seeds.len()];
+        for (i, seed) in seeds.iter().enumerate() {
+            let seed = seed.as_ref();
+            let len = 3 * seed.len();
+            if len > capacity {
+                states[i][3] = Status::Overflowed as u32;
+                continue;
+            }
+
+            let base = i * self.words_per_seed as usize;
+            for (j, &symbol) in seed.iter().enumerate() {
+                tapes[base + 3 * j / 32] |= (symbol as u32) << (3 * j % 32);
+            }
+            states[i][1] = len as u32;
+        }
+
+        let params = [
+            self.words_per_seed,
+            budget,
+            seeds.len() as u32,
+            STEPS_PER_DISPATCH,
+        ];
+        let params = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("params"),
+                contents: &to_bytes(&params),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let tapes = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("tapes"),
+                contents: &to_bytes(&tapes),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+        let states_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("states"),
+                contents: &to_bytes(states.as_flattened()),
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            });
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: states_buffer.size(),
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("sweep"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: tapes.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: states_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let workgroups = (seeds.len() as u32).div_ceil(WORKGROUP_SIZE);
+        let dispatches = budget.div_ceil(STEPS_PER_DISPATCH).max(1);
+        let mut dispatched = 0;
+        while dispatched < dispatches {
+            let mut encoder = self.device.create_command_encoder(&Default::default());
+            {
+                let mut pass = encoder.begin_compute_pass(&Default::default());
+                pass.set_pipeline(&self.pipeline);
+                pass.set_bind_group(0, &bind_group, &[]);
+                for _ in 0..DISPATCHES_PER_SUBMIT.min(dispatches - dispatched) {
+                    pass.dispatch_workgroups(workgroups, 1, 1);
+                    dispatched += 1;
+                }
+            }
+            encoder.copy_buffer_to_buffer(&states_buffer, 0, &readback, 0, readback.size());
+            self.queue.submit([encoder.finish()]);
+
+            states = self.read_states(&readback);
+            if states
+                .iter()
+                .all(|state| state[3] != Status::Running as u32)
+            {
+                break;
+            }
+        }
+
+        states
+            .iter()
+            .zip(seeds)
+            .map(|(state, seed)| match Status::from_u32(state[3]) {
+                Status::Halted => Some(state[2] as u64),
+                Status::Exhausted => None,
+                Status::Running => unreachable!("seed still running after its whole budget"),
+                Status::Overflowed => {
+                    let mut system = BitString::new_decompressed(seed.as_ref());
+                    system
+                        .evolve_multi(budget as usize)
+                        .break_value()
+                        .map(|steps| steps as u64)
+                }
+            })
+            .collect()
+    }
+
+    /// Wait for the queue to finish, and read the seed states from `readback`.
+    fn read_states(&self, readback: &wgpu::Buffer) -> Vec<[u32; 4]> {
+        let slice = readback.slice(..);
+        let (tx, rx) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        rx.recv()
+            .expect("map callback dropped")
+            .expect("failed to map readback buffer");
+
+        let states = slice
+            .get_mapped_range()
+            .chunks_exact(16)
+            .map(|state| {
+                let word =
+                    |i: usize| u32::from_le_bytes(state[4 * i..4 * i + 4].try_into().unwrap());
+                [word(0), word(1), word(2), word(3)]
+            })
+            .collect();
+        readback.unmap();
+
+        states
+    }
+}
+
+fn to_bytes(words: &[u32]) -> Vec<u8> {
+    words.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{batch, census::seeds};
+
+    use super::*;
+
+    #[test]
+    fn matches_cpu() {
+        // Four words is small enough that many seeds overflow and are finished on the CPU.
+        let sweeper = match GpuSweeper::new(4) {
+            Ok(sweeper) => sweeper,
+            Err(e) => {
+                eprintln!("skipping GPU test: {e}");
+                return;
+            }
+        };
+
+        let seeds: Vec<_> = seeds(8).collect();
+        assert_eq!(
+            sweeper.halting_times(&seeds, 2000),
+            batch::halting_times(&seeds, 2000)
+        );
+
+        let long = [vec![true; 60]];
+        assert_eq!(
+            sweeper.halting_times(&long, 2000),
+            batch::halting_times(&long, 2000)
+        );
+    }
+}
@@ -0,0 +1,94 @@
+// Runs one seed per invocation in a fixed-size ring buffer of bits, for up to `chunk` steps per dispatch.
+
+struct Params {
+    words_per_seed: u32,
+    budget: u32,
+    count: u32,
+    chunk: u32,
+}
+
+// The per-seed status codes, which must match `Status` in `mod.rs`.
+const RUNNING: u32 = 0u;
+const HALTED: u32 = 1u;
+const EXHAUSTED: u32 = 2u;
+const OVERFLOWED: u32 = 3u;
+
+@group(0) @binding(0) var<uniform> params: Params;
+// The ring buffer of each seed, `words_per_seed` words each, with bits stored least significant first.
+@group(0) @binding(1) var<storage, read_write> tapes: array<u32>;
+// The head, length, step, and status of each seed.
+@group(0) @binding(2) var<storage, read_write> states: array<vec4<u32>>;
+
+fn get_bit(base: u32, i: u32) -> u32 {
+    return (tapes[base + (i >> 5u)] >> (i & 31u)) & 1u;
+}
+
+fn set_bit(base: u32, i: u32, value: u32) {
+    let word = base + (i >> 5u);
+    let mask = 1u << (i & 31u);
+    if value == 1u {
+        tapes[word] |= mask;
+    } else {
+        tapes[word] &= ~mask;
+    }
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let seed = id.x;
+    if seed >= params.count {
+        return;
+    }
+
+    var state = states[seed];
+    if state.w != RUNNING {
+        return;
+    }
+
+    let base = seed * params.words_per_seed;
+    let capacity = params.words_per_seed * 32u;
+    var head = state.x;
+    var len = state.y;
+    var step = state.z;
+    var status = RUNNING;
+    let limit = min(params.budget, step + min(params.chunk, params.budget - step));
+
+    loop {
+        if step == params.budget {
+            status = EXHAUSTED;
+            break;
+        }
+        if len < 3u {
+            status = HALTED;
+            break;
+        }
+        if step == limit {
+            break;
+        }
+        if len + 1u > capacity {
+            status = OVERFLOWED;
+            break;
+        }
+
+        let first = get_bit(base, head);
+        head = (head + 3u) % capacity;
+        len -= 3u;
+
+        let tail = head + len;
+        if first == 1u {
+            set_bit(base, tail % capacity, 1u);
+            set_bit(base, (tail + 1u) % capacity, 1u);
+            set_bit(base, (tail + 2u) % capacity, 0u);
+            set_bit(base, (tail + 3u) % capacity, 1u);
+            len += 4u;
+        } else {
+            set_bit(base, tail % capacity, 0u);
+            set_bit(base, (tail + 1u) % capacity, 0u);
+            len += 2u;
+        }
+
+        step += 1u;
+    }
+
+    states[seed] = vec4<u32>(head, len, step, status);
+}
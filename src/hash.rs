@@ -0,0 +1,68 @@
+//! The hasher used for state fingerprints and hash-based cycle detection.
+//!
+//! By default this is the standard library's SipHash, which is robust but comparatively slow on short inputs.
+//! With the `ahash` feature it is aHash instead, which is much faster when millions of short states are hashed,
+//! as during a census with [`Algorithm::HashSet`](crate::cycle::Algorithm::HashSet).
+//! Either way, the hasher's keys are fixed, so fingerprints are reproducible between runs of the same build.
+//! aHash's output depends on the processor's features, though, so only SipHash fingerprints should be compared between machines.
+
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, Hash},
+};
+
+/// Builds the hasher used for states.
+#[cfg(not(feature = "ahash"))]
+pub type BuildStateHasher = std::hash::BuildHasherDefault<std::hash::DefaultHasher>;
+
+/// Builds the hasher used for states.
+#[cfg(feature = "ahash")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BuildStateHasher;
+
+#[cfg(feature = "ahash")]
+impl BuildHasher for BuildStateHasher {
+    type Hasher = ahash::AHasher;
+
+    fn build_hasher(&self) -> ahash::AHasher {
+        // Fixed keys, rather than aHash's default of keys chosen at random on startup.
+        ahash::RandomState::with_seeds(
+            0x243f_6a88_85a3_08d3,
+            0x1319_8a2e_0370_7344,
+            0xa409_3822_299f_31d0,
+            0x082e_fa98_ec4e_6c89,
+        )
+        .build_hasher()
+    }
+}
+
+/// A hash map keyed by states, hashed with [`BuildStateHasher`].
+pub type StateMap<K, V> = HashMap<K, V, BuildStateHasher>;
+
+/// Hash `value` with the state hasher.
+pub fn hash_one(value: &impl Hash) -> u64 {
+    let build: BuildStateHasher = Default::default();
+    build.hash_one(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{system::BitString, PostSystem};
+
+    use super::*;
+
+    #[test]
+    fn hashes_deterministically() {
+        let system = BitString::new_decompressed(&[true, false, true]);
+        assert_eq!(hash_one(&system), hash_one(&system.clone()));
+        assert_eq!(system.fingerprint(), hash_one(&system));
+        assert_ne!(
+            hash_one(&system),
+            hash_one(&BitString::new_decompressed(&[true, false, false]))
+        );
+
+        let mut map = StateMap::default();
+        map.insert(system.clone(), 1);
+        assert_eq!(map.get(&system), Some(&1));
+    }
+}
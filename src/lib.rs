@@ -1,63 +1,362 @@
+#[cfg(feature = "rkyv")]
+pub mod archive;
+pub mod backend;
+pub mod batch;
+pub mod cancel;
+pub mod census;
+pub mod cycle;
+pub mod delta;
+pub mod ensemble;
+pub mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod hash;
+pub mod merkle;
+pub mod progress;
+#[cfg(feature = "rand")]
+pub mod random;
+pub mod reach;
+pub mod record;
+pub mod render;
+pub mod runner;
+pub mod seeds;
+pub mod snapshot;
+pub mod symbolic;
 pub mod system;
+pub mod tag;
+pub mod telemetry;
+pub mod tune;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
-use std::{collections::VecDeque, ops::ControlFlow};
+use std::{
+    collections::VecDeque,
+    hash::Hash,
+    io::{self, Write},
+    ops::ControlFlow,
+};
 
-pub trait PostSystem: Clone + Eq {
+use cancel::CancellationToken;
+use system::ParseSeedError;
+
+/// A tag system with any rules: a string from whose front a fixed number of symbols are deleted each step,
+/// with a production chosen by the first of them appended to its end.
+///
+/// This is all the [`Runner`](runner::Runner) and [cycle detection](cycle) need,
+/// so they can simulate the systems with other rules in [`tag`] as well as every [`PostSystem`].
+pub trait TagSystem: Clone + Eq + Hash {
+    /// Get the length of the system.
+    fn length(&self) -> usize;
+
+    /// Estimate the number of bytes of memory occupied by the state.
+    fn memory_usage(&self) -> usize;
+
+    /// Compute a 64-bit fingerprint of the state, which is equal for equal states.
+    fn fingerprint(&self) -> u64 {
+        hash::hash_one(self)
+    }
+
+    /// Evolve the system by one step, returning [`ControlFlow::Break`] if the system halts.
+    fn evolve(&mut self) -> ControlFlow<()>;
+
+    /// Evolve the system by `n` steps.
+    ///
+    /// If the system halts, returns `Break(n)`, where `n` is the number of steps taken before halting.
+    fn evolve_multi(&mut self, n: usize) -> ControlFlow<usize> {
+        for i in 0..n {
+            if let ControlFlow::Break(()) = self.evolve() {
+                return ControlFlow::Break(i);
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Write a [`snapshot`] of the system at `step` to `out`, as [`Runner`](runner::Runner) checkpoints do.
+    ///
+    /// By default, this fails with [`io::ErrorKind::Unsupported`], since the format only holds binary states.
+    fn write_snapshot(&self, step: u64, out: &mut dyn Write) -> io::Result<()> {
+        let _ = (step, out);
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "snapshots can only hold binary states",
+        ))
+    }
+}
+
+impl<S: PostSystem> TagSystem for S {
+    fn length(&self) -> usize {
+        PostSystem::length(self)
+    }
+
+    fn memory_usage(&self) -> usize {
+        PostSystem::memory_usage(self)
+    }
+
+    fn fingerprint(&self) -> u64 {
+        PostSystem::fingerprint(self)
+    }
+
+    fn evolve(&mut self) -> ControlFlow<()> {
+        PostSystem::evolve(self)
+    }
+
+    fn evolve_multi(&mut self, n: usize) -> ControlFlow<usize> {
+        PostSystem::evolve_multi(self, n)
+    }
+
+    fn write_snapshot(&self, step: u64, mut out: &mut dyn Write) -> io::Result<()> {
+        snapshot::save(self, step, &mut out)
+    }
+}
+
+pub trait PostSystem: Clone + Eq + Hash {
     /// Initialize the system from a compressed representation of an initial string.
     fn new_decompressed(compressed: &[bool]) -> Self;
 
+    /// Initialize the system from a compressed seed given by the binary digits of `seed`, most significant first.
+    ///
+    /// For example, `0b1011` is the compressed seed `[true, false, true, true]`.
+    /// Zero is the single-symbol seed `[false]`.
+    fn from_seed_u128(seed: u128) -> Self {
+        let digits = (u128::BITS - seed.leading_zeros()).max(1);
+        let compressed: Vec<_> = (0..digits).rev().map(|i| (seed >> i) & 1 == 1).collect();
+
+        Self::new_decompressed(&compressed)
+    }
+
+    /// Initialize the system from a compressed seed given by the hexadecimal digits of a number, most significant first.
+    ///
+    /// Each digit contributes four binary digits, and leading zeros are dropped as in [`Self::from_seed_u128`],
+    /// so seeds of any length can be written. An optional `0x` prefix is allowed, but there must be at least one digit.
+    fn from_seed_hex(hex: &str) -> Result<Self, ParseSeedError> {
+        let digits = hex.strip_prefix("0x").unwrap_or(hex);
+        let offset = hex.len() - digits.len();
+        if digits.is_empty() {
+            return Err(ParseSeedError::Empty);
+        }
+
+        let mut compressed = Vec::with_capacity(4 * digits.len());
+        for (position, c) in digits.chars().enumerate() {
+            let digit = c.to_digit(16).ok_or(ParseSeedError::InvalidDigit {
+                position: offset + position,
+                found: c,
+            })?;
+            compressed.extend((0..4).rev().map(|i| (digit >> i) & 1 == 1));
+        }
+
+        let leading_zeros = compressed
+            .iter()
+            .take_while(|&&b| !b)
+            .count()
+            .min(compressed.len().saturating_sub(1));
+
+        Ok(Self::new_decompressed(&compressed[leading_zeros..]))
+    }
+
+    /// Recover the compressed representation of the system, as passed to [`Self::new_decompressed`],
+    /// or return `None` if the system isn't of that form.
+    ///
+    /// That is, the length must be a multiple of three, and the second and third symbols of each triple must be zero.
+    fn as_compressed(&self) -> Option<Vec<bool>> {
+        if !self.length().is_multiple_of(3) {
+            return None;
+        }
+
+        let mut compressed = Vec::with_capacity(self.length() / 3);
+        let mut bits = self.bits();
+        while let Some(first) = bits.next() {
+            if bits.next()? || bits.next()? {
+                return None;
+            }
+            compressed.push(first);
+        }
+
+        Some(compressed)
+    }
+
     /// Get the length of the system.
     fn length(&self) -> usize;
 
+    /// Get the number of symbols of the current state which will be read, one per step, before all of it has been deleted.
+    ///
+    /// These are the symbols at multiples of three, so this is the length of the state if it were compressed as in [`Self::new_decompressed`].
+    fn compressed_length(&self) -> usize {
+        self.length().div_ceil(3)
+    }
+
+    /// Get the phase of the end of the state relative to the read head: its length modulo three.
+    ///
+    /// The next symbol appended is read if and only if the alignment is zero when it is appended.
+    /// Deleting a triple leaves the alignment unchanged, while appending `00` and `1101` add two and one to it respectively.
+    fn alignment(&self) -> u8 {
+        (self.length() % 3) as u8
+    }
+
+    /// Estimate the number of bytes of memory occupied by the state.
+    ///
+    /// By default, this assumes one bit per symbol.
+    fn memory_usage(&self) -> usize {
+        self.length().div_ceil(8)
+    }
+
     /// Convert the system to a canonical list form.
     fn as_list(&self) -> VecDeque<bool>;
 
+    /// Iterate over the symbols of the system, in order.
+    ///
+    /// By default, this converts the system to a list first, but most systems can iterate without allocating.
+    fn bits(&self) -> impl Iterator<Item = bool> + '_ {
+        self.as_list().into_iter()
+    }
+
+    /// Count the ones in the system.
+    ///
+    /// By default, this iterates over every symbol, but most systems can count whole words at once.
+    fn count_ones(&self) -> usize {
+        self.bits().filter(|&b| b).count()
+    }
+
+    /// Get the symbol at `index`, or `None` if it is out of bounds.
+    ///
+    /// By default, this iterates up to the symbol, but most systems can find it in constant time.
+    fn get(&self, index: usize) -> Option<bool> {
+        self.bits().nth(index)
+    }
+
+    /// Initialize the system from its canonical list form.
+    fn from_list(list: VecDeque<bool>) -> Self;
+
+    /// Compute a 64-bit fingerprint of the state.
+    ///
+    /// Equal states have equal fingerprints, and fingerprints are stable across runs of the same build.
+    /// The hash function is chosen by the `ahash` feature, as described in [`hash`].
+    fn fingerprint(&self) -> u64 {
+        hash::hash_one(self)
+    }
+
     /// Evolve the system by one step, returning [`ControlFlow::Break`] if the system halts.
     fn evolve(&mut self) -> ControlFlow<()>;
 
+    /// Get every state which evolves into this one in a single step.
+    ///
+    /// A predecessor consists of a first bit, two arbitrary bits, and then this state with the production
+    /// for the first bit removed from its end, so there are either zero or four of them.
+    /// States with no predecessors are the "Gardens of Eden" which can only occur as seeds.
+    fn predecessors(&self) -> Vec<Self> {
+        let list = self.as_list();
+
+        let mut predecessors = Vec::new();
+        for (first, production) in [
+            (false, &[false, false][..]),
+            (true, &[true, true, false, true]),
+        ] {
+            let Some(kept) = list.len().checked_sub(production.len()) else {
+                continue;
+            };
+            if !list.range(kept..).eq(production) {
+                continue;
+            }
+
+            for middle in 0..4 {
+                let mut predecessor: VecDeque<bool> =
+                    [first, middle & 2 != 0, middle & 1 != 0].into();
+                predecessor.extend(list.range(..kept));
+                predecessors.push(Self::from_list(predecessor));
+            }
+        }
+
+        predecessors
+    }
+
     /// Evolve the system by `n` steps.
     ///
     /// If the system halts, returns `Break(n)`, where `n` is the number of steps taken before halting.
     fn evolve_multi(&mut self, n: usize) -> ControlFlow<usize> {
+        let timestep = self.preferred_timestep() as usize;
+
         let mut i = 0;
-        while i < n {
-            if self.length() >= 3 * Self::PREFERRED_TIMESTEP as usize {
-                self.evolve_preferred();
-                i += Self::PREFERRED_TIMESTEP as usize;
+        let mut jumped = 0;
+        let evolved = loop {
+            if i >= n {
+                break ControlFlow::Continue(());
+            }
+
+            if n - i >= timestep && self.length() >= 3 * timestep {
+                if let ControlFlow::Break(taken) = self.evolve_preferred() {
+                    break ControlFlow::Break(i + taken as usize);
+                }
+                i += timestep;
+                jumped += timestep;
             } else {
-                let res = self.evolve();
+                if let ControlFlow::Break(()) = self.evolve() {
+                    break ControlFlow::Break(i);
+                }
 
                 i += 1;
+            }
+        };
 
-                if let ControlFlow::Break(()) = res {
-                    return ControlFlow::Break(i);
-                }
+        telemetry::evolved(jumped as u64, (i - jumped) as u64);
+        evolved
+    }
+
+    /// Evolve the system by `n` steps like [`Self::evolve_multi`], checking `cancel` every [`cancel::CHUNK`] steps.
+    ///
+    /// If the system halts, returns `Break(n)`, where `n` is the number of steps taken before halting.
+    /// Otherwise, returns `Continue(n)`, where `n` is the number of steps taken, which is less than requested if `cancel` was cancelled.
+    fn evolve_multi_cancellable(
+        &mut self,
+        n: usize,
+        cancel: &CancellationToken,
+    ) -> ControlFlow<usize, usize> {
+        let mut i = 0;
+        while i < n && !cancel.is_cancelled() {
+            let chunk = (n - i).min(cancel::CHUNK);
+            if let ControlFlow::Break(steps) = self.evolve_multi(chunk) {
+                return ControlFlow::Break(i + steps);
             }
+
+            i += chunk;
         }
 
-        ControlFlow::Continue(())
+        ControlFlow::Continue(i)
     }
 
     /// The preferred number of steps to take when evolving the system.
-    const PREFERRED_TIMESTEP: u8 = 1;
+    fn preferred_timestep(&self) -> u8 {
+        1
+    }
 
-    /// Evolve the system by [`Self::PREFFERED_TIMESTEP`] steps.
+    /// Evolve the system by [`Self::preferred_timestep`] steps.
     ///
-    /// The result of calling this on a system with length less than `3 * Self::PREFERRED_TIMESTEP` is undefined.
-    fn evolve_preferred(&mut self) {
-        debug_assert!(self.length() >= 3 * Self::PREFERRED_TIMESTEP as usize);
-
-        for _ in 0..Self::PREFERRED_TIMESTEP {
-            self.evolve();
+    /// If the system halts partway, returns `Break(n)`, where `n` is the number of steps taken before halting.
+    ///
+    /// Implementations which jump by table lookups may require the length to be at least `3 * self.preferred_timestep()`,
+    /// as [`Self::evolve_multi`] ensures, and the result of calling them on shorter systems is undefined.
+    /// Since every step deletes three symbols, such a system can't halt partway.
+    /// The default implementation takes single steps, so it accepts any length.
+    fn evolve_preferred(&mut self) -> ControlFlow<u8> {
+        for i in 0..self.preferred_timestep() {
+            if let ControlFlow::Break(()) = self.evolve() {
+                return ControlFlow::Break(i);
+            }
         }
+
+        ControlFlow::Continue(())
     }
 }
 
 #[cfg(test)]
 pub(crate) mod tests {
-    use std::ops::ControlFlow;
+    use std::{collections::VecDeque, fmt::Display, ops::ControlFlow, str::FromStr};
 
-    use crate::PostSystem;
+    use crate::{
+        system::{ParseSeedError, ParseStateError},
+        PostSystem,
+    };
 
     #[macro_export]
     macro_rules! tests_for_system {
@@ -67,10 +366,60 @@ pub(crate) mod tests {
                 $crate::tests::decompresses::<$system>();
             }
 
+            #[test]
+            fn converts_lists() {
+                $crate::tests::converts_lists::<$system>();
+            }
+
+            #[test]
+            fn parses_and_displays() {
+                $crate::tests::parses_and_displays::<$system>();
+            }
+
+            #[test]
+            fn constructs_from_integers() {
+                $crate::tests::constructs_from_integers::<$system>();
+            }
+
             #[test]
             fn evolves() {
                 $crate::tests::evolves::<$system>();
             }
+
+            #[test]
+            fn evolves_multi() {
+                $crate::tests::evolves_multi::<$system>();
+            }
+
+            #[test]
+            fn halts() {
+                $crate::tests::halts::<$system>();
+            }
+
+            #[test]
+            fn iterates_bits() {
+                $crate::tests::iterates_bits::<$system>();
+            }
+
+            #[test]
+            fn gets_bits() {
+                $crate::tests::gets_bits::<$system>();
+            }
+
+            #[test]
+            fn counts_ones() {
+                $crate::tests::counts_ones::<$system>();
+            }
+
+            #[test]
+            fn recovers_compressed() {
+                $crate::tests::recovers_compressed::<$system>();
+            }
+
+            #[test]
+            fn finds_predecessors() {
+                $crate::tests::finds_predecessors::<$system>();
+            }
         };
     }
 
@@ -88,6 +437,64 @@ pub(crate) mod tests {
         );
     }
 
+    pub(crate) fn converts_lists<S: PostSystem>() {
+        let list: VecDeque<bool> = [true, false, true, true, false].into_iter().collect();
+        let system = S::from_list(list.clone());
+
+        assert_eq!(system.length(), 5);
+        assert_eq!(system.as_list(), list);
+        assert!(S::from_list(VecDeque::new()) == S::new_decompressed(&[]));
+    }
+
+    pub(crate) fn parses_and_displays<S>()
+    where
+        S: PostSystem + FromStr<Err = ParseStateError> + Display,
+    {
+        let system: S = "0110100".parse().unwrap();
+        assert_eq!(
+            system.as_list().make_contiguous(),
+            [false, true, true, false, true, false, false]
+        );
+        assert_eq!(system.to_string(), "0110100");
+
+        assert_eq!(S::from_str("").unwrap().length(), 0);
+        assert_eq!(
+            S::from_str("0120").err(),
+            Some(ParseStateError {
+                position: 2,
+                found: '2'
+            })
+        );
+
+        let system = S::new_decompressed(&[true, false, true]);
+        assert_eq!(system.to_string(), "100000100");
+    }
+
+    pub(crate) fn constructs_from_integers<S: PostSystem>() {
+        let compressed = [
+            true, false, true, true, false, true, true, false, true, true, true, true, false,
+        ];
+        let system = S::new_decompressed(&compressed);
+
+        assert!(S::from_seed_u128(0b1011011011110) == system);
+        assert!(S::from_seed_hex("16de").unwrap() == system);
+        assert!(S::from_seed_hex("0x016DE").unwrap() == system);
+
+        assert!(S::from_seed_u128(0) == S::new_decompressed(&[false]));
+        assert!(S::from_seed_hex("00").unwrap() == S::new_decompressed(&[false]));
+        assert!(S::from_seed_u128(u128::MAX).length() == 3 * 128);
+
+        assert_eq!(
+            S::from_seed_hex("0x1g").err(),
+            Some(ParseSeedError::InvalidDigit {
+                position: 3,
+                found: 'g'
+            })
+        );
+        assert_eq!(S::from_seed_hex("").err(), Some(ParseSeedError::Empty));
+        assert_eq!(S::from_seed_hex("0x").err(), Some(ParseSeedError::Empty));
+    }
+
     pub(crate) fn evolves<S: PostSystem>() {
         let mut system = S::new_decompressed(&[true]);
 
@@ -127,4 +534,144 @@ pub(crate) mod tests {
             [true, false, true, false, false]
         );
     }
+
+    pub(crate) fn halts<S: PostSystem>() {
+        let mut system = S::new_decompressed(&[false, false]);
+
+        assert_eq!(system.evolve_multi(4), ControlFlow::Continue(()));
+        assert_eq!(system.length(), 2);
+        assert_eq!(system.evolve(), ControlFlow::Break(()));
+        assert_eq!(system.as_list().make_contiguous(), [false, false]);
+
+        let mut system = S::new_decompressed(&[false, false]);
+        assert_eq!(system.evolve_multi(10), ControlFlow::Break(4));
+    }
+
+    pub(crate) fn evolves_multi<S: PostSystem>() {
+        let seed = [
+            true, false, true, true, false, true, true, false, true, true,
+        ];
+
+        let mut stepped = S::new_decompressed(&seed);
+        let mut multi = stepped.clone();
+
+        for n in [1, 2, 5, 11, 12, 33, 100] {
+            for _ in 0..n {
+                assert_eq!(stepped.evolve(), ControlFlow::Continue(()));
+            }
+            assert_eq!(multi.evolve_multi(n), ControlFlow::Continue(()));
+            assert_eq!(multi.as_list(), stepped.as_list());
+        }
+    }
+
+    pub(crate) fn iterates_bits<S: PostSystem>() {
+        let mut system = S::new_decompressed(&[true, false, true, true, false, true, true]);
+        assert!(system.bits().eq(system.as_list()));
+
+        for _ in 0..100 {
+            let _ = system.evolve_multi(37);
+            assert!(system.bits().eq(system.as_list()));
+            assert_eq!(system.bits().count(), system.length());
+        }
+
+        assert_eq!(S::from_list(VecDeque::new()).bits().next(), None);
+    }
+
+    pub(crate) fn gets_bits<S: PostSystem>() {
+        let mut system = S::new_decompressed(&[true, false, true, true, false, true, true]);
+
+        for _ in 0..100 {
+            let _ = system.evolve_multi(37);
+
+            for (i, bit) in system.bits().enumerate() {
+                assert_eq!(system.get(i), Some(bit));
+            }
+            assert_eq!(system.get(system.length()), None);
+            assert_eq!(system.get(usize::MAX), None);
+        }
+    }
+
+    pub(crate) fn counts_ones<S: PostSystem>() {
+        let mut system = S::new_decompressed(&[true, false, true, true, false, true, true]);
+        assert_eq!(system.count_ones(), 5);
+
+        for _ in 0..100 {
+            let _ = system.evolve_multi(37);
+            assert_eq!(system.count_ones(), system.bits().filter(|&b| b).count());
+        }
+
+        assert_eq!(S::from_list(VecDeque::new()).count_ones(), 0);
+    }
+
+    pub(crate) fn recovers_compressed<S: PostSystem>() {
+        for compressed in [&[][..], &[true], &[false], &[true, false, true, true]] {
+            let system = S::new_decompressed(compressed);
+            assert_eq!(system.as_compressed().as_deref(), Some(compressed));
+        }
+
+        // `100100` evolves to `1001101`, whose length isn't a multiple of three.
+        let mut system = S::new_decompressed(&[true, true]);
+        let _ = system.evolve();
+        assert_eq!(system.as_compressed(), None);
+
+        // Triples must end in two zeros.
+        assert_eq!(
+            S::from_list([true, true, false].into()).as_compressed(),
+            None
+        );
+        assert_eq!(
+            S::from_list([false, false, true].into()).as_compressed(),
+            None
+        );
+    }
+
+    pub(crate) fn finds_predecessors<S: PostSystem>() {
+        // `10100` is reached from `0xx101` and `001101` from `1xx00`.
+        let names = |system: &S| {
+            let mut names: Vec<String> = system
+                .predecessors()
+                .iter()
+                .map(|p| p.bits().map(|b| if b { '1' } else { '0' }).collect())
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(
+            names(&S::from_list([true, false, true, false, false].into())),
+            ["000101", "001101", "010101", "011101"]
+        );
+        assert_eq!(
+            names(&S::from_list(
+                [false, false, true, true, false, true].into()
+            )),
+            ["10000", "10100", "11000", "11100"]
+        );
+
+        // Check against the successors of every short state.
+        let states = |len: usize| {
+            (0..1u32 << len)
+                .map(move |n| S::from_list((0..len).map(|i| (n >> i) & 1 == 1).collect()))
+        };
+        for len in 0..=5 {
+            for state in states(len) {
+                let predecessors = state.predecessors();
+                assert!(matches!(predecessors.len(), 0 | 4));
+
+                for predecessor in &predecessors {
+                    let mut evolved = predecessor.clone();
+                    assert_eq!(evolved.evolve(), ControlFlow::Continue(()));
+                    assert!(evolved == state);
+                }
+
+                let expected = (3..=len + 1)
+                    .flat_map(states)
+                    .filter(|candidate| {
+                        let mut evolved = candidate.clone();
+                        evolved.evolve().is_continue() && evolved == state
+                    })
+                    .count();
+                assert_eq!(predecessors.len(), expected);
+            }
+        }
+    }
 }
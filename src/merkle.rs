@@ -0,0 +1,183 @@
+//! Merkle trees of chunk hashes, for comparing and diffing very long states.
+//!
+//! A state's bits are split into chunks of [`CHUNK_BITS`] bits, each chunk is hashed,
+//! and pairs of hashes are hashed together level by level up to a single root.
+//! Building a tree takes as long as comparing the state once, but afterwards two trees can be compared in constant time,
+//! and the first chunk at which they differ can be found in logarithmic time by descending from the root.
+//! This pays off when a long state is compared against many others, or when locating where two states diverge.
+//!
+//! Cycle detection and the systems' own equality don't use these trees:
+//! a detector compares each state just once before evolving it further, and building a tree is already a full pass over it.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use crate::PostSystem;
+
+/// The number of bits in each chunk.
+pub const CHUNK_BITS: usize = 1 << 12;
+
+/// A Merkle tree of the hashes of consecutive chunks of a state.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MerkleTree {
+    /// The length of the state in bits.
+    len: usize,
+    /// The levels of the tree, from the chunk hashes up to the root.
+    ///
+    /// Each level has half as many hashes as the one below it, rounded up, and the last has exactly one.
+    levels: Vec<Vec<u64>>,
+}
+
+impl MerkleTree {
+    /// Build the tree of `state`.
+    pub fn new<S: PostSystem>(state: &S) -> Self {
+        let mut bits = state.bits();
+        let mut leaves = Vec::with_capacity(state.length().div_ceil(CHUNK_BITS).max(1));
+
+        loop {
+            let mut words = [0u64; CHUNK_BITS / 64];
+            let mut count = 0;
+            for bit in bits.by_ref().take(CHUNK_BITS) {
+                words[count / 64] |= (bit as u64) << (count % 64);
+                count += 1;
+            }
+
+            if count == 0 && !leaves.is_empty() {
+                break;
+            }
+            leaves.push(hash(&words));
+            if count < CHUNK_BITS {
+                break;
+            }
+        }
+
+        let mut levels = vec![leaves];
+        while let [.., level] = &levels[..] {
+            if level.len() == 1 {
+                break;
+            }
+            let parents = level.chunks(2).map(hash).collect();
+            levels.push(parents);
+        }
+
+        Self {
+            len: state.length(),
+            levels,
+        }
+    }
+
+    /// Get the length of the state in bits.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check whether the state is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the root hash, which summarizes the whole state.
+    pub fn root(&self) -> u64 {
+        self.levels.last().unwrap()[0]
+    }
+
+    /// Get the hashes of the chunks, in order.
+    pub fn chunks(&self) -> &[u64] {
+        &self.levels[0]
+    }
+
+    /// Find the index of the first bit of the first chunk at which the states of the two trees differ.
+    ///
+    /// Returns `None` if the states are equal, except with negligible probability.
+    /// If one state is a prefix of the other, the chunk in which the shorter one ends is reported.
+    pub fn first_difference(&self, other: &Self) -> Option<usize> {
+        if self.len == other.len && self.root() == other.root() {
+            return None;
+        }
+
+        // Descend from the highest level the trees share, always into the leftmost differing child.
+        let shared = self.levels.len().min(other.levels.len());
+        let mut index = 0;
+        for level in (0..shared).rev() {
+            let (ours, theirs) = (&self.levels[level], &other.levels[level]);
+
+            index = (index * 2..)
+                .take(if level + 1 == shared {
+                    ours.len().max(theirs.len())
+                } else {
+                    2
+                })
+                .find(|&i| ours.get(i) != theirs.get(i))
+                .unwrap_or(index * 2);
+        }
+
+        Some(index * CHUNK_BITS)
+    }
+}
+
+/// Hash a slice of words.
+fn hash(words: &[u64]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    words.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::system::{BitString, VecDequeBools};
+
+    use super::*;
+
+    /// A state spanning a little over ten chunks.
+    fn long_state() -> BitString {
+        (0..10 * CHUNK_BITS + 123)
+            .map(|i| (i * 7 + i / 3) % 5 < 2)
+            .collect()
+    }
+
+    #[test]
+    fn compares_states() {
+        let system = long_state();
+        let tree = MerkleTree::new(&system);
+        assert_eq!(tree.len(), system.length());
+        assert_eq!(tree.chunks().len(), system.length().div_ceil(CHUNK_BITS));
+
+        // Trees depend only on the bits, not the backend.
+        assert_eq!(MerkleTree::new(&VecDequeBools::from(&system)), tree);
+        assert_eq!(tree.first_difference(&tree), None);
+
+        let mut evolved = system.clone();
+        let _ = evolved.evolve();
+        assert_ne!(MerkleTree::new(&evolved).root(), tree.root());
+        assert!(MerkleTree::new(&BitString::new()).is_empty());
+    }
+
+    #[test]
+    fn finds_first_difference() {
+        let system = long_state();
+        let tree = MerkleTree::new(&system);
+
+        for flipped in [
+            0,
+            1,
+            CHUNK_BITS - 1,
+            CHUNK_BITS,
+            3 * CHUNK_BITS + 17,
+            system.length() - 1,
+        ] {
+            let mut list = system.as_list();
+            list[flipped] ^= true;
+            let other = MerkleTree::new(&BitString::from_list(list));
+
+            let chunk = flipped / CHUNK_BITS * CHUNK_BITS;
+            assert_eq!(tree.first_difference(&other), Some(chunk), "{flipped}");
+            assert_eq!(other.first_difference(&tree), Some(chunk), "{flipped}");
+        }
+
+        // Truncation is found in the last chunk of the shorter state.
+        let mut list = system.as_list();
+        list.truncate(2 * CHUNK_BITS + 5);
+        let short = MerkleTree::new(&BitString::from_list(list));
+        assert_eq!(tree.first_difference(&short), Some(2 * CHUNK_BITS));
+        assert_eq!(short.first_difference(&tree), Some(2 * CHUNK_BITS));
+    }
+}
@@ -0,0 +1,54 @@
+//! Progress reporting for long runs.
+//!
+//! A [`Progress`] is passed to [`Runner::run_with_progress`](crate::runner::Runner::run_with_progress),
+//! which reports to it before each chunk of steps.
+//! Closures taking the same arguments as [`Progress::report`] implement it,
+//! and with the `indicatif` feature so do `indicatif::ProgressBar`s.
+
+/// Receives reports of a run's progress.
+pub trait Progress {
+    /// Report that `steps` steps have been simulated, the current state has length `length`,
+    /// and the run will stop after at most `total` steps.
+    fn report(&mut self, steps: u64, length: usize, total: u64);
+}
+
+impl<F: FnMut(u64, usize, u64)> Progress for F {
+    fn report(&mut self, steps: u64, length: usize, total: u64) {
+        self(steps, length, total)
+    }
+}
+
+#[cfg(feature = "indicatif")]
+impl Progress for indicatif::ProgressBar {
+    /// Show the steps as the bar's position out of `total`, and the length as its message.
+    fn report(&mut self, steps: u64, length: usize, total: u64) {
+        self.set_length(total);
+        self.set_position(steps);
+        self.set_message(format!("length {length}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "indicatif")]
+    #[test]
+    fn reports_to_progress_bars() {
+        let mut bar = indicatif::ProgressBar::hidden();
+        bar.report(42, 7, 100);
+
+        assert_eq!(bar.position(), 42);
+        assert_eq!(bar.length(), Some(100));
+        assert_eq!(bar.message(), "length 7");
+    }
+
+    #[test]
+    fn reports_to_closures() {
+        let mut reports = Vec::new();
+        let mut progress = |steps, length, total| reports.push((steps, length, total));
+        progress.report(1, 2, 3);
+
+        assert_eq!(reports, [(1, 2, 3)]);
+    }
+}
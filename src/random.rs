@@ -0,0 +1,78 @@
+//! Reproducible random seeds.
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// Generate a random compressed seed of length `len`, where each symbol is `1` with probability `density`.
+///
+/// `density` must be between zero and one.
+pub fn random_seed(rng: &mut impl Rng, len: usize, density: f64) -> Vec<bool> {
+    (0..len).map(|_| rng.random_bool(density)).collect()
+}
+
+/// An endless, reproducible stream of random compressed seeds.
+///
+/// The same `seed` always produces the same stream, on every platform.
+#[derive(Debug, Clone)]
+pub struct RandomSeeds {
+    rng: ChaCha8Rng,
+    len: usize,
+    density: f64,
+}
+
+impl RandomSeeds {
+    /// Create a stream of compressed seeds of length `len` and ones-density `density`, from the RNG seed `seed`.
+    ///
+    /// `density` must be between zero and one.
+    pub fn new(seed: u64, len: usize, density: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&density),
+            "density must be between zero and one"
+        );
+
+        Self {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            len,
+            density,
+        }
+    }
+}
+
+impl Iterator for RandomSeeds {
+    type Item = Vec<bool>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(random_seed(&mut self.rng, self.len, self.density))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_reproducible() {
+        let a: Vec<_> = RandomSeeds::new(42, 20, 0.5).take(10).collect();
+        let b: Vec<_> = RandomSeeds::new(42, 20, 0.5).take(10).collect();
+        let c: Vec<_> = RandomSeeds::new(43, 20, 0.5).take(10).collect();
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert!(a.iter().all(|seed| seed.len() == 20));
+    }
+
+    #[test]
+    fn respects_density() {
+        let ones = |density| {
+            RandomSeeds::new(0, 1000, density)
+                .take(10)
+                .flatten()
+                .filter(|&b| b)
+                .count()
+        };
+
+        assert_eq!(ones(0.0), 0);
+        assert_eq!(ones(1.0), 10_000);
+        assert!((2000..4000).contains(&ones(0.3)));
+    }
+}
@@ -0,0 +1,61 @@
+//! Reachability queries between states.
+
+use crate::PostSystem;
+
+/// Find the first step within `max_steps` steps of `from` at which the system is in the state `to`.
+///
+/// Returns `Some(0)` if the states are equal, and `None` if `to` does not occur in time or the system halts first.
+/// Each visited state is compared by length and then by [`PostSystem::fingerprint`],
+/// and only states with matching fingerprints are compared in full.
+pub fn reachable_within<S: PostSystem>(from: &S, to: &S, max_steps: u64) -> Option<u64> {
+    let length = to.length();
+    let fingerprint = to.fingerprint();
+
+    let mut system = from.clone();
+    let mut step = 0;
+    loop {
+        if system.length() == length && system.fingerprint() == fingerprint && system == *to {
+            return Some(step);
+        }
+
+        if step == max_steps || system.evolve().is_break() {
+            return None;
+        }
+        step += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{seeds, system::BitString};
+
+    use super::*;
+
+    #[test]
+    fn finds_reachable_states() {
+        // `100` passes through `1101`, `11101`, `011101`, `10100`, and then alternates with `001101`.
+        let seed = BitString::new_decompressed(&[true]);
+        let entry: BitString = "10100".parse().unwrap();
+        let next: BitString = "001101".parse().unwrap();
+
+        assert_eq!(reachable_within(&seed, &seed, 0), Some(0));
+        assert_eq!(reachable_within(&seed, &entry, 100), Some(4));
+        assert_eq!(reachable_within(&seed, &entry, 4), Some(4));
+        assert_eq!(reachable_within(&seed, &entry, 3), None);
+        assert_eq!(reachable_within(&entry, &next, 100), Some(1));
+        assert_eq!(reachable_within(&next, &entry, 100), Some(1));
+
+        // States before the cycle are never revisited.
+        assert_eq!(reachable_within(&entry, &seed, 100), None);
+    }
+
+    #[test]
+    fn stops_at_halting() {
+        let system: BitString = seeds::SEED_6008.system();
+        let mut end = system.clone();
+        let _ = end.evolve_multi(261_741);
+
+        assert_eq!(reachable_within(&system, &end, u64::MAX), Some(261_741));
+        assert_eq!(reachable_within(&end, &system, u64::MAX), None);
+    }
+}
@@ -0,0 +1,309 @@
+use std::{convert::Infallible, error::Error, fmt, marker::PhantomData, ops::ControlFlow};
+
+use crate::{snapshot::Snapshot, PostSystem};
+
+/// Receives the states sampled by a [`Recorder`].
+pub trait Sink<S> {
+    /// The error returned when a state cannot be recorded.
+    type Error;
+
+    /// Record the state of the system at `step`.
+    fn record(&mut self, step: u64, state: &S) -> Result<(), Self::Error>;
+}
+
+impl<S: Clone> Sink<S> for Vec<Snapshot<S>> {
+    type Error = Infallible;
+
+    fn record(&mut self, step: u64, state: &S) -> Result<(), Self::Error> {
+        self.push(Snapshot {
+            state: state.clone(),
+            step,
+        });
+        Ok(())
+    }
+}
+
+/// Records a sampled trajectory of a system as it evolves.
+///
+/// By default, the sampled states are kept in memory, but they can be streamed to any [`Sink`] instead.
+#[derive(Debug, Clone)]
+pub struct Recorder<S, K = Vec<Snapshot<S>>> {
+    /// The number of steps between recorded states.
+    stride: usize,
+
+    /// The number of steps taken so far.
+    step: u64,
+
+    /// Whether the initial state has been recorded.
+    started: bool,
+
+    /// The sink receiving the recorded states.
+    sink: K,
+
+    _system: PhantomData<fn(&S)>,
+}
+
+impl<S: PostSystem> Recorder<S> {
+    /// Create a new recorder which keeps every `stride`-th state in memory.
+    ///
+    /// `stride` must be nonzero.
+    pub fn new(stride: usize) -> Self {
+        Self::with_sink(stride, Vec::new())
+    }
+
+    /// Get the recorded states, with the steps at which they were recorded.
+    pub fn states(&self) -> &[Snapshot<S>] {
+        &self.sink
+    }
+
+    /// Consume the recorder, returning the recorded states.
+    pub fn into_states(self) -> Vec<Snapshot<S>> {
+        self.sink
+    }
+}
+
+impl<S: PostSystem, K: Sink<S>> Recorder<S, K> {
+    /// Create a new recorder which sends every `stride`-th state to `sink`.
+    ///
+    /// `stride` must be nonzero.
+    pub fn with_sink(stride: usize, sink: K) -> Self {
+        assert!(stride > 0, "stride must be nonzero");
+
+        Self {
+            stride,
+            step: 0,
+            started: false,
+            sink,
+            _system: PhantomData,
+        }
+    }
+
+    /// Evolve `system` by up to `steps` steps, recording the state at every step which is a multiple of `stride`.
+    ///
+    /// The initial state is recorded the first time this is called.
+    /// If the system halts, its final state is recorded and `Break(n)` is returned,
+    /// where `n` is the number of steps taken by this call before halting.
+    pub fn run(&mut self, system: &mut S, steps: usize) -> Result<ControlFlow<usize>, K::Error> {
+        if !self.started {
+            self.sink.record(self.step, system)?;
+            self.started = true;
+        }
+
+        let mut taken = 0;
+        while taken < steps {
+            // Stop at the next multiple of the stride, so states are sampled at the same steps however the run is split up.
+            let to_next = self.stride - (self.step % self.stride as u64) as usize;
+            let chunk = to_next.min(steps - taken);
+
+            if let ControlFlow::Break(n) = system.evolve_multi(chunk) {
+                taken += n;
+                self.step += n as u64;
+                self.sink.record(self.step, system)?;
+
+                return Ok(ControlFlow::Break(taken));
+            }
+
+            taken += chunk;
+            self.step += chunk as u64;
+
+            if self.step.is_multiple_of(self.stride as u64) {
+                self.sink.record(self.step, system)?;
+            }
+        }
+
+        Ok(ControlFlow::Continue(()))
+    }
+
+    /// Get the number of steps taken so far.
+    pub fn step(&self) -> u64 {
+        self.step
+    }
+
+    /// Get the sink receiving the recorded states.
+    pub fn sink(&self) -> &K {
+        &self.sink
+    }
+
+    /// Consume the recorder, returning its sink.
+    pub fn into_sink(self) -> K {
+        self.sink
+    }
+}
+
+/// An error returned when a recorded trajectory is inconsistent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The snapshot at `index` was taken at an earlier step than the one before it.
+    OutOfOrder { index: usize },
+    /// The system halted at step `halted_at`, before the step of the snapshot at `index`.
+    Halted { index: usize, halted_at: u64 },
+    /// Evolving the snapshot before the one at `index` did not reproduce its state.
+    Mismatch { index: usize },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfOrder { index } => {
+                write!(f, "snapshot {index} was taken before the one preceding it")
+            }
+            Self::Halted { index, halted_at } => write!(
+                f,
+                "the system halted at step {halted_at}, before snapshot {index} was taken"
+            ),
+            Self::Mismatch { index } => write!(
+                f,
+                "snapshot {index} does not match the evolution of the one preceding it"
+            ),
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+/// Check that a recorded trajectory is consistent, by evolving each snapshot up to the next and comparing their states.
+///
+/// The first snapshot is taken on trust, so include the seed at step zero to check the whole trajectory.
+/// Replaying takes as long as simulating the trajectory in the first place.
+pub fn verify<S: PostSystem>(trajectory: &[Snapshot<S>]) -> Result<(), ReplayError> {
+    for (index, pair) in trajectory.windows(2).enumerate() {
+        let (from, to) = (&pair[0], &pair[1]);
+        let index = index + 1;
+
+        let gap = to
+            .step
+            .checked_sub(from.step)
+            .ok_or(ReplayError::OutOfOrder { index })?;
+
+        let mut state = from.state.clone();
+        let mut taken = 0;
+        while taken < gap {
+            let chunk = (gap - taken).min(usize::MAX as u64);
+            if let ControlFlow::Break(n) = state.evolve_multi(chunk as usize) {
+                return Err(ReplayError::Halted {
+                    index,
+                    halted_at: from.step + taken + n as u64,
+                });
+            }
+            taken += chunk;
+        }
+
+        if state != to.state {
+            return Err(ReplayError::Mismatch { index });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+
+    use crate::{
+        seeds,
+        snapshot::Snapshot,
+        system::{BitString, VecDequeBools},
+        PostSystem,
+    };
+
+    use super::{verify, Recorder, ReplayError};
+
+    #[test]
+    fn records_every_stride() {
+        let mut system = VecDequeBools::new_decompressed(&[true, false, true, true]);
+        let mut recorder = Recorder::new(3);
+
+        assert_eq!(recorder.run(&mut system, 7), Ok(ControlFlow::Continue(())));
+
+        let steps: Vec<_> = recorder
+            .states()
+            .iter()
+            .map(|snapshot| snapshot.step)
+            .collect();
+        assert_eq!(steps, [0, 3, 6]);
+        assert_eq!(recorder.step(), 7);
+
+        let mut expected = VecDequeBools::new_decompressed(&[true, false, true, true]);
+        let _ = expected.evolve_multi(6);
+        assert_eq!(recorder.states()[2].state, expected);
+    }
+
+    #[test]
+    fn keeps_stride_across_calls() {
+        let mut system: BitString = seeds::SEED_5854.system();
+        let mut recorder = Recorder::new(10);
+
+        for steps in [7, 7, 1, 30, 5] {
+            assert_eq!(
+                recorder.run(&mut system, steps),
+                Ok(ControlFlow::Continue(()))
+            );
+        }
+
+        let steps: Vec<_> = recorder
+            .states()
+            .iter()
+            .map(|snapshot| snapshot.step)
+            .collect();
+        assert_eq!(steps, [0, 10, 20, 30, 40, 50]);
+        assert_eq!(verify(recorder.states()), Ok(()));
+    }
+
+    #[test]
+    fn records_halting_state() {
+        let mut system = VecDequeBools::new_decompressed(&[false, false]);
+        let mut recorder = Recorder::new(2);
+
+        assert_eq!(recorder.run(&mut system, 100), Ok(ControlFlow::Break(4)));
+
+        let last = recorder.states().last().unwrap();
+        assert_eq!(last.step, 4);
+        assert_eq!(last.state.length(), 2);
+    }
+
+    #[test]
+    fn verifies_recordings() {
+        let mut system: BitString = seeds::SEED_5854.system();
+        let mut recorder = Recorder::new(1000);
+        let _ = recorder.run(&mut system, 10_500);
+
+        let mut trajectory = recorder.into_states();
+        assert_eq!(trajectory.len(), 11);
+        assert_eq!(verify(&trajectory), Ok(()));
+
+        // Halting states are replayed too.
+        let mut halting = VecDequeBools::new_decompressed(&[false, false]);
+        let mut recorder = Recorder::new(3);
+        let _ = recorder.run(&mut halting, 100);
+        assert_eq!(verify(recorder.states()), Ok(()));
+
+        let mut tampered = trajectory.clone();
+        let _ = tampered[4].state.evolve();
+        assert_eq!(verify(&tampered), Err(ReplayError::Mismatch { index: 4 }));
+
+        trajectory.swap(6, 7);
+        assert_eq!(
+            verify(&trajectory),
+            Err(ReplayError::OutOfOrder { index: 7 })
+        );
+
+        let beyond = [
+            Snapshot {
+                state: VecDequeBools::new_decompressed(&[false, false]),
+                step: 0,
+            },
+            Snapshot {
+                state: VecDequeBools::new_decompressed(&[]),
+                step: 10,
+            },
+        ];
+        assert_eq!(
+            verify(&beyond),
+            Err(ReplayError::Halted {
+                index: 1,
+                halted_at: 4
+            })
+        );
+    }
+}
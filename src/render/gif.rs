@@ -0,0 +1,131 @@
+//! Animated GIFs of evolution.
+
+use std::io::Write;
+
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    Delay, Frame, ImageResult, Rgba, RgbaImage,
+};
+
+use crate::{snapshot::Snapshot, PostSystem};
+
+use super::Palette;
+
+/// Options for rendering an animated GIF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GifOptions {
+    /// Show only the first `window` symbols of each state, or the whole state if `None`.
+    pub window: Option<usize>,
+    /// The side length of each cell, in pixels.
+    pub cell_size: u32,
+    /// The colors of the animation.
+    pub palette: Palette,
+    /// How long each frame is shown, in milliseconds.
+    pub frame_delay_ms: u32,
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        Self {
+            window: None,
+            cell_size: 4,
+            palette: Palette::default(),
+            frame_delay_ms: 100,
+        }
+    }
+}
+
+/// Write a trajectory as an animated GIF, with one frame per recorded state.
+///
+/// Every frame has the same width: the window size, or the length of the longest state if there is no window.
+pub fn write_gif<S: PostSystem>(
+    trajectory: &[Snapshot<S>],
+    options: &GifOptions,
+    out: impl Write,
+) -> ImageResult<()> {
+    let symbols = options.window.unwrap_or_else(|| {
+        trajectory
+            .iter()
+            .map(|snapshot| snapshot.state.length())
+            .max()
+            .unwrap_or(0)
+    });
+
+    let cell = options.cell_size;
+    let width = (symbols as u32 * cell).max(1);
+
+    let mut encoder = GifEncoder::new(out);
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    for Snapshot { state, .. } in trajectory {
+        encoder.encode_frame(Frame::from_parts(
+            frame(state, symbols, width, options),
+            0,
+            0,
+            Delay::from_numer_denom_ms(options.frame_delay_ms, 1),
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// Render a single frame showing the first `symbols` symbols of `state`.
+fn frame<S: PostSystem>(state: &S, symbols: usize, width: u32, options: &GifOptions) -> RgbaImage {
+    let [r, g, b] = options.palette.background;
+    let mut image = RgbaImage::from_pixel(width, options.cell_size, Rgba([r, g, b, 0xFF]));
+
+    for (i, bit) in state.bits().take(symbols).enumerate() {
+        let [r, g, b] = match bit {
+            false => options.palette.zero,
+            true => options.palette.one,
+        };
+
+        for dx in 0..options.cell_size {
+            for y in 0..options.cell_size {
+                image.put_pixel(i as u32 * options.cell_size + dx, y, Rgba([r, g, b, 0xFF]));
+            }
+        }
+    }
+
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{codecs::gif::GifDecoder, AnimationDecoder};
+
+    use crate::{record::Recorder, system::BitString, PostSystem};
+
+    use super::*;
+
+    #[test]
+    fn writes_frames() {
+        let mut system = BitString::new_decompressed(&[true, false, true]);
+        let mut recorder = Recorder::new(1);
+        let _ = recorder.run(&mut system, 4).unwrap();
+
+        let options = GifOptions {
+            window: Some(6),
+            cell_size: 2,
+            ..Default::default()
+        };
+
+        let mut out = Vec::new();
+        write_gif(recorder.states(), &options, &mut out).unwrap();
+
+        let frames = GifDecoder::new(std::io::Cursor::new(out))
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+
+        assert_eq!(frames.len(), 5);
+        assert_eq!(frames[0].buffer().dimensions(), (12, 2));
+
+        // [true, false, false, ...]
+        let [r, g, b] = options.palette.one;
+        assert_eq!(frames[0].buffer().get_pixel(1, 1).0, [r, g, b, 0xFF]);
+        let [r, g, b] = options.palette.zero;
+        assert_eq!(frames[0].buffer().get_pixel(2, 0).0, [r, g, b, 0xFF]);
+    }
+}
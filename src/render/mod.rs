@@ -0,0 +1,28 @@
+//! Rendering of recorded trajectories as spacetime diagrams.
+
+#[cfg(feature = "gif")]
+pub mod gif;
+#[cfg(feature = "image")]
+pub mod png;
+pub mod svg;
+
+/// Colors used when rendering a spacetime diagram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    /// The color of a `0` symbol.
+    pub zero: [u8; 3],
+    /// The color of a `1` symbol.
+    pub one: [u8; 3],
+    /// The color of cells beyond the end of the string.
+    pub background: [u8; 3],
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            zero: [0xFF, 0xFF, 0xFF],
+            one: [0x00, 0x00, 0x00],
+            background: [0xC0, 0xC0, 0xC0],
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! PNG spacetime rasters.
+
+use std::path::Path;
+
+use image::{ImageResult, Rgb, RgbImage};
+
+use crate::{snapshot::Snapshot, PostSystem};
+
+use super::Palette;
+
+/// Render a trajectory as a spacetime raster, with one row per recorded state and one pixel per symbol.
+///
+/// The image is as wide as the longest recorded state; shorter rows are padded with the background color.
+pub fn spacetime<S: PostSystem>(trajectory: &[Snapshot<S>], palette: &Palette) -> RgbImage {
+    let width = trajectory
+        .iter()
+        .map(|snapshot| snapshot.state.length())
+        .max()
+        .unwrap_or(0);
+
+    let mut image = RgbImage::from_pixel(
+        width as u32,
+        trajectory.len() as u32,
+        Rgb(palette.background),
+    );
+
+    for (y, Snapshot { state, .. }) in trajectory.iter().enumerate() {
+        for (x, bit) in state.bits().enumerate() {
+            let color = match bit {
+                false => palette.zero,
+                true => palette.one,
+            };
+            image.put_pixel(x as u32, y as u32, Rgb(color));
+        }
+    }
+
+    image
+}
+
+/// Render a trajectory as a spacetime diagram and save it as a PNG.
+pub fn save_png<S: PostSystem>(
+    trajectory: &[Snapshot<S>],
+    palette: &Palette,
+    path: impl AsRef<Path>,
+) -> ImageResult<()> {
+    spacetime(trajectory, palette).save_with_format(path, image::ImageFormat::Png)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{record::Recorder, system::BitString, PostSystem};
+
+    use super::*;
+
+    #[test]
+    fn renders_rows() {
+        let mut system = BitString::new_decompressed(&[true]);
+        let mut recorder = Recorder::new(1);
+        let _ = recorder.run(&mut system, 2).unwrap();
+
+        let palette = Palette::default();
+        let image = spacetime(recorder.states(), &palette);
+
+        assert_eq!(image.dimensions(), (5, 3));
+
+        // [true, false, false]
+        assert_eq!(image.get_pixel(0, 0).0, palette.one);
+        assert_eq!(image.get_pixel(1, 0).0, palette.zero);
+        assert_eq!(image.get_pixel(3, 0).0, palette.background);
+
+        // [true, true, true, false, true]
+        assert_eq!(image.get_pixel(3, 2).0, palette.zero);
+        assert_eq!(image.get_pixel(4, 2).0, palette.one);
+    }
+}
@@ -0,0 +1,125 @@
+//! SVG spacetime diagrams.
+
+use std::io::{self, Write};
+
+use crate::{snapshot::Snapshot, PostSystem};
+
+use super::Palette;
+
+/// Options for rendering an SVG spacetime diagram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SvgOptions {
+    /// The side length of each cell, in SVG user units.
+    pub cell_size: u32,
+    /// The colors of the diagram.
+    pub palette: Palette,
+    /// Render only every `stride`-th recorded state.
+    pub stride: usize,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            cell_size: 4,
+            palette: Palette::default(),
+            stride: 1,
+        }
+    }
+}
+
+/// Write a trajectory as an SVG spacetime diagram, with one row of cells per sampled state.
+///
+/// Horizontal runs of equal symbols are merged into a single rectangle to keep the output small.
+pub fn write_svg<S: PostSystem>(
+    trajectory: &[Snapshot<S>],
+    options: &SvgOptions,
+    mut out: impl Write,
+) -> io::Result<()> {
+    assert!(options.stride > 0, "stride must be nonzero");
+
+    let rows: Vec<_> = trajectory
+        .iter()
+        .step_by(options.stride)
+        .map(|snapshot| snapshot.state.as_list())
+        .collect();
+
+    let cell = options.cell_size as usize;
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0) * cell;
+    let height = rows.len() * cell;
+
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" shape-rendering="crispEdges">"#
+    )?;
+    writeln!(
+        out,
+        r#"<rect width="{width}" height="{height}" fill="{}"/>"#,
+        hex(options.palette.background)
+    )?;
+
+    for (y, row) in rows.iter().enumerate() {
+        let mut x = 0;
+        while x < row.len() {
+            let bit = row[x];
+            let run = row.iter().skip(x).take_while(|&&b| b == bit).count();
+
+            let color = match bit {
+                false => options.palette.zero,
+                true => options.palette.one,
+            };
+            writeln!(
+                out,
+                r#"<rect x="{}" y="{}" width="{}" height="{cell}" fill="{}"/>"#,
+                x * cell,
+                y * cell,
+                run * cell,
+                hex(color)
+            )?;
+
+            x += run;
+        }
+    }
+
+    writeln!(out, "</svg>")
+}
+
+/// Format a color as a `#rrggbb` hex string.
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{record::Recorder, system::BitString, PostSystem};
+
+    use super::*;
+
+    #[test]
+    fn writes_runs() {
+        let mut system = BitString::new_decompressed(&[true]);
+        let mut recorder = Recorder::new(1);
+        let _ = recorder.run(&mut system, 2).unwrap();
+
+        let options = SvgOptions {
+            cell_size: 2,
+            stride: 2,
+            ..Default::default()
+        };
+
+        let mut out = Vec::new();
+        write_svg(recorder.states(), &options, &mut out).unwrap();
+        let svg = String::from_utf8(out).unwrap();
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(r#"width="10" height="4""#));
+
+        // [true, false, false]
+        assert!(svg.contains(r##"<rect x="0" y="0" width="2" height="2" fill="#000000"/>"##));
+        assert!(svg.contains(r##"<rect x="2" y="0" width="4" height="2" fill="#ffffff"/>"##));
+
+        // [true, true, true, false, true]
+        assert!(svg.contains(r##"<rect x="0" y="2" width="6" height="2" fill="#000000"/>"##));
+
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+}
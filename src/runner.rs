@@ -0,0 +1,1119 @@
+//! Running systems to completion under a step budget.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    ops::ControlFlow,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    cancel::CancellationToken,
+    cycle::{Algorithm, CycleDatabase, CycleInfo, Detection, Detector},
+    delta::DeltaLog,
+    hash,
+    progress::Progress,
+    record::Recorder,
+    snapshot::{self, Snapshot},
+    telemetry, PostSystem,
+};
+
+/// Why a run stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Outcome<S> {
+    /// The system halted after the given number of steps.
+    Halted(u64),
+    /// The system entered a cycle.
+    Cycled(CycleInfo<S>),
+    /// The step budget ran out before the system halted or a cycle was found.
+    BudgetExhausted,
+    /// The run was stopped at the given step because it exceeded the memory limit.
+    MemoryLimitExceeded(u64),
+    /// The run was stopped at the given step because its [`Runner::cancellation`] token was cancelled.
+    Cancelled(u64),
+    /// The run was stopped at the given step because it exceeded the time limit.
+    TimedOut(u64),
+    /// The run was stopped at the given step because the state grew longer than the length limit.
+    LengthLimitExceeded(u64),
+}
+
+/// The number of symbols a run deleted and appended, which measures its work more finely than its steps.
+///
+/// Every step deletes three symbols and appends two or four, so the counts follow from the number of steps
+/// and the lengths of the first and last states.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Work {
+    /// The number of symbols deleted from the front of the state.
+    pub deleted: u64,
+    /// The number of symbols appended to the end of the state.
+    pub appended: u64,
+}
+
+impl Work {
+    /// Get the work done by `steps` steps which took a state of length `start_length` to one of length `end_length`.
+    pub fn from_lengths(steps: u64, start_length: usize, end_length: usize) -> Self {
+        let deleted = 3 * steps;
+        Self {
+            deleted,
+            appended: (end_length as u64 + deleted)
+                .checked_sub(start_length as u64)
+                .expect("each step deletes only three symbols"),
+        }
+    }
+
+    /// Get the total number of symbols deleted and appended.
+    pub fn symbols(&self) -> u64 {
+        self.deleted + self.appended
+    }
+}
+
+/// The outcome of a run, along with measurements of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Report<S> {
+    /// Why the run stopped.
+    pub outcome: Outcome<S>,
+    /// The length of the longest state seen, as returned by [`Runner::run_with_peak`].
+    pub peak_length: u64,
+    /// The work done along the trajectory, from the state the run started from to the last state reached.
+    ///
+    /// This counts the steps of the trajectory, not the extra steps cycle detection takes to simulate it.
+    pub work: Work,
+}
+
+impl<S> Report<S> {
+    fn new(
+        outcome: Outcome<S>,
+        peak_length: u64,
+        steps: u64,
+        start_length: usize,
+        end_length: usize,
+    ) -> Self {
+        Self {
+            outcome,
+            peak_length,
+            work: Work::from_lengths(steps, start_length, end_length),
+        }
+    }
+}
+
+/// Where and how often a [`Runner`] writes checkpoints.
+///
+/// Each checkpoint is a snapshot in the format of [`crate::snapshot`], followed by a 64-bit identifier of the run's seed, step budget, and cycle detection,
+/// so that [`Runner::resume`] can refuse to resume a different run.
+/// The identifier is computed with the [state hasher](crate::hash), so a checkpoint can only be resumed by a build with the same hasher.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Checkpoint {
+    /// The path of the checkpoint file, which is replaced by each new checkpoint.
+    pub path: PathBuf,
+    /// Write a checkpoint after at most this many steps.
+    pub every_steps: Option<u64>,
+    /// Write a checkpoint after at most this much time.
+    pub every: Option<Duration>,
+}
+
+/// Runs systems until they halt, cycle, or exhaust a step budget.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Runner {
+    /// The maximum number of steps to simulate.
+    pub step_budget: u64,
+    /// The cycle detection algorithm to use, or `None` to only detect halting.
+    pub cycle_detection: Option<Algorithm>,
+    /// Where to periodically write snapshots of the run, if anywhere.
+    pub checkpoint: Option<Checkpoint>,
+    /// The number of bytes the run may use to store states, or `None` for no limit.
+    ///
+    /// Memory usage is estimated with [`crate::TagSystem::memory_usage`] and [`Detector::memory_usage`].
+    /// Without cycle detection it is only checked every so often, so the limit may be overshot by a few kilobytes.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub memory_limit: Option<u64>,
+    /// A token which stops the run when cancelled, or `None` if the run cannot be cancelled.
+    ///
+    /// The token is checked before each chunk of steps, so a cancelled run stops soon after, but not instantly.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub cancellation: Option<CancellationToken>,
+    /// The wall-clock time the run may take, or `None` for no limit.
+    ///
+    /// Like [`Self::cancellation`], this is checked before each chunk of steps, so the limit may be overshot slightly.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub time_limit: Option<Duration>,
+    /// The number of symbols the state may grow to, or `None` for no limit.
+    ///
+    /// Without cycle detection the run stops at exactly the first step whose state exceeds the limit;
+    /// with it, at the first detector iteration which passes through such a state.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub length_limit: Option<u64>,
+    /// The number of steps between the states kept by [`Self::run_recorded`], or `None` to keep only the first and any halting state.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub record_every: Option<u64>,
+}
+
+/// The number of steps (or cycle detection iterations) between checks for whether to write a checkpoint.
+const CHUNK: u64 = 1 << 16;
+
+/// The number of steps (or cycle detection iterations) between checks for whether the system has joined a known cycle.
+const KNOWN_CYCLE_STRIDE: u64 = 1 << 10;
+
+impl Runner {
+    /// Create a new runner.
+    pub fn new(step_budget: u64, cycle_detection: Option<Algorithm>) -> Self {
+        Self {
+            step_budget,
+            cycle_detection,
+            checkpoint: None,
+            memory_limit: None,
+            cancellation: None,
+            time_limit: None,
+            length_limit: None,
+            record_every: None,
+        }
+    }
+
+    /// Start building a runner, with no step budget, cycle detection, or limits until they are set.
+    ///
+    /// ```
+    /// # use post_tag::{cycle::Algorithm, runner::Runner};
+    /// let runner = Runner::builder()
+    ///     .step_budget(1_000_000)
+    ///     .cycle_detection(Algorithm::Brent)
+    ///     .memory_limit(1 << 20)
+    ///     .build();
+    /// ```
+    pub fn builder() -> RunnerBuilder {
+        RunnerBuilder(Self::new(u64::MAX, None))
+    }
+
+    /// Run `system` until it halts, cycles, or exhausts the step budget.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a checkpoint cannot be written. Use [`Self::try_run`] to handle this instead.
+    pub fn run<S: crate::TagSystem>(&self, system: S) -> Outcome<S> {
+        self.try_run(system).expect("failed to write checkpoint")
+    }
+
+    /// Run `system` until it halts, cycles, or exhausts the step budget, returning an error if a checkpoint cannot be written.
+    pub fn try_run<S: crate::TagSystem>(&self, mut system: S) -> io::Result<Outcome<S>> {
+        self.run_from(&mut system, 0, None, None, None, None)
+            .map(|report| report.outcome)
+    }
+
+    /// Run `system` like [`Self::run`], also returning the length of the longest state it passed through.
+    ///
+    /// With cycle detection every state is measured, but without it the length is only sampled every so often,
+    /// so the returned length is a lower bound.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a checkpoint cannot be written.
+    pub fn run_with_peak<S: crate::TagSystem>(&self, system: S) -> (Outcome<S>, u64) {
+        let report = self.run_with_report(system);
+        (report.outcome, report.peak_length)
+    }
+
+    /// Run `system` like [`Self::run`], returning a [`Report`] of the outcome, peak length, and work done.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a checkpoint cannot be written.
+    pub fn run_with_report<S: crate::TagSystem>(&self, mut system: S) -> Report<S> {
+        self.run_from(&mut system, 0, None, None, None, None)
+            .expect("failed to write checkpoint")
+    }
+
+    /// Run `system` like [`Self::run`], but stop as soon as it is found on one of the `known` cycles.
+    ///
+    /// Membership is only checked every so often, so the cycle is found at most a thousand or so steps after it is entered.
+    /// This is usually much sooner than cycle detection would find it, even when cycle detection is disabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a checkpoint cannot be written.
+    pub fn run_with_known_cycles<S: PostSystem>(
+        &self,
+        mut system: S,
+        known: &CycleDatabase<S>,
+    ) -> Outcome<S> {
+        self.run_from(&mut system, 0, None, Some(known), None, None)
+            .expect("failed to write checkpoint")
+            .outcome
+    }
+
+    /// Run `system` like [`Self::run`], logging every step in `log` so that the run can be stepped backward afterwards.
+    ///
+    /// `system` must be the state at [`DeltaLog::step`], from which steps are counted, and is left in the final state.
+    /// Cycle detection is not used, since it would simulate the trajectory more than once,
+    /// and the memory used by the log counts towards the memory limit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a checkpoint cannot be written.
+    pub fn run_with_deltas<S: PostSystem>(&self, system: &mut S, log: &mut DeltaLog) -> Outcome<S> {
+        self.run_from(system, log.step(), None, None, Some(log), None)
+            .expect("failed to write checkpoint")
+            .outcome
+    }
+
+    /// Run `system` like [`Self::run`], keeping its state at every multiple of [`Self::record_every`] steps.
+    ///
+    /// The initial state is always kept, as is the final state if the system halts.
+    /// Cycle detection is not used, since it would simulate the trajectory more than once,
+    /// and the memory used by the kept states counts towards the memory limit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a checkpoint cannot be written.
+    pub fn run_recorded<S: PostSystem>(&self, mut system: S) -> (Outcome<S>, Vec<Snapshot<S>>) {
+        let stride = self.record_every.map_or(usize::MAX, |every| {
+            usize::try_from(every.max(1)).unwrap_or(usize::MAX)
+        });
+        let mut recorder = Recorder::new(stride);
+
+        let outcome = self
+            .run_from(&mut system, 0, None, None, Some(&mut recorder), None)
+            .expect("failed to write checkpoint")
+            .outcome;
+        (outcome, recorder.into_states())
+    }
+
+    /// Run `system` like [`Self::run`], reporting to `progress` before each chunk of steps.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a checkpoint cannot be written.
+    pub fn run_with_progress<S: crate::TagSystem>(
+        &self,
+        mut system: S,
+        progress: &mut impl Progress,
+    ) -> Outcome<S> {
+        self.run_from(&mut system, 0, None, None, None, Some(progress))
+            .expect("failed to write checkpoint")
+            .outcome
+    }
+
+    /// Run `system` like [`Self::run_with_report`], reporting to `progress` before each chunk of steps,
+    /// and returning an error instead of panicking if a checkpoint cannot be written.
+    pub fn try_run_with_progress<S: crate::TagSystem>(
+        &self,
+        mut system: S,
+        progress: &mut dyn Progress,
+    ) -> io::Result<Report<S>> {
+        self.run_from(&mut system, 0, None, None, None, Some(progress))
+    }
+
+    /// Resume a run from the latest checkpoint, or start from `seed` if no checkpoint has been written yet.
+    ///
+    /// Cycle detection restarts from the checkpointed state, so a cycle may be detected later than in an uninterrupted run,
+    /// but the reported steps are still counted from the original seed.
+    /// Returns an error if the checkpoint was written by a run of a different seed, or with a different step budget or cycle detection.
+    pub fn resume<S: PostSystem>(&self, seed: S) -> io::Result<Outcome<S>> {
+        let Some(checkpoint) = &self.checkpoint else {
+            return self.try_run(seed);
+        };
+
+        match File::open(&checkpoint.path) {
+            Ok(file) => {
+                let mut input = BufReader::new(file);
+                let mut snapshot = snapshot::load(&mut input)?;
+
+                let mut written_by = [0; 8];
+                input.read_exact(&mut written_by)?;
+                let run = self.run_id(&seed, 0)?;
+                if u64::from_le_bytes(written_by) != run {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "checkpoint is of a different seed, step budget, or cycle detection",
+                    ));
+                }
+
+                self.run_from(
+                    &mut snapshot.state,
+                    snapshot.step,
+                    Some(run),
+                    None,
+                    None,
+                    None,
+                )
+                .map(|report| report.outcome)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => self.try_run(seed),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Identify a run of `system`, which is `start` steps from its seed, by hashing its state with the settings that affect its outcome.
+    fn run_id<S: crate::TagSystem>(&self, system: &S, start: u64) -> io::Result<u64> {
+        let mut state = Vec::new();
+        system.write_snapshot(start, &mut state)?;
+        Ok(hash::hash_one(&(
+            state,
+            self.step_budget,
+            self.cycle_detection,
+        )))
+    }
+
+    /// Run `system`, which is `start` steps from its seed, stopping early if it joins one of the `known` cycles.
+    ///
+    /// Checkpoints are marked with `run`, which identifies the run as in [`Self::run_id`],
+    /// or if it is `None`, the identity of a run starting from `system`.
+    /// Without cycle detection, `system` is left in the final state.
+    /// If a `log` is given, every step is logged in it and cycle detection is skipped.
+    /// If a `progress` is given, it is reported to before each chunk.
+    /// Returns a report of the run, whose work is counted from `system` rather than the seed.
+    fn run_from<S: crate::TagSystem>(
+        &self,
+        system: &mut S,
+        start: u64,
+        run: Option<u64>,
+        known: Option<&CycleDatabase<S>>,
+        mut log: Option<&mut dyn Log<S>>,
+        mut progress: Option<&mut dyn Progress>,
+    ) -> io::Result<Report<S>> {
+        let start_length = system.length();
+        let budget = self.step_budget.saturating_sub(start);
+        let run = match (&self.checkpoint, run) {
+            (None, _) => 0,
+            (Some(_), Some(run)) => run,
+            (Some(_), None) => self.run_id(system, start)?,
+        };
+        let mut checkpointer = Checkpointer::new(self.checkpoint.as_ref(), start, run);
+
+        let chunk = self
+            .checkpoint
+            .as_ref()
+            .and_then(|checkpoint| checkpoint.every_steps)
+            .map_or(CHUNK, |every_steps| every_steps.clamp(1, CHUNK));
+        let chunk = match known {
+            Some(_) => chunk.min(KNOWN_CYCLE_STRIDE),
+            None => chunk,
+        };
+
+        let exceeds_limit = |memory_usage: usize| {
+            self.memory_limit
+                .is_some_and(|limit| memory_usage as u64 > limit)
+        };
+
+        let exceeds_length =
+            |length: usize| self.length_limit.is_some_and(|limit| length as u64 > limit);
+
+        // Check whether the run should be stopped at step `step` before its next chunk.
+        let deadline = self.time_limit.map(|limit| Instant::now() + limit);
+        let interrupted = |step: u64| {
+            if self
+                .cancellation
+                .as_ref()
+                .is_some_and(|token| token.is_cancelled())
+            {
+                Some(Outcome::Cancelled(step))
+            } else if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                Some(Outcome::TimedOut(step))
+            } else {
+                None
+            }
+        };
+
+        // Check whether `system`, at step `step`, lies on a known cycle.
+        let joined = |system: &S, step: u64| {
+            let known = known?;
+            let id = known.find(system)?;
+            Some(Outcome::Cycled(CycleInfo {
+                detected_at: step,
+                period: known.get(id).period,
+                preperiod: None,
+                entry: system.clone(),
+            }))
+        };
+
+        if let Some(algorithm) = self.cycle_detection.filter(|_| log.is_none()) {
+            let mut detector = Detector::new(system.clone(), algorithm, budget);
+            // The detector's steps which have been added to the steps metric.
+            let mut counted = 0;
+            let report = |outcome: Outcome<S>, detector: &Detector<S>| {
+                // Floyd's algorithm can halt between the steps it counts, so trust the outcome's count.
+                let steps = match outcome {
+                    Outcome::Halted(steps) => steps - start,
+                    _ => detector.step(),
+                };
+                Report::new(
+                    outcome,
+                    detector.peak_length() as u64,
+                    steps,
+                    start_length,
+                    detector.state().length(),
+                )
+            };
+
+            loop {
+                if let Some(outcome) = interrupted(start + detector.step()) {
+                    return Ok(report(outcome, &detector));
+                }
+                if let Some(progress) = &mut progress {
+                    progress.report(
+                        start + detector.step(),
+                        detector.state().length(),
+                        self.step_budget,
+                    );
+                }
+
+                for _ in 0..chunk {
+                    if let ControlFlow::Break(detection) = detector.advance() {
+                        telemetry::steps(detector.step() - counted);
+                        let outcome = match detection {
+                            Detection::Cycle(info) => Outcome::Cycled(CycleInfo {
+                                detected_at: start + info.detected_at,
+                                // If the run resumed on the cycle, it may have been entered before `start`.
+                                preperiod: info
+                                    .preperiod
+                                    .filter(|&preperiod| preperiod > 0 || start == 0)
+                                    .map(|preperiod| start + preperiod),
+                                ..info
+                            }),
+                            Detection::Halted(steps) => Outcome::Halted(start + steps),
+                            Detection::Exhausted => Outcome::BudgetExhausted,
+                        };
+                        return Ok(report(outcome, &detector));
+                    }
+
+                    if exceeds_limit(detector.memory_usage()) {
+                        telemetry::steps(detector.step() - counted);
+                        return Ok(report(
+                            Outcome::MemoryLimitExceeded(start + detector.step()),
+                            &detector,
+                        ));
+                    }
+                    if exceeds_length(detector.peak_length()) {
+                        telemetry::steps(detector.step() - counted);
+                        return Ok(report(
+                            Outcome::LengthLimitExceeded(start + detector.step()),
+                            &detector,
+                        ));
+                    }
+                }
+                telemetry::steps(detector.step() - counted);
+                telemetry::live_state_bytes(detector.memory_usage());
+                counted = detector.step();
+
+                if let Some(outcome) = joined(detector.state(), start + detector.step()) {
+                    return Ok(report(outcome, &detector));
+                }
+                checkpointer.maybe_write(detector.state(), start + detector.step())?;
+            }
+        }
+
+        let mut steps = 0;
+        let mut peak_length = system.length() as u64;
+        while steps < budget {
+            let outcome = match exceeds_length(system.length()) {
+                true => Some(Outcome::LengthLimitExceeded(start + steps)),
+                false => interrupted(start + steps),
+            };
+            if let Some(outcome) = outcome {
+                return Ok(Report::new(
+                    outcome,
+                    peak_length,
+                    steps,
+                    start_length,
+                    system.length(),
+                ));
+            }
+            if let Some(progress) = &mut progress {
+                progress.report(start + steps, system.length(), self.step_budget);
+            }
+
+            let chunk = (budget - steps).min(chunk);
+            // Each step lengthens the state by at most one symbol, so the state can't exceed the length limit
+            // until this many steps have passed, and if it does then, this is the exact step it did.
+            let chunk = match self.length_limit {
+                Some(limit) => chunk.min((limit - system.length() as u64).saturating_add(1)),
+                None => chunk,
+            };
+
+            let evolved = match &mut log {
+                Some(log) => log.evolve_multi(system, chunk as usize),
+                None => system.evolve_multi(chunk as usize),
+            };
+            if let ControlFlow::Break(n) = evolved {
+                telemetry::steps(n as u64);
+                return Ok(Report::new(
+                    Outcome::Halted(start + steps + n as u64),
+                    peak_length,
+                    steps + n as u64,
+                    start_length,
+                    system.length(),
+                ));
+            }
+
+            steps += chunk;
+            peak_length = peak_length.max(system.length() as u64);
+            let log_memory = log.as_ref().map_or(0, |log| log.memory_usage());
+            telemetry::steps(chunk);
+            telemetry::live_state_bytes(system.memory_usage() + log_memory);
+            if exceeds_limit(system.memory_usage() + log_memory) {
+                return Ok(Report::new(
+                    Outcome::MemoryLimitExceeded(start + steps),
+                    peak_length,
+                    steps,
+                    start_length,
+                    system.length(),
+                ));
+            }
+            if let Some(outcome) = joined(system, start + steps) {
+                return Ok(Report::new(
+                    outcome,
+                    peak_length,
+                    steps,
+                    start_length,
+                    system.length(),
+                ));
+            }
+            checkpointer.maybe_write(system, start + steps)?;
+        }
+
+        Ok(Report::new(
+            Outcome::BudgetExhausted,
+            peak_length,
+            steps,
+            start_length,
+            system.length(),
+        ))
+    }
+}
+
+/// Builds a [`Runner`] one setting at a time, as returned by [`Runner::builder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunnerBuilder(Runner);
+
+impl RunnerBuilder {
+    /// Set [`Runner::step_budget`].
+    pub fn step_budget(mut self, step_budget: u64) -> Self {
+        self.0.step_budget = step_budget;
+        self
+    }
+
+    /// Set [`Runner::cycle_detection`].
+    pub fn cycle_detection(mut self, algorithm: Algorithm) -> Self {
+        self.0.cycle_detection = Some(algorithm);
+        self
+    }
+
+    /// Set [`Runner::checkpoint`].
+    pub fn checkpoint(mut self, checkpoint: Checkpoint) -> Self {
+        self.0.checkpoint = Some(checkpoint);
+        self
+    }
+
+    /// Set [`Runner::memory_limit`], in bytes.
+    pub fn memory_limit(mut self, bytes: u64) -> Self {
+        self.0.memory_limit = Some(bytes);
+        self
+    }
+
+    /// Set [`Runner::cancellation`].
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.0.cancellation = Some(token);
+        self
+    }
+
+    /// Set [`Runner::length_limit`], in symbols.
+    pub fn length_limit(mut self, symbols: u64) -> Self {
+        self.0.length_limit = Some(symbols);
+        self
+    }
+
+    /// Set [`Runner::time_limit`].
+    pub fn time_limit(mut self, limit: Duration) -> Self {
+        self.0.time_limit = Some(limit);
+        self
+    }
+
+    /// Set [`Runner::record_every`], in steps.
+    pub fn record_every(mut self, steps: u64) -> Self {
+        self.0.record_every = Some(steps);
+        self
+    }
+
+    /// Finish building the runner.
+    pub fn build(self) -> Runner {
+        self.0
+    }
+}
+
+/// Evolves systems while logging their steps, like a [`DeltaLog`], which only supports some systems.
+trait Log<S> {
+    /// Evolve `system` by `n` steps, like [`crate::TagSystem::evolve_multi`], logging each one.
+    fn evolve_multi(&mut self, system: &mut S, n: usize) -> ControlFlow<usize>;
+
+    /// Estimate the bytes occupied by the log.
+    fn memory_usage(&self) -> usize;
+}
+
+impl<S: PostSystem> Log<S> for DeltaLog {
+    fn evolve_multi(&mut self, system: &mut S, n: usize) -> ControlFlow<usize> {
+        DeltaLog::evolve_multi(self, system, n)
+    }
+
+    fn memory_usage(&self) -> usize {
+        DeltaLog::memory_usage(self)
+    }
+}
+
+impl<S: PostSystem> Log<S> for Recorder<S> {
+    fn evolve_multi(&mut self, system: &mut S, n: usize) -> ControlFlow<usize> {
+        match self.run(system, n) {
+            Ok(flow) => flow,
+            Err(never) => match never {},
+        }
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.states()
+            .iter()
+            .map(|snapshot| snapshot.state.memory_usage())
+            .sum()
+    }
+}
+
+/// Tracks when the last checkpoint was written, and writes new ones when they are due.
+struct Checkpointer<'a> {
+    checkpoint: Option<&'a Checkpoint>,
+    /// The identity of the run, as in [`Runner::run_id`], which is written after each snapshot.
+    run: u64,
+    last_step: u64,
+    last_time: Instant,
+}
+
+impl<'a> Checkpointer<'a> {
+    fn new(checkpoint: Option<&'a Checkpoint>, start: u64, run: u64) -> Self {
+        Self {
+            checkpoint,
+            run,
+            last_step: start,
+            last_time: Instant::now(),
+        }
+    }
+
+    /// Write a checkpoint of `system` at `step` if one is due.
+    fn maybe_write<S: crate::TagSystem>(&mut self, system: &S, step: u64) -> io::Result<()> {
+        let Some(checkpoint) = self.checkpoint else {
+            return Ok(());
+        };
+
+        let steps_due = checkpoint
+            .every_steps
+            .is_some_and(|every_steps| step - self.last_step >= every_steps);
+        let time_due = checkpoint
+            .every
+            .is_some_and(|every| self.last_time.elapsed() >= every);
+        if !steps_due && !time_due {
+            return Ok(());
+        }
+
+        // Write to a temporary file first, so a crash mid-write never clobbers the previous checkpoint.
+        let mut tmp = checkpoint.path.clone().into_os_string();
+        tmp.push(".tmp");
+
+        let mut out = BufWriter::new(File::create(&tmp)?);
+        system.write_snapshot(step, &mut out)?;
+        out.write_all(&self.run.to_le_bytes())?;
+        out.into_inner()?.sync_all()?;
+        fs::rename(&tmp, &checkpoint.path)?;
+
+        self.last_step = step;
+        self.last_time = Instant::now();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{seeds, system::BitString};
+
+    use super::*;
+
+    #[test]
+    fn classifies() {
+        let runner = Runner::new(1000, Some(Algorithm::Brent));
+
+        assert_eq!(
+            runner.run(BitString::new_decompressed(&[false, false])),
+            Outcome::Halted(4)
+        );
+        assert!(matches!(
+            runner.run(BitString::new_decompressed(&[true])),
+            Outcome::Cycled(CycleInfo { period: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn builds() {
+        assert_eq!(Runner::builder().build(), Runner::new(u64::MAX, None));
+
+        let token = CancellationToken::new();
+        let checkpoint = Checkpoint {
+            path: "run.snapshot".into(),
+            every_steps: Some(1000),
+            every: None,
+        };
+        let runner = Runner::builder()
+            .step_budget(1000)
+            .cycle_detection(Algorithm::Floyd)
+            .checkpoint(checkpoint.clone())
+            .memory_limit(1 << 20)
+            .cancellation(token.clone())
+            .time_limit(Duration::from_secs(5))
+            .length_limit(100)
+            .record_every(1000)
+            .build();
+
+        let mut expected = Runner::new(1000, Some(Algorithm::Floyd));
+        expected.checkpoint = Some(checkpoint);
+        expected.memory_limit = Some(1 << 20);
+        expected.cancellation = Some(token);
+        expected.time_limit = Some(Duration::from_secs(5));
+        expected.length_limit = Some(100);
+        expected.record_every = Some(1000);
+        assert_eq!(runner, expected);
+    }
+
+    #[test]
+    fn records_every_so_often() {
+        let runner = Runner::builder()
+            .step_budget(10_500)
+            .cycle_detection(Algorithm::Brent)
+            .record_every(1000)
+            .build();
+        let (outcome, states) = runner.run_recorded(seeds::SEED_5854.system::<BitString>());
+
+        assert_eq!(outcome, Outcome::BudgetExhausted);
+        let steps: Vec<_> = states.iter().map(|snapshot| snapshot.step).collect();
+        assert_eq!(steps, (0..=10).map(|i| i * 1000).collect::<Vec<_>>());
+        assert_eq!(crate::record::verify(&states), Ok(()));
+
+        let (outcome, states) =
+            Runner::new(1000, None).run_recorded(BitString::new_decompressed(&[false, false]));
+        assert_eq!(outcome, Outcome::Halted(4));
+        let steps: Vec<_> = states.iter().map(|snapshot| snapshot.step).collect();
+        assert_eq!(steps, [0, 4]);
+    }
+
+    #[test]
+    fn measures_peak_length() {
+        let runner = Runner::new(1000, Some(Algorithm::Brent));
+        let (outcome, peak_length) = runner.run_with_peak(BitString::new_decompressed(&[true]));
+
+        assert!(matches!(outcome, Outcome::Cycled(_)));
+        assert_eq!(peak_length, 6);
+    }
+
+    #[test]
+    fn counts_work() {
+        let seed: BitString = seeds::ONES_5.system();
+        let mut log = DeltaLog::new(0);
+        let _ = log.evolve_multi(&mut seed.clone(), 1000);
+        let appended = log
+            .deltas()
+            .iter()
+            .map(|delta| delta.appended().len() as u64)
+            .sum();
+
+        for algorithm in [None, Some(Algorithm::Floyd), Some(Algorithm::Brent)] {
+            let report = Runner::new(1000, algorithm).run_with_report(seed.clone());
+            assert_eq!(report.outcome, Outcome::Halted(409));
+            assert_eq!(
+                report.work,
+                Work {
+                    deleted: 3 * 409,
+                    appended
+                }
+            );
+        }
+
+        // Without cycle detection, the whole budget is simulated.
+        let report = Runner::new(1000, None).run_with_report(BitString::new_decompressed(&[true]));
+        assert_eq!(report.work.deleted, 3000);
+        assert_eq!(report.work.symbols(), 3000 + 3000 + 2);
+    }
+
+    #[test]
+    fn exhausts_budget() {
+        let runner = Runner::new(1000, None);
+
+        assert_eq!(
+            runner.run(BitString::new_decompressed(&[false, false])),
+            Outcome::Halted(4)
+        );
+        assert_eq!(
+            runner.run(BitString::new_decompressed(&[true])),
+            Outcome::BudgetExhausted
+        );
+    }
+
+    #[test]
+    fn stops_at_known_cycles() {
+        let seed = || BitString::new_decompressed(&[true, true, true, true, true, true, true]);
+
+        let Outcome::Cycled(detected) = Runner::new(1_000_000, Some(Algorithm::Brent)).run(seed())
+        else {
+            panic!("expected a cycle");
+        };
+
+        let mut known = CycleDatabase::new();
+        known.insert(&detected.entry, detected.period);
+
+        for runner in [
+            Runner::new(1_000_000, Some(Algorithm::Brent)),
+            Runner::new(1_000_000, None),
+        ] {
+            let Outcome::Cycled(joined) = runner.run_with_known_cycles(seed(), &known) else {
+                panic!("expected a cycle");
+            };
+            assert_eq!(joined.period, detected.period);
+            assert!(joined.detected_at < detected.detected_at);
+            assert!(known.find(&joined.entry).is_some());
+        }
+    }
+
+    #[test]
+    fn limits_memory() {
+        for cycle_detection in [None, Some(Algorithm::Brent), Some(Algorithm::HashSet)] {
+            let mut runner = Runner::new(100_000, cycle_detection);
+            runner.memory_limit = Some(100);
+
+            let outcome = runner.run(seeds::SEED_5854.system::<BitString>());
+            let Outcome::MemoryLimitExceeded(step) = outcome else {
+                panic!("expected to exceed the memory limit, got {outcome:?}");
+            };
+            assert!(step < 100_000);
+
+            // Without the limit, the run continues past that step.
+            runner.memory_limit = None;
+            match runner.run(seeds::SEED_5854.system::<BitString>()) {
+                Outcome::Halted(steps) => assert!(steps > step),
+                Outcome::Cycled(info) => assert!(info.detected_at > step),
+                outcome => assert_eq!(outcome, Outcome::BudgetExhausted),
+            }
+        }
+
+        // A limit which is never reached changes nothing.
+        let mut runner = Runner::new(1000, Some(Algorithm::Brent));
+        runner.memory_limit = Some(1000);
+        assert!(matches!(
+            runner.run(BitString::new_decompressed(&[true])),
+            Outcome::Cycled(CycleInfo { period: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn cancels() {
+        let token = CancellationToken::new();
+        let mut runner = Runner::new(u64::MAX, None);
+        runner.cancellation = Some(token.clone());
+
+        // `100` cycles forever, so without detection only cancellation stops the run.
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            token.cancel();
+        });
+        let Outcome::Cancelled(step) = runner.run(BitString::new_decompressed(&[true])) else {
+            panic!("run should be cancelled");
+        };
+        assert!(step > 0);
+        canceller.join().unwrap();
+
+        runner.cycle_detection = Some(Algorithm::Brent);
+        assert_eq!(
+            runner.run(seeds::SEED_5854.system::<BitString>()),
+            Outcome::Cancelled(0)
+        );
+    }
+
+    #[test]
+    fn stops_at_length_limit() {
+        let seed = seeds::ONES_5.system::<BitString>();
+        // Cycle detection measures every state, so the peak length is exact.
+        let peak_length = Runner::new(u64::MAX, Some(Algorithm::Brent))
+            .run_with_report(seed.clone())
+            .peak_length;
+        let mut runner = Runner::new(u64::MAX, None);
+
+        // Find the first step at which the state is longer than the limit.
+        let limit = peak_length - 10;
+        let mut system = seed.clone();
+        let mut exceeded_at = 0;
+        while system.length() as u64 <= limit {
+            let _ = system.evolve();
+            exceeded_at += 1;
+        }
+        assert!(exceeded_at > 0);
+
+        runner.length_limit = Some(limit);
+        assert_eq!(
+            runner.run(seed.clone()),
+            Outcome::LengthLimitExceeded(exceeded_at)
+        );
+
+        // With cycle detection, the run stops soon after.
+        runner.cycle_detection = Some(Algorithm::Brent);
+        let Outcome::LengthLimitExceeded(step) = runner.run(seed.clone()) else {
+            panic!("run should exceed the length limit");
+        };
+        assert!(step >= exceeded_at);
+
+        // A state already longer than the limit stops the run immediately.
+        runner.length_limit = Some(1);
+        runner.cycle_detection = None;
+        assert_eq!(runner.run(seed.clone()), Outcome::LengthLimitExceeded(0));
+
+        // A limit which is never exceeded changes nothing.
+        runner.length_limit = Some(peak_length);
+        assert_eq!(runner.run(seed), Outcome::Halted(409));
+    }
+
+    #[test]
+    fn times_out() {
+        let mut runner = Runner::new(u64::MAX, None);
+        runner.time_limit = Some(Duration::from_millis(10));
+
+        // `100` cycles forever, so without detection only the time limit stops the run.
+        let Outcome::TimedOut(step) = runner.run(BitString::new_decompressed(&[true])) else {
+            panic!("run should time out");
+        };
+        assert!(step > 0);
+
+        runner.time_limit = Some(Duration::ZERO);
+        runner.cycle_detection = Some(Algorithm::Brent);
+        assert_eq!(
+            runner.run(seeds::SEED_5854.system::<BitString>()),
+            Outcome::TimedOut(0)
+        );
+
+        // A limit which is never reached changes nothing.
+        runner.time_limit = Some(Duration::from_secs(3600));
+        assert_eq!(
+            runner.run(seeds::SEED_6008.system::<BitString>()),
+            Outcome::Halted(261_741)
+        );
+    }
+
+    #[test]
+    fn reports_progress() {
+        let runner = Runner::new(200_000, None);
+        let mut reports = Vec::new();
+        let outcome = runner.run_with_progress(
+            seeds::SEED_6008.system::<BitString>(),
+            &mut |steps, length, total| reports.push((steps, length, total)),
+        );
+        assert_eq!(outcome, Outcome::BudgetExhausted);
+
+        let steps: Vec<_> = reports.iter().map(|&(steps, _, _)| steps).collect();
+        assert_eq!(steps, [0, CHUNK, 2 * CHUNK, 3 * CHUNK]);
+        assert!(reports.iter().all(|&(_, _, total)| total == 200_000));
+
+        let mut system: BitString = seeds::SEED_6008.system();
+        let _ = system.evolve_multi(CHUNK as usize);
+        assert_eq!(reports[1].1, system.length());
+    }
+
+    #[test]
+    fn logs_deltas() {
+        let seed: BitString = seeds::SEED_6008.system();
+        let mut system = seed.clone();
+        let mut log = DeltaLog::new(0);
+
+        let mut runner = Runner::new(100_000, Some(Algorithm::Brent));
+        assert_eq!(
+            runner.run_with_deltas(&mut system, &mut log),
+            Outcome::BudgetExhausted
+        );
+        assert_eq!(log.step(), 100_000);
+
+        let mut expected = seed.clone();
+        let _ = expected.evolve_multi(100_000);
+        assert_eq!(system, expected);
+
+        // The run can be continued with a larger budget, and then stepped backward.
+        runner.step_budget = 1_000_000;
+        assert_eq!(
+            runner.run_with_deltas(&mut system, &mut log),
+            Outcome::Halted(261_741)
+        );
+        log.seek(&mut system, 0);
+        assert_eq!(system, seed);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_outcomes() {
+        let runner = Runner::new(1000, Some(Algorithm::Floyd));
+        let outcome = runner.run(BitString::new_decompressed(&[true, true]));
+
+        let json = serde_json::to_string(&(&runner, &outcome)).unwrap();
+        assert_eq!(
+            serde_json::from_str::<(Runner, Outcome<BitString>)>(&json).unwrap(),
+            (runner, outcome)
+        );
+    }
+
+    #[test]
+    fn checkpoints_and_resumes() {
+        let path = std::env::temp_dir().join(format!(
+            "post-tag-checkpoint-{}.snapshot",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        let seed = || seeds::SEED_6008.system::<BitString>();
+
+        // Stop partway, leaving a checkpoint behind.
+        let token = CancellationToken::new();
+        let mut runner = Runner::new(1_000_000, None);
+        runner.checkpoint = Some(Checkpoint {
+            path: path.clone(),
+            every_steps: Some(1000),
+            every: None,
+        });
+        runner.cancellation = Some(token.clone());
+        let outcome = runner.run_with_progress(seed(), &mut |steps, _, _| {
+            if steps >= 5000 {
+                token.cancel();
+            }
+        });
+        assert_eq!(outcome, Outcome::Cancelled(6000));
+
+        let snapshot = snapshot::load::<BitString>(&mut File::open(&path).unwrap()).unwrap();
+        let mut expected = seed();
+        let _ = expected.evolve_multi(6000);
+        assert_eq!(snapshot.step, 6000);
+        assert_eq!(snapshot.state, expected);
+
+        // Resuming continues from the checkpoint.
+        runner.cancellation = None;
+        assert_eq!(runner.resume(seed()).unwrap(), Outcome::Halted(261_741));
+
+        // A checkpoint of a different run is rejected.
+        let other_seed = runner.resume(seeds::SEED_5854.system::<BitString>());
+        assert_eq!(other_seed.unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let mut other_budget = runner.clone();
+        other_budget.step_budget = 2_000_000;
+        assert_eq!(
+            other_budget.resume(seed()).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let mut other_detection = runner.clone();
+        other_detection.cycle_detection = Some(Algorithm::Brent);
+        assert_eq!(
+            other_detection.resume(seed()).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,127 @@
+//! Notable seeds, with what is known about their fate.
+//!
+//! The fates recorded here were computed with this crate, and are checked by its tests.
+
+use crate::PostSystem;
+
+/// The eventual behavior of a seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Fate {
+    /// The system halts after `steps` steps.
+    Halts { steps: u64 },
+    /// The system enters a cycle of length `period` after exactly `preperiod` steps.
+    Cycles { preperiod: u64, period: u64 },
+}
+
+/// A notable seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NotableSeed {
+    /// A short human-readable name.
+    pub name: &'static str,
+    /// The compressed seed, as in [`PostSystem::from_seed_u128`].
+    pub seed: u128,
+    /// What happens to the system.
+    pub fate: Fate,
+}
+
+impl NotableSeed {
+    /// Initialize a system from this seed.
+    pub fn system<S: PostSystem>(&self) -> S {
+        S::from_seed_u128(self.seed)
+    }
+}
+
+/// Seed 5854, `1011011011110`, which takes 342,034 steps to enter a cycle.
+pub const SEED_5854: NotableSeed = NotableSeed {
+    name: "5854",
+    seed: 5854,
+    fate: Fate::Cycles {
+        preperiod: 342_034,
+        period: 6,
+    },
+};
+
+/// Seed 6008, `1011101111000`, which takes longer to halt than any other seed of length at most 13.
+pub const SEED_6008: NotableSeed = NotableSeed {
+    name: "6008",
+    seed: 6008,
+    fate: Fate::Halts { steps: 261_741 },
+};
+
+/// `(100)^5`, the shortest string of the form `(100)^n` which halts.
+pub const ONES_5: NotableSeed = NotableSeed {
+    name: "(100)^5",
+    seed: 0b11111,
+    fate: Fate::Halts { steps: 409 },
+};
+
+/// `(100)^7`, which enters a cycle of length 28.
+pub const ONES_7: NotableSeed = NotableSeed {
+    name: "(100)^7",
+    seed: 0b1111111,
+    fate: Fate::Cycles {
+        preperiod: 2128,
+        period: 28,
+    },
+};
+
+/// `(100)^13`, which halts.
+pub const ONES_13: NotableSeed = NotableSeed {
+    name: "(100)^13",
+    seed: 0b1111111111111,
+    fate: Fate::Halts { steps: 701 },
+};
+
+/// `(100)^14`, which halts after a long transient.
+pub const ONES_14: NotableSeed = NotableSeed {
+    name: "(100)^14",
+    seed: 0b11111111111111,
+    fate: Fate::Halts { steps: 37_910 },
+};
+
+/// All notable seeds in this module.
+pub const ALL: &[NotableSeed] = &[SEED_5854, SEED_6008, ONES_5, ONES_7, ONES_13, ONES_14];
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+
+    use crate::system::BitString;
+
+    use super::*;
+
+    #[test]
+    fn fates_are_correct() {
+        for seed in ALL {
+            match seed.fate {
+                Fate::Halts { steps } => {
+                    let mut system: BitString = seed.system();
+                    assert_eq!(
+                        system.evolve_multi(steps as usize + 1),
+                        ControlFlow::Break(steps as usize),
+                        "{}",
+                        seed.name
+                    );
+                }
+                Fate::Cycles { preperiod, period } => {
+                    let mut before: BitString = seed.system();
+                    let _ = before.evolve_multi(preperiod as usize - 1);
+
+                    let mut entry = before.clone();
+                    let _ = entry.evolve();
+
+                    let mut after = entry.clone();
+                    let mut wrapped = Vec::new();
+                    for _ in 0..period {
+                        wrapped.push(after.clone());
+                        let _ = after.evolve();
+                    }
+
+                    assert_eq!(after, entry, "{}", seed.name);
+                    assert!(!wrapped[1..].contains(&entry), "{}", seed.name);
+                    assert_ne!(&before, wrapped.last().unwrap(), "{}", seed.name);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! A compact, versioned binary snapshot format for pausing and resuming runs.
+//!
+//! A snapshot consists of, in order and little-endian:
+//!
+//! - the magic bytes `PTAG`,
+//! - the format version, as a `u16`,
+//! - the step count, as a `u64`,
+//! - the length of the state in bits, as a `u64`,
+//! - the bits of the state, packed eight to a byte starting from the least significant bit.
+
+use std::{
+    collections::VecDeque,
+    io::{self, Read, Write},
+};
+
+use crate::PostSystem;
+
+/// The magic bytes at the start of every snapshot.
+pub const MAGIC: [u8; 4] = *b"PTAG";
+
+/// The version of the snapshot format written by this crate.
+pub const VERSION: u16 = 1;
+
+/// A state of a system, along with the number of steps taken to reach it from its seed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot<S> {
+    /// The state of the system.
+    pub state: S,
+    /// The number of steps taken to reach the state.
+    pub step: u64,
+}
+
+impl<S: PostSystem> Snapshot<S> {
+    /// Write the snapshot to `out` in the binary format, as with [`save`].
+    pub fn write_to(&self, out: &mut impl Write) -> io::Result<()> {
+        save(&self.state, self.step, out)
+    }
+}
+
+/// Write a snapshot of `system` at `step` to `out`.
+pub fn save<S: PostSystem>(system: &S, step: u64, out: &mut impl Write) -> io::Result<()> {
+    out.write_all(&MAGIC)?;
+    out.write_all(&VERSION.to_le_bytes())?;
+    out.write_all(&step.to_le_bytes())?;
+    out.write_all(&(system.length() as u64).to_le_bytes())?;
+
+    let mut bytes = vec![0u8; system.length().div_ceil(8)];
+    for (i, bit) in system.bits().enumerate() {
+        bytes[i / 8] |= (bit as u8) << (i % 8);
+    }
+    out.write_all(&bytes)
+}
+
+/// Read a snapshot from `input`.
+pub fn load<S: PostSystem>(input: &mut impl Read) -> io::Result<Snapshot<S>> {
+    let mut magic = [0; 4];
+    input.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(invalid_data("not a post-tag snapshot"));
+    }
+
+    let version = u16::from_le_bytes(read_array(input)?);
+    if version > VERSION {
+        return Err(invalid_data(format!(
+            "unsupported snapshot version {version}, expected at most {VERSION}"
+        )));
+    }
+
+    let step = u64::from_le_bytes(read_array(input)?);
+    let len = usize::try_from(u64::from_le_bytes(read_array(input)?))
+        .map_err(|_| invalid_data("snapshot is too long for this platform"))?;
+
+    let mut bytes = Vec::new();
+    input.take(len.div_ceil(8) as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len.div_ceil(8) {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    let list: VecDeque<bool> = (0..len)
+        .map(|i| (bytes[i / 8] >> (i % 8)) & 1 == 1)
+        .collect();
+
+    Ok(Snapshot {
+        state: S::from_list(list),
+        step,
+    })
+}
+
+fn read_array<const N: usize>(input: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut buf = [0; N];
+    input.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::system::{BitString, VecDequeBools};
+
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        let mut system = BitString::new_decompressed(&[true, false, true, true, false]);
+        let _ = system.evolve_multi(1000);
+
+        let snapshot = Snapshot {
+            state: system,
+            step: 1000,
+        };
+
+        let mut bytes = Vec::new();
+        snapshot.write_to(&mut bytes).unwrap();
+
+        assert_eq!(&bytes[..4], b"PTAG");
+        assert_eq!(
+            bytes.len(),
+            4 + 2 + 8 + 8 + snapshot.state.length().div_ceil(8)
+        );
+
+        assert_eq!(load::<BitString>(&mut bytes.as_slice()).unwrap(), snapshot);
+
+        // Snapshots are independent of the backend.
+        let loaded = load::<VecDequeBools>(&mut bytes.as_slice()).unwrap();
+        assert_eq!(loaded.state, VecDequeBools::from(&snapshot.state));
+        assert_eq!(loaded.step, 1000);
+    }
+
+    #[test]
+    fn rejects_invalid() {
+        let mut bytes = Vec::new();
+        save(&BitString::new_decompressed(&[true]), 0, &mut bytes).unwrap();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(load::<BitString>(&mut bad_magic.as_slice()).is_err());
+
+        let mut future = bytes.clone();
+        future[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert!(load::<BitString>(&mut future.as_slice()).is_err());
+
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(load::<BitString>(&mut &truncated[..]).is_err());
+    }
+}
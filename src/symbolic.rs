@@ -0,0 +1,369 @@
+//! Evolving families and sets of states at once, by leaving some of their symbols unknown.
+
+use std::{collections::VecDeque, fmt, ops::ControlFlow};
+
+use crate::{hash::StateMap, PostSystem};
+
+/// A family of states of the same length, in which each symbol is either known (`Some`) or unknown (`None`).
+///
+/// The family contains every state that agrees with it on the known symbols.
+/// Unknown symbols are never copied by evolution, only deleted, so the family evolves exactly:
+/// after each step it contains precisely the successors of the states it contained before.
+/// When an unknown symbol is read, the family is split in two, one for each value of the symbol.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SymbolicState(VecDeque<Option<bool>>);
+
+/// A family reached by [`SymbolicState::explore`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Branch {
+    /// The family of states reached.
+    pub state: SymbolicState,
+    /// The number of steps taken to reach it.
+    pub step: u64,
+    /// Whether the states halted at `step`, rather than exploration stopping there.
+    pub halted: bool,
+}
+
+impl SymbolicState {
+    /// Create the family of initial strings of the compressed seeds matching `compressed`.
+    ///
+    /// As with [`PostSystem::new_decompressed`], each compressed symbol `x` becomes `x00`.
+    pub fn new_decompressed(compressed: &[Option<bool>]) -> Self {
+        compressed
+            .iter()
+            .flat_map(|&symbol| [symbol, Some(false), Some(false)])
+            .collect()
+    }
+
+    /// Get the length of every state in the family.
+    pub fn length(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Get the number of unknown symbols, so that the family contains `2^unknowns` states.
+    pub fn unknowns(&self) -> usize {
+        self.0.iter().filter(|symbol| symbol.is_none()).count()
+    }
+
+    /// Get the symbols, with `None` for unknown symbols.
+    pub fn symbols(&self) -> impl Iterator<Item = Option<bool>> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// Check whether `state` is in the family.
+    pub fn matches<S: PostSystem>(&self, state: &S) -> bool {
+        self.length() == state.length()
+            && self
+                .symbols()
+                .zip(state.bits())
+                .all(|(symbol, bit)| symbol.is_none_or(|symbol| symbol == bit))
+    }
+
+    /// Iterate over every state in the family.
+    pub fn states<S: PostSystem>(&self) -> impl Iterator<Item = S> + '_ {
+        let unknowns = self.unknowns();
+        assert!(
+            unknowns < u64::BITS as usize,
+            "too many unknown symbols to enumerate"
+        );
+
+        (0..1u64 << unknowns).map(move |assignment| {
+            let mut next = 0;
+            S::from_list(
+                self.symbols()
+                    .map(|symbol| {
+                        symbol.unwrap_or_else(|| {
+                            next += 1;
+                            (assignment >> (next - 1)) & 1 == 1
+                        })
+                    })
+                    .collect(),
+            )
+        })
+    }
+
+    /// Evolve the family by one step, returning [`ControlFlow::Break`] if its states halt.
+    ///
+    /// If the symbol read is unknown, the family is split: `self` becomes the family in which it was zero,
+    /// and the family in which it was one is returned in `Continue(Some(_))`.
+    pub fn evolve(&mut self) -> ControlFlow<(), Option<Self>> {
+        if self.length() < 3 {
+            return ControlFlow::Break(());
+        }
+
+        let first = self.0.pop_front().unwrap();
+        self.0.pop_front();
+        self.0.pop_front();
+
+        let split = match first {
+            Some(first) => {
+                self.append_production(first);
+                None
+            }
+            None => {
+                let mut other = self.clone();
+                other.append_production(true);
+                self.append_production(false);
+                Some(other)
+            }
+        };
+
+        ControlFlow::Continue(split)
+    }
+
+    fn append_production(&mut self, first: bool) {
+        let production: &[bool] = match first {
+            false => &[false, false],
+            true => &[true, true, false, true],
+        };
+        self.0.extend(production.iter().copied().map(Some));
+    }
+
+    /// Evolve the family for up to `max_steps` steps, splitting it as needed.
+    ///
+    /// Returns the families reached, each of which either halted or was evolved for `max_steps` steps.
+    /// Every state of the original family evolves into exactly one of the returned families.
+    pub fn explore(self, max_steps: u64) -> Vec<Branch> {
+        let mut branches = Vec::new();
+        let mut pending = vec![(self, 0)];
+
+        while let Some((mut state, mut step)) = pending.pop() {
+            let halted = loop {
+                if step == max_steps {
+                    break false;
+                }
+
+                match state.evolve() {
+                    ControlFlow::Break(()) => break true,
+                    ControlFlow::Continue(split) => {
+                        step += 1;
+                        if let Some(other) = split {
+                            pending.push((other, step));
+                        }
+                    }
+                }
+            };
+
+            branches.push(Branch {
+                state,
+                step,
+                halted,
+            });
+        }
+
+        branches
+    }
+}
+
+impl<S: PostSystem> From<&S> for SymbolicState {
+    fn from(state: &S) -> Self {
+        state.bits().map(Some).collect()
+    }
+}
+
+impl FromIterator<Option<bool>> for SymbolicState {
+    fn from_iter<I: IntoIterator<Item = Option<bool>>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl fmt::Display for SymbolicState {
+    /// Write the family as `0`s, `1`s, and `?`s for unknown symbols.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for symbol in self.symbols() {
+            f.write_str(match symbol {
+                Some(false) => "0",
+                Some(true) => "1",
+                None => "?",
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A set of states evolved in lockstep, sharing work between states wherever possible.
+///
+/// The set is stored as [`SymbolicState`] families, so states share all work until their first differing symbol is read,
+/// and identical families are merged, so trajectories which meet are only simulated once from then on.
+/// Each state is weighted by the number of initial states whose trajectories pass through it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateSet {
+    /// The running families, each mapped to the number of initial states reaching each of its states.
+    families: StateMap<SymbolicState, u64>,
+    /// The number of steps taken.
+    step: u64,
+    /// The steps at which initial states halted, with how many halted at each.
+    halted: Vec<(u64, u64)>,
+}
+
+impl StateSet {
+    /// Create the set of initial strings of every compressed seed of length `len`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is 64 or more, since the seeds could not be counted.
+    pub fn all_seeds(len: usize) -> Self {
+        assert!(len < u64::BITS as usize, "too many seeds to count");
+        Self::from_family(SymbolicState::new_decompressed(&vec![None; len]))
+    }
+
+    /// Create the set of every state in `family`.
+    pub fn from_family(family: SymbolicState) -> Self {
+        Self {
+            families: [(family, 1)].into_iter().collect(),
+            step: 0,
+            halted: Vec::new(),
+        }
+    }
+
+    /// Get the number of steps taken.
+    pub fn step(&self) -> u64 {
+        self.step
+    }
+
+    /// Get the running families, with the number of initial states reaching each of their states.
+    pub fn families(&self) -> impl Iterator<Item = (&SymbolicState, u64)> {
+        self.families
+            .iter()
+            .map(|(family, &multiplicity)| (family, multiplicity))
+    }
+
+    /// Get the number of initial states which are still running.
+    pub fn running(&self) -> u64 {
+        self.families()
+            .map(|(family, multiplicity)| multiplicity << family.unknowns())
+            .sum()
+    }
+
+    /// Get the steps at which initial states halted, in order, with the number which halted at each.
+    pub fn halted(&self) -> &[(u64, u64)] {
+        &self.halted
+    }
+
+    /// Evolve every running state by one step.
+    pub fn evolve(&mut self) {
+        let mut evolved =
+            StateMap::with_capacity_and_hasher(self.families.len(), Default::default());
+        let mut halted = 0;
+
+        for (mut family, multiplicity) in self.families.drain() {
+            match family.evolve() {
+                ControlFlow::Break(()) => halted += multiplicity << family.unknowns(),
+                ControlFlow::Continue(split) => {
+                    if let Some(other) = split {
+                        *evolved.entry(other).or_default() += multiplicity;
+                    }
+                    *evolved.entry(family).or_default() += multiplicity;
+                }
+            }
+        }
+
+        if halted > 0 {
+            self.halted.push((self.step, halted));
+        }
+        self.families = evolved;
+        self.step += 1;
+    }
+
+    /// Evolve every running state by `n` steps, stopping early if they all halt.
+    pub fn evolve_multi(&mut self, n: u64) {
+        for _ in 0..n {
+            if self.families.is_empty() {
+                return;
+            }
+            self.evolve();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{census, system::BitString};
+
+    use super::*;
+
+    #[test]
+    fn splits_on_unknown_symbols() {
+        let mut state = SymbolicState::new_decompressed(&[Some(true), None]);
+        assert_eq!(state.to_string(), "100?00");
+        assert_eq!(state.unknowns(), 1);
+
+        assert_eq!(state.evolve(), ControlFlow::Continue(None));
+        assert_eq!(state.to_string(), "?001101");
+
+        let ControlFlow::Continue(Some(other)) = state.evolve() else {
+            panic!("reading an unknown symbol should split the state");
+        };
+        assert_eq!(state.to_string(), "110100");
+        assert_eq!(other.to_string(), "11011101");
+    }
+
+    #[test]
+    fn enumerates_states() {
+        let state = SymbolicState::new_decompressed(&[None, Some(true), None]);
+        let states: Vec<BitString> = state.states().collect();
+
+        assert_eq!(states.len(), 4);
+        assert!(states.iter().all(|s| state.matches(s)));
+        assert!(!state.matches(&BitString::new_decompressed(&[true, false, true])));
+        assert!(!state.matches(&BitString::new_decompressed(&[true, true])));
+    }
+
+    #[test]
+    fn explores_like_each_seed() {
+        let family = SymbolicState::new_decompressed(&[Some(true), None, None, Some(true), None]);
+        let branches = family.clone().explore(100);
+
+        for seed in family.states::<BitString>() {
+            assert!(
+                branches.iter().any(|branch| {
+                    let mut system = seed.clone();
+                    system.evolve_multi(branch.step as usize).is_continue()
+                        && branch.state.matches(&system)
+                        && system.evolve().is_break() == branch.halted
+                }),
+                "{seed:?}"
+            );
+        }
+
+        // Splitting partitions the family, so no states are gained or lost.
+        let sizes: usize = branches
+            .iter()
+            .map(|branch| 1 << branch.state.unknowns())
+            .sum();
+        assert_eq!(sizes, 1 << family.unknowns());
+
+        assert!(branches
+            .iter()
+            .all(|branch| branch.halted || branch.step == 100));
+    }
+
+    #[test]
+    fn evolves_all_seeds_at_once() {
+        let len = 8;
+        let mut set = StateSet::all_seeds(len);
+        assert_eq!(set.running(), 1 << len);
+
+        set.evolve_multi(1000);
+        assert!(set.families().count() < 1 << len);
+
+        let mut halted: HashMap<u64, u64> = HashMap::new();
+        let mut running = 0;
+        for seed in census::seeds(len as u32).filter(|seed| seed.len() == len) {
+            let mut system = BitString::new_decompressed(&seed);
+            match system.evolve_multi(1000) {
+                ControlFlow::Break(steps) => *halted.entry(steps as u64).or_default() += 1,
+                ControlFlow::Continue(()) => running += 1,
+            }
+        }
+
+        assert_eq!(set.running(), running);
+        assert_eq!(set.halted().len(), halted.len());
+        for &(step, count) in set.halted() {
+            assert_eq!(halted[&step], count, "halted at step {step}");
+        }
+    }
+}
@@ -0,0 +1,469 @@
+//! Lookup tables for evolving a bit string several steps at once.
+
+use std::{
+    error::Error,
+    fmt,
+    io::{self, Read, Write},
+    marker::PhantomData,
+    mem, slice,
+    sync::Arc,
+};
+
+use super::Word;
+
+/// The largest timestep a [`Builder`] accepts, which bounds tables to `2^24` entries.
+pub const MAX_TIMESTEP: u8 = 24;
+
+/// A lookup table chosen at runtime, shared between every bit string using it.
+///
+/// Build one with [`GenericBitString::builder`](super::GenericBitString::builder),
+/// and attach it with [`GenericBitString::with_lut`](super::GenericBitString::with_lut).
+/// Cloning is cheap, since the table itself is reference-counted.
+///
+/// Large tables can be saved with [`Self::write_to`] and loaded again with [`Self::read_from`],
+/// or with `Lut::map` if the `mmap` feature is enabled, which is much faster than generating them.
+#[derive(Clone)]
+pub struct Lut<W> {
+    timestep: u8,
+    lookups: u8,
+    table: Arc<Table<W>>,
+}
+
+/// The entries of a [`Lut`], either generated in memory or mapped from a file.
+enum Table<W> {
+    Owned(Box<[W]>),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl<W: Word> Table<W> {
+    fn entries(&self) -> &[W] {
+        match self {
+            Self::Owned(entries) => entries,
+            // SAFETY: the map was checked to hold a whole number of aligned words after the header when it was created,
+            // and every bit pattern is a valid word.
+            #[cfg(feature = "mmap")]
+            Self::Mapped(map) => unsafe {
+                let entries = &map[HEADER_LEN..];
+                slice::from_raw_parts(entries.as_ptr().cast(), entries.len() / mem::size_of::<W>())
+            },
+        }
+    }
+}
+
+/// The magic bytes at the start of a saved table.
+const MAGIC: &[u8; 6] = b"PTLUT\0";
+
+/// The length of the header of a saved table, which keeps the entries aligned for any word.
+const HEADER_LEN: usize = 16;
+
+/// Build the header of a saved table.
+///
+/// Entries are saved in native byte order, which the header records so that a table is never loaded on a machine which disagrees.
+fn header<W>(timestep: u8, lookups: u8) -> [u8; HEADER_LEN] {
+    let mut header = [0; HEADER_LEN];
+    header[..6].copy_from_slice(MAGIC);
+    header[6] = mem::size_of::<W>() as u8;
+    header[7] = cfg!(target_endian = "big") as u8;
+    header[8] = timestep;
+    header[9] = lookups;
+    header
+}
+
+/// Parse and validate the header of a saved table, returning its timestep and number of lookups.
+fn parse_header<W: Word>(header: &[u8]) -> io::Result<(u8, u8)> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    if header.len() < HEADER_LEN || &header[..6] != MAGIC {
+        return Err(invalid("not a lookup table".into()));
+    }
+    if header[6] as usize != mem::size_of::<W>() || header[7] != cfg!(target_endian = "big") as u8 {
+        return Err(invalid(format!(
+            "lookup table was saved for {}-byte words with a different byte order",
+            header[6]
+        )));
+    }
+
+    let (timestep, lookups) = (header[8], header[9]);
+    Builder::<W>::new()
+        .timestep(timestep)
+        .lookups(lookups)
+        .validate()
+        .map_err(|e| invalid(e.to_string()))?;
+
+    Ok((timestep, lookups))
+}
+
+/// View words as their bytes in native byte order.
+fn as_bytes<W: Word>(words: &[W]) -> &[u8] {
+    // SAFETY: words are plain integers with no padding.
+    unsafe { slice::from_raw_parts(words.as_ptr().cast(), mem::size_of_val(words)) }
+}
+
+impl<W: Word> Lut<W> {
+    /// The number of steps taken per table access.
+    pub fn timestep(&self) -> u8 {
+        self.timestep
+    }
+
+    /// The number of table accesses composed into a single jump.
+    pub fn lookups(&self) -> u8 {
+        self.lookups
+    }
+
+    /// The number of steps taken per jump.
+    pub fn jump(&self) -> u8 {
+        self.timestep * self.lookups
+    }
+
+    /// Get the bits appended by a jump which deleted `deleted`, and how many there are.
+    pub(super) fn compose(&self, deleted: W) -> (W, u8) {
+        compose(self.table.entries(), self.timestep, self.lookups, deleted)
+    }
+
+    /// Save the table, so that it can be loaded without generating it again.
+    pub fn write_to(&self, mut out: impl Write) -> io::Result<()> {
+        out.write_all(&header::<W>(self.timestep, self.lookups))?;
+        out.write_all(as_bytes(self.table.entries()))
+    }
+
+    /// Load a table saved by [`Self::write_to`].
+    ///
+    /// The entries are trusted, so a corrupted table gives wrong results, but never undefined behavior.
+    pub fn read_from(mut input: impl Read) -> io::Result<Self> {
+        let mut header = [0; HEADER_LEN];
+        input.read_exact(&mut header)?;
+        let (timestep, lookups) = parse_header::<W>(&header)?;
+
+        let mut entries = vec![W::ZERO; 1 << timestep].into_boxed_slice();
+        // SAFETY: the bytes cover exactly the entries, and every bit pattern is a valid word.
+        let bytes = unsafe {
+            slice::from_raw_parts_mut(
+                entries.as_mut_ptr().cast::<u8>(),
+                mem::size_of_val(&*entries),
+            )
+        };
+        input.read_exact(bytes)?;
+
+        Ok(Self {
+            timestep,
+            lookups,
+            table: Arc::new(Table::Owned(entries)),
+        })
+    }
+
+    /// Map a table saved by [`Self::write_to`] into memory, rather than reading it.
+    ///
+    /// Pages of the table are only read from disk when they are first used,
+    /// so this is the fastest way for a short-lived process to load a large table.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified while the table, or any bit string using it, is alive.
+    #[cfg(feature = "mmap")]
+    pub unsafe fn map(file: &std::fs::File) -> io::Result<Self> {
+        let map = unsafe { memmap2::Mmap::map(file)? };
+        let (timestep, lookups) = parse_header::<W>(&map)?;
+
+        if map.len() != HEADER_LEN + (mem::size_of::<W>() << timestep) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "lookup table has the wrong length",
+            ));
+        }
+        if !map[HEADER_LEN..].as_ptr().cast::<W>().is_aligned() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "lookup table is not aligned in memory",
+            ));
+        }
+
+        Ok(Self {
+            timestep,
+            lookups,
+            table: Arc::new(Table::Mapped(map)),
+        })
+    }
+}
+
+impl<W> fmt::Debug for Lut<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lut")
+            .field("timestep", &self.timestep)
+            .field("lookups", &self.lookups)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A builder for a [`Lut`], which defaults to the table the word type uses on its own.
+#[derive(Debug, Clone, Copy)]
+pub struct Builder<W> {
+    timestep: u8,
+    lookups: u8,
+    word: PhantomData<W>,
+}
+
+impl<W: Word> Builder<W> {
+    pub(super) fn new() -> Self {
+        Self {
+            timestep: W::TIMESTEP,
+            lookups: W::LOOKUPS,
+            word: PhantomData,
+        }
+    }
+
+    /// Set the number of steps taken per table access.
+    ///
+    /// The table has `2^timestep` entries, so each increment doubles its size.
+    pub fn timestep(mut self, timestep: u8) -> Self {
+        self.timestep = timestep;
+        self
+    }
+
+    /// Set the number of table accesses composed into a single jump.
+    pub fn lookups(mut self, lookups: u8) -> Self {
+        self.lookups = lookups;
+        self
+    }
+
+    /// Generate the table.
+    ///
+    /// A jump deletes three bits and appends up to four per step, all in one word,
+    /// so `4 * timestep * lookups` must be at most the width of the word.
+    pub fn build(self) -> Result<Lut<W>, LutError> {
+        self.validate()?;
+
+        Ok(Lut {
+            timestep: self.timestep,
+            lookups: self.lookups,
+            table: Arc::new(Table::Owned(build(self.timestep).into())),
+        })
+    }
+
+    /// Check that the configuration is usable, as described in [`Self::build`].
+    fn validate(&self) -> Result<(), LutError> {
+        if self.timestep == 0 || self.lookups == 0 {
+            return Err(LutError::Empty);
+        }
+        if self.timestep > MAX_TIMESTEP {
+            return Err(LutError::TooLarge {
+                timestep: self.timestep,
+            });
+        }
+        let steps = self.timestep as u32 * self.lookups as u32;
+        if 4 * steps > W::BITS {
+            return Err(LutError::TooWide {
+                steps,
+                word_bits: W::BITS,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// An error returned when a [`Builder`] is given an unusable configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LutError {
+    /// The timestep or the number of lookups was zero.
+    Empty,
+    /// The timestep was greater than [`MAX_TIMESTEP`].
+    TooLarge { timestep: u8 },
+    /// The bits appended by a jump of `steps` steps might not fit in a word.
+    TooWide { steps: u32, word_bits: u32 },
+}
+
+impl fmt::Display for LutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("the timestep and number of lookups must be nonzero"),
+            Self::TooLarge { timestep } => write!(
+                f,
+                "timestep {timestep} is too large, the maximum is {MAX_TIMESTEP}"
+            ),
+            Self::TooWide { steps, word_bits } => write!(
+                f,
+                "a jump of {steps} steps does not fit in a {word_bits}-bit word"
+            ),
+        }
+    }
+}
+
+impl Error for LutError {}
+
+/// Build a table for evolving `timestep` steps at once.
+///
+/// The table is indexed by the first symbols of the deleted triples, least significant first,
+/// and each entry holds the bits to append.
+pub(super) fn build<W: Word>(timestep: u8) -> Vec<W> {
+    (0..1usize << timestep)
+        .map(|key| {
+            let mut bits = W::ZERO;
+            let mut len = 0;
+
+            for i in 0..timestep {
+                if (key >> i) & 1 == 1 {
+                    bits |= W::from_u32(0b1011) << len;
+                    len += 4;
+                } else {
+                    len += 2;
+                }
+            }
+
+            bits
+        })
+        .collect()
+}
+
+/// Get the number of bits appended by the table entry at `key` for a timestep of `timestep`.
+///
+/// Each zero symbol appends two bits and each one symbol appends four.
+fn append_len(key: usize, timestep: u8) -> u8 {
+    2 * timestep + 2 * key.count_ones() as u8
+}
+
+/// Look up each group of `timestep` triples of `deleted` in turn, concatenating the results.
+///
+/// Returns the bits to append and how many there are.
+#[inline(always)]
+pub(super) fn compose<W: Word>(table: &[W], timestep: u8, lookups: u8, deleted: W) -> (W, u8) {
+    let mut bits = W::ZERO;
+    let mut len = 0;
+
+    for lookup in 0..lookups as u32 {
+        let group = deleted >> (3 * timestep as u32 * lookup);
+
+        let mut key = 0;
+        for i in 0..timestep as u32 {
+            if (group >> (3 * i)) & W::ONE == W::ONE {
+                key |= 1 << i;
+            }
+        }
+
+        bits |= table[key] << len as u32;
+        len += append_len(key, timestep);
+    }
+
+    (bits, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+
+    use crate::{
+        system::{BitString, BitString128, BitString32},
+        PostSystem,
+    };
+
+    use super::*;
+
+    #[test]
+    fn validates_configuration() {
+        assert_eq!(
+            BitString::builder().timestep(0).build().err(),
+            Some(LutError::Empty)
+        );
+        assert_eq!(
+            BitString128::builder().timestep(25).build().err(),
+            Some(LutError::TooLarge { timestep: 25 })
+        );
+        assert_eq!(
+            BitString32::builder().timestep(4).lookups(3).build().err(),
+            Some(LutError::TooWide {
+                steps: 12,
+                word_bits: 32
+            })
+        );
+        assert!(BitString128::builder()
+            .timestep(8)
+            .lookups(4)
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn evolves_with_custom_tables() {
+        let seed = [
+            true, false, true, true, false, true, true, false, true, true,
+        ];
+        let mut expected = BitString128::new_decompressed(&seed);
+
+        let mut tuned: Vec<_> = [(1, 1), (5, 6), (13, 2), (16, 1)]
+            .into_iter()
+            .map(|(timestep, lookups)| {
+                let lut = BitString128::builder()
+                    .timestep(timestep)
+                    .lookups(lookups)
+                    .build()
+                    .unwrap();
+                assert_eq!(lut.jump(), timestep * lookups);
+
+                BitString128::new_decompressed(&seed).with_lut(lut)
+            })
+            .collect();
+
+        for _ in 0..50 {
+            assert_eq!(expected.evolve_multi(97), ControlFlow::Continue(()));
+            for system in &mut tuned {
+                assert_eq!(system.evolve_multi(97), ControlFlow::Continue(()));
+                assert_eq!(*system, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn saves_and_loads() {
+        let lut = BitString128::builder()
+            .timestep(13)
+            .lookups(2)
+            .build()
+            .unwrap();
+
+        let mut saved = Vec::new();
+        lut.write_to(&mut saved).unwrap();
+        assert_eq!(saved.len(), HEADER_LEN + (16 << 13));
+
+        let loaded = Lut::<u128>::read_from(&saved[..]).unwrap();
+        assert_eq!((loaded.timestep(), loaded.lookups()), (13, 2));
+        assert_eq!(loaded.table.entries(), lut.table.entries());
+
+        // Tables are specific to their word type, and must be complete.
+        assert!(Lut::<u64>::read_from(&saved[..]).is_err());
+        assert!(Lut::<u128>::read_from(&saved[..saved.len() - 1]).is_err());
+        assert!(Lut::<u128>::read_from(&saved[1..]).is_err());
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn maps() {
+        use std::fs::{self, File};
+
+        use crate::system::BitString64;
+
+        let path = std::env::temp_dir().join(format!("post-tag-lut-{}.bin", std::process::id()));
+        let lut = BitString64::builder()
+            .timestep(12)
+            .lookups(1)
+            .build()
+            .unwrap();
+        lut.write_to(File::create(&path).unwrap()).unwrap();
+
+        // SAFETY: nothing else touches the file.
+        let mapped = unsafe { Lut::<u64>::map(&File::open(&path).unwrap()) }.unwrap();
+        assert_eq!(mapped.table.entries(), lut.table.entries());
+
+        let seed = [
+            true, false, true, true, false, true, true, false, true, true,
+        ];
+        let mut expected = BitString64::new_decompressed(&seed);
+        let mut system = BitString64::new_decompressed(&seed).with_lut(mapped);
+        assert_eq!(expected.evolve_multi(10_000), system.evolve_multi(10_000));
+        assert_eq!(system, expected);
+
+        fs::write(&path, b"not a table").unwrap();
+        assert!(unsafe { Lut::<u64>::map(&File::open(&path).unwrap()) }.is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
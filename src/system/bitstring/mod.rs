@@ -0,0 +1,1004 @@
+mod lut;
+mod simd;
+mod word;
+mod words;
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    hash::{Hash, Hasher},
+    mem,
+    ops::{ControlFlow, Range},
+    str::FromStr,
+};
+
+use crate::PostSystem;
+
+use super::ParseStateError;
+
+pub use lut::{Builder, Lut, LutError, MAX_TIMESTEP};
+pub use word::Word;
+
+use words::Words;
+
+/// A bit string stored in a queue of machine words of type `W`.
+///
+/// Wider words mean fewer buffer operations per bit, while narrower words waste less space on short strings.
+/// The width also bounds how many steps a single lookup table access can take; see [`Word::TIMESTEP`].
+///
+/// Besides evolving as a [`PostSystem`], a bit string can be edited directly as a queue of bits,
+/// with [`Self::append`] and [`Self::push_bit`] at the end, and [`Self::delete`] and [`Self::pop_front_bit`] at the start.
+#[derive(Debug, Clone)]
+pub struct GenericBitString<W> {
+    /// The words of the bit string.
+    /// The bits are stored in little-endian order.
+    /// There is always at least one word.
+    words: Words<W>,
+
+    /// The index of the first bit in the first word.
+    start: u8,
+    /// The index of the last bit in the last word.
+    end: u8,
+
+    /// Length of the bit string.
+    len: usize,
+
+    /// The lookup table to jump with, or `None` to use the word type's own table.
+    lut: Option<Lut<W>>,
+}
+
+/// A bit string stored in native machine words.
+pub type BitString = GenericBitString<usize>;
+/// A bit string stored in 32-bit words.
+pub type BitString32 = GenericBitString<u32>;
+/// A bit string stored in 64-bit words.
+pub type BitString64 = GenericBitString<u64>;
+/// A bit string stored in 128-bit words.
+pub type BitString128 = GenericBitString<u128>;
+
+impl<W: Word> GenericBitString<W> {
+    /// The number of steps per jump of the word type's own table.
+    pub(super) const TABLE_JUMP: u8 = W::TIMESTEP * W::LOOKUPS;
+
+    /// Create a new empty bit string.
+    pub fn new() -> Self {
+        Self {
+            words: Words::new(W::ZERO),
+            start: 0,
+            end: 0,
+            len: 0,
+            lut: None,
+        }
+    }
+
+    /// Create a new empty bit string with room for at least `bits` bits without reallocating.
+    pub fn with_capacity(bits: usize) -> Self {
+        Self {
+            words: Words::with_capacity(W::ZERO, Self::words_for(bits)),
+            ..Self::new()
+        }
+    }
+
+    /// Get the number of bits the string can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        (self.words.capacity() * W::BITS as usize).saturating_sub(self.start as usize + 1)
+    }
+
+    /// Make room for at least `additional_bits` more bits without reallocating.
+    ///
+    /// Reserving ahead of time avoids repeatedly growing the buffer while evolving a system whose growth is known.
+    pub fn reserve(&mut self, additional_bits: usize) {
+        let needed = Self::words_for(self.start as usize + self.len + additional_bits);
+        self.words.reserve(needed.saturating_sub(self.words.len()));
+    }
+
+    /// Copy the bits in `range` into a new bit string, which evolves with the same lookup table.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is decreasing or extends beyond the end of the bit string.
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "range {range:?} is out of bounds for a bit string of length {}",
+            self.len
+        );
+
+        let mut slice = Self::with_capacity(range.len());
+        slice.lut = self.lut.clone();
+
+        let mut position = range.start;
+        while position < range.end {
+            let count = (range.end - position).min(W::BITS as usize) as u8;
+            slice.append_bits(self.bits_at(position, count), count);
+            position += count as usize;
+        }
+
+        slice
+    }
+
+    /// Release any memory not needed to hold the bit string, moving it to the start of its first word.
+    ///
+    /// The buffer only ever grows while evolving, so a system whose state shrinks dramatically keeps its peak allocation until this is called.
+    /// This copies the whole string, so avoid calling it more often than necessary.
+    pub fn compact(&mut self) {
+        let mut words = Vec::with_capacity(Self::words_for(self.len));
+        words.extend(self.normalized_words());
+        if self.len.is_multiple_of(W::BITS as usize) {
+            words.push(W::ZERO);
+        }
+
+        self.words = Words::from_vec(words);
+        self.start = 0;
+        self.end = (self.len % W::BITS as usize) as u8;
+    }
+
+    /// Get the number of words needed to hold `bits` bits starting at the start of the first word.
+    ///
+    /// There is always a last word with room for at least one more bit, so this is more than `bits / W::BITS` even when it divides evenly.
+    fn words_for(bits: usize) -> usize {
+        bits / W::BITS as usize + 1
+    }
+
+    /// Start building a lookup table to evolve bit strings with a timestep chosen at runtime.
+    ///
+    /// Larger tables take more memory but jump further, so which is fastest depends on the workload.
+    pub fn builder() -> Builder<W> {
+        Builder::new()
+    }
+
+    /// Evolve the bit string using `lut` rather than the word type's own table.
+    pub fn with_lut(mut self, lut: Lut<W>) -> Self {
+        self.lut = Some(lut);
+        self
+    }
+
+    /// Append `count` bits to the end of the bit string, from the little-endian `bits`.
+    ///
+    /// Any bits of `bits` beyond the `count`-th are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is greater than `W::BITS`.
+    pub fn append(&mut self, bits: W, count: u8) {
+        assert!(
+            count as u32 <= W::BITS,
+            "cannot append more than {} bits at once",
+            W::BITS
+        );
+
+        if (count as u32) < W::BITS {
+            self.append_bits(bits & !(W::MAX << count as u32), count);
+        } else {
+            self.append_bits(bits, count);
+        }
+    }
+
+    /// Delete `count` bits from the start of the bit string, returning them little-endian.
+    ///
+    /// If the bit string is shorter than `count`, all of it is deleted and returned, and the remaining bits of the result are zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is greater than `W::BITS`.
+    pub fn delete(&mut self, count: u8) -> W {
+        assert!(
+            count as u32 <= W::BITS,
+            "cannot delete more than {} bits at once",
+            W::BITS
+        );
+
+        if count == 0 {
+            return W::ZERO;
+        }
+        if count as usize >= self.len {
+            let deleted = if self.len == 0 {
+                W::ZERO
+            } else {
+                self.bits_at(0, self.len as u8)
+            };
+            self.words.reset(W::ZERO);
+            self.start = 0;
+            self.end = 0;
+            self.len = 0;
+
+            return deleted;
+        }
+
+        self.delete_bits(count)
+    }
+
+    /// Append `count` bits to the end of the bit string, from the little-endian `bits`, without checking them.
+    ///
+    /// `count` must be at most `W::BITS`, and `bits` must not have any bits set beyond the `count`-th bit.
+    #[inline]
+    pub(super) fn append_bits(&mut self, bits: W, count: u8) {
+        debug_assert!(count as u32 <= W::BITS);
+
+        let rotated = bits.rotate_left(self.end as u32);
+
+        let lower_mask = W::MAX << self.end as u32;
+        let upper_mask = !lower_mask;
+
+        *self.words.back_mut().unwrap() |= rotated & lower_mask;
+        self.end += count;
+
+        if self.end as u32 >= W::BITS {
+            self.end = (self.end as u32 % W::BITS) as u8;
+
+            self.words.push_back(rotated & upper_mask);
+        }
+
+        self.len += count as usize;
+    }
+
+    /// Delete `count` bits from the start of the bit string, returning them, without checking the count.
+    ///
+    /// `count` must be at least one, and at most `W::BITS` and the length of the bit string.
+    #[inline]
+    pub(super) fn delete_bits(&mut self, count: u8) -> W {
+        debug_assert!(count > 0 && count as u32 <= W::BITS && count as usize <= self.len);
+
+        let mask = W::MAX >> (W::BITS - count as u32);
+
+        let lower = *self.words.front().unwrap() >> self.start as u32;
+        self.start += count;
+
+        let upper = if self.start as u32 >= W::BITS {
+            self.start = (self.start as u32 % W::BITS) as u8;
+
+            // The last word is never popped, since it holds the end of the string.
+            self.words.pop_front().unwrap();
+
+            self.words
+                .front()
+                .unwrap()
+                .checked_shl((count - self.start) as u32)
+                .unwrap_or(W::ZERO)
+        } else {
+            W::ZERO
+        };
+
+        self.len -= count as usize;
+
+        (lower | upper) & mask
+    }
+
+    /// Append a single bit to the end of the bit string.
+    pub fn push_bit(&mut self, bit: bool) {
+        self.append(W::from_u32(bit as u32), 1);
+    }
+
+    /// Delete the first bit of the bit string and return it, or return `None` if the string is empty.
+    pub fn pop_front_bit(&mut self) -> Option<bool> {
+        if self.len == 0 {
+            return None;
+        }
+
+        Some(self.delete(1) == W::ONE)
+    }
+
+    /// Delete bits from the end of the bit string, keeping only the first `len`.
+    pub(super) fn truncate(&mut self, len: usize) {
+        debug_assert!(len <= self.len);
+
+        let end = self.start as usize + len;
+        self.words.truncate(end / W::BITS as usize + 1);
+        self.end = (end % W::BITS as usize) as u8;
+        *self.words.back_mut().unwrap() &= !(W::MAX << self.end as u32);
+
+        self.len = len;
+    }
+
+    /// Evolve the bit string by one jump of its custom lookup table.
+    ///
+    /// This is kept out of line so that the default path of [`PostSystem::evolve_preferred`] stays small.
+    #[inline(never)]
+    fn evolve_with_lut(&mut self) {
+        let (bits, len) = self.take_jump();
+        self.append_bits(bits, len);
+    }
+
+    /// Delete the symbols read by one [`PostSystem::evolve_preferred`] jump, returning the bits it would append and how many there are.
+    ///
+    /// This lets the symbols read and the symbols written live in different strings.
+    #[inline]
+    pub(super) fn take_jump(&mut self) -> (W, u8) {
+        debug_assert!(self.length() >= 3 * self.preferred_timestep() as usize);
+
+        match self.lut.as_ref().map(Lut::jump) {
+            Some(jump) => {
+                let deleted = self.delete_bits(3 * jump);
+                self.lut.as_ref().unwrap().compose(deleted)
+            }
+            None => table_jump(self.delete_bits(3 * Self::TABLE_JUMP)),
+        }
+    }
+
+    /// Evolve the bit string by `n` steps, like [`PostSystem::evolve_multi`], a whole generation at a time where possible.
+    ///
+    /// A generation is the `self.length() / 3` steps which only read symbols of the current string,
+    /// so it can be computed in a single pass over the string, writing the successor into a fresh buffer.
+    /// This is only faster for long strings, and whole generations are only taken while they fit in the remaining steps.
+    pub fn evolve_blocks(&mut self, n: usize) -> ControlFlow<usize> {
+        // Each generation is written into the other buffer, and then the two are swapped.
+        let mut spare = Self::new();
+
+        let mut i = 0;
+        loop {
+            let generation = self.len / 3;
+            if generation == 0 || generation > n - i {
+                break;
+            }
+
+            self.evolve_generation(&mut spare);
+            i += generation;
+        }
+
+        match self.evolve_multi(n - i) {
+            ControlFlow::Break(steps) => ControlFlow::Break(i + steps),
+            ControlFlow::Continue(()) => ControlFlow::Continue(()),
+        }
+    }
+
+    /// Evolve the bit string by `self.length() / 3` steps, in a single pass over the current string.
+    ///
+    /// The successor is written into `spare`, which is then swapped with `self`, so its allocation can be reused.
+    fn evolve_generation(&mut self, spare: &mut Self) {
+        let steps = self.len / 3;
+        let jump = self.preferred_timestep() as usize;
+
+        spare.words.reset(W::ZERO);
+        spare.start = 0;
+        spare.end = 0;
+        spare.len = 0;
+
+        // The symbols left over after the last triple come first.
+        let read = 3 * steps;
+        if read < self.len {
+            let count = (self.len - read) as u8;
+            spare.append_bits(self.bits_at(read, count), count);
+        }
+
+        let mut step = 0;
+        while steps - step >= jump {
+            let deleted = self.bits_at(3 * step, 3 * jump as u8);
+            let (bits, len) = match &self.lut {
+                Some(lut) => lut.compose(deleted),
+                None => table_jump(deleted),
+            };
+            spare.append_bits(bits, len);
+            step += jump;
+        }
+        for step in step..steps {
+            if self.bits_at(3 * step, 1) == W::ZERO {
+                spare.append_bits(W::ZERO, 2);
+            } else {
+                spare.append_bits(W::from_u32(0b1011), 4);
+            }
+        }
+
+        spare.lut = self.lut.take();
+        mem::swap(self, spare);
+    }
+
+    /// Get the `count` bits starting `position` bits into the bit string, little-endian.
+    ///
+    /// `count` must be at most `W::BITS`, and the bits must lie within the bit string.
+    fn bits_at(&self, position: usize, count: u8) -> W {
+        debug_assert!(count as u32 <= W::BITS && position + count as usize <= self.len);
+
+        let absolute = self.start as usize + position;
+        let word = simd::shifted_word(
+            self.words.as_slice(),
+            (absolute % W::BITS as usize) as u8,
+            absolute / W::BITS as usize,
+        );
+
+        if (count as u32) < W::BITS {
+            word & !(W::MAX << count as u32)
+        } else {
+            word
+        }
+    }
+
+    /// Iterate over the words of the bit string as if it started at bit zero of the first word.
+    ///
+    /// Bits beyond the end of the string are zeroed, so equal strings yield equal words.
+    pub(super) fn normalized_words(&self) -> impl Iterator<Item = W> + '_ {
+        let count = self.len.div_ceil(W::BITS as usize);
+
+        (0..count).map(|i| self.normalized_word(i))
+    }
+
+    /// Get the `i`th word of [`Self::normalized_words`].
+    fn normalized_word(&self, i: usize) -> W {
+        let word = simd::shifted_word(self.words.as_slice(), self.start, i);
+
+        let remaining = self.len - i * W::BITS as usize;
+        if remaining < W::BITS as usize {
+            word & !(W::MAX << remaining as u32)
+        } else {
+            word
+        }
+    }
+}
+
+impl<W: Word> Default for GenericBitString<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Get the bits appended by one jump of the word type's own table, which read every third bit of `deleted`, and how many there are.
+#[inline(always)]
+pub(super) fn table_jump<W: Word>(deleted: W) -> (W, u8) {
+    W::with_lut(|table| lut::compose(table, W::TIMESTEP, W::LOOKUPS, deleted))
+}
+
+impl<W: Word> PartialEq for GenericBitString<W> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.len != other.len {
+            return false;
+        }
+
+        // Compare the complete words in bulk, and then the partial word at the end, if any.
+        let full = self.len / W::BITS as usize;
+        simd::shifted_words_eq(
+            self.words.as_slice(),
+            self.start,
+            other.words.as_slice(),
+            other.start,
+            full,
+        ) && (self.len.is_multiple_of(W::BITS as usize)
+            || self.normalized_word(full) == other.normalized_word(full))
+    }
+}
+impl<W: Word> Eq for GenericBitString<W> {}
+
+impl<W: Word> Hash for GenericBitString<W> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for word in self.normalized_words() {
+            word.hash(state);
+        }
+    }
+}
+
+impl<W: Word> FromStr for GenericBitString<W> {
+    type Err = ParseStateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        super::parse_bits(s).map(Self::from_list)
+    }
+}
+
+impl<W: Word> fmt::Display for GenericBitString<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        super::fmt_bits(self.bits(), f)
+    }
+}
+
+impl<W: Word> Extend<bool> for GenericBitString<W> {
+    fn extend<I: IntoIterator<Item = bool>>(&mut self, iter: I) {
+        let mut bits = iter.into_iter();
+        self.reserve(bits.size_hint().0);
+
+        loop {
+            let mut word = W::ZERO;
+            let mut count = 0;
+            for bit in bits.by_ref().take(W::BITS as usize) {
+                word |= W::from_u32(bit as u32) << count;
+                count += 1;
+            }
+
+            if count == 0 {
+                break;
+            }
+            self.append_bits(word, count as u8);
+        }
+    }
+}
+
+impl<W: Word> FromIterator<bool> for GenericBitString<W> {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut this = Self::new();
+        this.extend(iter);
+        this
+    }
+}
+
+impl<W: Word> PostSystem for GenericBitString<W> {
+    fn new_decompressed(compressed: &[bool]) -> Self {
+        let mut this = Self::with_capacity(3 * compressed.len());
+
+        for &b in compressed {
+            this.append_bits(W::from_u32(b as u32), 3);
+        }
+
+        this
+    }
+
+    fn length(&self) -> usize {
+        self.len
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.words.allocated() * mem::size_of::<W>()
+    }
+
+    fn as_list(&self) -> VecDeque<bool> {
+        self.bits().collect()
+    }
+
+    fn count_ones(&self) -> usize {
+        let words = self.words.as_slice();
+        let (first, last) = (W::MAX << self.start as u32, !(W::MAX << self.end as u32));
+
+        match words {
+            [word] => (*word & first & last).count_ones() as usize,
+            [head, middle @ .., tail] => {
+                (*head & first).count_ones() as usize
+                    + middle
+                        .iter()
+                        .map(|word| word.count_ones() as usize)
+                        .sum::<usize>()
+                    + (*tail & last).count_ones() as usize
+            }
+            [] => unreachable!("bit strings always have at least one word"),
+        }
+    }
+
+    fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+
+        let i = self.start as usize + index;
+        let word = self.words.as_slice()[i / W::BITS as usize];
+        Some((word >> (i % W::BITS as usize) as u32) & W::ONE == W::ONE)
+    }
+
+    fn bits(&self) -> impl Iterator<Item = bool> + '_ {
+        let words = self.words.as_slice();
+        let bits = W::BITS as usize;
+
+        (self.start as usize..self.start as usize + self.len)
+            .map(move |i| (words[i / bits] >> (i % bits) as u32) & W::ONE == W::ONE)
+    }
+
+    fn from_list(list: VecDeque<bool>) -> Self {
+        list.into_iter().collect()
+    }
+
+    fn evolve(&mut self) -> ControlFlow<()> {
+        if self.length() < 3 {
+            return ControlFlow::Break(());
+        }
+
+        let deleted = self.delete_bits(3);
+
+        if deleted & W::ONE == W::ZERO {
+            self.append_bits(W::ZERO, 2);
+        } else {
+            self.append_bits(W::from_u32(0b1011), 4);
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn preferred_timestep(&self) -> u8 {
+        match &self.lut {
+            Some(lut) => lut.jump(),
+            None => Self::TABLE_JUMP,
+        }
+    }
+
+    fn evolve_preferred(&mut self) -> ControlFlow<u8> {
+        debug_assert!(self.length() >= 3 * self.preferred_timestep() as usize);
+
+        if self.lut.is_some() {
+            self.evolve_with_lut();
+        } else {
+            let deleted = self.delete_bits(3 * Self::TABLE_JUMP);
+            let (bits, len) = table_jump(deleted);
+
+            self.append_bits(bits, len);
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests_for_system;
+
+    use super::*;
+
+    tests_for_system!(BitString);
+
+    mod word_32 {
+        crate::tests_for_system!(crate::system::BitString32);
+    }
+
+    mod word_64 {
+        crate::tests_for_system!(crate::system::BitString64);
+    }
+
+    mod word_128 {
+        crate::tests_for_system!(crate::system::BitString128);
+    }
+
+    #[test]
+    fn agrees_across_word_widths() {
+        let seed = [
+            true, false, true, true, false, true, true, false, true, true,
+        ];
+
+        let mut native = BitString::new_decompressed(&seed);
+        let mut narrow = BitString32::new_decompressed(&seed);
+        let mut wide = BitString128::new_decompressed(&seed);
+
+        for _ in 0..50 {
+            assert_eq!(native.evolve_multi(97), ControlFlow::Continue(()));
+            assert_eq!(narrow.evolve_multi(97), ControlFlow::Continue(()));
+            assert_eq!(wide.evolve_multi(97), ControlFlow::Continue(()));
+
+            assert_eq!(narrow.as_list(), native.as_list());
+            assert_eq!(wide.as_list(), native.as_list());
+        }
+    }
+
+    #[test]
+    fn tests_equality() {
+        let mut bit_string = BitString::new();
+        let mut other = BitString::new();
+
+        assert_eq!(bit_string, other);
+
+        bit_string.append(0b101, 3);
+        assert_ne!(bit_string, other);
+
+        other.append(0b101, 3);
+        assert_eq!(bit_string, other);
+
+        bit_string.append(0b010, 3);
+        assert_ne!(bit_string, other);
+
+        other.append(0b010, 3);
+        assert_eq!(bit_string, other);
+
+        bit_string.append(0b0, 1);
+        assert_ne!(bit_string, other);
+
+        other.append(0b0, 1);
+        assert_eq!(bit_string, other);
+
+        bit_string.append(usize::MAX, usize::BITS as u8);
+        assert_ne!(bit_string, other);
+
+        other.append(usize::MAX, usize::BITS as u8);
+        assert_eq!(bit_string, other);
+
+        let mut bit_string = BitString::new();
+        let mut other = BitString::new();
+
+        bit_string.append(0b1010, 4);
+        other.append(0b10, 2);
+        assert_ne!(bit_string, other);
+
+        bit_string.delete(2);
+        assert_eq!(bit_string, other);
+
+        bit_string.append(usize::MAX, usize::BITS as u8);
+        other.append(usize::MAX, usize::BITS as u8);
+        assert_eq!(bit_string, other);
+
+        bit_string.append(0b1010, 4);
+        assert_ne!(bit_string, other);
+
+        // Consumed bits must not affect equality.
+        let mut bit_string = BitString::new();
+        let mut other = BitString::new();
+
+        bit_string.append(0b01101, 5);
+        other.append(0b01010, 5);
+        bit_string.delete(3);
+        other.delete(3);
+        assert_eq!(bit_string, other);
+    }
+
+    #[test]
+    fn hashes_consistently_with_equality() {
+        use std::hash::{BuildHasher, RandomState};
+
+        let hasher = RandomState::new();
+
+        let mut bit_string = BitString::new();
+        let mut other = BitString::new();
+
+        bit_string.append(0b1010, 4);
+        bit_string.append(usize::MAX, usize::BITS as u8);
+        bit_string.delete(2);
+
+        other.append(0b10, 2);
+        other.append(usize::MAX, usize::BITS as u8);
+
+        assert_eq!(bit_string, other);
+        assert_eq!(hasher.hash_one(&bit_string), hasher.hash_one(&other));
+
+        other.append(0b0, 1);
+        assert_ne!(hasher.hash_one(&bit_string), hasher.hash_one(&other));
+    }
+
+    #[test]
+    fn appends() {
+        let mut bit_string = BitString::new();
+        assert_eq!(bit_string.as_list().make_contiguous(), [false; 0]);
+
+        bit_string.append(0b101, 3);
+        assert_eq!(bit_string.as_list().make_contiguous(), [true, false, true]);
+
+        bit_string.append(0b010, 3);
+        assert_eq!(
+            bit_string.as_list().make_contiguous(),
+            [true, false, true, false, true, false]
+        );
+
+        bit_string.append(0b0, 1);
+        assert_eq!(
+            bit_string.as_list().make_contiguous(),
+            [true, false, true, false, true, false, false]
+        );
+
+        bit_string.append(usize::MAX, usize::BITS as u8);
+        assert_eq!(
+            bit_string.as_list().make_contiguous().len(),
+            (usize::BITS + 7) as usize
+        );
+    }
+
+    #[test]
+    fn deletes() {
+        let mut bit_string = BitString::new();
+        bit_string.append(0xAAAA_AAAA_AAAA_AAA7, 64);
+        bit_string.append(0xF, 4);
+
+        assert_eq!(bit_string.delete(8), 0xA7);
+        assert_eq!(bit_string.delete(64), 0x0FAA_AAAA_AAAA_AAAA);
+
+        assert_eq!(bit_string.as_list().make_contiguous(), [false; 0]);
+    }
+
+    #[test]
+    fn edits_like_a_deque() {
+        let mut bit_string = BitString64::new();
+        let mut expected = VecDeque::new();
+
+        // Extra bits beyond the count are ignored.
+        bit_string.append(0b1111_0110, 4);
+        expected.extend([false, true, true, false]);
+
+        for i in 0..5000u64 {
+            let count = (i * 7 % 65) as u8;
+            let bits = i.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+            bit_string.append(bits, count);
+            expected.extend((0..count).map(|j| (bits >> j) & 1 == 1));
+
+            bit_string.push_bit(i % 3 == 0);
+            expected.push_back(i % 3 == 0);
+
+            // Deleting more bits than remain empties the string.
+            let count = (i * 11 % 65) as u8;
+            let deleted = bit_string.delete(count);
+            let mut expected_deleted = 0;
+            for j in 0..count {
+                if let Some(bit) = expected.pop_front() {
+                    expected_deleted |= (bit as u64) << j;
+                }
+            }
+            assert_eq!(deleted, expected_deleted);
+
+            assert_eq!(bit_string.pop_front_bit(), expected.pop_front());
+            assert_eq!(bit_string.length(), expected.len());
+            assert_eq!(bit_string.as_list(), expected);
+        }
+
+        while let Some(bit) = expected.pop_front() {
+            assert_eq!(bit_string.pop_front_bit(), Some(bit));
+        }
+        assert_eq!(bit_string.pop_front_bit(), None);
+        assert_eq!(bit_string.delete(10), 0);
+        assert_eq!(bit_string, BitString64::new());
+    }
+
+    #[test]
+    #[should_panic]
+    fn refuses_to_append_too_many_bits() {
+        BitString64::new().append(0, 65);
+    }
+
+    #[test]
+    fn evolves_in_blocks() {
+        fn check<W: Word>(lut: Option<Lut<W>>) {
+            let seed = [
+                true, false, true, true, false, true, true, false, true, true, true, true, false,
+            ];
+
+            let mut expected = GenericBitString::<W>::new_decompressed(&seed);
+            let mut blocks = expected.clone();
+            if let Some(lut) = lut {
+                blocks = blocks.with_lut(lut);
+            }
+
+            for n in [0, 1, 7, 100, 1000, 12_345, 100_000] {
+                assert_eq!(blocks.evolve_blocks(n), expected.evolve_multi(n));
+                assert_eq!(blocks, expected);
+            }
+
+            // Halting partway through is reported as by `evolve_multi`.
+            let mut expected = GenericBitString::<W>::from_seed_u128(0b11111);
+            let mut blocks = expected.clone();
+            assert_eq!(blocks.evolve_blocks(1000), ControlFlow::Break(409));
+            assert_eq!(expected.evolve_multi(1000), ControlFlow::Break(409));
+            assert_eq!(blocks, expected);
+        }
+
+        check::<usize>(None);
+        check::<u32>(None);
+        check::<u128>(None);
+        check::<u64>(Some(
+            BitString64::builder()
+                .timestep(3)
+                .lookups(5)
+                .build()
+                .unwrap(),
+        ));
+    }
+
+    #[test]
+    fn gets_length() {
+        let mut bit_string = BitString::new();
+        for l in 0..usize::BITS * 4 {
+            assert_eq!(bit_string.length(), l as usize);
+            bit_string.append(0, 1);
+        }
+
+        bit_string.delete(7);
+        assert_eq!(bit_string.length(), usize::BITS as usize * 4 - 7);
+    }
+
+    #[test]
+    fn reserves_capacity() {
+        let mut bit_string = BitString64::with_capacity(1000);
+        assert_eq!(bit_string.length(), 0);
+        assert!(bit_string.capacity() >= 1000);
+
+        let capacity = bit_string.capacity();
+        for _ in 0..1000 {
+            bit_string.append(1, 1);
+        }
+        bit_string.delete(37);
+        assert_eq!(bit_string.capacity(), capacity - 37);
+
+        bit_string.reserve(5000);
+        assert!(bit_string.capacity() >= bit_string.length() + 5000);
+        let capacity = bit_string.capacity();
+        for _ in 0..5000 {
+            bit_string.append(0, 1);
+        }
+        assert_eq!(bit_string.capacity(), capacity);
+
+        let seeded = BitString64::new_decompressed(&[true; 100]);
+        assert!(seeded.capacity() >= 300);
+    }
+
+    #[test]
+    fn compacts() {
+        use std::hash::{BuildHasher, RandomState};
+
+        let hasher = RandomState::new();
+
+        let mut bit_string = BitString64::new();
+        for i in 0..10_000u64 {
+            bit_string.append(i.count_ones() as u64 % 2, 1);
+        }
+        for _ in 0..(9_900 / 50) {
+            bit_string.delete(50);
+        }
+        bit_string.delete(13);
+        assert!(bit_string.memory_usage() >= 9_000 / 8);
+
+        let expected = BitString64::from_list(bit_string.as_list());
+        bit_string.compact();
+        assert!(bit_string.memory_usage() <= 16);
+        assert_eq!(bit_string.start, 0);
+        assert_eq!(bit_string, expected);
+        assert_eq!(hasher.hash_one(&bit_string), hasher.hash_one(&expected));
+        assert_eq!(bit_string.as_list(), expected.as_list());
+
+        // The compacted string keeps working as usual.
+        let mut expected = expected;
+        assert_eq!(bit_string.evolve_multi(1000), expected.evolve_multi(1000));
+        assert_eq!(bit_string, expected);
+
+        for len in [0, 1, 63, 64, 65, 128] {
+            let mut bit_string = BitString64::from_list(vec![true; len].into());
+            bit_string.compact();
+            assert_eq!(bit_string.as_list(), VecDeque::from(vec![true; len]));
+            bit_string.append(0, 1);
+            assert_eq!(bit_string.length(), len + 1);
+        }
+    }
+
+    #[test]
+    fn slices() {
+        let list: VecDeque<_> = (0..1000u32)
+            .map(|i| i.count_ones().is_multiple_of(3))
+            .collect();
+
+        let mut bit_string = BitString64::from_list(list.clone());
+        bit_string.delete(13);
+        let list: Vec<_> = list.into_iter().skip(13).collect();
+
+        for range in [0..0, 0..1, 5..70, 64..128, 100..987, 0..987, 987..987] {
+            let slice = bit_string.slice(range.clone());
+            assert_eq!(slice.length(), range.len());
+            assert!(slice.bits().eq(list[range].iter().copied()));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn refuses_to_slice_out_of_bounds() {
+        BitString64::new_decompressed(&[true]).slice(2..4);
+    }
+
+    #[test]
+    fn extends_and_collects() {
+        let bits = || (0..300u32).map(|i| i.count_ones().is_multiple_of(2));
+
+        let collected: BitString32 = bits().collect();
+        assert!(collected.bits().eq(bits()));
+
+        // Iterators without a length are packed just the same.
+        let filtered: BitString32 = bits().filter(|_| true).collect();
+        assert_eq!(filtered, collected);
+
+        let mut extended = BitString32::new_decompressed(&[true, false, true]);
+        extended.delete(5);
+        extended.extend(bits());
+        assert!(extended
+            .bits()
+            .eq([false, true, false, false].into_iter().chain(bits())));
+    }
+
+    #[test]
+    fn measures_productions_and_alignment() {
+        let mut bit_string = BitString::new_decompressed(&[true, false, true]);
+        assert_eq!(bit_string.compressed_length(), 3);
+        assert_eq!(bit_string.alignment(), 0);
+
+        // `100 000 100` evolves to `000 100 1101`, `100 1101 00`, and `1101 00 1101`.
+        let _ = bit_string.evolve();
+        assert_eq!(
+            (bit_string.compressed_length(), bit_string.alignment()),
+            (4, 1)
+        );
+        let _ = bit_string.evolve();
+        assert_eq!(
+            (bit_string.compressed_length(), bit_string.alignment()),
+            (3, 0)
+        );
+        let _ = bit_string.evolve();
+        assert_eq!(
+            (bit_string.compressed_length(), bit_string.alignment()),
+            (4, 1)
+        );
+
+        // The symbols at multiples of three are the ones which will be read.
+        let read: Vec<_> = bit_string.bits().step_by(3).collect();
+        assert_eq!(read.len(), bit_string.compressed_length());
+    }
+}
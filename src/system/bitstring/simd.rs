@@ -0,0 +1,159 @@
+//! Vectorized comparison of offset word buffers.
+//!
+//! Comparing two bit strings means comparing their words after shifting each by its own bit offset.
+//! On x86-64 with AVX2 this is done four words at a time, and elsewhere with a scalar loop,
+//! which the compiler is free to vectorize.
+
+use super::Word;
+
+/// The number of words compared per vector.
+const BLOCK: usize = 4;
+
+/// The number of words below which it isn't worth setting up vectorized comparison.
+const SMALL: usize = 4 * BLOCK;
+
+/// Check whether the first `count` words of `a` and `b` are equal,
+/// after shifting them down by `a_start` and `b_start` bits respectively.
+///
+/// Each slice must have a word after the last compared word if its offset is nonzero.
+pub(super) fn shifted_words_eq<W: Word>(
+    a: &[W],
+    a_start: u8,
+    b: &[W],
+    b_start: u8,
+    count: usize,
+) -> bool {
+    // Compare as much as possible in bulk, including the extra word for the shift, and then the rest one by one.
+    let bulk = if count < SMALL {
+        0
+    } else {
+        let n = count
+            .min(a.len().saturating_sub(1))
+            .min(b.len().saturating_sub(1));
+        n - n % BLOCK
+    };
+
+    (bulk == 0 || W::slices_eq(&a[..bulk + 1], a_start, &b[..bulk + 1], b_start))
+        && (bulk..count).all(|i| shifted_word(a, a_start, i) == shifted_word(b, b_start, i))
+}
+
+/// Get the word at index `i` of `words`, shifted down by `start` bits, with the bits from the next word shifted in.
+pub(super) fn shifted_word<W: Word>(words: &[W], start: u8, i: usize) -> W {
+    let lower = words[i] >> start as u32;
+    let upper = match words.get(i + 1) {
+        Some(&next) if start > 0 => next << (W::BITS - start as u32),
+        _ => W::ZERO,
+    };
+    lower | upper
+}
+
+/// [`Word::slices_eq`] for 64-bit words, vectorized with AVX2 where it is available.
+pub(super) fn slices_eq_u64(a: &[u64], a_start: u8, b: &[u64], b_start: u8) -> bool {
+    debug_assert_eq!(a.len(), b.len());
+    debug_assert_eq!((a.len() - 1) % BLOCK, 0);
+
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // SAFETY: AVX2 is available, and the slices satisfy the length requirements.
+        return unsafe { avx2::slices_eq(a, a_start, b, b_start) };
+    }
+
+    slices_eq_scalar(a, a_start, b, b_start)
+}
+
+/// [`Word::slices_eq`] for any word, as a scalar loop.
+pub(super) fn slices_eq_scalar<W: Word>(a: &[W], a_start: u8, b: &[W], b_start: u8) -> bool {
+    fn shift<W: Word>(pair: &[W], start: u8) -> W {
+        (pair[0] >> start as u32)
+            | pair[1]
+                .checked_shl(W::BITS - start as u32)
+                .unwrap_or(W::ZERO)
+    }
+
+    a.windows(2)
+        .zip(b.windows(2))
+        .all(|(a, b)| shift(a, a_start) == shift(b, b_start))
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use std::arch::x86_64::*;
+
+    use super::BLOCK;
+
+    /// Vectorized [`super::slices_eq_u64`].
+    ///
+    /// # Safety
+    ///
+    /// AVX2 must be available, and both slices must be one word longer than a multiple of [`BLOCK`].
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn slices_eq(a: &[u64], a_start: u8, b: &[u64], b_start: u8) -> bool {
+        // Shifts of 64 or more produce zero, so a zero offset needs no special case.
+        let a_down = _mm_cvtsi32_si128(a_start as i32);
+        let a_up = _mm_cvtsi32_si128(64 - a_start as i32);
+        let b_down = _mm_cvtsi32_si128(b_start as i32);
+        let b_up = _mm_cvtsi32_si128(64 - b_start as i32);
+
+        let mut i = 0;
+        while i + BLOCK < a.len() {
+            let a_lower = _mm256_loadu_si256(a.as_ptr().add(i).cast());
+            let a_upper = _mm256_loadu_si256(a.as_ptr().add(i + 1).cast());
+            let b_lower = _mm256_loadu_si256(b.as_ptr().add(i).cast());
+            let b_upper = _mm256_loadu_si256(b.as_ptr().add(i + 1).cast());
+
+            let a_words = _mm256_or_si256(
+                _mm256_srl_epi64(a_lower, a_down),
+                _mm256_sll_epi64(a_upper, a_up),
+            );
+            let b_words = _mm256_or_si256(
+                _mm256_srl_epi64(b_lower, b_down),
+                _mm256_sll_epi64(b_upper, b_up),
+            );
+
+            let diff = _mm256_xor_si256(a_words, b_words);
+            if _mm256_testz_si256(diff, diff) == 0 {
+                return false;
+            }
+
+            i += BLOCK;
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_shifted_words() {
+        // The same bits, offset by 5 and 61 bits.
+        let bits: Vec<bool> = (0..64 * 40).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+
+        let pack = |start: u8| {
+            let mut words = vec![0u64; 42];
+            for (i, &bit) in bits.iter().enumerate() {
+                let position = i + start as usize;
+                words[position / 64] |= (bit as u64) << (position % 64);
+            }
+            words
+        };
+
+        let a = pack(5);
+        let b = pack(61);
+        assert!(shifted_words_eq(&a, 5, &b, 61, 40));
+        assert!(slices_eq_scalar(&a[..41], 5, &b[..41], 61));
+
+        let mut c = b.clone();
+        c[30] ^= 1 << 62;
+        assert!(!shifted_words_eq(&a, 5, &c, 61, 40));
+        assert!(shifted_words_eq(&a, 5, &c, 61, 30));
+
+        // Words beyond the bulk comparison are compared one by one.
+        assert!(shifted_words_eq(&a, 5, &b, 61, 39));
+        c = b.clone();
+        c[38] ^= 1 << 62;
+        assert!(!shifted_words_eq(&a, 5, &c, 61, 39));
+    }
+}
@@ -0,0 +1,190 @@
+//! Machine words which bit strings can be stored in.
+
+use std::{
+    fmt,
+    hash::Hash,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, Shl, Shr},
+    sync::OnceLock,
+};
+
+use super::{lut, simd};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// An unsigned integer type which a [`GenericBitString`](super::GenericBitString) can be stored in.
+///
+/// This is implemented for `u32`, `u64`, `u128`, and `usize`.
+pub trait Word:
+    Copy
+    + Eq
+    + Hash
+    + fmt::Debug
+    + Not<Output = Self>
+    + BitAnd<Output = Self>
+    + BitAndAssign
+    + BitOr<Output = Self>
+    + BitOrAssign
+    + Shl<u32, Output = Self>
+    + Shr<u32, Output = Self>
+    + sealed::Sealed
+    + 'static
+{
+    /// The number of bits in the word.
+    const BITS: u32;
+    /// The word with no bits set.
+    const ZERO: Self;
+    /// The word with only the lowest bit set.
+    const ONE: Self;
+    /// The word with every bit set.
+    const MAX: Self;
+
+    /// The number of steps taken per lookup table access.
+    ///
+    /// Each step deletes three bits and appends up to four, so this is at most a quarter of [`Self::BITS`].
+    /// The lookup table has `2^TIMESTEP` entries.
+    const TIMESTEP: u8;
+
+    /// The number of lookup table accesses composed into a single jump.
+    ///
+    /// A jump deletes and appends all of its bits at once, so as many lookups are composed as their appended bits fit in a word.
+    const LOOKUPS: u8 = (Self::BITS / (4 * Self::TIMESTEP as u32)) as u8;
+
+    /// Convert a `u32` to a word, truncating it if the word is narrower.
+    fn from_u32(value: u32) -> Self;
+
+    /// Rotate the bits of the word left by `n`.
+    fn rotate_left(self, n: u32) -> Self;
+
+    /// Shift the word left by `n`, returning `None` if `n` is at least [`Self::BITS`].
+    fn checked_shl(self, n: u32) -> Option<Self>;
+
+    /// Count the ones in the word.
+    fn count_ones(self) -> u32;
+
+    /// Call `f` with the lookup table for evolving [`Self::TIMESTEP`] steps at once.
+    ///
+    /// The table is indexed by the first symbols of the deleted triples, least significant first,
+    /// and each entry holds the bits to append.
+    /// The number of bits to append isn't stored, since it follows from the number of ones in the index.
+    ///
+    /// Tables with up to `2^16` entries are generated at compile time,
+    /// and larger ones are generated once on first use and shared between threads.
+    fn with_lut<R>(f: impl FnOnce(&[Self]) -> R) -> R;
+
+    /// Check whether all but the last words of `a` and `b` are equal, after shifting each down by its offset.
+    ///
+    /// The number of words compared is a multiple of four.
+    fn slices_eq(a: &[Self], a_start: u8, b: &[Self], b_start: u8) -> bool {
+        simd::slices_eq_scalar(a, a_start, b, b_start)
+    }
+}
+
+/// The largest timestep whose lookup table is generated at compile time.
+///
+/// Larger tables would bloat the binary and take too long to evaluate, so they are generated on first use instead.
+const CONST_LUT_TIMESTEP: u8 = 16;
+
+macro_rules! impl_word {
+    ($word:ident, timestep = $timestep:expr) => {
+        impl sealed::Sealed for $word {}
+
+        impl Word for $word {
+            const BITS: u32 = <$word>::BITS;
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            const MAX: Self = <$word>::MAX;
+
+            const TIMESTEP: u8 = $timestep;
+
+            fn from_u32(value: u32) -> Self {
+                value as Self
+            }
+
+            fn rotate_left(self, n: u32) -> Self {
+                <$word>::rotate_left(self, n)
+            }
+
+            fn checked_shl(self, n: u32) -> Option<Self> {
+                <$word>::checked_shl(self, n)
+            }
+
+            fn count_ones(self) -> u32 {
+                <$word>::count_ones(self)
+            }
+
+            fn with_lut<R>(f: impl FnOnce(&[Self]) -> R) -> R {
+                const TIMESTEP: u8 = <$word as Word>::TIMESTEP;
+                const CONST_LEN: usize = if TIMESTEP <= CONST_LUT_TIMESTEP {
+                    1 << TIMESTEP
+                } else {
+                    0
+                };
+
+                // The same table as `lut::build`, evaluated at compile time.
+                const fn build_const() -> [$word; CONST_LEN] {
+                    let mut table = [0; CONST_LEN];
+
+                    let mut key = 0;
+                    while key < CONST_LEN {
+                        let mut bits = 0;
+                        let mut len = 0;
+
+                        let mut i = 0;
+                        while i < TIMESTEP {
+                            if (key >> i) & 1 == 1 {
+                                bits |= 0b1011 << len;
+                                len += 4;
+                            } else {
+                                len += 2;
+                            }
+                            i += 1;
+                        }
+
+                        table[key] = bits;
+                        key += 1;
+                    }
+
+                    table
+                }
+
+                static CONST_LUT: [$word; CONST_LEN] = build_const();
+                static LAZY_LUT: OnceLock<Vec<$word>> = OnceLock::new();
+
+                if TIMESTEP <= CONST_LUT_TIMESTEP {
+                    f(&CONST_LUT)
+                } else {
+                    f(LAZY_LUT.get_or_init(|| lut::build(TIMESTEP)))
+                }
+            }
+
+            impl_word!(@slices_eq $word);
+        }
+    };
+    (@slices_eq u64) => {
+        fn slices_eq(a: &[Self], a_start: u8, b: &[Self], b_start: u8) -> bool {
+            simd::slices_eq_u64(a, a_start, b, b_start)
+        }
+    };
+    (@slices_eq usize) => {
+        #[cfg(target_pointer_width = "64")]
+        fn slices_eq(a: &[Self], a_start: u8, b: &[Self], b_start: u8) -> bool {
+            // SAFETY: `usize` and `u64` have the same size and alignment on 64-bit targets.
+            let cast = |words: &[usize]| unsafe {
+                std::slice::from_raw_parts(words.as_ptr().cast::<u64>(), words.len())
+            };
+            simd::slices_eq_u64(cast(a), a_start, cast(b), b_start)
+        }
+    };
+    (@slices_eq $word:ty) => {};
+}
+
+impl_word!(u32, timestep = 8);
+impl_word!(u64, timestep = 11);
+// A 20-step table of 128-bit words takes 16 MiB, so it is opt-in.
+impl_word!(
+    u128,
+    timestep = if cfg!(feature = "large-lut") { 20 } else { 11 }
+);
+impl_word!(usize, timestep = if usize::BITS >= 64 { 11 } else { 8 });
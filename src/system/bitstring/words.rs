@@ -0,0 +1,195 @@
+//! A contiguous buffer of words supporting deletion from the front.
+
+use std::fmt;
+
+/// A queue of words which are always stored contiguously.
+///
+/// Popping from the front only advances an index, and the remaining words are moved back to the start of the allocation
+/// when a push would otherwise grow it while at least half of it is already popped.
+/// Each word is moved at most once per word pushed after it, so pushing and popping are amortized constant time,
+/// just like in a ring buffer, but the words never wrap around and can always be viewed as a single slice.
+pub(super) struct Words<W> {
+    /// The allocation, including words which have already been popped.
+    buf: Vec<W>,
+    /// The index in `buf` of the first word which hasn't been popped.
+    head: usize,
+}
+
+impl<W> Words<W> {
+    /// Create a buffer holding just `word`.
+    pub(super) fn new(word: W) -> Self {
+        Self {
+            buf: vec![word],
+            head: 0,
+        }
+    }
+
+    /// Create a buffer holding just `word`, with room for at least `capacity` words without reallocating.
+    pub(super) fn with_capacity(word: W, capacity: usize) -> Self {
+        let mut buf = Vec::with_capacity(capacity.max(1));
+        buf.push(word);
+
+        Self { buf, head: 0 }
+    }
+
+    /// Create a buffer holding `buf`, which must not be empty, keeping its allocation.
+    pub(super) fn from_vec(buf: Vec<W>) -> Self {
+        debug_assert!(!buf.is_empty());
+
+        Self { buf, head: 0 }
+    }
+
+    /// Get the number of words the buffer can hold without reallocating.
+    pub(super) fn capacity(&self) -> usize {
+        self.buf.capacity() - self.head
+    }
+
+    /// Get the number of words allocated, including words which have already been popped.
+    pub(super) fn allocated(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Make room for at least `additional` more words without reallocating,
+    /// moving the remaining words back to the start of the allocation first if that makes enough room.
+    pub(super) fn reserve(&mut self, additional: usize) {
+        if self.buf.capacity() - self.buf.len() < additional {
+            self.buf.drain(..self.head);
+            self.head = 0;
+        }
+
+        self.buf.reserve(additional);
+    }
+
+    /// Get the words as a slice.
+    pub(super) fn as_slice(&self) -> &[W] {
+        &self.buf[self.head..]
+    }
+
+    /// Get the number of words.
+    pub(super) fn len(&self) -> usize {
+        self.buf.len() - self.head
+    }
+
+    /// Get the first word.
+    pub(super) fn front(&self) -> Option<&W> {
+        self.as_slice().first()
+    }
+
+    /// Get the last word mutably.
+    pub(super) fn back_mut(&mut self) -> Option<&mut W> {
+        self.buf[self.head..].last_mut()
+    }
+
+    /// Replace the words with just `word`, keeping the allocation.
+    pub(super) fn reset(&mut self, word: W) {
+        self.buf.clear();
+        self.buf.push(word);
+        self.head = 0;
+    }
+
+    /// Keep only the first `len` words.
+    pub(super) fn truncate(&mut self, len: usize) {
+        self.buf.truncate(self.head + len);
+    }
+
+    /// Append a word.
+    pub(super) fn push_back(&mut self, word: W) {
+        if self.buf.len() == self.buf.capacity() && self.head >= self.buf.len() / 2 {
+            self.buf.drain(..self.head);
+            self.head = 0;
+        }
+
+        self.buf.push(word);
+    }
+}
+
+impl<W: Copy> Words<W> {
+    /// Remove the first word, if any.
+    pub(super) fn pop_front(&mut self) -> Option<W> {
+        let word = *self.buf.get(self.head)?;
+        self.head += 1;
+
+        if self.head == self.buf.len() {
+            self.buf.clear();
+            self.head = 0;
+        }
+
+        Some(word)
+    }
+}
+
+impl<W: Clone> Clone for Words<W> {
+    fn clone(&self) -> Self {
+        // Leave the popped words behind.
+        Self {
+            buf: self.as_slice().to_vec(),
+            head: 0,
+        }
+    }
+}
+
+impl<W: fmt::Debug> fmt::Debug for Words<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+
+    #[test]
+    fn behaves_like_a_queue() {
+        let mut words = Words::new(0u32);
+        let mut expected = VecDeque::from([0]);
+
+        for i in 1..10_000 {
+            words.push_back(i);
+            expected.push_back(i);
+
+            if i % 3 != 0 {
+                assert_eq!(words.pop_front(), expected.pop_front());
+            }
+            if i % 1000 == 0 {
+                while let Some(word) = expected.pop_front() {
+                    assert_eq!(words.pop_front(), Some(word));
+                }
+                assert!(words.as_slice().is_empty());
+                assert_eq!(words.pop_front(), None);
+            }
+
+            assert!(words.as_slice().iter().eq(&expected));
+            assert_eq!(words.clone().as_slice(), words.as_slice());
+        }
+
+        // The allocation stays proportional to the number of live words.
+        assert!(words.buf.capacity() <= 4 * words.len().max(16));
+    }
+
+    #[test]
+    fn reserves() {
+        let mut words = Words::with_capacity(0u32, 100);
+        assert!(words.capacity() >= 100);
+
+        for i in 1..100 {
+            words.push_back(i);
+        }
+        for _ in 0..60 {
+            words.pop_front();
+        }
+
+        // Popped words are reclaimed before growing the allocation.
+        let allocation = words.buf.as_ptr();
+        words.reserve(50);
+        assert!(words.capacity() >= words.len() + 50);
+        assert_eq!(words.buf.as_ptr(), allocation);
+        assert!(words.as_slice().iter().eq(&(60..100).collect::<Vec<_>>()));
+
+        for i in 100..150 {
+            words.push_back(i);
+        }
+        assert_eq!(words.buf.as_ptr(), allocation);
+    }
+}
@@ -0,0 +1,140 @@
+//! The canonical serialized form shared by all backends.
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::PostSystem;
+
+use super::{GenericBitString, VecDequeBools, Word};
+
+/// A state packed into little-endian 64-bit words, starting at bit zero of the first word.
+///
+/// Bits beyond `len` are zero, so equal states always serialize identically regardless of backend or internal offsets.
+#[derive(Serialize, Deserialize)]
+struct Canonical {
+    len: u64,
+    words: Vec<u64>,
+}
+
+impl Canonical {
+    fn from_bits(len: usize, bits: impl IntoIterator<Item = bool>) -> Self {
+        let mut words = vec![0; len.div_ceil(64)];
+        for (i, bit) in bits.into_iter().enumerate() {
+            words[i / 64] |= (bit as u64) << (i % 64);
+        }
+
+        Self {
+            len: len as u64,
+            words,
+        }
+    }
+
+    fn validate<E: de::Error>(self) -> Result<Self, E> {
+        if self.words.len() as u64 != self.len.div_ceil(64) {
+            return Err(E::invalid_length(
+                self.words.len(),
+                &"one word per 64 bits of length",
+            ));
+        }
+
+        if !self.len.is_multiple_of(64) && self.words.last().unwrap() >> (self.len % 64) != 0 {
+            return Err(E::custom("bits set beyond the end of the state"));
+        }
+
+        Ok(self)
+    }
+
+    fn bits(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len as usize).map(|i| (self.words[i / 64] >> (i % 64)) & 1 == 1)
+    }
+}
+
+impl<W: Word> Serialize for GenericBitString<W> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Canonical::from_bits(self.length(), self.bits()).serialize(serializer)
+    }
+}
+
+impl<'de, W: Word> Deserialize<'de> for GenericBitString<W> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let canonical = Canonical::deserialize(deserializer)?.validate()?;
+
+        let mut this = Self::new();
+        for (i, &word) in canonical.words.iter().enumerate() {
+            let count = (canonical.len - 64 * i as u64).min(64) as u8;
+
+            // Append in 32-bit halves so this works regardless of the word width.
+            this.append(W::from_u32(word as u32), count.min(32));
+            if count > 32 {
+                this.append(W::from_u32((word >> 32) as u32), count - 32);
+            }
+        }
+
+        Ok(this)
+    }
+}
+
+impl Serialize for VecDequeBools {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Canonical::from_bits(self.length(), self.0.iter().copied()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for VecDequeBools {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let canonical = Canonical::deserialize(deserializer)?.validate()?;
+
+        Ok(Self(canonical.bits().collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        system::{BitString, BitString128, BitString32, BitString64, VecDequeBools},
+        PostSystem,
+    };
+
+    #[test]
+    fn round_trips() {
+        let mut bit_string = BitString::new_decompressed(&[true, false, true, true]);
+        let _ = bit_string.evolve_multi(50);
+
+        let json = serde_json::to_string(&bit_string).unwrap();
+        assert_eq!(
+            serde_json::from_str::<BitString>(&json).unwrap(),
+            bit_string
+        );
+
+        let mut vec_deque_bools = VecDequeBools::new_decompressed(&[true, false, true, true]);
+        let _ = vec_deque_bools.evolve_multi(50);
+
+        // Both backends share a canonical form, independent of internal offsets.
+        assert_eq!(serde_json::to_string(&vec_deque_bools).unwrap(), json);
+        assert_eq!(
+            serde_json::from_str::<VecDequeBools>(&json).unwrap(),
+            vec_deque_bools
+        );
+    }
+
+    #[test]
+    fn shares_form_across_word_widths() {
+        let mut bit_string = BitString::new_decompressed(&[true, false, true, true]);
+        let _ = bit_string.evolve_multi(50);
+        let json = serde_json::to_string(&bit_string).unwrap();
+
+        fn check<S: PostSystem + serde::Serialize + serde::de::DeserializeOwned>(json: &str) {
+            let system: S = serde_json::from_str(json).unwrap();
+            assert_eq!(serde_json::to_string(&system).unwrap(), json);
+        }
+        check::<BitString32>(&json);
+        check::<BitString64>(&json);
+        check::<BitString128>(&json);
+    }
+
+    #[test]
+    fn rejects_invalid() {
+        assert!(serde_json::from_str::<BitString>(r#"{"len":3,"words":[]}"#).is_err());
+        assert!(serde_json::from_str::<BitString>(r#"{"len":3,"words":[8]}"#).is_err());
+        assert!(serde_json::from_str::<BitString>(r#"{"len":3,"words":[5]}"#).is_ok());
+    }
+}
@@ -0,0 +1,264 @@
+//! Bit strings which store their middle in fixed-size chunks, keeping only their ends as [`BitString64`]s.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    hash::{Hash, Hasher},
+    mem,
+    ops::{ControlFlow, Deref},
+    str::FromStr,
+};
+
+use crate::PostSystem;
+
+use super::{BitString64, ParseStateError};
+
+/// A queue of chunks of exactly [`Self::WORDS`] words, storing the middle of a [`Chunked`] bit string.
+pub trait Chunks: fmt::Debug + Clone + Default {
+    /// The number of words in each chunk.
+    const WORDS: usize;
+
+    /// A chunk taken from the queue.
+    type Chunk: Deref<Target = [u64]>;
+
+    /// Get the number of chunks.
+    fn len(&self) -> usize;
+
+    /// Check whether there are no chunks.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Append a chunk.
+    fn push_back(&mut self, chunk: Vec<u64>);
+
+    /// Remove the first chunk, if any.
+    fn pop_front(&mut self) -> Option<Self::Chunk>;
+
+    /// Iterate over the chunks, in order.
+    fn iter(&self) -> impl Iterator<Item = Self::Chunk> + '_;
+}
+
+/// A bit string whose middle is stored in a queue of chunks `C`.
+///
+/// Symbols are only ever read from the start of a tag system's string and written to its end,
+/// so only two parts of the string need to be directly accessible:
+/// the head, which is being read, and the tail, which is being written.
+/// Once the tail fills a chunk, the chunk is moved into the queue,
+/// and once the head runs out, the next chunk is moved into it.
+/// Every bit is therefore copied from the tail to the head on its way through,
+/// so evolution takes around twice as long as with [`BitString64`], whatever the chunks are.
+///
+/// Comparing, hashing, or converting the whole string visits every chunk, so avoid doing so more than necessary.
+#[derive(Debug, Clone)]
+pub struct Chunked<C> {
+    /// The start of the string, which is read from.
+    pub(super) head: BitString64,
+    /// The middle of the string.
+    pub(super) chunks: C,
+    /// The end of the string, which is written to.
+    pub(super) tail: BitString64,
+}
+
+impl<C: Chunks> Chunked<C> {
+    /// The number of bits in each chunk.
+    const CHUNK_BITS: usize = C::WORDS * u64::BITS as usize;
+
+    /// Create a new empty bit string.
+    fn new() -> Self {
+        Self {
+            head: BitString64::new(),
+            chunks: C::default(),
+            tail: BitString64::new(),
+        }
+    }
+
+    /// Append `count` bits to the end of the bit string, from the little-endian `bits`, moving the tail into a chunk if it fills one.
+    pub(super) fn append(&mut self, bits: u64, count: u8) {
+        self.tail.append_bits(bits, count);
+        self.flush_tail();
+    }
+
+    /// Move the start of the tail into a chunk if it fills one.
+    fn flush_tail(&mut self) {
+        if self.tail.length() >= Self::CHUNK_BITS {
+            let chunk = (0..C::WORDS).map(|_| self.tail.delete_bits(64)).collect();
+            self.chunks.push_back(chunk);
+        }
+    }
+
+    /// Make sure the head holds at least `needed` bits, or all of the string if it is shorter.
+    fn refill(&mut self, needed: usize) {
+        if self.head.length() >= needed {
+            return;
+        }
+
+        if let Some(chunk) = self.chunks.pop_front() {
+            for &word in chunk.iter() {
+                self.head.append_bits(word, 64);
+            }
+        } else if self.head.length() == 0 {
+            mem::swap(&mut self.head, &mut self.tail);
+        } else {
+            let tail = mem::take(&mut self.tail);
+            for (word, count) in super::chunks(&tail) {
+                self.head.append_bits(word, count);
+            }
+        }
+    }
+
+    /// Iterate over the bits of the string in little-endian chunks of up to 64 bits, with the number of bits in each.
+    fn words(&self) -> impl Iterator<Item = (u64, u8)> + '_ {
+        super::chunks(&self.head)
+            .chain(
+                self.chunks
+                    .iter()
+                    .flat_map(|chunk| (0..C::WORDS).map(move |i| (chunk[i], 64))),
+            )
+            .chain(super::chunks(&self.tail))
+    }
+}
+
+impl<C: Chunks> PartialEq for Chunked<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.length() == other.length()
+            && super::pack_words(self.words()).eq(super::pack_words(other.words()))
+    }
+}
+impl<C: Chunks> Eq for Chunked<C> {}
+
+impl<C: Chunks> Hash for Chunked<C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.length().hash(state);
+        for word in super::pack_words(self.words()) {
+            word.hash(state);
+        }
+    }
+}
+
+impl<C: Chunks> FromStr for Chunked<C> {
+    type Err = ParseStateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        super::parse_bits(s).map(Self::from_list)
+    }
+}
+
+impl<C: Chunks> fmt::Display for Chunked<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        super::fmt_bits(self.bits(), f)
+    }
+}
+
+impl<C: Chunks> PostSystem for Chunked<C> {
+    fn new_decompressed(compressed: &[bool]) -> Self {
+        let mut this = Self::new();
+
+        for &b in compressed {
+            this.append(b as u64, 3);
+        }
+
+        this
+    }
+
+    fn length(&self) -> usize {
+        self.head.length() + self.chunks.len() * Self::CHUNK_BITS + self.tail.length()
+    }
+
+    fn as_list(&self) -> VecDeque<bool> {
+        self.bits().collect()
+    }
+
+    fn bits(&self) -> impl Iterator<Item = bool> + '_ {
+        self.words()
+            .flat_map(|(word, count)| (0..count).map(move |i| (word >> i) & 1 == 1))
+    }
+
+    fn count_ones(&self) -> usize {
+        self.words()
+            .map(|(word, _)| word.count_ones() as usize)
+            .sum()
+    }
+
+    fn from_list(list: VecDeque<bool>) -> Self {
+        let mut this = Self::new();
+
+        let mut bits = list.into_iter();
+        loop {
+            let mut word = 0;
+            let mut count = 0;
+            for bit in bits.by_ref().take(64) {
+                word |= (bit as u64) << count;
+                count += 1;
+            }
+
+            if count == 0 {
+                break;
+            }
+            this.append(word, count);
+        }
+
+        this
+    }
+
+    fn evolve(&mut self) -> ControlFlow<()> {
+        self.refill(3);
+        if self.head.length() < 3 {
+            return ControlFlow::Break(());
+        }
+
+        if self.head.delete_bits(3) & 1 == 0 {
+            self.append(0, 2);
+        } else {
+            self.append(0b1011, 4);
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn evolve_multi(&mut self, n: usize) -> ControlFlow<usize> {
+        let timestep = self.preferred_timestep() as usize;
+
+        let mut i = 0;
+        while i < n {
+            self.refill(3 * timestep);
+
+            // Jump for as long as the head has enough bits and the tail has room before the next chunk,
+            // without checking either after every jump.
+            let jumps = ((n - i) / timestep)
+                .min(self.head.length() / (3 * timestep))
+                .min(Self::CHUNK_BITS.saturating_sub(self.tail.length()) / (4 * timestep) + 1);
+            for _ in 0..jumps {
+                let (bits, len) = self.head.take_jump();
+                self.tail.append_bits(bits, len);
+            }
+            self.flush_tail();
+            i += jumps * timestep;
+
+            if jumps == 0 {
+                if let ControlFlow::Break(()) = self.evolve() {
+                    return ControlFlow::Break(i);
+                }
+
+                i += 1;
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn preferred_timestep(&self) -> u8 {
+        self.head.preferred_timestep()
+    }
+
+    fn evolve_preferred(&mut self) -> ControlFlow<u8> {
+        debug_assert!(self.length() >= 3 * self.preferred_timestep() as usize);
+
+        self.refill(3 * self.preferred_timestep() as usize);
+
+        let (bits, len) = self.head.take_jump();
+        self.append(bits, len);
+
+        ControlFlow::Continue(())
+    }
+}
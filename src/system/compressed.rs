@@ -0,0 +1,109 @@
+//! A bit string which keeps most of its bits compressed, for states too long to hold in memory otherwise.
+
+use std::collections::VecDeque;
+
+use super::chunked::{Chunked, Chunks};
+
+/// A bit string whose middle is stored as a queue of LZ4-compressed blocks.
+///
+/// Only the ends of the string are ever uncompressed; see [`Chunked`].
+/// The memory used therefore depends on how well the states compress,
+/// and comparing, hashing, or converting the whole string decompresses every block.
+pub type CompressedBitString = Chunked<Blocks>;
+
+impl CompressedBitString {
+    /// Get the number of bytes taken by the compressed blocks.
+    pub fn compressed_size(&self) -> usize {
+        self.chunks.0.iter().map(|block| block.len()).sum()
+    }
+}
+
+/// A queue of LZ4-compressed blocks, storing the middle of a [`CompressedBitString`].
+#[derive(Debug, Clone, Default)]
+pub struct Blocks(VecDeque<Box<[u8]>>);
+
+impl Chunks for Blocks {
+    // Blocks are kept tiny in tests so that short runs still cross many block boundaries.
+    const WORDS: usize = if cfg!(test) { 4 } else { 1 << 14 };
+
+    type Chunk = Vec<u64>;
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn push_back(&mut self, chunk: Vec<u64>) {
+        let bytes: Vec<_> = chunk.iter().flat_map(|word| word.to_le_bytes()).collect();
+
+        self.0
+            .push_back(lz4_flex::compress_prepend_size(&bytes).into_boxed_slice());
+    }
+
+    fn pop_front(&mut self) -> Option<Vec<u64>> {
+        self.0.pop_front().map(|block| decompress(&block))
+    }
+
+    fn iter(&self) -> impl Iterator<Item = Vec<u64>> + '_ {
+        self.0.iter().map(|block| decompress(block))
+    }
+}
+
+fn decompress(block: &[u8]) -> Vec<u64> {
+    lz4_flex::decompress_size_prepended(block)
+        .expect("blocks are only ever compressed by `Blocks::push_back`")
+        .chunks_exact(8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        hash::{BuildHasher, RandomState},
+        ops::ControlFlow,
+    };
+
+    use crate::{seeds, system::BitString64, tests_for_system, PostSystem};
+
+    use super::*;
+
+    tests_for_system!(CompressedBitString);
+
+    #[test]
+    fn agrees_with_bit_string() {
+        let mut expected: BitString64 = seeds::SEED_5854.system();
+        let mut compressed: CompressedBitString = seeds::SEED_5854.system();
+
+        for n in [0, 1, 10, 1000, 12_345, 100_000] {
+            assert_eq!(compressed.evolve_multi(n), expected.evolve_multi(n));
+            assert_eq!(compressed.length(), expected.length());
+            assert_eq!(compressed.as_list(), expected.as_list());
+        }
+        assert!(!compressed.chunks.is_empty());
+        assert!(compressed.compressed_size() > 0);
+
+        // Halting partway through is reported as by other systems.
+        let mut system = CompressedBitString::from_seed_u128(0b11111);
+        assert_eq!(system.evolve_multi(1000), ControlFlow::Break(409));
+    }
+
+    #[test]
+    fn compares_regardless_of_blocks() {
+        let hasher = RandomState::new();
+
+        let mut system: CompressedBitString = seeds::SEED_5854.system();
+        for _ in 0..100 {
+            let _ = system.evolve_multi(997);
+
+            // Rebuilding the string splits it into blocks at different points.
+            let rebuilt = CompressedBitString::from_list(system.as_list());
+            assert_eq!(rebuilt, system);
+            assert_eq!(hasher.hash_one(&rebuilt), hasher.hash_one(&system));
+            assert_eq!(rebuilt.to_string(), system.to_string());
+        }
+
+        let mut other = system.clone();
+        other.append(0, 1);
+        assert_ne!(other, system);
+    }
+}
@@ -0,0 +1,270 @@
+//! A bit string which keeps most of its bits in temporary files, for states larger than memory.
+
+use std::{
+    collections::VecDeque,
+    env,
+    fs::{self, File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use memmap2::Mmap;
+
+use super::chunked::{Chunked, Chunks};
+
+/// A bit string whose middle is stored in temporary files, which are memory-mapped once written.
+///
+/// Only the ends of the string are kept in memory; see [`Chunked`].
+/// The files are created in [`env::temp_dir`], and deleted once their chunks have been read or the string is dropped.
+/// Cloning the string copies its files.
+///
+/// # Panics
+///
+/// Evolving, creating, or cloning the string panics if the temporary files can't be created, written, or read,
+/// for example because the disk is full.
+pub type DiskBitString = Chunked<DiskChunks>;
+
+/// The number of bytes in each chunk.
+const CHUNK_BYTES: usize = DiskChunks::WORDS * 8;
+
+/// The number of chunks in each file.
+///
+/// Files are kept tiny in tests so that short runs still cross many file boundaries.
+const SEGMENT_CHUNKS: usize = if cfg!(test) { 3 } else { 1 << 10 };
+
+/// A queue of chunks stored in temporary files, storing the middle of a [`DiskBitString`].
+#[derive(Debug, Clone, Default)]
+pub struct DiskChunks {
+    segments: VecDeque<Segment>,
+    /// The number of chunks in all the segments together.
+    len: usize,
+}
+
+/// A temporary file holding up to [`SEGMENT_CHUNKS`] chunks.
+#[derive(Debug)]
+struct Segment {
+    path: PathBuf,
+    file: File,
+    /// The contents of the file, once it holds all its chunks and will no longer be written.
+    map: Option<Mmap>,
+    /// The number of chunks written to the file.
+    written: usize,
+    /// The number of chunks already read from the file.
+    read: usize,
+}
+
+impl Segment {
+    /// Create an empty segment in a new temporary file.
+    fn create() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let path = env::temp_dir().join(format!(
+            "post-tag-{}-{}.seg",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .expect("failed to create temporary file");
+
+        Self {
+            path,
+            file,
+            map: None,
+            written: 0,
+            read: 0,
+        }
+    }
+
+    /// Map the file once it is full.
+    fn seal(&mut self) {
+        if self.written == SEGMENT_CHUNKS {
+            // SAFETY: the file was created by this segment, which never writes to it again once it is full.
+            self.map =
+                Some(unsafe { Mmap::map(&self.file) }.expect("failed to map temporary file"));
+        }
+    }
+
+    fn write(&mut self, chunk: &[u64]) {
+        let bytes: Vec<_> = chunk.iter().flat_map(|word| word.to_le_bytes()).collect();
+
+        self.file
+            .seek(SeekFrom::Start((self.written * CHUNK_BYTES) as u64))
+            .and_then(|_| self.file.write_all(&bytes))
+            .expect("failed to write temporary file");
+        self.written += 1;
+
+        self.seal();
+    }
+
+    /// Read the `i`th chunk of the file.
+    fn get(&self, i: usize) -> Vec<u64> {
+        let mut buf;
+        let bytes = match &self.map {
+            Some(map) => &map[i * CHUNK_BYTES..(i + 1) * CHUNK_BYTES],
+            None => {
+                buf = vec![0; CHUNK_BYTES];
+                let mut file = &self.file;
+                file.seek(SeekFrom::Start((i * CHUNK_BYTES) as u64))
+                    .and_then(|_| file.read_exact(&mut buf))
+                    .expect("failed to read temporary file");
+                &buf
+            }
+        };
+
+        bytes
+            .chunks_exact(8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+            .collect()
+    }
+}
+
+impl Clone for Segment {
+    fn clone(&self) -> Self {
+        let mut segment = Self::create();
+        fs::copy(&self.path, &segment.path).expect("failed to copy temporary file");
+        segment.written = self.written;
+        segment.read = self.read;
+        segment.seal();
+
+        segment
+    }
+}
+
+impl Drop for Segment {
+    fn drop(&mut self) {
+        self.map = None;
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl Chunks for DiskChunks {
+    // Chunks are kept tiny in tests so that short runs still cross many chunk boundaries.
+    const WORDS: usize = if cfg!(test) { 4 } else { 1 << 13 };
+
+    type Chunk = Vec<u64>;
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn push_back(&mut self, chunk: Vec<u64>) {
+        if self
+            .segments
+            .back()
+            .is_none_or(|segment| segment.written == SEGMENT_CHUNKS)
+        {
+            self.segments.push_back(Segment::create());
+        }
+
+        self.segments.back_mut().unwrap().write(&chunk);
+        self.len += 1;
+    }
+
+    fn pop_front(&mut self) -> Option<Vec<u64>> {
+        let segment = self.segments.front_mut()?;
+        if segment.read == segment.written {
+            return None;
+        }
+
+        let chunk = segment.get(segment.read);
+        segment.read += 1;
+        if segment.read == SEGMENT_CHUNKS {
+            self.segments.pop_front();
+        }
+        self.len -= 1;
+
+        Some(chunk)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = Vec<u64>> + '_ {
+        self.segments
+            .iter()
+            .flat_map(|segment| (segment.read..segment.written).map(|i| segment.get(i)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        hash::{BuildHasher, RandomState},
+        ops::ControlFlow,
+    };
+
+    use crate::{seeds, system::BitString64, tests_for_system, PostSystem};
+
+    use super::*;
+
+    tests_for_system!(DiskBitString);
+
+    #[test]
+    fn agrees_with_bit_string() {
+        let mut expected: BitString64 = seeds::SEED_5854.system();
+        let mut disk: DiskBitString = seeds::SEED_5854.system();
+
+        for n in [0, 1, 10, 1000, 12_345, 100_000] {
+            assert_eq!(disk.evolve_multi(n), expected.evolve_multi(n));
+            assert_eq!(disk.length(), expected.length());
+            assert_eq!(disk.as_list(), expected.as_list());
+        }
+        assert!(disk.chunks.segments.len() > 1);
+        assert!(disk.chunks.segments[0].map.is_some());
+
+        // Halting partway through is reported as by other systems.
+        let mut system = DiskBitString::from_seed_u128(0b11111);
+        assert_eq!(system.evolve_multi(1000), ControlFlow::Break(409));
+    }
+
+    #[test]
+    fn clones_independently() {
+        let hasher = RandomState::new();
+
+        let mut system: DiskBitString = seeds::SEED_5854.system();
+        let _ = system.evolve_multi(100_000);
+        let list = system.as_list();
+
+        let mut clone = system.clone();
+        assert_eq!(clone, system);
+        assert_eq!(hasher.hash_one(&clone), hasher.hash_one(&system));
+
+        let _ = clone.evolve_multi(1234);
+        assert_ne!(clone, system);
+        assert_eq!(system.as_list(), list);
+
+        let _ = system.evolve_multi(1234);
+        assert_eq!(clone, system);
+    }
+
+    #[test]
+    fn deletes_files() {
+        let mut system: DiskBitString = seeds::SEED_5854.system();
+        let _ = system.evolve_multi(100_000);
+
+        let paths: Vec<_> = system
+            .chunks
+            .segments
+            .iter()
+            .map(|segment| segment.path.clone())
+            .collect();
+        assert!(paths.iter().all(|path| path.exists()));
+
+        // Files are deleted as they are read...
+        let _ = system.evolve_multi(100_000);
+        assert!(!paths[0].exists());
+
+        // ...and when the string is dropped.
+        let paths: Vec<_> = system
+            .chunks
+            .segments
+            .iter()
+            .map(|segment| segment.path.clone())
+            .collect();
+        drop(system);
+        assert!(!paths.is_empty() && paths.iter().all(|path| !path.exists()));
+    }
+}
@@ -0,0 +1,204 @@
+//! Systems whose representation is chosen at runtime.
+//!
+//! [`PostSystem`] requires [`Clone`] and has generic methods, so it can't be made into a trait object.
+//! [`DynPostSystem`] has the same core methods in an object-safe form and is implemented for every system,
+//! so systems of different types can be kept together as `Box<dyn DynPostSystem>`s.
+//! Such boxes can be cloned, compared, and hashed by their states, whatever their types.
+
+use std::{
+    any::Any,
+    collections::VecDeque,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::ControlFlow,
+};
+
+use crate::PostSystem;
+
+/// An object-safe counterpart of [`PostSystem`].
+pub trait DynPostSystem {
+    /// Get the length of the system, as in [`PostSystem::length`].
+    fn length(&self) -> usize;
+
+    /// Estimate the bytes occupied by the state, as in [`PostSystem::memory_usage`].
+    fn memory_usage(&self) -> usize;
+
+    /// Convert the system to a canonical list form, as in [`PostSystem::as_list`].
+    fn as_list(&self) -> VecDeque<bool>;
+
+    /// Iterate over the symbols of the system, as in [`PostSystem::bits`].
+    fn bits(&self) -> Box<dyn Iterator<Item = bool> + '_>;
+
+    /// Count the ones in the system, as in [`PostSystem::count_ones`].
+    fn count_ones(&self) -> usize;
+
+    /// Get the symbol at `index`, as in [`PostSystem::get`].
+    fn get(&self, index: usize) -> Option<bool>;
+
+    /// Compute the fingerprint of the underlying system, as in [`PostSystem::fingerprint`].
+    ///
+    /// Equal states of different types may have different fingerprints.
+    fn fingerprint(&self) -> u64;
+
+    /// Evolve the system by one step, as in [`PostSystem::evolve`].
+    fn evolve(&mut self) -> ControlFlow<()>;
+
+    /// Evolve the system by `n` steps, as in [`PostSystem::evolve_multi`].
+    fn evolve_multi(&mut self, n: usize) -> ControlFlow<usize>;
+
+    /// Clone the system into a new box.
+    fn clone_box(&self) -> Box<dyn DynPostSystem>;
+
+    /// Get the underlying system, so it can be downcast to its type.
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<S: PostSystem + 'static> DynPostSystem for S {
+    fn length(&self) -> usize {
+        PostSystem::length(self)
+    }
+
+    fn memory_usage(&self) -> usize {
+        PostSystem::memory_usage(self)
+    }
+
+    fn as_list(&self) -> VecDeque<bool> {
+        PostSystem::as_list(self)
+    }
+
+    fn bits(&self) -> Box<dyn Iterator<Item = bool> + '_> {
+        Box::new(PostSystem::bits(self))
+    }
+
+    fn count_ones(&self) -> usize {
+        PostSystem::count_ones(self)
+    }
+
+    fn get(&self, index: usize) -> Option<bool> {
+        PostSystem::get(self, index)
+    }
+
+    fn fingerprint(&self) -> u64 {
+        PostSystem::fingerprint(self)
+    }
+
+    fn evolve(&mut self) -> ControlFlow<()> {
+        PostSystem::evolve(self)
+    }
+
+    fn evolve_multi(&mut self, n: usize) -> ControlFlow<usize> {
+        PostSystem::evolve_multi(self, n)
+    }
+
+    fn clone_box(&self) -> Box<dyn DynPostSystem> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Systems are equal if their states are, whatever their types.
+impl PartialEq for dyn DynPostSystem {
+    fn eq(&self, other: &Self) -> bool {
+        self.length() == other.length() && self.bits().eq(other.bits())
+    }
+}
+
+impl Eq for dyn DynPostSystem {}
+
+/// The hash depends only on the state, not the type, to agree with equality.
+impl Hash for dyn DynPostSystem {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.length());
+
+        let mut bits = self.bits().peekable();
+        while bits.peek().is_some() {
+            let word = bits
+                .by_ref()
+                .take(64)
+                .enumerate()
+                .fold(0u64, |word, (i, bit)| word | (bit as u64) << i);
+            state.write_u64(word);
+        }
+    }
+}
+
+impl fmt::Display for dyn DynPostSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        super::fmt_bits(self.bits(), f)
+    }
+}
+
+impl fmt::Debug for dyn DynPostSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DynPostSystem({self})")
+    }
+}
+
+impl Clone for Box<dyn DynPostSystem> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        seeds::{SEED_5854, SEED_6008},
+        system::{BitString, HybridBitString, VecDequeBools},
+    };
+
+    use super::*;
+
+    #[test]
+    fn behaves_like_the_underlying_system() {
+        let mut concrete = SEED_5854.system::<BitString>();
+        let mut system: Box<dyn DynPostSystem> = Box::new(concrete.clone());
+
+        for n in [1, 10, 1000] {
+            assert_eq!(system.evolve(), PostSystem::evolve(&mut concrete));
+            assert_eq!(
+                system.evolve_multi(n),
+                PostSystem::evolve_multi(&mut concrete, n)
+            );
+
+            assert_eq!(system.length(), PostSystem::length(&concrete));
+            assert_eq!(system.memory_usage(), PostSystem::memory_usage(&concrete));
+            assert_eq!(system.as_list(), PostSystem::as_list(&concrete));
+            assert!(system.bits().eq(PostSystem::bits(&concrete)));
+            assert_eq!(system.count_ones(), PostSystem::count_ones(&concrete));
+            assert_eq!(system.get(4), PostSystem::get(&concrete, 4));
+            assert_eq!(system.fingerprint(), PostSystem::fingerprint(&concrete));
+        }
+
+        assert_eq!(system.as_any().downcast_ref::<BitString>(), Some(&concrete));
+        assert!(system.as_any().downcast_ref::<VecDequeBools>().is_none());
+        assert_eq!(system.to_string(), concrete.to_string());
+    }
+
+    #[test]
+    fn mixes_types() {
+        let mut mixed: Vec<Box<dyn DynPostSystem>> = vec![
+            Box::new(SEED_6008.system::<BitString>()),
+            Box::new(SEED_6008.system::<VecDequeBools>()),
+            Box::new(SEED_6008.system::<HybridBitString>()),
+        ];
+        assert!(*mixed[0] == *mixed[1] && *mixed[1] == *mixed[2]);
+        assert_eq!(
+            crate::hash::hash_one(&mixed[0]),
+            crate::hash::hash_one(&mixed[1])
+        );
+
+        for system in &mut mixed {
+            assert_eq!(system.evolve_multi(1_000_000), ControlFlow::Break(261_741));
+        }
+        assert!(*mixed[0] == *mixed[2]);
+
+        let mut copy = mixed[1].clone();
+        assert!(*copy == *mixed[1]);
+        assert_eq!(copy.evolve(), ControlFlow::Break(()));
+        assert!(copy.as_any().downcast_ref::<VecDequeBools>().is_some());
+    }
+}
@@ -0,0 +1,365 @@
+//! A bit string of fixed capacity, which never allocates.
+
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+    ops::ControlFlow,
+    str::FromStr,
+};
+use std::{collections::VecDeque, error::Error};
+
+use crate::PostSystem;
+
+use super::{bitstring, BitString64, ParseStateError};
+
+/// An error returned when a state would grow beyond the capacity of a [`FixedBitString`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapacityError {
+    /// The capacity of the string, in bits.
+    pub capacity: usize,
+    /// The number of steps taken before the step which would have exceeded the capacity.
+    pub steps: usize,
+}
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "state would exceed capacity of {} bits after {} steps",
+            self.capacity, self.steps
+        )
+    }
+}
+
+impl Error for CapacityError {}
+
+/// A bit string stored in a ring buffer of `WORDS` 64-bit words, inline and without ever allocating.
+///
+/// Evolving a string which would outgrow the buffer fails with a [`CapacityError`], leaving the string as it was before the failing step,
+/// so this suits runs whose memory must be bounded up front.
+/// The methods whose names start with `try_` never allocate.
+/// The [`PostSystem`] implementation panics instead of failing, and converts to and from heap-allocated lists.
+/// Like the rest of the crate, this needs the standard library, so it is not available in `no_std` builds.
+#[derive(Debug, Clone)]
+pub struct FixedBitString<const WORDS: usize> {
+    /// The ring buffer.
+    /// Bits outside the string are always zero.
+    words: [u64; WORDS],
+    /// The index in the buffer of the first bit of the string.
+    start: usize,
+    /// Length of the bit string.
+    len: usize,
+}
+
+impl<const WORDS: usize> FixedBitString<WORDS> {
+    /// The capacity of the string, in bits.
+    pub const CAPACITY: usize = WORDS * u64::BITS as usize;
+
+    /// Create a new empty bit string.
+    pub const fn new() -> Self {
+        Self {
+            words: [0; WORDS],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    /// Initialize the system from a compressed representation of an initial string, as in [`PostSystem::new_decompressed`],
+    /// or fail if it doesn't fit.
+    pub fn try_new_decompressed(compressed: &[bool]) -> Result<Self, CapacityError> {
+        let mut this = Self::new();
+
+        for &b in compressed {
+            this.try_append(b as u64, 3)?;
+        }
+
+        Ok(this)
+    }
+
+    /// Evolve the system by one step, as in [`PostSystem::evolve`], or fail without changing it if the result wouldn't fit.
+    pub fn try_evolve(&mut self) -> Result<ControlFlow<()>, CapacityError> {
+        if self.len < 3 {
+            return Ok(ControlFlow::Break(()));
+        }
+
+        let (bits, count) = if self.read(0, 1) == 0 {
+            (0, 2)
+        } else {
+            (0b1011, 4)
+        };
+        self.check(3, count, 0)?;
+
+        self.advance(3);
+        self.try_append(bits, count)?;
+
+        Ok(ControlFlow::Continue(()))
+    }
+
+    /// Evolve the system by `n` steps, as in [`PostSystem::evolve_multi`],
+    /// or fail if the string would outgrow the buffer, leaving it as it was before the failing step.
+    pub fn try_evolve_multi(&mut self, n: usize) -> Result<ControlFlow<usize>, CapacityError> {
+        let timestep = BitString64::TABLE_JUMP as usize;
+
+        let mut i = 0;
+        while i < n {
+            if n - i >= timestep && self.len >= 3 * timestep {
+                let (bits, count) = bitstring::table_jump(self.read(0, 3 * timestep as u8));
+
+                // A jump which doesn't fit is retried one step at a time, to find exactly which step overflows.
+                if self.check(3 * timestep, count, i).is_ok() {
+                    self.advance(3 * timestep);
+                    self.try_append(bits, count)?;
+                    i += timestep;
+                    continue;
+                }
+            }
+
+            match self.try_evolve() {
+                Ok(ControlFlow::Continue(())) => i += 1,
+                Ok(ControlFlow::Break(())) => return Ok(ControlFlow::Break(i)),
+                Err(e) => return Err(CapacityError { steps: i, ..e }),
+            }
+        }
+
+        Ok(ControlFlow::Continue(()))
+    }
+
+    /// Check that deleting `deleted` bits and then appending `appended` bits would fit, after `steps` steps.
+    fn check(&self, deleted: usize, appended: u8, steps: usize) -> Result<(), CapacityError> {
+        if self.len - deleted + appended as usize > Self::CAPACITY {
+            return Err(CapacityError {
+                capacity: Self::CAPACITY,
+                steps,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Append `count` bits to the end of the bit string, from the little-endian `bits`, or fail if they don't fit.
+    ///
+    /// `count` must be at most 64, and `bits` must not have any bits set beyond the `count`-th bit.
+    fn try_append(&mut self, bits: u64, count: u8) -> Result<(), CapacityError> {
+        self.check(0, count, 0)?;
+
+        let position = (self.start + self.len) % Self::CAPACITY;
+        let (word, offset) = (position / 64, (position % 64) as u32);
+
+        self.words[word] |= bits << offset;
+        if offset != 0 && offset + count as u32 > 64 {
+            self.words[(word + 1) % WORDS] |= bits >> (64 - offset);
+        }
+        self.len += count as usize;
+
+        Ok(())
+    }
+
+    /// Get the `count` bits starting `position` bits into the bit string, little-endian.
+    ///
+    /// `count` must be at most 64, and the bits must lie within the bit string.
+    fn read(&self, position: usize, count: u8) -> u64 {
+        debug_assert!(count <= 64 && position + count as usize <= self.len);
+
+        let position = (self.start + position) % Self::CAPACITY;
+        let (word, offset) = (position / 64, (position % 64) as u32);
+
+        let mut bits = self.words[word] >> offset;
+        if offset != 0 {
+            bits |= self.words[(word + 1) % WORDS] << (64 - offset);
+        }
+
+        if count < 64 {
+            bits & !(u64::MAX << count)
+        } else {
+            bits
+        }
+    }
+
+    /// Delete `count` bits from the start of the bit string, which must be at most its length.
+    fn advance(&mut self, count: usize) {
+        debug_assert!(count <= self.len);
+
+        let mut cleared = 0;
+        while cleared < count {
+            let (word, offset) = (self.start / 64, self.start % 64);
+            let chunk = (count - cleared).min(64 - offset);
+
+            self.words[word] &= !(u64::MAX >> (64 - chunk) << offset);
+            self.start = (self.start + chunk) % Self::CAPACITY;
+            cleared += chunk;
+        }
+
+        self.len -= count;
+    }
+
+    /// Iterate over the bits of the string in little-endian chunks of up to 64 bits, with the number of bits in each.
+    fn chunks(&self) -> impl Iterator<Item = (u64, u8)> + '_ {
+        (0..self.len.div_ceil(64)).map(|i| {
+            let count = (self.len - 64 * i).min(64) as u8;
+            (self.read(64 * i, count), count)
+        })
+    }
+}
+
+impl<const WORDS: usize> Default for FixedBitString<WORDS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const WORDS: usize> PartialEq for FixedBitString<WORDS> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.chunks().eq(other.chunks())
+    }
+}
+impl<const WORDS: usize> Eq for FixedBitString<WORDS> {}
+
+impl<const WORDS: usize> Hash for FixedBitString<WORDS> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for (word, _) in self.chunks() {
+            word.hash(state);
+        }
+    }
+}
+
+impl<const WORDS: usize> FromStr for FixedBitString<WORDS> {
+    type Err = ParseStateError;
+
+    /// Parse a string of `0`s and `1`s.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string is longer than the capacity.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        super::parse_bits(s).map(Self::from_list)
+    }
+}
+
+impl<const WORDS: usize> fmt::Display for FixedBitString<WORDS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        super::fmt_bits(self.bits(), f)
+    }
+}
+
+/// # Panics
+///
+/// The methods of this implementation panic if the string would outgrow the buffer.
+/// Use the methods of [`FixedBitString`] whose names start with `try_` to handle this instead.
+impl<const WORDS: usize> PostSystem for FixedBitString<WORDS> {
+    fn new_decompressed(compressed: &[bool]) -> Self {
+        Self::try_new_decompressed(compressed).expect("seed should fit in the buffer")
+    }
+
+    fn length(&self) -> usize {
+        self.len
+    }
+
+    fn as_list(&self) -> VecDeque<bool> {
+        self.bits().collect()
+    }
+
+    fn bits(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|i| self.read(i, 1) == 1)
+    }
+
+    fn count_ones(&self) -> usize {
+        self.chunks()
+            .map(|(word, _)| word.count_ones() as usize)
+            .sum()
+    }
+
+    fn get(&self, index: usize) -> Option<bool> {
+        (index < self.len).then(|| self.read(index, 1) == 1)
+    }
+
+    fn from_list(list: VecDeque<bool>) -> Self {
+        let mut this = Self::new();
+
+        for bit in list {
+            this.try_append(bit as u64, 1)
+                .expect("state should fit in the buffer");
+        }
+
+        this
+    }
+
+    fn evolve(&mut self) -> ControlFlow<()> {
+        self.try_evolve().expect("state should fit in the buffer")
+    }
+
+    fn evolve_multi(&mut self, n: usize) -> ControlFlow<usize> {
+        self.try_evolve_multi(n)
+            .expect("state should fit in the buffer")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::{BuildHasher, RandomState};
+
+    use crate::{seeds, tests_for_system};
+
+    use super::*;
+
+    tests_for_system!(FixedBitString<6>);
+
+    #[test]
+    fn agrees_with_bit_string() {
+        let mut expected: BitString64 = seeds::SEED_5854.system();
+        let mut fixed: FixedBitString<64> = seeds::SEED_5854.system();
+
+        for n in [0, 1, 10, 100, 1000, 12_345, 100_000, 300_000] {
+            assert_eq!(fixed.try_evolve_multi(n), Ok(expected.evolve_multi(n)));
+            assert_eq!(fixed.as_list(), expected.as_list());
+        }
+
+        // Halting partway through is reported as by other systems.
+        let mut system = FixedBitString::<1>::from_seed_u128(0b11111);
+        assert_eq!(system.try_evolve_multi(1000), Ok(ControlFlow::Break(409)));
+    }
+
+    #[test]
+    fn fails_when_full() {
+        let mut expected: BitString64 = seeds::SEED_5854.system();
+        let mut fixed: FixedBitString<2> = seeds::SEED_5854.system();
+
+        let error = fixed.try_evolve_multi(1_000_000).unwrap_err();
+        assert_eq!(error.capacity, 128);
+
+        // The string is left as it was before the step which would have overflowed.
+        assert_eq!(
+            expected.evolve_multi(error.steps),
+            ControlFlow::Continue(())
+        );
+        assert_eq!(fixed.as_list(), expected.as_list());
+        assert_eq!(fixed.try_evolve().unwrap_err().steps, 0);
+        assert_eq!(fixed.as_list(), expected.as_list());
+
+        assert_eq!(
+            FixedBitString::<1>::try_new_decompressed(&[true; 22]).err(),
+            Some(CapacityError {
+                capacity: 64,
+                steps: 0
+            })
+        );
+    }
+
+    #[test]
+    fn wraps_around() {
+        let hasher = RandomState::new();
+
+        // `(100)^7` enters a cycle of length 28 without growing much.
+        let mut system = FixedBitString::<3>::try_new_decompressed(&[true; 7]).unwrap();
+        let mut expected = BitString64::new_decompressed(&[true; 7]);
+        for _ in 0..1000 {
+            assert_eq!(system.try_evolve_multi(7), Ok(expected.evolve_multi(7)));
+
+            // Rebuilding the string moves it back to the start of the buffer.
+            let rebuilt = FixedBitString::<3>::from_list(system.as_list());
+            assert_eq!(rebuilt, system);
+            assert_eq!(hasher.hash_one(&rebuilt), hasher.hash_one(&system));
+            assert_eq!(rebuilt.to_string(), expected.to_string());
+        }
+    }
+}
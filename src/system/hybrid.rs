@@ -0,0 +1,440 @@
+//! A bit string which stores long runs of equal bits by length and everything else packed into words.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    hash::{Hash, Hasher},
+    iter,
+    ops::ControlFlow,
+    str::FromStr,
+};
+
+use crate::PostSystem;
+
+use super::{bitstring, BitString64, ParseStateError};
+
+/// The length of the shortest run stored by length rather than packed.
+///
+/// Shorter runs take less space packed, and splitting the packed segments around them would cost more than it saves.
+const MIN_RUN: usize = 256;
+
+/// A bit string stored as a queue of segments, each either a run of equal bits or a packed [`BitString64`].
+///
+/// Bits are appended to the last packed segment until a run of at least [`MIN_RUN`] equal bits forms at its end,
+/// at which point the run is split off and extended for as long as equal bits keep being appended.
+/// Runs of zeros are also cheap to read: each step within one only shortens it by three and appends two more zeros,
+/// so [`PostSystem::evolve_multi`] takes all the steps within a run at once.
+/// Strings with few long runs are stored as packed words, but still pay for the bookkeeping on every step,
+/// so they evolve around half as fast as with [`BitString64`]; measure before choosing this over it.
+#[derive(Debug, Clone)]
+pub struct HybridBitString {
+    segments: VecDeque<Segment>,
+
+    /// Length of the bit string.
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Run {
+        bit: bool,
+        len: usize,
+    },
+    Packed {
+        bits: BitString64,
+        /// The length of the run of equal bits at the end of `bits`.
+        run: usize,
+        /// The bit repeated in that run.
+        run_bit: bool,
+    },
+}
+
+impl HybridBitString {
+    /// Create a new empty bit string.
+    fn new() -> Self {
+        Self {
+            segments: VecDeque::new(),
+            len: 0,
+        }
+    }
+
+    /// Get the number of runs currently stored by length.
+    pub fn runs(&self) -> usize {
+        self.segments
+            .iter()
+            .filter(|segment| matches!(segment, Segment::Run { .. }))
+            .count()
+    }
+
+    /// Append `count` bits to the end of the bit string, from the little-endian `bits`.
+    ///
+    /// `count` must be at most 64, and `bits` must not have any bits set beyond the `count`-th bit.
+    fn append(&mut self, bits: u64, count: u8) {
+        if count == 0 {
+            return;
+        }
+        self.len += count as usize;
+
+        // The bits of the chunk, shifted so that its last bit is the top bit of the word.
+        let top = bits << (u64::BITS - count as u32);
+        let last = top >> (u64::BITS - 1) == 1;
+        let trailing = if last {
+            (!top).leading_zeros()
+        } else {
+            top.leading_zeros()
+        }
+        .min(count as u32) as usize;
+        let uniform = trailing == count as usize;
+
+        match self.segments.back_mut() {
+            Some(Segment::Run { bit, len }) if uniform && *bit == last => *len += count as usize,
+            Some(Segment::Packed {
+                bits: packed,
+                run,
+                run_bit,
+            }) => {
+                packed.append(bits, count);
+                if uniform && *run_bit == last {
+                    *run += count as usize;
+                } else {
+                    *run = trailing;
+                    *run_bit = last;
+                }
+
+                if *run >= MIN_RUN {
+                    let len = *run;
+                    let bit = *run_bit;
+
+                    packed.truncate(packed.length() - len);
+                    if packed.length() == 0 {
+                        self.segments.pop_back();
+                    }
+                    self.segments.push_back(Segment::Run { bit, len });
+                }
+            }
+            _ => {
+                let mut packed = BitString64::new();
+                packed.append(bits, count);
+                self.segments.push_back(Segment::Packed {
+                    bits: packed,
+                    run: trailing,
+                    run_bit: last,
+                });
+            }
+        }
+    }
+
+    /// Append `count` zeros to the end of the bit string.
+    fn append_zeros(&mut self, mut count: usize) {
+        if let Some(Segment::Run { bit: false, len }) = self.segments.back_mut() {
+            *len += count;
+            self.len += count;
+            return;
+        }
+
+        while count > 0 {
+            let chunk = count.min(64);
+            self.append(0, chunk as u8);
+            count -= chunk;
+        }
+    }
+
+    /// Delete `count` bits from the start of the bit string, returning them.
+    ///
+    /// `count` must be at most 64.
+    /// If `count` is greater than the number of bits in the bit string, the result is truncated and the string is left empty.
+    fn delete(&mut self, count: u8) -> u64 {
+        let mut deleted = 0;
+        let mut taken = 0;
+
+        while taken < count {
+            let Some(front) = self.segments.front_mut() else {
+                break;
+            };
+
+            let wanted = count - taken;
+            let (bits, took, empty) = match front {
+                Segment::Run { bit, len } => {
+                    let took = (*len).min(wanted as usize) as u8;
+                    *len -= took as usize;
+
+                    let bits = if *bit {
+                        u64::MAX >> (u64::BITS - took as u32)
+                    } else {
+                        0
+                    };
+                    (bits, took, *len == 0)
+                }
+                Segment::Packed { bits, run, .. } => {
+                    let took = bits.length().min(wanted as usize) as u8;
+                    let deleted = bits.delete(took);
+                    *run = (*run).min(bits.length());
+
+                    (deleted, took, bits.length() == 0)
+                }
+            };
+
+            deleted |= bits.checked_shl(taken as u32).unwrap_or(0);
+            taken += took;
+            if empty {
+                self.segments.pop_front();
+            }
+        }
+
+        self.len -= taken as usize;
+        deleted
+    }
+
+    /// Take every step which reads a zero in a run of zeros at the start of the string, up to `n` steps, returning how many were taken.
+    fn skip_zeros(&mut self, n: usize) -> usize {
+        let Some(Segment::Run { bit: false, len }) = self.segments.front_mut() else {
+            return 0;
+        };
+
+        let steps = (*len / 3).min(n);
+        *len -= 3 * steps;
+        if *len == 0 {
+            self.segments.pop_front();
+        }
+        self.len -= 3 * steps;
+
+        self.append_zeros(2 * steps);
+        steps
+    }
+
+    /// Iterate over the bits of the string in little-endian chunks of up to 64 bits, with the number of bits in each.
+    fn chunks(&self) -> impl Iterator<Item = (u64, u8)> + '_ {
+        self.segments
+            .iter()
+            .flat_map(|segment| -> Box<dyn Iterator<Item = _>> {
+                match *segment {
+                    Segment::Run { bit, len } => {
+                        let word = if bit { u64::MAX } else { 0 };
+                        let rest = len % 64;
+
+                        Box::new(
+                            iter::repeat_n((word, 64), len / 64)
+                                .chain((rest > 0).then(|| (word >> (64 - rest), rest as u8))),
+                        )
+                    }
+                    Segment::Packed { ref bits, .. } => Box::new(super::chunks(bits)),
+                }
+            })
+    }
+}
+
+impl PartialEq for HybridBitString {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && super::pack_words(self.chunks()).eq(super::pack_words(other.chunks()))
+    }
+}
+impl Eq for HybridBitString {}
+
+impl Hash for HybridBitString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for word in super::pack_words(self.chunks()) {
+            word.hash(state);
+        }
+    }
+}
+
+impl FromStr for HybridBitString {
+    type Err = ParseStateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        super::parse_bits(s).map(Self::from_list)
+    }
+}
+
+impl fmt::Display for HybridBitString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        super::fmt_bits(self.bits(), f)
+    }
+}
+
+impl PostSystem for HybridBitString {
+    fn new_decompressed(compressed: &[bool]) -> Self {
+        let mut this = Self::new();
+
+        for &b in compressed {
+            this.append(b as u64, 3);
+        }
+
+        this
+    }
+
+    fn length(&self) -> usize {
+        self.len
+    }
+
+    fn as_list(&self) -> VecDeque<bool> {
+        self.bits().collect()
+    }
+
+    fn bits(&self) -> impl Iterator<Item = bool> + '_ {
+        self.chunks()
+            .flat_map(|(word, count)| (0..count).map(move |i| (word >> i) & 1 == 1))
+    }
+
+    fn count_ones(&self) -> usize {
+        self.chunks()
+            .map(|(word, _)| word.count_ones() as usize)
+            .sum()
+    }
+
+    fn from_list(list: VecDeque<bool>) -> Self {
+        let mut this = Self::new();
+
+        let mut bits = list.into_iter();
+        loop {
+            let mut word = 0;
+            let mut count = 0;
+            for bit in bits.by_ref().take(64) {
+                word |= (bit as u64) << count;
+                count += 1;
+            }
+
+            if count == 0 {
+                break;
+            }
+            this.append(word, count);
+        }
+
+        this
+    }
+
+    fn evolve(&mut self) -> ControlFlow<()> {
+        if self.len < 3 {
+            return ControlFlow::Break(());
+        }
+
+        if self.delete(3) & 1 == 0 {
+            self.append(0, 2);
+        } else {
+            self.append(0b1011, 4);
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn evolve_multi(&mut self, n: usize) -> ControlFlow<usize> {
+        let timestep = self.preferred_timestep() as usize;
+
+        let mut i = 0;
+        while i < n {
+            let skipped = self.skip_zeros(n - i);
+            if skipped > 0 {
+                i += skipped;
+            } else if n - i >= timestep && self.len >= 3 * timestep {
+                if let ControlFlow::Break(taken) = self.evolve_preferred() {
+                    return ControlFlow::Break(i + taken as usize);
+                }
+                i += timestep;
+            } else {
+                if let ControlFlow::Break(()) = self.evolve() {
+                    return ControlFlow::Break(i);
+                }
+
+                i += 1;
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn preferred_timestep(&self) -> u8 {
+        BitString64::TABLE_JUMP
+    }
+
+    fn evolve_preferred(&mut self) -> ControlFlow<u8> {
+        debug_assert!(self.length() >= 3 * self.preferred_timestep() as usize);
+
+        let deleted = self.delete(3 * self.preferred_timestep());
+        let (bits, len) = bitstring::table_jump(deleted);
+        self.append(bits, len);
+
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::{BuildHasher, RandomState};
+
+    use crate::{seeds, tests_for_system};
+
+    use super::*;
+
+    tests_for_system!(HybridBitString);
+
+    #[test]
+    fn agrees_with_bit_string() {
+        // A one followed by a long run of zeros, which stays run-length encoded for a while.
+        let mut seed = vec![true; 5];
+        seed.extend([false; 1000]);
+        seed.push(true);
+
+        let systems = [
+            (
+                BitString64::new_decompressed(&seed),
+                HybridBitString::new_decompressed(&seed),
+            ),
+            (seeds::SEED_5854.system(), seeds::SEED_5854.system()),
+        ];
+        for (mut expected, mut hybrid) in systems {
+            for n in [0, 1, 10, 1000, 12_345, 100_000] {
+                assert_eq!(hybrid.evolve_multi(n), expected.evolve_multi(n));
+                assert_eq!(hybrid.length(), expected.length());
+                assert_eq!(hybrid.as_list(), expected.as_list());
+            }
+        }
+
+        // Halting partway through is reported as by other systems.
+        let mut system = HybridBitString::from_seed_u128(0b11111);
+        assert_eq!(system.evolve_multi(1000), ControlFlow::Break(409));
+    }
+
+    #[test]
+    fn stores_long_runs_by_length() {
+        let mut list = VecDeque::new();
+        list.extend([true; 10]);
+        list.extend([false; 1000]);
+        list.extend([true, false, true]);
+        list.extend([true; 300]);
+
+        let system = HybridBitString::from_list(list.clone());
+        assert_eq!(system.runs(), 2);
+        assert_eq!(system.as_list(), list);
+
+        let system = HybridBitString::new_decompressed(&[true; 100]);
+        assert_eq!(system.runs(), 0);
+    }
+
+    #[test]
+    fn compares_regardless_of_segments() {
+        let hasher = RandomState::new();
+
+        let mut seed = vec![true; 5];
+        seed.extend([false; 1000]);
+        seed.push(true);
+
+        let mut system = HybridBitString::new_decompressed(&seed);
+        let mut packed = BitString64::new_decompressed(&seed);
+        for _ in 0..100 {
+            let _ = system.evolve_multi(97);
+            let _ = packed.evolve_multi(97);
+
+            // Rebuilding the string splits it into segments at different points.
+            let rebuilt = HybridBitString::from_list(packed.as_list());
+            assert_eq!(rebuilt, system);
+            assert_eq!(hasher.hash_one(&rebuilt), hasher.hash_one(&system));
+            assert_eq!(rebuilt.to_string(), system.to_string());
+        }
+
+        let mut other = system.clone();
+        other.append(0, 1);
+        assert_ne!(other, system);
+    }
+}
@@ -1,5 +1,139 @@
-pub mod vec_deque_bools;
 pub mod bitstring;
+#[cfg(feature = "serde")]
+mod canonical;
+pub mod chunked;
+#[cfg(feature = "compress")]
+pub mod compressed;
+#[cfg(feature = "mmap")]
+pub mod disk;
+pub mod dynamic;
+pub mod fixed;
+pub mod hybrid;
+pub mod rope;
+pub mod shared;
+pub mod small;
+pub mod vec_deque_bools;
+
+use std::{collections::VecDeque, error::Error, fmt, mem};
 
+use crate::PostSystem;
+
+pub use bitstring::{
+    BitString, BitString128, BitString32, BitString64, GenericBitString, Lut, LutError, Word,
+};
+#[cfg(feature = "compress")]
+pub use compressed::CompressedBitString;
+#[cfg(feature = "mmap")]
+pub use disk::DiskBitString;
+pub use fixed::{CapacityError, FixedBitString};
+pub use hybrid::HybridBitString;
+pub use rope::RopeBitString;
+pub use shared::SharedBitString;
+pub use small::SmallBitString;
 pub use vec_deque_bools::VecDequeBools;
-pub use bitstring::BitString;
\ No newline at end of file
+
+/// An error returned when parsing a state from a string of `0`s and `1`s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseStateError {
+    /// The index of the offending character.
+    pub position: usize,
+    /// The offending character.
+    pub found: char,
+}
+
+impl fmt::Display for ParseStateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid symbol {:?} at position {}, expected 0 or 1",
+            self.found, self.position
+        )
+    }
+}
+
+impl Error for ParseStateError {}
+
+/// An error returned when parsing a compressed seed written as a hexadecimal number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseSeedError {
+    /// There were no digits.
+    Empty,
+    /// A character was not a hexadecimal digit.
+    InvalidDigit {
+        /// The index of the offending character.
+        position: usize,
+        /// The offending character.
+        found: char,
+    },
+}
+
+impl fmt::Display for ParseSeedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "seed has no digits"),
+            Self::InvalidDigit { position, found } => write!(
+                f,
+                "invalid digit {found:?} at position {position}, expected a hexadecimal digit"
+            ),
+        }
+    }
+}
+
+impl Error for ParseSeedError {}
+
+/// Parse a string of `0`s and `1`s into a list of bits.
+fn parse_bits(s: &str) -> Result<VecDeque<bool>, ParseStateError> {
+    s.chars()
+        .enumerate()
+        .map(|(position, c)| match c {
+            '0' => Ok(false),
+            '1' => Ok(true),
+            found => Err(ParseStateError { position, found }),
+        })
+        .collect()
+}
+
+/// Write a sequence of bits as `0`s and `1`s.
+fn fmt_bits(bits: impl IntoIterator<Item = bool>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for bit in bits {
+        f.write_str(if bit { "1" } else { "0" })?;
+    }
+
+    Ok(())
+}
+
+/// Iterate over the bits of `bit_string` in little-endian chunks of up to 64 bits, with the number of bits in each.
+fn chunks(bit_string: &BitString64) -> impl Iterator<Item = (u64, u8)> + '_ {
+    let len = bit_string.length();
+
+    bit_string
+        .normalized_words()
+        .enumerate()
+        .map(move |(i, word)| (word, (len - 64 * i).min(64) as u8))
+}
+
+/// Pack little-endian chunks of up to 64 bits, given with the number of bits in each, into whole words.
+///
+/// Bits beyond the end of the last chunk are zeroed, so the same bits yield the same words however they are chunked.
+fn pack_words(mut chunks: impl Iterator<Item = (u64, u8)>) -> impl Iterator<Item = u64> {
+    let mut word = 0u64;
+    let mut filled = 0;
+    std::iter::from_fn(move || {
+        for (bits, count) in chunks.by_ref() {
+            word |= bits << filled;
+
+            if filled + count as u32 >= u64::BITS {
+                let full = word;
+                word = bits.checked_shr(u64::BITS - filled).unwrap_or(0);
+                filled = filled + count as u32 - u64::BITS;
+                return Some(full);
+            }
+            filled += count as u32;
+        }
+
+        (filled > 0).then(|| {
+            filled = 0;
+            mem::take(&mut word)
+        })
+    })
+}
@@ -0,0 +1,263 @@
+//! A bit string stored in a balanced tree of shared chunks.
+
+use std::{iter, sync::Arc};
+
+use super::chunked::{Chunked, Chunks};
+
+/// A bit string whose middle is stored as a persistent, balanced tree of immutable, reference-counted chunks.
+///
+/// Like [`SharedBitString`](super::SharedBitString), clones share their chunks, so cloning takes constant time;
+/// see [`Chunked`].
+/// Appending or removing a chunk rebuilds the path to one end of the tree, so it always takes logarithmic time,
+/// whereas a [`SharedBitString`](super::SharedBitString) occasionally has to reverse its whole queue,
+/// and a [`BitString`](super::BitString) occasionally has to move or reallocate its whole buffer.
+pub type RopeBitString = Chunked<Rope>;
+
+/// A persistent AVL tree of reference-counted chunks, storing the middle of a [`RopeBitString`].
+#[derive(Debug, Clone, Default)]
+pub struct Rope(Option<Arc<Node>>);
+
+#[derive(Debug)]
+enum Node {
+    Leaf(Arc<[u64]>),
+    Branch {
+        left: Arc<Node>,
+        right: Arc<Node>,
+        /// The length of the longest path to a leaf.
+        height: u8,
+        /// The number of leaves.
+        len: usize,
+    },
+}
+
+impl Node {
+    fn height(&self) -> u8 {
+        match self {
+            Node::Leaf(_) => 0,
+            Node::Branch { height, .. } => *height,
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf(_) => 1,
+            Node::Branch { len, .. } => *len,
+        }
+    }
+
+    /// Get the children of a branch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is a leaf.
+    fn children(&self) -> (&Arc<Node>, &Arc<Node>) {
+        match self {
+            Node::Leaf(_) => unreachable!("leaves are never taller than their siblings"),
+            Node::Branch { left, right, .. } => (left, right),
+        }
+    }
+}
+
+/// Create a branch with the given children, whose heights must differ by at most one.
+fn branch(left: Arc<Node>, right: Arc<Node>) -> Arc<Node> {
+    debug_assert!(left.height().abs_diff(right.height()) <= 1);
+
+    Arc::new(Node::Branch {
+        height: left.height().max(right.height()) + 1,
+        len: left.len() + right.len(),
+        left,
+        right,
+    })
+}
+
+/// Concatenate two trees, keeping the result balanced.
+fn join(left: Arc<Node>, right: Arc<Node>) -> Arc<Node> {
+    if left.height() > right.height() + 1 {
+        let (ll, lr) = left.children();
+        rebalance(ll.clone(), join(lr.clone(), right))
+    } else if right.height() > left.height() + 1 {
+        let (rl, rr) = right.children();
+        rebalance(join(left, rl.clone()), rr.clone())
+    } else {
+        branch(left, right)
+    }
+}
+
+/// Create a balanced tree from two children, whose heights must differ by at most two.
+fn rebalance(left: Arc<Node>, right: Arc<Node>) -> Arc<Node> {
+    if left.height() > right.height() + 1 {
+        let (ll, lr) = left.children();
+        if ll.height() >= lr.height() {
+            branch(ll.clone(), branch(lr.clone(), right))
+        } else {
+            let (lrl, lrr) = lr.children();
+            branch(branch(ll.clone(), lrl.clone()), branch(lrr.clone(), right))
+        }
+    } else if right.height() > left.height() + 1 {
+        let (rl, rr) = right.children();
+        if rr.height() >= rl.height() {
+            branch(branch(left, rl.clone()), rr.clone())
+        } else {
+            let (rll, rlr) = rl.children();
+            branch(branch(left, rll.clone()), branch(rlr.clone(), rr.clone()))
+        }
+    } else {
+        branch(left, right)
+    }
+}
+
+/// Split the first leaf from a tree, returning its chunk and the rest of the tree, if any.
+fn split_first(node: &Arc<Node>) -> (Arc<[u64]>, Option<Arc<Node>>) {
+    match &**node {
+        Node::Leaf(chunk) => (chunk.clone(), None),
+        Node::Branch { left, right, .. } => {
+            let (chunk, rest) = split_first(left);
+            let rest = match rest {
+                Some(rest) => join(rest, right.clone()),
+                None => right.clone(),
+            };
+
+            (chunk, Some(rest))
+        }
+    }
+}
+
+impl Chunks for Rope {
+    // Chunks are kept tiny in tests so that short runs still build deep trees.
+    const WORDS: usize = if cfg!(test) { 2 } else { 64 };
+
+    type Chunk = Arc<[u64]>;
+
+    fn len(&self) -> usize {
+        self.0.as_ref().map_or(0, |root| root.len())
+    }
+
+    fn push_back(&mut self, chunk: Vec<u64>) {
+        let leaf = Arc::new(Node::Leaf(chunk.into()));
+
+        self.0 = Some(match self.0.take() {
+            Some(root) => join(root, leaf),
+            None => leaf,
+        });
+    }
+
+    fn pop_front(&mut self) -> Option<Arc<[u64]>> {
+        let (chunk, rest) = split_first(self.0.as_ref()?);
+        self.0 = rest;
+
+        Some(chunk)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = Arc<[u64]>> + '_ {
+        let mut stack: Vec<&Node> = self.0.as_deref().into_iter().collect();
+
+        iter::from_fn(move || loop {
+            match stack.pop()? {
+                Node::Leaf(chunk) => return Some(chunk.clone()),
+                Node::Branch { left, right, .. } => {
+                    stack.push(right);
+                    stack.push(left);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        hash::{BuildHasher, RandomState},
+        ops::ControlFlow,
+    };
+
+    use crate::{seeds, system::BitString64, tests_for_system, PostSystem};
+
+    use super::*;
+
+    tests_for_system!(RopeBitString);
+
+    /// Check that every branch of `node` is balanced and correctly annotated, returning its height.
+    fn check_balanced(node: &Node) -> u8 {
+        match node {
+            Node::Leaf(chunk) => {
+                assert_eq!(chunk.len(), Rope::WORDS);
+                0
+            }
+            Node::Branch {
+                left,
+                right,
+                height,
+                len,
+            } => {
+                let (hl, hr) = (check_balanced(left), check_balanced(right));
+                assert!(hl.abs_diff(hr) <= 1);
+                assert_eq!(*height, hl.max(hr) + 1);
+                assert_eq!(*len, left.len() + right.len());
+                *height
+            }
+        }
+    }
+
+    #[test]
+    fn stays_balanced() {
+        let mut rope = Rope::default();
+        let mut next = 0;
+        let mut first = 0;
+
+        for i in 0..5000 {
+            for _ in 0..(i % 3) {
+                rope.push_back(vec![next; Rope::WORDS]);
+                next += 1;
+            }
+            if i % 2 == 0 {
+                if let Some(chunk) = rope.pop_front() {
+                    assert_eq!(chunk[0], first);
+                    first += 1;
+                }
+            }
+
+            if let Some(root) = &rope.0 {
+                let height = check_balanced(root);
+                assert!(height as f64 <= 1.45 * (root.len() as f64 + 2.0).log2());
+            }
+            assert_eq!(rope.len() as u64, next - first);
+        }
+
+        assert!(rope.iter().map(|chunk| chunk[0]).eq(first..next));
+    }
+
+    #[test]
+    fn agrees_with_bit_string() {
+        let mut expected: BitString64 = seeds::SEED_5854.system();
+        let mut rope: RopeBitString = seeds::SEED_5854.system();
+
+        for n in [0, 1, 10, 1000, 12_345, 100_000] {
+            assert_eq!(rope.evolve_multi(n), expected.evolve_multi(n));
+            assert_eq!(rope.length(), expected.length());
+            assert_eq!(rope.as_list(), expected.as_list());
+        }
+        assert!(!rope.chunks.is_empty());
+
+        // Halting partway through is reported as by other systems.
+        let mut system = RopeBitString::from_seed_u128(0b11111);
+        assert_eq!(system.evolve_multi(1000), ControlFlow::Break(409));
+    }
+
+    #[test]
+    fn clones_independently() {
+        let hasher = RandomState::new();
+
+        let mut system: RopeBitString = seeds::SEED_5854.system();
+        let _ = system.evolve_multi(100_000);
+        let list = system.as_list();
+
+        let mut clone = system.clone();
+        let _ = clone.evolve_multi(1234);
+        assert_ne!(clone, system);
+        assert_eq!(system.as_list(), list);
+
+        let _ = system.evolve_multi(1234);
+        assert_eq!(clone, system);
+        assert_eq!(hasher.hash_one(&clone), hasher.hash_one(&system));
+    }
+}
@@ -0,0 +1,175 @@
+//! A bit string which shares its bits between clones, so that cloning takes constant time.
+
+use std::sync::Arc;
+
+use super::chunked::{Chunked, Chunks};
+
+/// A bit string whose middle is stored as a persistent queue of immutable, reference-counted chunks.
+///
+/// Only the ends of the string are owned by each clone, and neither is ever longer than a couple of chunks,
+/// so cloning takes the same time however long the string is; see [`Chunked`].
+/// This suits cycle detection, which keeps clones of the system around and compares them.
+pub type SharedBitString = Chunked<SharedChunks>;
+
+/// A persistent queue of reference-counted chunks, storing the middle of a [`SharedBitString`].
+///
+/// The chunks are kept in two persistent linked lists, one popped from and one pushed to, as in a banker's queue.
+#[derive(Debug, Clone, Default)]
+pub struct SharedChunks {
+    /// The first chunks, in order.
+    front: List,
+    /// The last chunks, in reverse order.
+    back: List,
+    /// The number of chunks in `front` and `back` together.
+    len: usize,
+}
+
+/// A persistent linked list of chunks.
+type List = Option<Arc<Node>>;
+
+#[derive(Debug)]
+struct Node {
+    chunk: Arc<[u64]>,
+    next: List,
+}
+
+impl Drop for Node {
+    // Dropping the nodes one at a time, rather than recursively, avoids overflowing the stack on long lists.
+    fn drop(&mut self) {
+        let mut next = self.next.take();
+        while let Some(node) = next {
+            match Arc::try_unwrap(node) {
+                Ok(mut node) => next = node.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+/// Iterate over the chunks of a list, in order.
+fn iter(list: &List) -> impl Iterator<Item = &Arc<[u64]>> {
+    std::iter::successors(list.as_deref(), |node| node.next.as_deref()).map(|node| &node.chunk)
+}
+
+impl Chunks for SharedChunks {
+    // Chunks are kept tiny in tests so that short runs still cross many chunk boundaries.
+    const WORDS: usize = if cfg!(test) { 2 } else { 64 };
+
+    type Chunk = Arc<[u64]>;
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn push_back(&mut self, chunk: Vec<u64>) {
+        self.back = Some(Arc::new(Node {
+            chunk: chunk.into(),
+            next: self.back.take(),
+        }));
+        self.len += 1;
+    }
+
+    fn pop_front(&mut self) -> Option<Arc<[u64]>> {
+        if self.front.is_none() {
+            for chunk in iter(&self.back) {
+                self.front = Some(Arc::new(Node {
+                    chunk: chunk.clone(),
+                    next: self.front.take(),
+                }));
+            }
+            self.back = None;
+        }
+
+        let node = self.front.take()?;
+        self.front = node.next.clone();
+        self.len -= 1;
+
+        Some(node.chunk.clone())
+    }
+
+    fn iter(&self) -> impl Iterator<Item = Arc<[u64]>> + '_ {
+        let back: Vec<_> = iter(&self.back).collect();
+
+        iter(&self.front).chain(back.into_iter().rev()).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        hash::{BuildHasher, RandomState},
+        ops::ControlFlow,
+    };
+
+    use crate::{cycle, seeds, system::BitString64, tests_for_system, PostSystem};
+
+    use super::*;
+
+    tests_for_system!(SharedBitString);
+
+    #[test]
+    fn agrees_with_bit_string() {
+        let mut expected: BitString64 = seeds::SEED_5854.system();
+        let mut shared: SharedBitString = seeds::SEED_5854.system();
+
+        for n in [0, 1, 10, 1000, 12_345, 100_000] {
+            assert_eq!(shared.evolve_multi(n), expected.evolve_multi(n));
+            assert_eq!(shared.length(), expected.length());
+            assert_eq!(shared.as_list(), expected.as_list());
+        }
+        assert!(!shared.chunks.is_empty());
+
+        // Halting partway through is reported as by other systems.
+        let mut system = SharedBitString::from_seed_u128(0b11111);
+        assert_eq!(system.evolve_multi(1000), ControlFlow::Break(409));
+    }
+
+    #[test]
+    fn clones_independently() {
+        let hasher = RandomState::new();
+
+        let mut system: SharedBitString = seeds::SEED_5854.system();
+        let _ = system.evolve_multi(100_000);
+        let list = system.as_list();
+
+        let mut clone = system.clone();
+        assert_eq!(clone, system);
+        assert_eq!(hasher.hash_one(&clone), hasher.hash_one(&system));
+
+        let _ = clone.evolve_multi(1234);
+        assert_ne!(clone, system);
+        assert_eq!(system.as_list(), list);
+
+        // The original catches up with its clone, although their chunks are now split differently.
+        let _ = system.evolve_multi(1234);
+        assert_eq!(clone, system);
+        assert_eq!(hasher.hash_one(&clone), hasher.hash_one(&system));
+        assert_eq!(clone.to_string(), system.to_string());
+
+        let rebuilt = SharedBitString::from_list(system.as_list());
+        assert_eq!(rebuilt, system);
+    }
+
+    #[test]
+    fn detects_cycles() {
+        let detection = cycle::detect(
+            seeds::SEED_5854.system::<SharedBitString>(),
+            cycle::Algorithm::Brent,
+            1_000_000,
+        );
+        assert!(matches!(detection, cycle::Detection::Cycle(info) if info.period == 6));
+    }
+
+    #[test]
+    fn drops_long_strings() {
+        let mut system = SharedBitString::from_list(VecDeque::new());
+        for _ in 0..1_000_000 {
+            system.append(u64::MAX, 64);
+        }
+        let clone = system.clone();
+
+        drop(system);
+        assert_eq!(clone.length(), 64_000_000);
+    }
+}
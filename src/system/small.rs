@@ -0,0 +1,304 @@
+//! A bit string which stores short states inline, without allocating.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::ControlFlow,
+    str::FromStr,
+};
+
+use crate::PostSystem;
+
+use super::{bitstring, BitString64, ParseStateError};
+
+/// The number of words stored inline.
+const INLINE_WORDS: usize = 3;
+
+/// The length of the longest string stored inline.
+const INLINE_BITS: usize = INLINE_WORDS * u64::BITS as usize;
+
+/// A bit string which is stored inline while it is at most 192 bits long, and in a [`BitString64`] once it grows longer.
+///
+/// Most seeds in a census halt or cycle while their states are still short,
+/// so allocating a buffer for each of them is pure overhead.
+/// Inline strings are shifted along as symbols are deleted, rather than tracking an offset into a buffer,
+/// which only takes a few instructions at this size.
+/// Strings which outgrow the inline storage stay on the heap, even if they shrink again.
+#[derive(Debug, Clone)]
+pub enum SmallBitString {
+    /// A short string, stored from the first bit of the first word, with the bits beyond its end zeroed.
+    Inline { words: [u64; INLINE_WORDS], len: u8 },
+    /// A string which grew too long to store inline.
+    Heap(BitString64),
+}
+
+impl SmallBitString {
+    /// Create a new empty bit string.
+    fn new() -> Self {
+        Self::Inline {
+            words: [0; INLINE_WORDS],
+            len: 0,
+        }
+    }
+
+    /// Append `count` bits to the end of the bit string, from the little-endian `bits`, moving it to the heap if it no longer fits inline.
+    ///
+    /// `count` must be at most 64, and `bits` must not have any bits set beyond the `count`-th bit.
+    fn append(&mut self, bits: u64, count: u8) {
+        match self {
+            Self::Inline { words, len } if *len as usize + count as usize <= INLINE_BITS => {
+                let (word, offset) = (*len as usize / 64, *len as u32 % 64);
+
+                words[word] |= bits << offset;
+                if offset != 0 && offset + count as u32 > 64 {
+                    words[word + 1] |= bits >> (64 - offset);
+                }
+                *len += count;
+            }
+            Self::Inline { .. } => {
+                let mut heap = BitString64::new();
+                for (word, count) in self.chunks() {
+                    heap.append_bits(word, count);
+                }
+                heap.append_bits(bits, count);
+
+                *self = Self::Heap(heap);
+            }
+            Self::Heap(heap) => heap.append_bits(bits, count),
+        }
+    }
+
+    /// Delete `count` bits from the start of the bit string, returning them.
+    ///
+    /// `count` must be strictly less than 64, and at most the length of the bit string.
+    fn delete(&mut self, count: u8) -> u64 {
+        match self {
+            Self::Inline { words, len } => {
+                debug_assert!(count < 64 && count <= *len);
+
+                let deleted = words[0] & !(u64::MAX << count);
+                if count > 0 {
+                    for i in 0..INLINE_WORDS - 1 {
+                        words[i] = (words[i] >> count) | (words[i + 1] << (64 - count));
+                    }
+                    words[INLINE_WORDS - 1] >>= count;
+                }
+                *len -= count;
+
+                deleted
+            }
+            Self::Heap(heap) => heap.delete_bits(count),
+        }
+    }
+
+    /// Iterate over the bits of the string in little-endian chunks of up to 64 bits, with the number of bits in each.
+    fn chunks(&self) -> Box<dyn Iterator<Item = (u64, u8)> + '_> {
+        match self {
+            Self::Inline { words, len } => Box::new(
+                (0..(*len as usize).div_ceil(64))
+                    .map(|i| (words[i], (*len as usize - 64 * i).min(64) as u8)),
+            ),
+            Self::Heap(heap) => Box::new(super::chunks(heap)),
+        }
+    }
+}
+
+impl PartialEq for SmallBitString {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Self::Inline { words, len },
+                Self::Inline {
+                    words: other_words,
+                    len: other_len,
+                },
+            ) => len == other_len && words == other_words,
+            (Self::Heap(heap), Self::Heap(other)) => heap == other,
+            _ => {
+                self.length() == other.length()
+                    && self
+                        .chunks()
+                        .map(|(word, _)| word)
+                        .eq(other.chunks().map(|(word, _)| word))
+            }
+        }
+    }
+}
+impl Eq for SmallBitString {}
+
+impl Hash for SmallBitString {
+    // Hash the same words as `BitString64` does, so that inline and heap strings with the same bits hash the same.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Self::Inline { .. } => {
+                self.length().hash(state);
+                for (word, _) in self.chunks() {
+                    word.hash(state);
+                }
+            }
+            Self::Heap(heap) => heap.hash(state),
+        }
+    }
+}
+
+impl FromStr for SmallBitString {
+    type Err = ParseStateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        super::parse_bits(s).map(Self::from_list)
+    }
+}
+
+impl fmt::Display for SmallBitString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        super::fmt_bits(self.bits(), f)
+    }
+}
+
+impl PostSystem for SmallBitString {
+    fn new_decompressed(compressed: &[bool]) -> Self {
+        let mut this = Self::new();
+
+        for &b in compressed {
+            this.append(b as u64, 3);
+        }
+
+        this
+    }
+
+    fn length(&self) -> usize {
+        match self {
+            Self::Inline { len, .. } => *len as usize,
+            Self::Heap(heap) => heap.length(),
+        }
+    }
+
+    fn as_list(&self) -> VecDeque<bool> {
+        self.bits().collect()
+    }
+
+    fn bits(&self) -> impl Iterator<Item = bool> + '_ {
+        self.chunks()
+            .flat_map(|(word, count)| (0..count).map(move |i| (word >> i) & 1 == 1))
+    }
+
+    fn count_ones(&self) -> usize {
+        self.chunks()
+            .map(|(word, _)| word.count_ones() as usize)
+            .sum()
+    }
+
+    fn get(&self, index: usize) -> Option<bool> {
+        match self {
+            Self::Inline { words, len } => {
+                (index < *len as usize).then(|| (words[index / 64] >> (index % 64)) & 1 == 1)
+            }
+            Self::Heap(heap) => heap.get(index),
+        }
+    }
+
+    fn from_list(list: VecDeque<bool>) -> Self {
+        let mut this = Self::new();
+
+        let mut bits = list.into_iter();
+        loop {
+            let mut word = 0;
+            let mut count = 0;
+            for bit in bits.by_ref().take(64) {
+                word |= (bit as u64) << count;
+                count += 1;
+            }
+
+            if count == 0 {
+                break;
+            }
+            this.append(word, count);
+        }
+
+        this
+    }
+
+    fn evolve(&mut self) -> ControlFlow<()> {
+        if self.length() < 3 {
+            return ControlFlow::Break(());
+        }
+
+        if self.delete(3) & 1 == 0 {
+            self.append(0, 2);
+        } else {
+            self.append(0b1011, 4);
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn preferred_timestep(&self) -> u8 {
+        BitString64::TABLE_JUMP
+    }
+
+    fn evolve_preferred(&mut self) -> ControlFlow<u8> {
+        debug_assert!(self.length() >= 3 * self.preferred_timestep() as usize);
+
+        let deleted = self.delete(3 * BitString64::TABLE_JUMP);
+        let (bits, len) = bitstring::table_jump(deleted);
+        self.append(bits, len);
+
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::{BuildHasher, RandomState};
+
+    use crate::{seeds, tests_for_system};
+
+    use super::*;
+
+    tests_for_system!(SmallBitString);
+
+    #[test]
+    fn agrees_with_bit_string() {
+        let mut expected: BitString64 = seeds::SEED_5854.system();
+        let mut small: SmallBitString = seeds::SEED_5854.system();
+
+        for n in [0, 1, 10, 100, 1000, 12_345, 100_000] {
+            assert_eq!(small.evolve_multi(n), expected.evolve_multi(n));
+            assert_eq!(small.as_list(), expected.as_list());
+        }
+        assert!(matches!(small, SmallBitString::Heap(_)));
+
+        // Halting partway through is reported as by other systems.
+        let mut system = SmallBitString::from_seed_u128(0b11111);
+        assert_eq!(system.evolve_multi(1000), ControlFlow::Break(409));
+        assert!(matches!(system, SmallBitString::Inline { .. }));
+    }
+
+    #[test]
+    fn compares_inline_and_heap_strings() {
+        let hasher = RandomState::new();
+
+        for len in [0, 1, 63, 64, 65, 150, 192] {
+            let list: VecDeque<_> = (0..len)
+                .map(|i: u32| i.count_ones().is_multiple_of(2))
+                .collect();
+
+            let inline = SmallBitString::from_list(list.clone());
+            assert!(matches!(inline, SmallBitString::Inline { .. }));
+
+            // Grow a string onto the heap, and then delete bits until it has the same contents.
+            let mut heap =
+                SmallBitString::from_list([false; 200].into_iter().chain(list).collect());
+            assert!(matches!(heap, SmallBitString::Heap(_)));
+            for _ in 0..40 {
+                heap.delete(5);
+            }
+
+            assert_eq!(inline, heap);
+            assert_eq!(heap, inline);
+            assert_eq!(hasher.hash_one(&inline), hasher.hash_one(&heap));
+            assert_eq!(inline.to_string(), heap.to_string());
+        }
+    }
+}
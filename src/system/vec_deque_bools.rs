@@ -1,9 +1,82 @@
-use std::{collections::VecDeque, ops::ControlFlow};
+use std::{collections::VecDeque, fmt, ops::ControlFlow, str::FromStr};
 
 use crate::PostSystem;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct VecDequeBools(VecDeque<bool>);
+use super::{GenericBitString, ParseStateError, Word};
+
+/// The number of steps taken at once by [`VecDequeBools::evolve_preferred`].
+const TIMESTEP: u8 = 8;
+
+/// The symbols appended by [`TIMESTEP`] steps and how many there are,
+/// indexed by the symbols read, with the first in the least significant bit.
+static TABLE: [([bool; 4 * TIMESTEP as usize], u8); 1 << TIMESTEP] = table();
+
+const fn table() -> [([bool; 4 * TIMESTEP as usize], u8); 1 << TIMESTEP] {
+    let mut table = [([false; 4 * TIMESTEP as usize], 0); 1 << TIMESTEP];
+
+    let mut index = 0;
+    while index < table.len() {
+        let (symbols, len) = &mut table[index];
+
+        let mut step = 0;
+        while step < TIMESTEP {
+            if index >> step & 1 == 1 {
+                symbols[*len as usize] = true;
+                symbols[*len as usize + 1] = true;
+                symbols[*len as usize + 3] = true;
+                *len += 4;
+            } else {
+                *len += 2;
+            }
+            step += 1;
+        }
+
+        index += 1;
+    }
+
+    table
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VecDequeBools(pub(super) VecDeque<bool>);
+
+impl FromStr for VecDequeBools {
+    type Err = ParseStateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        super::parse_bits(s).map(Self)
+    }
+}
+
+impl fmt::Display for VecDequeBools {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        super::fmt_bits(self.0.iter().copied(), f)
+    }
+}
+
+impl Extend<bool> for VecDequeBools {
+    fn extend<I: IntoIterator<Item = bool>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl FromIterator<bool> for VecDequeBools {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<W: Word> From<&GenericBitString<W>> for VecDequeBools {
+    fn from(bit_string: &GenericBitString<W>) -> Self {
+        bit_string.bits().collect()
+    }
+}
+
+impl<W: Word> From<&VecDequeBools> for GenericBitString<W> {
+    fn from(system: &VecDequeBools) -> Self {
+        system.0.iter().copied().collect()
+    }
+}
 
 impl PostSystem for VecDequeBools {
     fn new_decompressed(compressed: &[bool]) -> Self {
@@ -14,11 +87,31 @@ impl PostSystem for VecDequeBools {
         self.0.len()
     }
 
+    fn memory_usage(&self) -> usize {
+        self.0.capacity()
+    }
+
     fn as_list(&self) -> VecDeque<bool> {
         self.0.clone()
     }
 
+    fn bits(&self) -> impl Iterator<Item = bool> + '_ {
+        self.0.iter().copied()
+    }
+
+    fn get(&self, index: usize) -> Option<bool> {
+        self.0.get(index).copied()
+    }
+
+    fn from_list(list: VecDeque<bool>) -> Self {
+        Self(list)
+    }
+
     fn evolve(&mut self) -> ControlFlow<()> {
+        if self.length() < 3 {
+            return ControlFlow::Break(());
+        }
+
         let first = pop_front_or_break(&mut self.0)?;
         pop_front_or_break(&mut self.0)?;
         pop_front_or_break(&mut self.0)?;
@@ -30,6 +123,34 @@ impl PostSystem for VecDequeBools {
 
         ControlFlow::Continue(())
     }
+
+    fn preferred_timestep(&self) -> u8 {
+        TIMESTEP
+    }
+
+    fn evolve_preferred(&mut self) -> ControlFlow<u8> {
+        // Too short to jump, since the jump would read appended symbols, so step until it halts.
+        if self.length() < 3 * TIMESTEP as usize {
+            for i in 0..TIMESTEP {
+                if let ControlFlow::Break(()) = self.evolve() {
+                    return ControlFlow::Break(i);
+                }
+            }
+            return ControlFlow::Continue(());
+        }
+
+        let index = self
+            .0
+            .drain(..3 * TIMESTEP as usize)
+            .step_by(3)
+            .enumerate()
+            .fold(0, |index, (i, symbol)| index | (symbol as usize) << i);
+
+        let (symbols, len) = &TABLE[index];
+        self.0.extend(&symbols[..*len as usize]);
+
+        ControlFlow::Continue(())
+    }
 }
 
 fn pop_front_or_break<T>(deque: &mut VecDeque<T>) -> ControlFlow<(), T> {
@@ -41,5 +162,57 @@ fn pop_front_or_break<T>(deque: &mut VecDeque<T>) -> ControlFlow<(), T> {
 
 #[cfg(test)]
 mod tests {
-    crate::tests_for_system!(super::VecDequeBools);
+    use crate::{seeds, system::BitString32};
+
+    use super::*;
+
+    crate::tests_for_system!(VecDequeBools);
+
+    #[test]
+    fn jumps_like_single_steps() {
+        let mut jumped: VecDequeBools = seeds::SEED_5854.system();
+        let mut stepped = jumped.clone();
+
+        for _ in 0..1000 {
+            assert_eq!(jumped.evolve_preferred(), ControlFlow::Continue(()));
+            for _ in 0..TIMESTEP {
+                assert_eq!(stepped.evolve(), ControlFlow::Continue(()));
+            }
+            assert_eq!(jumped, stepped);
+        }
+    }
+
+    #[test]
+    fn halts_within_preferred_steps() {
+        // `00000` halts after three steps, which may fall within a single preferred step.
+        let mut system: VecDequeBools = "00000".parse().unwrap();
+        let mut steps = 0;
+        let taken = loop {
+            match system.evolve_preferred() {
+                ControlFlow::Continue(()) => steps += system.preferred_timestep() as usize,
+                ControlFlow::Break(taken) => break taken,
+            }
+        };
+        assert_eq!(steps + taken as usize, 3);
+        assert_eq!(system.length(), 2);
+    }
+
+    #[test]
+    fn converts_to_and_from_bit_strings() {
+        let mut simple: VecDequeBools = seeds::SEED_5854.system();
+        let mut fast = BitString32::from(&simple);
+
+        for _ in 0..100 {
+            assert_eq!(simple.evolve_multi(1000), fast.evolve_multi(1000));
+            assert_eq!(VecDequeBools::from(&fast), simple);
+            assert_eq!(BitString32::from(&simple), fast);
+        }
+    }
+
+    #[test]
+    fn extends_and_collects() {
+        let mut system: VecDequeBools = [true, false, false].into_iter().collect();
+        system.extend([true, true]);
+        assert_eq!(system.to_string(), "10011");
+    }
 }
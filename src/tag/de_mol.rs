@@ -0,0 +1,120 @@
+//! The tag system studied by De Mol, which computes the Collatz map.
+//!
+//! Its rules delete two symbols per step and have three symbols, `a -> bc`, `b -> a`, and `c -> aaa`.
+//! Starting from `a^n`, the system reaches `a^(n / 2)` if `n` is even, or `a^((3n + 1) / 2)` if `n` is odd,
+//! without passing through any other state consisting only of `a`s.
+//! So the lengths of those states follow the Collatz map, until the system halts at `a`.
+//!
+//! L. De Mol, "Tag systems and Collatz-like functions", *Theoretical Computer Science* 390 (2008).
+
+use std::{ops::ControlFlow, sync::Arc};
+
+use crate::TagSystem;
+
+use super::{PackedRules, PackedString, SymbolString, TagRules};
+
+/// The symbol `a`, whose runs encode numbers.
+pub const A: u8 = 0;
+/// The symbol `b`.
+pub const B: u8 = 1;
+/// The symbol `c`.
+pub const C: u8 = 2;
+
+/// De Mol's rules: delete two symbols, with productions `a -> bc`, `b -> a`, and `c -> aaa`.
+pub fn rules() -> TagRules {
+    TagRules::new(2, vec![vec![B, C], vec![A], vec![A, A, A]]).unwrap()
+}
+
+/// The Collatz map, halving even numbers and taking odd `n` to `(3n + 1) / 2`.
+pub fn collatz(n: u64) -> u64 {
+    if n.is_multiple_of(2) {
+        n / 2
+    } else {
+        (3 * n).div_ceil(2)
+    }
+}
+
+/// Encode `n` as `a^n`, following De Mol's rules.
+pub fn encode(n: usize) -> SymbolString {
+    SymbolString::new(Arc::new(rules()), [A].repeat(n)).unwrap()
+}
+
+/// Encode `n` as `a^n` in a [`PackedString`], following De Mol's rules.
+pub fn encode_packed(n: usize) -> PackedString {
+    let rules = PackedRules::new(rules()).unwrap();
+    PackedString::new(Arc::new(rules), [A].repeat(n)).unwrap()
+}
+
+/// Decode the number encoded by a state, if it consists only of `a`s.
+pub fn decode(symbols: impl IntoIterator<Item = u8>) -> Option<usize> {
+    symbols
+        .into_iter()
+        .try_fold(0, |n, symbol| (symbol == A).then_some(n + 1))
+}
+
+/// Iterate over the numbers encoded by the system starting from `a^n`, with the step at which each is reached.
+///
+/// The iteration ends once the system halts, which happens when it reaches `a`,
+/// so for any `n` reaching one under the Collatz map, the last item is `1`.
+pub fn trajectory(n: usize) -> impl Iterator<Item = (u64, usize)> {
+    let mut system = encode(n);
+    let mut step = 0;
+    let mut first = true;
+
+    std::iter::from_fn(move || {
+        if first {
+            first = false;
+            return Some((0, n));
+        }
+
+        loop {
+            if let ControlFlow::Break(()) = system.evolve() {
+                return None;
+            }
+            step += 1;
+
+            if system.get(0) == Some(A) {
+                if let Some(n) = decode(system.symbols()) {
+                    return Some((step, n));
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_the_collatz_map() {
+        for n in 1..50 {
+            let numbers: Vec<usize> = trajectory(n).map(|(_, n)| n).collect();
+
+            let mut expected = vec![n];
+            while *expected.last().unwrap() > 1 {
+                expected.push(collatz(*expected.last().unwrap() as u64) as usize);
+            }
+            assert_eq!(numbers, expected, "trajectory of {n}");
+        }
+
+        let (steps, last) = trajectory(27).last().unwrap();
+        assert_eq!(last, 1);
+        assert_eq!(
+            encode(27).evolve_multi(1_000_000),
+            ControlFlow::Break(steps as usize)
+        );
+        assert_eq!(
+            encode_packed(27).evolve_multi(1_000_000),
+            ControlFlow::Break(steps as usize)
+        );
+    }
+
+    #[test]
+    fn decodes() {
+        assert_eq!(decode(encode(7).symbols()), Some(7));
+        assert_eq!(decode(encode_packed(7).symbols()), Some(7));
+        assert_eq!(decode([A, B, A]), None);
+        assert_eq!(decode([]), Some(0));
+    }
+}
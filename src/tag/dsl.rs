@@ -0,0 +1,444 @@
+//! The textual form of [`TagRules`], for writing rule sets inline.
+
+use std::{error::Error, fmt, str::FromStr};
+
+use super::{RulesError, TagRules};
+
+/// Parses the rules in the form described in the [module documentation](super#writing-rules).
+impl FromStr for TagRules {
+    type Err = ParseRulesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Parser { s, pos: 0 }.rules()
+    }
+}
+
+/// Writes the rules in the form [`TagRules::from_str`] parses.
+impl fmt::Display for TagRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v={}", self.deletion)?;
+
+        for (symbol, production) in self.productions().enumerate() {
+            f.write_str("; ")?;
+            write_symbol(f, symbol as u8)?;
+            f.write_str(" ->")?;
+            if !production.is_empty() {
+                f.write_str(" ")?;
+            }
+            for &symbol in production {
+                write_symbol(f, symbol)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn write_symbol(f: &mut fmt::Formatter<'_>, symbol: u8) -> fmt::Result {
+    match char::from_digit(symbol as u32, 36) {
+        Some(digit) => write!(f, "{digit}"),
+        None => write!(f, "<{symbol}>"),
+    }
+}
+
+/// An error returned when parsing [`TagRules`] from their textual form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRulesError {
+    /// The line of the error, counting from one.
+    pub line: usize,
+    /// The column of the error in characters, counting from one.
+    pub column: usize,
+    /// What went wrong.
+    pub kind: ParseRulesErrorKind,
+}
+
+/// The kinds of [`ParseRulesError`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseRulesErrorKind {
+    /// Something other than `expected` was found, or `None` for the end of the input.
+    Unexpected {
+        expected: &'static str,
+        found: Option<char>,
+    },
+    /// A symbol in angle brackets is too large to fit in a byte.
+    SymbolTooLarge,
+    /// The deletion number is too large to fit in a `usize`.
+    DeletionTooLarge,
+    /// The deletion number is given more than once.
+    DuplicateDeletion,
+    /// The deletion number is never given.
+    MissingDeletion,
+    /// The symbol is given more than one production.
+    DuplicateProduction(u8),
+    /// The symbol is in the alphabet but has no production.
+    MissingProduction(u8),
+    /// The rules parsed, but are invalid.
+    Rules(RulesError),
+}
+
+impl fmt::Display for ParseRulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.kind, self.line, self.column
+        )
+    }
+}
+
+impl Error for ParseRulesError {}
+
+impl fmt::Display for ParseRulesErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unexpected {
+                expected,
+                found: Some(c),
+            } => write!(f, "expected {expected}, found {c:?}"),
+            Self::Unexpected {
+                expected,
+                found: None,
+            } => write!(f, "expected {expected}, found the end of the rules"),
+            Self::SymbolTooLarge => {
+                write!(f, "symbols must be less than {}", TagRules::MAX_ALPHABET)
+            }
+            Self::DeletionTooLarge => write!(f, "the deletion number is too large"),
+            Self::DuplicateDeletion => write!(f, "the deletion number is given twice"),
+            Self::MissingDeletion => {
+                write!(f, "the deletion number is never given, e.g. as `v=2`")
+            }
+            Self::DuplicateProduction(symbol) => {
+                write!(f, "symbol {symbol} is given a second production")
+            }
+            Self::MissingProduction(symbol) => write!(f, "symbol {symbol} has no production"),
+            Self::Rules(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+/// A recursive-descent parser over the characters of the rules.
+struct Parser<'a> {
+    s: &'a str,
+    /// The byte offset of the next character.
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn rules(mut self) -> Result<TagRules, ParseRulesError> {
+        let mut deletion = None;
+        let mut productions: Vec<Option<Vec<u8>>> = Vec::new();
+        // Where each symbol was first mentioned, to point at when it turns out to lack a production.
+        let mut mentions: Vec<Option<usize>> = Vec::new();
+
+        loop {
+            self.skip_blanks();
+            let start = self.pos;
+
+            match self.peek() {
+                None => break,
+                Some(';' | '\n') => {}
+                Some(_) if self.at_deletion() => {
+                    let value = self.deletion()?;
+                    if deletion.replace(value).is_some() {
+                        return Err(self.error(start, ParseRulesErrorKind::DuplicateDeletion));
+                    }
+                }
+                Some(_) => {
+                    let (read, production) = self.production(|symbol, pos| {
+                        mention(&mut mentions, symbol, pos);
+                    })?;
+
+                    if productions.len() <= read as usize {
+                        productions.resize(read as usize + 1, None);
+                    }
+                    if productions[read as usize].replace(production).is_some() {
+                        return Err(
+                            self.error(start, ParseRulesErrorKind::DuplicateProduction(read))
+                        );
+                    }
+                }
+            }
+
+            self.skip_blanks();
+            match self.bump() {
+                None => break,
+                Some(';' | '\n') => {}
+                found => {
+                    return Err(self.error(
+                        self.pos - found.map_or(0, char::len_utf8),
+                        ParseRulesErrorKind::Unexpected {
+                            expected: "a symbol, `;`, or a new line",
+                            found,
+                        },
+                    ))
+                }
+            }
+        }
+
+        let Some(deletion) = deletion else {
+            return Err(self.error(self.s.len(), ParseRulesErrorKind::MissingDeletion));
+        };
+
+        productions.resize(productions.len().max(mentions.len()), None);
+        let productions = productions
+            .into_iter()
+            .enumerate()
+            .map(|(symbol, production)| {
+                production.ok_or_else(|| {
+                    let pos = mentions
+                        .get(symbol)
+                        .copied()
+                        .flatten()
+                        .unwrap_or(self.s.len());
+                    self.error(pos, ParseRulesErrorKind::MissingProduction(symbol as u8))
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        TagRules::new(deletion, productions)
+            .map_err(|e| self.error(0, ParseRulesErrorKind::Rules(e)))
+    }
+
+    /// Whether the next statement gives the deletion number, since `v` alone is also a symbol.
+    fn at_deletion(&self) -> bool {
+        let rest = &self.s[self.pos..];
+        rest.starts_with('v') && rest[1..].trim_start_matches([' ', '\t']).starts_with('=')
+    }
+
+    /// Parse `v=N`.
+    fn deletion(&mut self) -> Result<usize, ParseRulesError> {
+        self.bump();
+        self.skip_blanks();
+        self.bump();
+        self.skip_blanks();
+
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.bump();
+        }
+        if start == self.pos {
+            return Err(self.unexpected("a deletion number"));
+        }
+
+        self.s[start..self.pos]
+            .parse()
+            .map_err(|_| self.error(start, ParseRulesErrorKind::DeletionTooLarge))
+    }
+
+    /// Parse `a -> xyz`, calling `mention` with each symbol and its position.
+    fn production(
+        &mut self,
+        mut mention: impl FnMut(u8, usize),
+    ) -> Result<(u8, Vec<u8>), ParseRulesError> {
+        let start = self.pos;
+        let read = self.symbol()?;
+        mention(read, start);
+
+        self.skip_blanks();
+        if !self.s[self.pos..].starts_with("->") {
+            return Err(self.unexpected("`->`"));
+        }
+        self.pos += 2;
+
+        let mut production = Vec::new();
+        loop {
+            self.skip_blanks();
+            match self.peek() {
+                None | Some(';' | '\n') => break,
+                Some(_) => {
+                    let start = self.pos;
+                    let symbol = self.symbol()?;
+                    mention(symbol, start);
+                    production.push(symbol);
+                }
+            }
+        }
+
+        Ok((read, production))
+    }
+
+    /// Parse a single symbol, either a base-36 digit or a decimal number in angle brackets.
+    fn symbol(&mut self) -> Result<u8, ParseRulesError> {
+        let start = self.pos;
+        match self.peek() {
+            Some('<') => {
+                self.bump();
+                let digits = self.pos;
+                while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    self.bump();
+                }
+                if digits == self.pos {
+                    return Err(self.unexpected("a decimal symbol"));
+                }
+                let symbol = self.s[digits..self.pos]
+                    .parse()
+                    .map_err(|_| self.error(start, ParseRulesErrorKind::SymbolTooLarge))?;
+
+                if self.peek() != Some('>') {
+                    return Err(self.unexpected("`>`"));
+                }
+                self.bump();
+
+                Ok(symbol)
+            }
+            Some(c) if c.is_ascii_digit() || c.is_ascii_lowercase() => {
+                self.bump();
+                Ok(c.to_digit(36).unwrap() as u8)
+            }
+            _ => Err(self.unexpected("a symbol")),
+        }
+    }
+
+    /// Skip spaces, tabs, and comments, but not new lines, which end statements.
+    fn skip_blanks(&mut self) {
+        loop {
+            match self.peek() {
+                Some(' ' | '\t' | '\r') => {
+                    self.bump();
+                }
+                Some('#') => {
+                    while self.peek().is_some_and(|c| c != '\n') {
+                        self.bump();
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn unexpected(&self, expected: &'static str) -> ParseRulesError {
+        self.error(
+            self.pos,
+            ParseRulesErrorKind::Unexpected {
+                expected,
+                found: self.peek(),
+            },
+        )
+    }
+
+    /// Build an error at the byte offset `pos`, converting it to a line and column.
+    fn error(&self, pos: usize, kind: ParseRulesErrorKind) -> ParseRulesError {
+        let before = &self.s[..pos];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+        ParseRulesError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            kind,
+        }
+    }
+}
+
+/// Record that `symbol` was mentioned at `pos`, unless it was mentioned before.
+fn mention(mentions: &mut Vec<Option<usize>>, symbol: u8, pos: usize) {
+    if mentions.len() <= symbol as usize {
+        mentions.resize(symbol as usize + 1, None);
+    }
+    mentions[symbol as usize].get_or_insert(pos);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collatz() -> TagRules {
+        TagRules::new(2, vec![vec![1, 2], vec![0], vec![0, 0, 0]]).unwrap()
+    }
+
+    fn error(s: &str) -> (usize, usize, ParseRulesErrorKind) {
+        let e = s.parse::<TagRules>().unwrap_err();
+        (e.line, e.column, e.kind)
+    }
+
+    #[test]
+    fn parses() {
+        assert_eq!("v=3; 0 -> 00; 1 -> 1101".parse(), Ok(TagRules::post()));
+        assert_eq!("1->1101;0->00;v=3;".parse(), Ok(TagRules::post()));
+        assert_eq!(
+            "# De Mol's rules\nv = 2\n0 -> 1 2  # a -> bc\n1 -> 0\n2 -> 000\n".parse(),
+            Ok(collatz())
+        );
+
+        // `v` is a symbol unless it is followed by `=`, and any symbol can be written in brackets.
+        assert_eq!("v=3; <0> -> 00; <1> -> 1101".parse(), Ok(TagRules::post()));
+        assert_eq!(
+            error("v=1; 0 -> 0; 1 -> v"),
+            (1, 20, ParseRulesErrorKind::MissingProduction(2))
+        );
+    }
+
+    #[test]
+    fn writes_parseable_rules() {
+        assert_eq!(TagRules::post().to_string(), "v=3; 0 -> 00; 1 -> 1101");
+
+        let large = TagRules::new(1, vec![vec![39, 0]; 40]).unwrap();
+        assert!(large.to_string().ends_with("; <39> -> <39>0"));
+
+        for rules in [TagRules::post(), collatz(), large] {
+            assert_eq!(rules.to_string().parse(), Ok(rules));
+        }
+    }
+
+    #[test]
+    fn reports_errors() {
+        use ParseRulesErrorKind::*;
+
+        assert_eq!(
+            error("v=3; 0 => 00"),
+            (
+                1,
+                8,
+                Unexpected {
+                    expected: "`->`",
+                    found: Some('=')
+                }
+            )
+        );
+        assert_eq!(
+            error("v=3\n0 -> 00\n1 -> 11!1"),
+            (
+                3,
+                8,
+                Unexpected {
+                    expected: "a symbol",
+                    found: Some('!')
+                }
+            )
+        );
+        assert_eq!(
+            error("v="),
+            (
+                1,
+                3,
+                Unexpected {
+                    expected: "a deletion number",
+                    found: None
+                }
+            )
+        );
+        assert_eq!(error("0 -> 00"), (1, 8, MissingDeletion));
+        assert_eq!(error("v=2; v=3"), (1, 6, DuplicateDeletion));
+        assert_eq!(
+            error("v=2; 0 -> 0; 0 -> 00"),
+            (1, 14, DuplicateProduction(0))
+        );
+        assert_eq!(error("v=2; 0 -> 02"), (1, 13, MissingProduction(1)));
+        assert_eq!(error("v=2; 0 -> <256>"), (1, 11, SymbolTooLarge));
+        assert_eq!(error("v=0; 0 -> 0"), (1, 1, Rules(RulesError::NoDeletion)));
+
+        assert_eq!(
+            "v=3; 0 -> 0x".parse::<TagRules>().unwrap_err().to_string(),
+            "symbol 1 has no production at line 1, column 13"
+        );
+    }
+}
@@ -0,0 +1,206 @@
+//! The serialized form of [`TagRules`], for loading rule sets from files.
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{RulesError, TagRules};
+
+#[derive(Serialize, Deserialize)]
+struct Definition {
+    deletion: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    alphabet: Option<usize>,
+    productions: Vec<Production>,
+}
+
+/// A production, written either way.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Production {
+    Digits(String),
+    Symbols(Vec<u8>),
+}
+
+impl Production {
+    fn into_symbols<E: de::Error>(self) -> Result<Vec<u8>, E> {
+        match self {
+            Self::Digits(digits) => digits
+                .chars()
+                .map(|c| {
+                    c.to_digit(36).map(|digit| digit as u8).ok_or_else(|| {
+                        E::invalid_value(de::Unexpected::Char(c), &"a base-36 digit")
+                    })
+                })
+                .collect(),
+            Self::Symbols(symbols) => Ok(symbols),
+        }
+    }
+}
+
+impl Serialize for TagRules {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let productions = self
+            .productions()
+            .map(|production| {
+                if self.alphabet() <= 36 {
+                    Production::Digits(
+                        production
+                            .iter()
+                            .map(|&symbol| char::from_digit(symbol as u32, 36).unwrap())
+                            .collect(),
+                    )
+                } else {
+                    Production::Symbols(production.to_vec())
+                }
+            })
+            .collect();
+
+        Definition {
+            deletion: self.deletion,
+            alphabet: Some(self.alphabet()),
+            productions,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for TagRules {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let definition = Definition::deserialize(deserializer)?;
+
+        let productions: Vec<Vec<u8>> = definition
+            .productions
+            .into_iter()
+            .map(Production::into_symbols)
+            .collect::<Result<_, _>>()?;
+
+        if let Some(alphabet) = definition.alphabet {
+            if alphabet != productions.len() {
+                return Err(de::Error::custom(RulesError::AlphabetMismatch {
+                    alphabet,
+                    productions: productions.len(),
+                }));
+            }
+        }
+
+        TagRules::new(definition.deletion, productions).map_err(de::Error::custom)
+    }
+}
+
+impl TagRules {
+    /// Load rules from TOML.
+    ///
+    /// Rules are written as a deletion number, an optional alphabet size, and a list of productions:
+    ///
+    /// ```toml
+    /// deletion = 2
+    /// alphabet = 3
+    /// productions = ["12", "0", "000"]
+    /// ```
+    ///
+    /// Productions are strings of digits, continuing with letters for alphabets of up to 36 symbols,
+    /// or arrays of numbers for any alphabet.
+    /// If the alphabet is given, it must match the number of productions, which catches a production being forgotten.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(s: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(s)
+    }
+
+    /// Write the rules as TOML, in a form [`Self::from_toml`] reads back.
+    #[cfg(feature = "toml")]
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("rules always serialize")
+    }
+
+    /// Load rules from JSON, with the same fields as [`Self::from_toml`].
+    #[cfg(feature = "json")]
+    pub fn from_json(s: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+
+    /// Write the rules as JSON, in a form [`Self::from_json`] reads back.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("rules always serialize")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collatz() -> TagRules {
+        TagRules::new(2, vec![vec![1, 2], vec![0], vec![0, 0, 0]]).unwrap()
+    }
+
+    #[test]
+    fn round_trips() {
+        for rules in [
+            TagRules::post(),
+            collatz(),
+            TagRules::new(1, vec![vec![39, 0]; 40]).unwrap(),
+        ] {
+            let json = serde_json::to_string(&rules).unwrap();
+            assert_eq!(serde_json::from_str::<TagRules>(&json).unwrap(), rules);
+        }
+
+        assert_eq!(
+            serde_json::to_string(&TagRules::post()).unwrap(),
+            r#"{"deletion":3,"alphabet":2,"productions":["00","1101"]}"#
+        );
+    }
+
+    #[test]
+    fn reads_either_production_form() {
+        let rules: TagRules =
+            serde_json::from_str(r#"{"deletion":2,"productions":["12",[0],[0,0,0]]}"#).unwrap();
+        assert_eq!(rules, collatz());
+    }
+
+    #[test]
+    fn rejects_invalid_definitions() {
+        for (json, message) in [
+            (
+                r#"{"deletion":2,"alphabet":4,"productions":["12","0","000"]}"#,
+                "4 symbols",
+            ),
+            (
+                r#"{"deletion":2,"productions":["1x","0","000"]}"#,
+                "symbol 33",
+            ),
+            (
+                r#"{"deletion":2,"productions":["1!","0","000"]}"#,
+                "base-36",
+            ),
+            (r#"{"deletion":0,"productions":["0"]}"#, "at least one"),
+        ] {
+            let error = serde_json::from_str::<TagRules>(json).unwrap_err();
+            assert!(
+                error.to_string().contains(message),
+                "{json}: {error} should mention {message:?}"
+            );
+        }
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn loads_toml() {
+        let rules = TagRules::from_toml(
+            r#"
+            deletion = 2
+            alphabet = 3
+            productions = ["12", "0", "000"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(rules, collatz());
+        assert_eq!(TagRules::from_toml(&rules.to_toml()).unwrap(), rules);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn loads_json() {
+        let rules = TagRules::post();
+        assert_eq!(TagRules::from_json(&rules.to_json()).unwrap(), rules);
+        assert!(TagRules::from_json("{}").is_err());
+    }
+}
@@ -0,0 +1,106 @@
+//! Checks for rules whose behaviour is trivial, which are usually mistakes.
+
+use std::fmt;
+
+use super::TagRules;
+
+/// A property of [`TagRules`] worth pointing out, although the rules are valid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lint {
+    /// Every production is shorter than the deletion number, so every system shrinks each step until it halts.
+    AlwaysHalts,
+    /// Every production is longer than the deletion number,
+    /// so every system long enough to take a step grows forever, never halting or cycling.
+    AlwaysExpands,
+    /// Every production is exactly as long as the deletion number,
+    /// so every system long enough to take a step keeps its length, and must eventually cycle.
+    ConstantLength,
+    /// No production is shorter than the deletion number, but some are equal,
+    /// so no system long enough to take a step ever halts.
+    NeverHalts,
+    /// The symbol appears in no production, so it can only occur in initial states.
+    Unproduced(u8),
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlwaysHalts => write!(
+                f,
+                "every production is shorter than the deletion number, so every system halts"
+            ),
+            Self::AlwaysExpands => write!(
+                f,
+                "every production is longer than the deletion number, so systems grow forever"
+            ),
+            Self::ConstantLength => write!(
+                f,
+                "every production is as long as the deletion number, so systems never change length"
+            ),
+            Self::NeverHalts => write!(
+                f,
+                "no production is shorter than the deletion number, so systems never halt"
+            ),
+            Self::Unproduced(symbol) => write!(
+                f,
+                "symbol {symbol} appears in no production, so it only occurs in initial states"
+            ),
+        }
+    }
+}
+
+impl TagRules {
+    /// Check the rules for trivial behaviour.
+    pub fn lints(&self) -> Vec<Lint> {
+        let mut lints = Vec::new();
+
+        let shortest = self.productions().map(<[u8]>::len).min().unwrap();
+        let longest = self.productions().map(<[u8]>::len).max().unwrap();
+        if longest < self.deletion {
+            lints.push(Lint::AlwaysHalts);
+        } else if shortest > self.deletion {
+            lints.push(Lint::AlwaysExpands);
+        } else if shortest == self.deletion && longest == self.deletion {
+            lints.push(Lint::ConstantLength);
+        } else if shortest == self.deletion {
+            lints.push(Lint::NeverHalts);
+        }
+
+        let mut produced = vec![false; self.alphabet()];
+        for &symbol in self.productions().flatten() {
+            produced[symbol as usize] = true;
+        }
+        lints.extend(
+            (0..self.alphabet())
+                .filter(|&symbol| !produced[symbol])
+                .map(|symbol| Lint::Unproduced(symbol as u8)),
+        );
+
+        lints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lints(rules: &str) -> Vec<Lint> {
+        rules.parse::<TagRules>().unwrap().lints()
+    }
+
+    #[test]
+    fn finds_trivial_rules() {
+        assert_eq!(lints("v=3; 0 -> 00; 1 -> 1101"), []);
+        assert_eq!(lints("v=2; 0 -> 12; 1 -> 0; 2 -> 000"), []);
+
+        assert_eq!(lints("v=3; 0 -> 0; 1 -> 10"), [Lint::AlwaysHalts]);
+        assert_eq!(lints("v=1; 0 -> 01; 1 -> 10"), [Lint::AlwaysExpands]);
+        assert_eq!(lints("v=2; 0 -> 01; 1 -> 10"), [Lint::ConstantLength]);
+        assert_eq!(lints("v=2; 0 -> 01; 1 -> 100"), [Lint::NeverHalts]);
+
+        assert_eq!(
+            lints("v=2; 0 -> 11; 1 -> 1; 2 -> 111"),
+            [Lint::Unproduced(0), Lint::Unproduced(2)]
+        );
+    }
+}
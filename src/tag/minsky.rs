@@ -0,0 +1,463 @@
+//! Minsky's two-counter machines, and their simulation by tag systems deleting two symbols per step.
+//!
+//! This is the route by which Minsky showed tag systems universal:
+//! any computation can be carried out by a machine with two counters,
+//! and any such machine can be simulated by a tag system.
+//!
+//! # Encoding
+//!
+//! A configuration with the machine at instruction `i` and counters `a` and `b` is encoded as the state
+//!
+//! ```text
+//! A_i x (a_i x)^(2^a) (b_i x)^(2^b)
+//! ```
+//!
+//! where `A_i`, `a_i`, and `b_i` are symbols belonging to the instruction, and `x` is a filler which is never read.
+//! Since two symbols are deleted per step, the system reads every other symbol,
+//! so one pass over the state replaces each pair by the production of its first symbol.
+//!
+//! Incrementing a counter doubles its block in a single pass.
+//! Decrementing halves it, which takes three passes:
+//! the first replaces each pair of the block by a single symbol,
+//! so the second reads only half of them, and if the block had odd length, which is to say the counter was zero,
+//! the second pass ends having read one symbol into the third pass's output.
+//! That shifts the third pass onto the other symbol of each pair it reads, which selects the productions for the zero branch.
+
+use std::{error::Error, fmt, ops::ControlFlow, sync::Arc};
+
+use crate::TagSystem;
+
+use super::{RulesError, SymbolString, TagRules};
+
+/// One of the two counters of a [`MinskyMachine`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Counter {
+    A,
+    B,
+}
+
+/// An instruction of a [`MinskyMachine`], with the indices of the instructions it continues to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    /// Increment the counter, then continue to the instruction.
+    Inc(Counter, usize),
+    /// If the counter is positive, decrement it and continue to the first instruction;
+    /// otherwise continue to the second.
+    Dec(Counter, usize, usize),
+    /// Stop.
+    Halt,
+}
+
+/// A configuration of a [`MinskyMachine`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Configuration {
+    /// The index of the next instruction.
+    pub instruction: usize,
+    /// The value of counter [`Counter::A`].
+    pub a: u32,
+    /// The value of counter [`Counter::B`].
+    pub b: u32,
+}
+
+/// A machine with two counters, running a program which starts at its first instruction.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MinskyMachine {
+    program: Vec<Instruction>,
+}
+
+impl MinskyMachine {
+    /// Create a machine running `program`.
+    pub fn new(program: Vec<Instruction>) -> Result<Self, MinskyError> {
+        if program.is_empty() {
+            return Err(MinskyError::EmptyProgram);
+        }
+
+        for (instruction, &op) in program.iter().enumerate() {
+            let targets = match op {
+                Instruction::Inc(_, next) => [next, next],
+                Instruction::Dec(_, nonzero, zero) => [nonzero, zero],
+                Instruction::Halt => continue,
+            };
+            if let Some(&target) = targets.iter().find(|&&target| target >= program.len()) {
+                return Err(MinskyError::UnknownTarget {
+                    instruction,
+                    target,
+                });
+            }
+        }
+
+        Ok(Self { program })
+    }
+
+    /// Get the program.
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
+    /// Run the machine directly from `config` for up to `step_budget` instructions,
+    /// returning the configuration at which it halted, or `None` if it didn't halt in time.
+    pub fn run(&self, mut config: Configuration, step_budget: u64) -> Option<Configuration> {
+        for _ in 0..step_budget {
+            config = match self.program[config.instruction] {
+                Instruction::Halt => return Some(config),
+                Instruction::Inc(counter, next) => {
+                    *config.counter_mut(counter) += 1;
+                    Configuration {
+                        instruction: next,
+                        ..config
+                    }
+                }
+                Instruction::Dec(counter, nonzero, zero) => {
+                    let value = config.counter_mut(counter);
+                    if *value == 0 {
+                        Configuration {
+                            instruction: zero,
+                            ..config
+                        }
+                    } else {
+                        *value -= 1;
+                        Configuration {
+                            instruction: nonzero,
+                            ..config
+                        }
+                    }
+                }
+            };
+        }
+
+        matches!(self.program[config.instruction], Instruction::Halt).then_some(config)
+    }
+
+    /// Encode the machine as a tag system, as described in the [module documentation](self).
+    ///
+    /// Each decrement takes twelve symbols and every other instruction three,
+    /// so this fails with [`RulesError::AlphabetTooLarge`] for programs with more than about twenty decrements.
+    pub fn encode(&self) -> Result<Encoding, RulesError> {
+        let decrements = self
+            .program
+            .iter()
+            .filter(|op| matches!(op, Instruction::Dec(..)))
+            .count();
+        let alphabet = 1 + 3 * self.program.len() + 9 * decrements;
+        if alphabet > TagRules::MAX_ALPHABET {
+            return Err(RulesError::AlphabetTooLarge {
+                len: alphabet,
+                max: TagRules::MAX_ALPHABET,
+            });
+        }
+
+        // Symbol zero is the filler, so the rest are allocated from one.
+        let mut last = FILLER;
+        let mut symbol = || {
+            last += 1;
+            last
+        };
+        let blocks: Vec<[u8; 3]> = self
+            .program
+            .iter()
+            .map(|_| [(); 3].map(|()| symbol()))
+            .collect();
+        let decrements: Vec<Option<[u8; 9]>> = self
+            .program
+            .iter()
+            .map(|op| matches!(op, Instruction::Dec(..)).then(|| [(); 9].map(|()| symbol())))
+            .collect();
+
+        let mut productions = vec![Vec::new(); alphabet];
+        for (i, &op) in self.program.iter().enumerate() {
+            let [header, a, b] = blocks[i];
+            let mut set = |symbol: u8, production: &[u8]| {
+                productions[symbol as usize] = production.to_vec();
+            };
+
+            match op {
+                Instruction::Inc(counter, next) => {
+                    let [next_header, next_a, next_b] = blocks[next];
+                    set(header, &[next_header, FILLER]);
+                    let (doubled, copied) = match counter {
+                        Counter::A => ((a, next_a), (b, next_b)),
+                        Counter::B => ((b, next_b), (a, next_a)),
+                    };
+                    set(doubled.0, &[doubled.1, FILLER, doubled.1, FILLER]);
+                    set(copied.0, &[copied.1, FILLER]);
+                }
+                Instruction::Dec(counter, nonzero, zero) => {
+                    let [d, half_a, half_b, j, a_j, b_j, k, a_k, b_k] = decrements[i].unwrap();
+
+                    // Halve one block by replacing its pairs with single symbols, and copy the other.
+                    set(header, &[d, FILLER]);
+                    match counter {
+                        Counter::A => {
+                            set(a, &[half_a]);
+                            set(b, &[half_b, half_b]);
+                        }
+                        Counter::B => {
+                            set(a, &[half_a, half_a]);
+                            set(b, &[half_b]);
+                        }
+                    }
+
+                    // Pair up a symbol for each branch, so the phase of the next pass picks between them.
+                    set(d, &[j, k]);
+                    set(half_a, &[a_j, a_k]);
+                    set(half_b, &[b_j, b_k]);
+
+                    let [header_j, a_nonzero, b_nonzero] = blocks[nonzero];
+                    set(j, &[header_j, FILLER]);
+                    set(a_j, &[a_nonzero, FILLER]);
+                    set(b_j, &[b_nonzero, FILLER]);
+
+                    // The filler leading the zero branch is deleted unread, restoring the phase.
+                    let [header_k, a_zero, b_zero] = blocks[zero];
+                    set(k, &[FILLER, header_k, FILLER]);
+                    set(a_k, &[a_zero, FILLER]);
+                    set(b_k, &[b_zero, FILLER]);
+                }
+                // Every production stays empty, so the state is erased and the system halts.
+                Instruction::Halt => {}
+            }
+        }
+
+        Ok(Encoding {
+            rules: Arc::new(TagRules::new(2, productions)?),
+            blocks,
+            halts: self
+                .program
+                .iter()
+                .map(|&op| op == Instruction::Halt)
+                .collect(),
+        })
+    }
+}
+
+impl Configuration {
+    fn counter_mut(&mut self, counter: Counter) -> &mut u32 {
+        match counter {
+            Counter::A => &mut self.a,
+            Counter::B => &mut self.b,
+        }
+    }
+}
+
+/// The filler symbol, which is never read.
+const FILLER: u8 = 0;
+
+/// A [`MinskyMachine`] encoded as a tag system, with the symbols needed to encode and decode its configurations.
+#[derive(Debug, Clone)]
+pub struct Encoding {
+    rules: Arc<TagRules>,
+    /// The header and counter symbols of each instruction.
+    blocks: Vec<[u8; 3]>,
+    /// Whether each instruction halts.
+    halts: Vec<bool>,
+}
+
+impl Encoding {
+    /// Get the rules of the tag system.
+    pub fn rules(&self) -> &Arc<TagRules> {
+        &self.rules
+    }
+
+    /// Encode a configuration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either counter is too large for its block of `2^counter` pairs to fit in memory.
+    pub fn encode(&self, config: Configuration) -> SymbolString {
+        let block = |counter: u32| {
+            1usize
+                .checked_shl(counter)
+                .filter(|len| len.checked_mul(4).is_some())
+                .expect("counter is too large to encode")
+        };
+        let [header, a, b] = self.blocks[config.instruction];
+
+        let symbols = [header, FILLER]
+            .into_iter()
+            .chain([a, FILLER].repeat(block(config.a)))
+            .chain([b, FILLER].repeat(block(config.b)));
+        SymbolString::new(self.rules.clone(), symbols).expect("symbols are in the alphabet")
+    }
+
+    /// Decode a state, if it encodes a configuration.
+    ///
+    /// The system passes through many states between the encodings of consecutive configurations, which decode to `None`.
+    pub fn decode(&self, system: &SymbolString) -> Option<Configuration> {
+        let mut pairs = system
+            .symbols()
+            .collect::<Vec<_>>()
+            .chunks(2)
+            .map(|pair| match *pair {
+                [symbol, FILLER] => Some(symbol),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .peekable();
+
+        let header = pairs.next()?;
+        let instruction = self.blocks.iter().position(|block| block[0] == header)?;
+        let [_, a, b] = self.blocks[instruction];
+
+        let mut count = |symbol: u8| {
+            let mut len = 0usize;
+            while pairs.next_if_eq(&symbol).is_some() {
+                len += 1;
+            }
+            len.is_power_of_two().then(|| len.trailing_zeros())
+        };
+        let config = Configuration {
+            instruction,
+            a: count(a)?,
+            b: count(b)?,
+        };
+
+        pairs.next().is_none().then_some(config)
+    }
+
+    /// Evolve the encoding of `start` for up to `step_budget` steps,
+    /// returning the configuration at which the machine halted, or `None` if it didn't halt in time.
+    ///
+    /// The system would go on to erase its state, so this stops as soon as the machine reaches a halting instruction.
+    pub fn run(&self, start: Configuration, step_budget: u64) -> Option<Configuration> {
+        let mut system = self.encode(start);
+        let halting_headers: Vec<u8> = self
+            .blocks
+            .iter()
+            .zip(&self.halts)
+            .filter(|&(_, &halts)| halts)
+            .map(|(block, _)| block[0])
+            .collect();
+
+        for _ in 0..=step_budget {
+            // A header is only ever at the front when it's about to be read, with its configuration after it.
+            if system
+                .get(0)
+                .is_some_and(|symbol| halting_headers.contains(&symbol))
+            {
+                return self.decode(&system);
+            }
+
+            if let ControlFlow::Break(()) = system.evolve() {
+                return None;
+            }
+        }
+
+        None
+    }
+}
+
+/// An error returned when creating an invalid [`MinskyMachine`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MinskyError {
+    /// The program has no instructions.
+    EmptyProgram,
+    /// The instruction continues to `target`, which is past the end of the program.
+    UnknownTarget { instruction: usize, target: usize },
+}
+
+impl fmt::Display for MinskyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyProgram => write!(f, "the program has no instructions"),
+            Self::UnknownTarget {
+                instruction,
+                target,
+            } => write!(
+                f,
+                "instruction {instruction} continues to instruction {target}, which does not exist"
+            ),
+        }
+    }
+}
+
+impl Error for MinskyError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use Counter::*;
+    use Instruction::*;
+
+    fn config(instruction: usize, a: u32, b: u32) -> Configuration {
+        Configuration { instruction, a, b }
+    }
+
+    #[test]
+    fn runs_directly() {
+        // Add `b` to `a`.
+        let add = MinskyMachine::new(vec![Dec(B, 1, 2), Inc(A, 0), Halt]).unwrap();
+        assert_eq!(add.run(config(0, 2, 3), 100), Some(config(2, 5, 0)));
+        assert_eq!(add.run(config(0, 2, 3), 3), None);
+
+        assert_eq!(
+            MinskyMachine::new(vec![Inc(A, 1)]),
+            Err(MinskyError::UnknownTarget {
+                instruction: 0,
+                target: 1
+            })
+        );
+        assert_eq!(MinskyMachine::new(vec![]), Err(MinskyError::EmptyProgram));
+    }
+
+    #[test]
+    fn encodes_and_decodes() {
+        let machine = MinskyMachine::new(vec![Dec(B, 1, 2), Inc(A, 0), Halt]).unwrap();
+        let encoding = machine.encode().unwrap();
+        assert_eq!(encoding.rules().deletion(), 2);
+        assert_eq!(encoding.rules().alphabet(), 1 + 3 * 3 + 9);
+
+        for c in [config(0, 0, 0), config(1, 3, 0), config(2, 1, 4)] {
+            let system = encoding.encode(c);
+            assert_eq!(system.length(), 2 + 2 * (1 << c.a) + 2 * (1 << c.b));
+            assert_eq!(encoding.decode(&system), Some(c));
+        }
+
+        let mut system = encoding.encode(config(0, 1, 1));
+        let _ = system.evolve();
+        assert_eq!(encoding.decode(&system), None);
+    }
+
+    #[test]
+    fn simulates_machines() {
+        let machines = [
+            // Add `b` to `a`.
+            vec![Dec(B, 1, 2), Inc(A, 0), Halt],
+            // Move `a` to `b`, doubling it.
+            vec![Dec(A, 1, 3), Inc(B, 2), Inc(B, 0), Halt],
+            // Halt at instruction 3 if `a` is even, or 4 if it's odd.
+            vec![Dec(A, 1, 3), Dec(A, 0, 4), Halt, Halt, Halt],
+            // Compute `a - b`, or halt at instruction 2 if `b` is larger.
+            vec![Dec(B, 1, 3), Dec(A, 0, 2), Halt, Halt],
+        ];
+
+        for program in machines {
+            let machine = MinskyMachine::new(program).unwrap();
+            let encoding = machine.encode().unwrap();
+
+            for a in 0..5 {
+                for b in 0..4 {
+                    let start = config(0, a, b);
+                    let expected = machine.run(start, 1000).unwrap();
+                    assert_eq!(
+                        encoding.run(start, 1_000_000),
+                        Some(expected),
+                        "{:?} from {start:?}",
+                        machine.program()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn halts_by_erasing() {
+        let machine = MinskyMachine::new(vec![Inc(A, 1), Halt]).unwrap();
+        let encoding = machine.encode().unwrap();
+
+        let mut system = encoding.encode(config(0, 0, 0));
+        assert!(system.evolve_multi(100).is_break());
+        assert_eq!(system.length(), 0);
+    }
+}
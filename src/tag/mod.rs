@@ -0,0 +1,214 @@
+//! Tag systems with rules other than Post's.
+//!
+//! A [`TagRules`] gives the number of symbols deleted per step and a production for each symbol of the alphabet,
+//! which is appended whenever that symbol is read.
+//! Systems following such rules implement [`TagSystem`](crate::TagSystem),
+//! so they can be run and checked for cycles like any [`PostSystem`](crate::PostSystem),
+//! although the binary-specific machinery like lookup tables and snapshots is unavailable to them.
+//!
+//! # Writing rules
+//!
+//! Rules parse from a compact textual form, in which statements are separated by `;` or new lines:
+//! `v=N` gives the deletion number, and `a -> xyz` gives the production of the symbol `a`.
+//! Post's rules are `v=3; 0 -> 00; 1 -> 1101`.
+//!
+//! Symbols are digits, continuing with letters up to `z` for 35, or any symbol as a decimal number in angle brackets, like `<40>`.
+//! Blanks between symbols are ignored, productions may be empty, and `#` starts a comment running to the end of the line.
+//! The alphabet is every symbol up to the largest one mentioned, and each of them needs exactly one production.
+//!
+//! ```
+//! use post_tag::tag::TagRules;
+//!
+//! let rules: TagRules = "v=2; 0 -> 12; 1 -> 0; 2 -> 000".parse().unwrap();
+//! assert_eq!(rules.alphabet(), 3);
+//! assert_eq!(rules.to_string().parse(), Ok(rules));
+//! ```
+
+pub mod de_mol;
+mod dsl;
+#[cfg(feature = "serde")]
+mod format;
+mod lint;
+pub mod minsky;
+mod packed;
+pub mod presets;
+mod symbols;
+
+use std::{error::Error, fmt};
+
+pub use dsl::{ParseRulesError, ParseRulesErrorKind};
+pub use lint::Lint;
+pub use packed::{PackedRules, PackedString};
+pub use symbols::SymbolString;
+
+/// The rules of a tag system.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TagRules {
+    deletion: usize,
+    productions: Vec<Vec<u8>>,
+}
+
+impl TagRules {
+    /// The largest alphabet rules can have, so that every symbol fits in a byte.
+    pub const MAX_ALPHABET: usize = 1 << u8::BITS;
+
+    /// Create rules which delete `deletion` symbols per step and append `productions[symbol]` when `symbol` is read.
+    ///
+    /// The alphabet is the symbols from zero up to the number of productions.
+    pub fn new(deletion: usize, productions: Vec<Vec<u8>>) -> Result<Self, RulesError> {
+        if deletion == 0 {
+            return Err(RulesError::NoDeletion);
+        }
+        if productions.is_empty() {
+            return Err(RulesError::EmptyAlphabet);
+        }
+        if productions.len() > Self::MAX_ALPHABET {
+            return Err(RulesError::AlphabetTooLarge {
+                len: productions.len(),
+                max: Self::MAX_ALPHABET,
+            });
+        }
+
+        for (read, production) in productions.iter().enumerate() {
+            if let Some(&symbol) = production
+                .iter()
+                .find(|&&symbol| symbol as usize >= productions.len())
+            {
+                return Err(RulesError::UnknownSymbol {
+                    read: read as u8,
+                    symbol,
+                });
+            }
+        }
+
+        Ok(Self {
+            deletion,
+            productions,
+        })
+    }
+
+    /// Post's rules, which every [`PostSystem`](crate::PostSystem) follows:
+    /// delete three symbols, then append `00` after reading `0` or `1101` after reading `1`.
+    pub fn post() -> Self {
+        Self {
+            deletion: 3,
+            productions: vec![vec![0, 0], vec![1, 1, 0, 1]],
+        }
+    }
+
+    /// Get the number of symbols deleted per step.
+    pub fn deletion(&self) -> usize {
+        self.deletion
+    }
+
+    /// Get the number of symbols in the alphabet.
+    pub fn alphabet(&self) -> usize {
+        self.productions.len()
+    }
+
+    /// Get the production appended after reading `symbol`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `symbol` is not in the alphabet.
+    pub fn production(&self, symbol: u8) -> &[u8] {
+        &self.productions[symbol as usize]
+    }
+
+    /// Iterate over the productions, in order of the symbols which select them.
+    pub fn productions(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        self.productions.iter().map(Vec::as_slice)
+    }
+}
+
+/// An error returned when creating invalid [`TagRules`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RulesError {
+    /// The rules delete no symbols, so systems would never shrink or read past their first symbol.
+    NoDeletion,
+    /// The rules have no productions, so there are no symbols.
+    EmptyAlphabet,
+    /// The rules have `len` productions, but at most `max` are allowed,
+    /// either [`TagRules::MAX_ALPHABET`] or the limit of a particular representation.
+    AlphabetTooLarge { len: usize, max: usize },
+    /// The production for `read` contains `symbol`, which is not in the alphabet.
+    UnknownSymbol { read: u8, symbol: u8 },
+    /// The rules declare an alphabet of `alphabet` symbols, but have a different number of productions.
+    AlphabetMismatch { alphabet: usize, productions: usize },
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoDeletion => write!(f, "rules must delete at least one symbol per step"),
+            Self::EmptyAlphabet => write!(f, "rules must have at least one production"),
+            Self::AlphabetTooLarge { len, max } => write!(
+                f,
+                "rules have {len} productions, but at most {max} are allowed"
+            ),
+            Self::UnknownSymbol { read, symbol } => write!(
+                f,
+                "production for symbol {read} contains symbol {symbol}, which has no production"
+            ),
+            Self::AlphabetMismatch {
+                alphabet,
+                productions,
+            } => write!(
+                f,
+                "rules declare an alphabet of {alphabet} symbols, but have {productions} productions"
+            ),
+        }
+    }
+}
+
+impl Error for RulesError {}
+
+/// An error returned when a state contains a symbol outside the alphabet of its rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolError {
+    /// The index of the offending symbol.
+    pub position: usize,
+    /// The offending symbol.
+    pub symbol: u8,
+}
+
+impl fmt::Display for SymbolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "symbol {} at position {} is not in the alphabet",
+            self.symbol, self.position
+        )
+    }
+}
+
+impl Error for SymbolError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_rules() {
+        let rules = TagRules::new(2, vec![vec![1, 2], vec![0], vec![0, 0, 0]]).unwrap();
+        assert_eq!(rules.deletion(), 2);
+        assert_eq!(rules.alphabet(), 3);
+        assert_eq!(rules.production(2), [0, 0, 0]);
+
+        assert_eq!(
+            TagRules::new(3, vec![vec![0, 0], vec![1, 1, 0, 1]]),
+            Ok(TagRules::post())
+        );
+
+        assert_eq!(TagRules::new(0, vec![vec![0]]), Err(RulesError::NoDeletion));
+        assert_eq!(TagRules::new(2, vec![]), Err(RulesError::EmptyAlphabet));
+        assert_eq!(
+            TagRules::new(2, vec![vec![]; 257]),
+            Err(RulesError::AlphabetTooLarge { len: 257, max: 256 })
+        );
+        assert_eq!(
+            TagRules::new(2, vec![vec![0], vec![1, 2]]),
+            Err(RulesError::UnknownSymbol { read: 1, symbol: 2 })
+        );
+    }
+}
@@ -0,0 +1,356 @@
+//! A string of symbols packed two bits per symbol, for rules with at most four symbols.
+
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    iter,
+    ops::ControlFlow,
+    sync::Arc,
+};
+
+use crate::{system::BitString64, PostSystem, TagSystem};
+
+use super::{RulesError, SymbolError, TagRules};
+
+/// The number of symbols which fit in a word of the underlying bit string.
+const SYMBOLS_PER_WORD: usize = 32;
+
+/// The most steps a single table lookup can take, so that the table has at most `4^MAX_STEPS` entries.
+const MAX_STEPS: usize = 6;
+
+/// [`TagRules`] prepared for [`PackedString`], with their productions packed and a jump table.
+///
+/// Preparing the table takes a moment, so the result is shared between systems following the same rules.
+pub struct PackedRules {
+    rules: TagRules,
+    /// The production of each symbol, packed into words of up to [`SYMBOLS_PER_WORD`] symbols each.
+    productions: Vec<Vec<(u64, u8)>>,
+    /// The number of steps taken per table lookup.
+    steps: usize,
+    /// The concatenated productions for every sequence of `steps` symbols read,
+    /// indexed by those symbols packed with the first read in the lowest bits.
+    table: Vec<(u64, u8)>,
+}
+
+impl PackedRules {
+    /// The largest alphabet whose symbols fit in two bits.
+    pub const MAX_ALPHABET: usize = 4;
+
+    /// Prepare `rules`, if their alphabet is small enough to pack.
+    pub fn new(rules: TagRules) -> Result<Self, RulesError> {
+        if rules.alphabet() > Self::MAX_ALPHABET {
+            return Err(RulesError::AlphabetTooLarge {
+                len: rules.alphabet(),
+                max: Self::MAX_ALPHABET,
+            });
+        }
+
+        let productions = rules
+            .productions()
+            .map(|production| production.chunks(SYMBOLS_PER_WORD).map(pack).collect())
+            .collect();
+
+        // Every symbol deleted by a jump is deleted at once, and every symbol appended is appended at once,
+        // so both must fit in a word.
+        let longest = rules.productions().map(<[u8]>::len).max().unwrap_or(0);
+        let per_step = rules.deletion().max(longest);
+        let steps = (1..=MAX_STEPS)
+            .rev()
+            .find(|steps| steps * per_step <= SYMBOLS_PER_WORD)
+            .unwrap_or(0);
+
+        let table = if steps > 1 {
+            (0..1 << (2 * steps))
+                .map(|index: usize| {
+                    let symbols: Vec<u8> = (0..steps)
+                        .flat_map(|i| {
+                            let read = (index >> (2 * i)) & 0b11;
+                            // Sequences containing symbols outside the alphabet are never looked up.
+                            rules.productions.get(read).map_or(&[][..], Vec::as_slice)
+                        })
+                        .copied()
+                        .collect();
+                    pack(&symbols)
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            rules,
+            productions,
+            steps,
+            table,
+        })
+    }
+
+    /// Get the rules being followed.
+    pub fn rules(&self) -> &TagRules {
+        &self.rules
+    }
+
+    /// Get the number of steps taken per table lookup.
+    ///
+    /// This is zero if the deletion number or a production is too long for any jump to fit in a word,
+    /// in which case systems step one at a time.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+}
+
+impl fmt::Debug for PackedRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PackedRules")
+            .field("rules", &self.rules)
+            .field("steps", &self.steps)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Pack `symbols`, which must be at most [`SYMBOLS_PER_WORD`], into a word and its length in bits.
+fn pack(symbols: &[u8]) -> (u64, u8) {
+    debug_assert!(symbols.len() <= SYMBOLS_PER_WORD);
+
+    let word = symbols
+        .iter()
+        .enumerate()
+        .fold(0, |word, (i, &symbol)| word | (symbol as u64) << (2 * i));
+
+    (word, 2 * symbols.len() as u8)
+}
+
+/// A tag system following [`PackedRules`], with its state stored two bits per symbol in a [`BitString64`].
+///
+/// Packing quarters the memory of a [`SymbolString`](super::SymbolString),
+/// and the rules' jump table lets it take several steps per lookup, like a [`BitString`](crate::system::BitString).
+#[derive(Debug, Clone)]
+pub struct PackedString {
+    rules: Arc<PackedRules>,
+    bits: BitString64,
+}
+
+impl PackedString {
+    /// Create a system following `rules` with the state `symbols`.
+    pub fn new(
+        rules: Arc<PackedRules>,
+        symbols: impl IntoIterator<Item = u8>,
+    ) -> Result<Self, SymbolError> {
+        let mut bits = BitString64::new();
+        for (position, symbol) in symbols.into_iter().enumerate() {
+            if symbol as usize >= rules.rules.alphabet() {
+                return Err(SymbolError { position, symbol });
+            }
+
+            bits.append(symbol as u64, 2);
+        }
+
+        Ok(Self { rules, bits })
+    }
+
+    /// Get the rules the system follows.
+    pub fn rules(&self) -> &Arc<PackedRules> {
+        &self.rules
+    }
+
+    /// Iterate over the symbols of the state, in order.
+    pub fn symbols(&self) -> impl Iterator<Item = u8> + '_ {
+        let mut bits = PostSystem::bits(&self.bits);
+
+        iter::from_fn(move || {
+            let low = bits.next()?;
+            let high = bits.next()?;
+            Some(low as u8 | (high as u8) << 1)
+        })
+    }
+
+    /// Get the symbol at `index`, or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<u8> {
+        let low = PostSystem::get(&self.bits, 2 * index)?;
+        let high = PostSystem::get(&self.bits, 2 * index + 1)?;
+
+        Some(low as u8 | (high as u8) << 1)
+    }
+
+    /// Take [`PackedRules::steps`] steps with a single table lookup.
+    ///
+    /// The state must be at least as long as the symbols deleted, so every symbol read is already present.
+    fn jump(&mut self) {
+        let deletion = self.rules.rules.deletion();
+        let steps = self.rules.steps;
+
+        let deleted = self.bits.delete((2 * steps * deletion) as u8);
+        let index = (0..steps).fold(0, |index, i| {
+            index | ((deleted >> (2 * i * deletion)) & 0b11) << (2 * i)
+        });
+
+        let (word, len) = self.rules.table[index as usize];
+        self.bits.append(word, len);
+    }
+}
+
+/// Systems are equal if they have equal rules and states.
+impl PartialEq for PackedString {
+    fn eq(&self, other: &Self) -> bool {
+        (Arc::ptr_eq(&self.rules, &other.rules) || self.rules.rules == other.rules.rules)
+            && self.bits == other.bits
+    }
+}
+
+impl Eq for PackedString {}
+
+/// Only the state is hashed, since systems being compared almost always share their rules.
+impl Hash for PackedString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bits.hash(state);
+    }
+}
+
+impl fmt::Display for PackedString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for symbol in self.symbols() {
+            let digit = char::from_digit(symbol as u32, 4).expect("symbol is a digit");
+            write!(f, "{digit}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TagSystem for PackedString {
+    fn length(&self) -> usize {
+        PostSystem::length(&self.bits) / 2
+    }
+
+    fn memory_usage(&self) -> usize {
+        PostSystem::memory_usage(&self.bits)
+    }
+
+    fn evolve(&mut self) -> ControlFlow<()> {
+        let deletion = self.rules.rules.deletion();
+        if self.length() < deletion {
+            return ControlFlow::Break(());
+        }
+
+        let first = deletion.min(SYMBOLS_PER_WORD);
+        let read = self.bits.delete(2 * first as u8) & 0b11;
+        let mut remaining = deletion - first;
+        while remaining > 0 {
+            let count = remaining.min(SYMBOLS_PER_WORD);
+            self.bits.delete(2 * count as u8);
+            remaining -= count;
+        }
+
+        for &(word, len) in &self.rules.productions[read as usize] {
+            self.bits.append(word, len);
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn evolve_multi(&mut self, n: usize) -> ControlFlow<usize> {
+        let steps = self.rules.steps;
+        let reach = steps * self.rules.rules.deletion();
+
+        let mut i = 0;
+        while i < n {
+            if steps > 1 && n - i >= steps && self.length() >= reach {
+                self.jump();
+                i += steps;
+            } else {
+                self.evolve().map_break(|()| i)?;
+                i += 1;
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        runner::{Outcome, Runner},
+        seeds::{SEED_5854, SEED_6008},
+        system::BitString,
+        tag::SymbolString,
+    };
+
+    use super::*;
+
+    fn post(system: &BitString) -> PackedString {
+        let rules = Arc::new(PackedRules::new(TagRules::post()).unwrap());
+        PackedString::new(rules, system.bits().map(u8::from)).unwrap()
+    }
+
+    #[test]
+    fn follows_post_rules() {
+        let mut bits = SEED_5854.system::<BitString>();
+        let mut packed = post(&bits);
+        assert_eq!(packed.rules().steps(), 6);
+
+        for n in [1, 10, 1000, 12_345] {
+            assert_eq!(
+                TagSystem::evolve_multi(&mut packed, n),
+                PostSystem::evolve_multi(&mut bits, n)
+            );
+            assert_eq!(packed, post(&bits));
+            assert_eq!(packed.to_string(), bits.to_string());
+        }
+
+        let runner = Runner::new(1_000_000, None);
+        assert_eq!(
+            runner.run(post(&SEED_6008.system())),
+            Outcome::Halted(261_741)
+        );
+    }
+
+    #[test]
+    fn agrees_with_symbol_strings() {
+        let collatz = TagRules::new(2, vec![vec![1, 2], vec![0], vec![0, 0, 0]]).unwrap();
+        // Long enough that neither deletions nor productions fit in a word, so there is no table.
+        let long = TagRules::new(
+            33,
+            vec![
+                (0..40u8).map(|i| i % 4).collect(),
+                vec![3, 2],
+                vec![1; 70],
+                (0..50usize).map(|i| ((i * 7 + 1) % 4) as u8).collect(),
+            ],
+        )
+        .unwrap();
+
+        for (rules, seed) in [
+            (collatz, vec![0; 7]),
+            (long, (0..100usize).map(|i| (i * i % 7 % 4) as u8).collect()),
+        ] {
+            let packed_rules = Arc::new(PackedRules::new(rules.clone()).unwrap());
+            let mut packed = PackedString::new(packed_rules, seed.iter().copied()).unwrap();
+            let mut symbols = SymbolString::new(Arc::new(rules), seed).unwrap();
+
+            for n in [1, 2, 5, 100, 1000] {
+                assert_eq!(packed.evolve_multi(n), symbols.evolve_multi(n));
+                assert!(packed.symbols().eq(symbols.symbols()));
+                assert_eq!(packed.length(), symbols.length());
+                assert_eq!(packed.get(3), symbols.get(3));
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_large_alphabets() {
+        assert_eq!(
+            PackedRules::new(TagRules::new(2, vec![vec![]; 5]).unwrap()).unwrap_err(),
+            RulesError::AlphabetTooLarge { len: 5, max: 4 }
+        );
+
+        let rules = Arc::new(PackedRules::new(TagRules::post()).unwrap());
+        assert_eq!(
+            PackedString::new(rules, [0, 1, 2]).unwrap_err(),
+            SymbolError {
+                position: 2,
+                symbol: 2
+            }
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Rule sets studied in the literature, with where they come from.
+
+use super::TagRules;
+
+/// A named rule set from the literature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Preset {
+    /// A short name, used to select the preset, e.g. from the command line.
+    pub name: &'static str,
+    /// What the rules are and why they are studied.
+    pub description: &'static str,
+    /// The publication the rules come from.
+    pub citation: &'static str,
+    /// The rules, in the [textual form](super#writing-rules).
+    pub source: &'static str,
+}
+
+impl Preset {
+    /// Construct the rules.
+    pub fn rules(&self) -> TagRules {
+        self.source.parse().expect("presets are valid")
+    }
+}
+
+/// Post's original system, whose halting problem is still open.
+pub const POST: Preset = Preset {
+    name: "post",
+    description: "Post's original system, whose halting problem is still open",
+    citation: "E. L. Post, \"Formal reductions of the general combinatorial decision problem\", \
+               American Journal of Mathematics 65 (1943)",
+    source: "v=3; 0 -> 00; 1 -> 1101",
+};
+
+/// Post's system with its productions swapped, so that reading `0` grows the state.
+pub const POST_SWAPPED: Preset = Preset {
+    name: "post-swapped",
+    description: "Post's system with its productions swapped, so that reading 0 grows the state",
+    citation: "a variant of E. L. Post, \"Formal reductions of the general combinatorial decision problem\", \
+               American Journal of Mathematics 65 (1943)",
+    source: "v=3; 0 -> 1101; 1 -> 00",
+};
+
+/// De Mol's system computing the Collatz map; see [`de_mol`](super::de_mol).
+pub const DE_MOL: Preset = Preset {
+    name: "de-mol",
+    description:
+        "De Mol's system, under which a^n reaches a^(n/2) or a^((3n+1)/2) as in the Collatz map",
+    citation: "L. De Mol, \"Tag systems and Collatz-like functions\", \
+               Theoretical Computer Science 390 (2008)",
+    source: "v=2; 0 -> 12; 1 -> 0; 2 -> 000",
+};
+
+/// Every preset.
+pub const ALL: &[Preset] = &[POST, POST_SWAPPED, DE_MOL];
+
+/// Get the preset named `name`.
+pub fn get(name: &str) -> Option<&'static Preset> {
+    ALL.iter().find(|preset| preset.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tag::de_mol;
+
+    use super::*;
+
+    #[test]
+    fn constructs_presets() {
+        for preset in ALL {
+            assert_eq!(preset.rules().to_string(), preset.source);
+            assert_eq!(get(preset.name), Some(preset));
+        }
+        assert_eq!(get("unknown"), None);
+
+        assert_eq!(POST.rules(), TagRules::post());
+        assert_eq!(POST_SWAPPED.rules().production(0), [1, 1, 0, 1]);
+        assert_eq!(DE_MOL.rules(), de_mol::rules());
+    }
+}
@@ -0,0 +1,199 @@
+//! A string of symbols stored one per byte, for rules with any alphabet.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::ControlFlow,
+    sync::Arc,
+};
+
+use crate::TagSystem;
+
+use super::{SymbolError, TagRules};
+
+/// A tag system following any [`TagRules`], with its state stored one symbol per byte.
+///
+/// The rules are shared between clones, so cloning only copies the state.
+#[derive(Debug, Clone)]
+pub struct SymbolString {
+    rules: Arc<TagRules>,
+    symbols: VecDeque<u8>,
+}
+
+impl SymbolString {
+    /// Create a system following `rules` with the state `symbols`.
+    pub fn new(
+        rules: Arc<TagRules>,
+        symbols: impl IntoIterator<Item = u8>,
+    ) -> Result<Self, SymbolError> {
+        let symbols: VecDeque<u8> = symbols.into_iter().collect();
+        if let Some((position, &symbol)) = symbols
+            .iter()
+            .enumerate()
+            .find(|&(_, &symbol)| symbol as usize >= rules.alphabet())
+        {
+            return Err(SymbolError { position, symbol });
+        }
+
+        Ok(Self { rules, symbols })
+    }
+
+    /// Get the rules the system follows.
+    pub fn rules(&self) -> &Arc<TagRules> {
+        &self.rules
+    }
+
+    /// Iterate over the symbols of the state, in order.
+    pub fn symbols(&self) -> impl Iterator<Item = u8> + '_ {
+        self.symbols.iter().copied()
+    }
+
+    /// Get the symbol at `index`, or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<u8> {
+        self.symbols.get(index).copied()
+    }
+}
+
+/// Systems are equal if they have equal rules and states.
+impl PartialEq for SymbolString {
+    fn eq(&self, other: &Self) -> bool {
+        (Arc::ptr_eq(&self.rules, &other.rules) || self.rules == other.rules)
+            && self.symbols == other.symbols
+    }
+}
+
+impl Eq for SymbolString {}
+
+/// Only the state is hashed, since systems being compared almost always share their rules.
+impl Hash for SymbolString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.symbols.hash(state);
+    }
+}
+
+/// Symbols are written as digits, continuing with letters for alphabets of up to 36 symbols,
+/// and as decimal numbers separated by spaces for larger alphabets.
+impl fmt::Display for SymbolString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.rules.alphabet() <= 36 {
+            for symbol in self.symbols() {
+                let digit = char::from_digit(symbol as u32, 36).expect("symbol is a digit");
+                write!(f, "{digit}")?;
+            }
+        } else {
+            for (i, symbol) in self.symbols().enumerate() {
+                if i > 0 {
+                    f.write_str(" ")?;
+                }
+                write!(f, "{symbol}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl TagSystem for SymbolString {
+    fn length(&self) -> usize {
+        self.symbols.len()
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.symbols.capacity()
+    }
+
+    fn evolve(&mut self) -> ControlFlow<()> {
+        if self.symbols.len() < self.rules.deletion() {
+            return ControlFlow::Break(());
+        }
+
+        let read = self.symbols[0];
+        self.symbols.drain(..self.rules.deletion());
+        self.symbols.extend(self.rules.production(read));
+
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cycle::{self, Algorithm, Detection},
+        runner::{Outcome, Runner},
+        seeds::{SEED_5854, SEED_6008},
+        system::BitString,
+        PostSystem,
+    };
+
+    use super::*;
+
+    fn post(system: &BitString) -> SymbolString {
+        SymbolString::new(Arc::new(TagRules::post()), system.bits().map(u8::from)).unwrap()
+    }
+
+    #[test]
+    fn follows_post_rules() {
+        let mut bits = SEED_5854.system::<BitString>();
+        let mut symbols = post(&bits);
+
+        for n in [1, 10, 1000] {
+            assert_eq!(
+                TagSystem::evolve_multi(&mut symbols, n),
+                PostSystem::evolve_multi(&mut bits, n)
+            );
+            assert_eq!(symbols, post(&bits));
+            assert_eq!(symbols.to_string(), bits.to_string());
+        }
+
+        let runner = Runner::new(1_000_000, None);
+        assert_eq!(
+            runner.run(post(&SEED_6008.system())),
+            Outcome::Halted(261_741)
+        );
+
+        let Detection::Cycle(info) =
+            cycle::detect(post(&SEED_5854.system()), Algorithm::Brent, 1_000_000)
+        else {
+            panic!("seed 5854 should cycle");
+        };
+        assert_eq!(info.period, 6);
+    }
+
+    #[test]
+    fn follows_other_rules() {
+        // De Mol's system, under which `a^n` reaches `a^(n / 2)` or `a^((3n + 1) / 2)`, as in the Collatz map.
+        let rules = Arc::new(TagRules::new(2, vec![vec![1, 2], vec![0], vec![0, 0, 0]]).unwrap());
+        let mut system = SymbolString::new(rules.clone(), [0; 3]).unwrap();
+
+        assert_eq!(
+            TagSystem::evolve_multi(&mut system, 4),
+            ControlFlow::Continue(())
+        );
+        assert_eq!(system, SymbolString::new(rules.clone(), [0; 5]).unwrap());
+        assert_eq!(system.to_string(), "00000");
+
+        assert_eq!(
+            SymbolString::new(rules.clone(), [0, 3]),
+            Err(SymbolError {
+                position: 1,
+                symbol: 3
+            })
+        );
+
+        // `a` alone is shorter than the deletion number, so it halts immediately.
+        let runner = Runner::new(100, Some(Algorithm::Brent));
+        assert_eq!(
+            runner.run(SymbolString::new(rules, [0]).unwrap()),
+            Outcome::Halted(0)
+        );
+    }
+
+    #[test]
+    fn writes_large_alphabets() {
+        let rules = Arc::new(TagRules::new(1, vec![vec![]; 40]).unwrap());
+        let system = SymbolString::new(rules, [1, 39, 0]).unwrap();
+
+        assert_eq!(system.to_string(), "1 39 0");
+    }
+}
@@ -0,0 +1,198 @@
+//! Metrics for monitoring long-running work, recorded through the [`metrics`](https://docs.rs/metrics) facade.
+//!
+//! With the `metrics` feature, the library records the metrics named below to whichever recorder is installed,
+//! such as a Prometheus exporter; without it, recording compiles to nothing.
+//! Metrics are recorded in bulk, once per chunk of steps or per evolution call, rather than on every step.
+
+/// Counter of the steps simulated by [`Runner`](crate::runner::Runner)s.
+pub const STEPS: &str = "post_tag_steps_total";
+
+/// Counter of the seeds classified by [`Census`](crate::census::Census)es, labelled by `class`: `halted`, `cycled`, or `unknown`.
+pub const SEEDS_CLASSIFIED: &str = "post_tag_seeds_classified_total";
+
+/// Counter of the steps taken by the default [`PostSystem::evolve_multi`](crate::PostSystem::evolve_multi), labelled by `by`:
+/// `jump` for steps taken in [preferred jumps](crate::PostSystem::evolve_preferred), which for bit strings are lookup table hits,
+/// or `single` for steps taken one at a time because too few steps or symbols were left for a jump.
+pub const EVOLVED_STEPS: &str = "post_tag_evolved_steps_total";
+
+/// Gauge of the bytes held by the states of the most recently checked run, as in [`Runner::memory_limit`](crate::runner::Runner::memory_limit).
+///
+/// This is the memory in use at the latest check, not a count of the bytes allocated over the run.
+pub const LIVE_STATE_BYTES: &str = "post_tag_live_state_bytes";
+
+/// Describe every metric to the installed recorder, so exporters can show what they mean.
+#[cfg(feature = "metrics")]
+pub fn describe() {
+    use metrics::{describe_counter, describe_gauge, Unit};
+
+    describe_counter!(STEPS, Unit::Count, "Steps simulated by runners.");
+    describe_counter!(
+        SEEDS_CLASSIFIED,
+        Unit::Count,
+        "Seeds classified by censuses."
+    );
+    describe_counter!(
+        EVOLVED_STEPS,
+        Unit::Count,
+        "Steps evolved, by lookup table jumps or one at a time."
+    );
+    describe_gauge!(
+        LIVE_STATE_BYTES,
+        Unit::Bytes,
+        "Bytes of states held by the latest run when last checked."
+    );
+}
+
+/// Record that `n` steps were simulated.
+#[inline]
+pub(crate) fn steps(n: u64) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(STEPS).increment(n);
+    #[cfg(not(feature = "metrics"))]
+    let _ = n;
+}
+
+/// Record that a seed was classified as `class`.
+#[inline]
+pub(crate) fn seed_classified(class: &crate::census::Class) {
+    #[cfg(feature = "metrics")]
+    {
+        use crate::census::Class;
+
+        let class = match class {
+            Class::Halted(_) => "halted",
+            Class::Cycled { .. } => "cycled",
+            Class::Unknown => "unknown",
+        };
+        metrics::counter!(SEEDS_CLASSIFIED, "class" => class).increment(1);
+    }
+    #[cfg(not(feature = "metrics"))]
+    let _ = class;
+}
+
+/// Record that one call to evolve took `jumped` steps in preferred jumps and `single` steps one at a time.
+#[inline]
+pub(crate) fn evolved(jumped: u64, single: u64) {
+    #[cfg(feature = "metrics")]
+    {
+        metrics::counter!(EVOLVED_STEPS, "by" => "jump").increment(jumped);
+        metrics::counter!(EVOLVED_STEPS, "by" => "single").increment(single);
+    }
+    #[cfg(not(feature = "metrics"))]
+    let _ = (jumped, single);
+}
+
+/// Record the bytes held by a run's states.
+#[inline]
+pub(crate) fn live_state_bytes(bytes: usize) {
+    #[cfg(feature = "metrics")]
+    metrics::gauge!(LIVE_STATE_BYTES).set(bytes as f64);
+    #[cfg(not(feature = "metrics"))]
+    let _ = bytes;
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, Mutex,
+        },
+    };
+
+    use metrics::{
+        Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+    };
+
+    use crate::{census::Census, cycle::Algorithm, runner::Runner, system::BitString, PostSystem};
+
+    use super::*;
+
+    /// A recorder which keeps every counter and gauge in memory.
+    #[derive(Default)]
+    struct TestRecorder(Mutex<HashMap<Key, Arc<AtomicU64>>>);
+
+    impl TestRecorder {
+        fn get(&self, name: &'static str, labels: &[(&'static str, &'static str)]) -> u64 {
+            let key = Key::from_parts(
+                name,
+                labels
+                    .iter()
+                    .map(|&(key, value)| metrics::Label::new(key, value))
+                    .collect::<Vec<_>>(),
+            );
+            self.0
+                .lock()
+                .unwrap()
+                .get(&key)
+                .map_or(0, |value| value.load(Ordering::Relaxed))
+        }
+
+        fn register(&self, key: &Key) -> Arc<AtomicU64> {
+            self.0
+                .lock()
+                .unwrap()
+                .entry(key.clone())
+                .or_default()
+                .clone()
+        }
+    }
+
+    impl Recorder for TestRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            Counter::from_arc(self.register(key))
+        }
+
+        fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::from_arc(self.register(key))
+        }
+
+        fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::noop()
+        }
+    }
+
+    #[test]
+    fn records_metrics() {
+        let recorder = TestRecorder::default();
+        metrics::with_local_recorder(&recorder, || {
+            describe();
+
+            let runner = Runner::new(100_000, None);
+            let _ = runner.run(BitString::new_decompressed(&[true]));
+            assert_eq!(recorder.get(STEPS, &[]), 100_000);
+            assert!(recorder.get(LIVE_STATE_BYTES, &[]) > 0);
+
+            // A state this short never has room for a lookup table jump.
+            assert_eq!(recorder.get(EVOLVED_STEPS, &[("by", "jump")]), 0);
+            assert_eq!(recorder.get(EVOLVED_STEPS, &[("by", "single")]), 100_000);
+
+            // But nearly every step of a long state is one.
+            let _ = runner.run(crate::seeds::SEED_5854.system::<BitString>());
+            let jumped = recorder.get(EVOLVED_STEPS, &[("by", "jump")]);
+            let single = recorder.get(EVOLVED_STEPS, &[("by", "single")]) - 100_000;
+            assert_eq!(jumped + single, 100_000);
+            assert!(jumped > 99 * single);
+
+            let census = Census::new(3, Runner::new(10_000, Some(Algorithm::Brent)));
+            let records = census.run::<BitString>();
+            let halted = records
+                .iter()
+                .filter(|record| matches!(record.class, crate::census::Class::Halted(_)))
+                .count();
+            assert_eq!(
+                recorder.get(SEEDS_CLASSIFIED, &[("class", "halted")]),
+                halted as u64
+            );
+            assert_eq!(
+                recorder.get(SEEDS_CLASSIFIED, &[("class", "cycled")]),
+                (records.len() - halted) as u64
+            );
+        });
+    }
+}
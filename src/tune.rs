@@ -0,0 +1,275 @@
+//! Choosing the fastest bit string configuration for the current machine.
+//!
+//! Which word width and lookup table size is fastest depends on the processor's caches and the sizes of the states involved,
+//! so [`tune`] measures a few candidates rather than guessing.
+//!
+//! Nothing is tuned automatically: runners, censuses, ensembles, and the command-line tool use whichever system they are given,
+//! which is the default [`BitString`](crate::system::BitString) unless the caller chooses otherwise.
+//! To use a tuned configuration, create systems with its [`Backend`] and run them with [`Runner::run_tuned`] or any other runner method.
+
+use std::{
+    collections::VecDeque,
+    hash::{Hash, Hasher},
+    ops::ControlFlow,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    runner::{Outcome, Runner},
+    seeds,
+    system::{BitString128, BitString32, BitString64, GenericBitString, Lut, LutError, Word},
+    PostSystem,
+};
+
+/// The width of the words a bit string is stored in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WordWidth {
+    W32,
+    W64,
+    W128,
+}
+
+/// A bit string configuration, as chosen by [`tune`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tuning {
+    /// The width of the words to store states in.
+    pub width: WordWidth,
+    /// The number of steps per lookup table access.
+    pub timestep: u8,
+    /// The number of lookup table accesses per jump.
+    pub lookups: u8,
+}
+
+impl Tuning {
+    /// Generate the lookup table for this configuration.
+    pub fn backend(&self) -> Result<Backend, LutError> {
+        fn lut<W: Word>(tuning: &Tuning) -> Result<Lut<W>, LutError> {
+            GenericBitString::<W>::builder()
+                .timestep(tuning.timestep)
+                .lookups(tuning.lookups)
+                .build()
+        }
+
+        Ok(match self.width {
+            WordWidth::W32 => Backend::W32(lut(self)?),
+            WordWidth::W64 => Backend::W64(lut(self)?),
+            WordWidth::W128 => Backend::W128(lut(self)?),
+        })
+    }
+}
+
+/// A [`Tuning`] with its lookup table generated, ready to create systems.
+#[derive(Debug, Clone)]
+pub enum Backend {
+    W32(Lut<u32>),
+    W64(Lut<u64>),
+    W128(Lut<u128>),
+}
+
+impl Backend {
+    /// Initialize a system from a compressed representation of an initial string, as in [`PostSystem::new_decompressed`].
+    pub fn new_decompressed(&self, compressed: &[bool]) -> TunedBitString {
+        match self {
+            Self::W32(lut) => {
+                TunedBitString::W32(BitString32::new_decompressed(compressed).with_lut(lut.clone()))
+            }
+            Self::W64(lut) => {
+                TunedBitString::W64(BitString64::new_decompressed(compressed).with_lut(lut.clone()))
+            }
+            Self::W128(lut) => TunedBitString::W128(
+                BitString128::new_decompressed(compressed).with_lut(lut.clone()),
+            ),
+        }
+    }
+
+    /// Initialize a system from the compressed seed given by the binary digits of `seed`, as in [`PostSystem::from_seed_u128`].
+    pub fn from_seed_u128(&self, seed: u128) -> TunedBitString {
+        match self {
+            Self::W32(lut) => {
+                TunedBitString::W32(BitString32::from_seed_u128(seed).with_lut(lut.clone()))
+            }
+            Self::W64(lut) => {
+                TunedBitString::W64(BitString64::from_seed_u128(seed).with_lut(lut.clone()))
+            }
+            Self::W128(lut) => {
+                TunedBitString::W128(BitString128::from_seed_u128(seed).with_lut(lut.clone()))
+            }
+        }
+    }
+}
+
+/// A bit string of whichever word width a [`Backend`] chose.
+///
+/// States of different widths are never equal, so all states compared with each other should come from the same backend.
+/// Systems created through [`PostSystem`] rather than a [`Backend`] use 64-bit words and their default lookup table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TunedBitString {
+    W32(BitString32),
+    W64(BitString64),
+    W128(BitString128),
+}
+
+/// Evaluate `$body` with `$s` bound to the bit string inside `$self`, whatever its width.
+macro_rules! dispatch {
+    ($self:expr, $s:ident => $body:expr) => {
+        match $self {
+            TunedBitString::W32($s) => $body,
+            TunedBitString::W64($s) => $body,
+            TunedBitString::W128($s) => $body,
+        }
+    };
+}
+
+impl Hash for TunedBitString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        dispatch!(self, s => s.hash(state))
+    }
+}
+
+impl PostSystem for TunedBitString {
+    fn new_decompressed(compressed: &[bool]) -> Self {
+        Self::W64(BitString64::new_decompressed(compressed))
+    }
+
+    fn length(&self) -> usize {
+        dispatch!(self, s => s.length())
+    }
+
+    fn as_list(&self) -> VecDeque<bool> {
+        dispatch!(self, s => s.as_list())
+    }
+
+    fn count_ones(&self) -> usize {
+        dispatch!(self, s => s.count_ones())
+    }
+
+    fn get(&self, index: usize) -> Option<bool> {
+        dispatch!(self, s => s.get(index))
+    }
+
+    fn bits(&self) -> impl Iterator<Item = bool> + '_ {
+        dispatch!(self, s => Box::new(s.bits()) as Box<dyn Iterator<Item = bool> + '_>)
+    }
+
+    fn from_list(list: VecDeque<bool>) -> Self {
+        Self::W64(BitString64::from_list(list))
+    }
+
+    fn evolve(&mut self) -> ControlFlow<()> {
+        dispatch!(self, s => s.evolve())
+    }
+
+    // Dispatch once per call rather than once per step.
+    fn evolve_multi(&mut self, n: usize) -> ControlFlow<usize> {
+        dispatch!(self, s => s.evolve_multi(n))
+    }
+
+    fn preferred_timestep(&self) -> u8 {
+        dispatch!(self, s => s.preferred_timestep())
+    }
+
+    fn evolve_preferred(&mut self) -> ControlFlow<u8> {
+        dispatch!(self, s => s.evolve_preferred())
+    }
+}
+
+impl Runner {
+    /// Run the system with the compressed seed `compressed` using `backend`, like [`Self::run`].
+    ///
+    /// Other runs use whichever system they are given, so this is the way to opt in to a [`tune`]d configuration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a checkpoint cannot be written.
+    pub fn run_tuned(&self, backend: &Backend, compressed: &[bool]) -> Outcome<TunedBitString> {
+        self.run(backend.new_decompressed(compressed))
+    }
+}
+
+/// The timesteps [`tune`] tries for each word width.
+///
+/// Tables for larger timesteps no longer fit in cache, so they are rarely worth trying.
+const CANDIDATE_TIMESTEPS: [u8; 6] = [6, 8, 10, 11, 12, 14];
+
+/// The number of steps each candidate is timed for.
+const CALIBRATION_STEPS: usize = 200_000;
+
+/// The number of times each candidate is timed, keeping the fastest.
+const CALIBRATION_REPEATS: usize = 3;
+
+/// Find the fastest configuration on this machine, by timing a short run of each candidate.
+///
+/// Each candidate composes as many lookups per jump as fit in its word.
+/// This takes a few tens of milliseconds, so it is worth calling once before a long run or census, but not per seed.
+pub fn tune() -> Tuning {
+    candidates()
+        .min_by_key(time)
+        .expect("there is always a candidate")
+}
+
+/// List the configurations [`tune`] tries.
+fn candidates() -> impl Iterator<Item = Tuning> {
+    [
+        (WordWidth::W32, 32),
+        (WordWidth::W64, 64),
+        (WordWidth::W128, 128),
+    ]
+    .into_iter()
+    .flat_map(|(width, bits)| {
+        CANDIDATE_TIMESTEPS
+            .into_iter()
+            .filter(move |&timestep| 4 * timestep as u32 <= bits)
+            .map(move |timestep| Tuning {
+                width,
+                timestep,
+                lookups: (bits / (4 * timestep as u32)) as u8,
+            })
+    })
+}
+
+/// Time a run of `tuning` on a seed with a long transient.
+fn time(tuning: &Tuning) -> Duration {
+    let backend = tuning.backend().expect("candidates are valid");
+
+    (0..CALIBRATION_REPEATS)
+        .map(|_| {
+            let mut system = backend.from_seed_u128(seeds::SEED_5854.seed);
+            let start = Instant::now();
+            let _ = std::hint::black_box(system.evolve_multi(CALIBRATION_STEPS));
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cycle::Algorithm;
+
+    use super::*;
+
+    #[test]
+    fn lists_valid_candidates() {
+        assert!(candidates().all(|tuning| tuning.backend().is_ok()));
+        assert!(candidates().any(|tuning| tuning.width == WordWidth::W128 && tuning.lookups > 1));
+    }
+
+    #[test]
+    fn tunes() {
+        let tuning = tune();
+        let backend = tuning.backend().unwrap();
+        assert_eq!(
+            backend.from_seed_u128(seeds::SEED_5854.seed).as_list(),
+            seeds::SEED_5854.system::<BitString64>().as_list()
+        );
+
+        // `(100)^7` enters a cycle of length 28.
+        let runner = Runner::new(1_000_000, Some(Algorithm::Brent));
+        match runner.run_tuned(&backend, &[true; 7]) {
+            Outcome::Cycled(info) => assert_eq!(info.period, 28),
+            outcome => panic!("unexpected outcome {outcome:?}"),
+        }
+    }
+}
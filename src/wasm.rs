@@ -0,0 +1,129 @@
+//! JavaScript bindings, for running the simulator in a browser.
+//!
+//! Build with the `wasm` feature for the `wasm32-unknown-unknown` target and run `wasm-bindgen` on the output.
+//! Only the simulator itself is exposed: the [`Runner`](crate::runner::Runner) and parallel cycle detection rely on clocks and threads,
+//! which are unavailable in the browser without extra setup.
+
+use std::{ops::ControlFlow, str::FromStr};
+
+use wasm_bindgen::prelude::*;
+
+use crate::{system::BitString, PostSystem};
+
+/// A 3-tag system, evolved in place.
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct System {
+    state: BitString,
+    step: f64,
+    halted: bool,
+}
+
+#[wasm_bindgen]
+impl System {
+    /// Create a system from its state, a string of `0`s and `1`s.
+    #[wasm_bindgen(constructor)]
+    pub fn new(state: &str) -> Result<System, JsError> {
+        Ok(Self::from_state(BitString::from_str(state)?))
+    }
+
+    /// Create a system from a compressed seed, a string of `0`s and `1`s, each of which becomes itself followed by `00`.
+    #[wasm_bindgen(js_name = fromCompressed)]
+    pub fn from_compressed(seed: &str) -> Result<System, JsError> {
+        let seed: Vec<bool> = BitString::from_str(seed)?.bits().collect();
+        Ok(Self::from_state(BitString::new_decompressed(&seed)))
+    }
+
+    fn from_state(state: BitString) -> Self {
+        Self {
+            state,
+            step: 0.0,
+            halted: false,
+        }
+    }
+
+    /// Evolve the system by up to `n` steps, returning the number taken, which is fewer if it halts.
+    pub fn evolve(&mut self, n: u32) -> u32 {
+        if self.halted {
+            return 0;
+        }
+
+        let taken = match self.state.evolve_multi(n as usize) {
+            ControlFlow::Break(taken) => {
+                self.halted = true;
+                taken as u32
+            }
+            ControlFlow::Continue(()) => n,
+        };
+        self.step += taken as f64;
+        taken
+    }
+
+    /// Get the number of steps taken since the system was created.
+    ///
+    /// This is exact up to `2^53` steps, far more than a browser can simulate.
+    #[wasm_bindgen(getter)]
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    /// Check whether the system has halted.
+    #[wasm_bindgen(getter)]
+    pub fn halted(&self) -> bool {
+        self.halted
+    }
+
+    /// Get the length of the state.
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.state.length()
+    }
+
+    /// Get up to `len` symbols of the state starting at `start`, one byte per symbol, for drawing.
+    pub fn symbols(&self, start: usize, len: usize) -> Vec<u8> {
+        self.state
+            .bits()
+            .skip(start)
+            .take(len)
+            .map(u8::from)
+            .collect()
+    }
+
+    /// Get up to `len` symbols of the state starting at `start` as a string of `0`s and `1`s.
+    pub fn slice(&self, start: usize, len: usize) -> String {
+        let start = start.min(self.state.length());
+        let end = start.saturating_add(len).min(self.state.length());
+        self.state.slice(start..end).to_string()
+    }
+
+    /// Get the whole state as a string of `0`s and `1`s.
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_js_string(&self) -> String {
+        self.state.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evolves() {
+        let mut system = System::from_compressed("1").unwrap();
+        assert_eq!(system.to_js_string(), "100");
+
+        assert_eq!(system.evolve(2), 2);
+        assert_eq!(system.to_js_string(), "11101");
+        assert_eq!(system.step(), 2.0);
+        assert_eq!(system.length(), 5);
+        assert_eq!(system.slice(1, 3), "110");
+        assert_eq!(system.slice(3, 100), "01");
+        assert_eq!(system.symbols(2, 2), [1, 0]);
+
+        let mut system = System::new("00000").unwrap();
+        assert_eq!(system.evolve(10), 3);
+        assert!(system.halted());
+        assert_eq!(system.evolve(10), 0);
+        assert_eq!(system.step(), 3.0);
+    }
+}